@@ -1,8 +1,11 @@
 use crate::channels::{EventEmitterTask, IndexingThreadConfig};
 use crate::fibex_utils::gather_fibex_data;
 use crossbeam_channel as cc;
+use dlt::dlt_parse::DltFraming;
+use dlt::dlt_reader::DltReaderMode;
 use dlt::fibex::FibexMetadata;
 use dlt::filtering;
+use dlt::payload_search::{self, PayloadMatch};
 use indexer_base::chunks::ChunkResults;
 use indexer_base::config::FibexConfig;
 use indexer_base::config::IndexingConfig;
@@ -26,6 +29,7 @@ impl IndexingDltEventEmitter {
         thread_conf: IndexingThreadConfig,
         filter_conf: Option<filtering::DltFilterConfig>,
         fibex: FibexConfig,
+        jobs: usize,
     ) {
         info!("start_indexing_dlt_in_thread: {:?}", thread_conf);
 
@@ -44,6 +48,7 @@ impl IndexingDltEventEmitter {
                 chunk_result_sender.clone(),
                 Some(shutdown_rx),
                 fibex_metadata,
+                jobs,
             );
             debug!("back after DLT indexing finished!");
         }));
@@ -56,6 +61,7 @@ fn index_dlt_file_with_progress(
     tx: cc::Sender<ChunkResults>,
     shutdown_receiver: Option<cc::Receiver<()>>,
     fibex_metadata: Option<FibexMetadata>,
+    jobs: usize,
 ) {
     trace!("index_dlt_file_with_progress");
     let source_file_size = match config.in_file.metadata() {
@@ -70,18 +76,47 @@ fn index_dlt_file_with_progress(
             0
         }
     };
-    match dlt::dlt_file::create_index_and_mapping_dlt(
+    let out_path = config.out_path;
+    match dlt::dlt_file::create_index_and_mapping_dlt_parallel(
         config,
         source_file_size,
         filter_conf,
         &tx,
         shutdown_receiver,
         fibex_metadata,
+        DltReaderMode::default(),
+        DltFraming::default(),
+        jobs,
     ) {
         Err(why) => {
-            error!("create_index_and_mapping_dlt: couldn't process: {}", why);
+            error!(
+                "create_index_and_mapping_dlt_parallel: couldn't process: {}",
+                why
+            );
+        }
+        Ok(matches) => {
+            trace!("create_index_and_mapping_dlt_parallel returned ok");
+            send_payload_matches(out_path, &matches, &tx);
         }
-        Ok(_) => trace!("create_index_and_mapping_dlt returned ok"),
+    }
+}
+
+/// the matches of the payload search are written next to the index file
+/// (`<out_path>.matches.json`), a warning is sent if that fails
+pub(crate) fn send_payload_matches(
+    out_path: &path::Path,
+    matches: &[PayloadMatch],
+    tx: &cc::Sender<ChunkResults>,
+) {
+    if matches.is_empty() {
+        return;
+    }
+    if let Err(e) = payload_search::write_payload_matches(out_path, matches) {
+        let _ = tx.try_send(Err(Notification {
+            severity: Severity::WARNING,
+            content: format!("could not write matches of payload search: {}", e),
+            line: None,
+        }));
     }
 }
 // interface of the Rust code for js, exposes the `poll` and `shutdown` methods
@@ -99,6 +134,8 @@ declare_types! {
             trace!("{:?}", filter_conf);
             let arg_fibex_conf = cx.argument::<JsValue>(6)?;
             let fibex_conf: FibexConfig = neon_serde::from_value(&mut cx, arg_fibex_conf)?;
+            // number of worker threads, with less than 2 the file is indexed sequentially
+            let jobs: usize = cx.argument::<JsNumber>(7)?.value() as usize;
 
             let shutdown_channel = cc::unbounded();
             let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
@@ -121,6 +158,7 @@ declare_types! {
                 },
                 Some(filter_conf),
                 fibex_conf,
+                jobs,
             );
             Ok(emitter)
         }
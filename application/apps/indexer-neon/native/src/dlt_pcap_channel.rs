@@ -1,5 +1,6 @@
 use crate::channels::EventEmitterTask;
 use crate::channels::IndexingThreadConfig;
+use crate::dlt_indexer_channel::send_payload_matches;
 use crate::fibex_utils::gather_fibex_data;
 use crossbeam_channel as cc;
 use dlt::fibex::FibexMetadata;
@@ -44,9 +45,11 @@ impl PcapDltEventEmitter {
                 filter_conf,
                 &chunk_result_sender,
                 shutdown_rx,
-                fibex_metadata.map(std::rc::Rc::new),
+                fibex_metadata.map(std::sync::Arc::new),
             ) {
-                Ok(_) => {}
+                Ok(matches) => {
+                    send_payload_matches(&thread_conf.out_path, &matches, &chunk_result_sender)
+                }
                 Err(e) => warn!("error for pcap dlt stream: {}", e),
             }
             debug!("back after DLT pcap indexing finished!");
@@ -1,6 +1,7 @@
 use crate::channels::EventEmitterTask;
 use crossbeam_channel as cc;
-use dlt::dlt_parse::StatisticsResults;
+use dlt::dlt_parse::{DltFraming, StatisticsResults};
+use dlt::dlt_reader::DltReaderMode;
 use indexer_base::progress::{Notification, Severity};
 use neon::prelude::*;
 use std::path;
@@ -33,7 +34,13 @@ fn dlt_stats_with_progress(
     shutdown_receiver: Option<cc::Receiver<()>>,
 ) {
     trace!("calling dlt stats with progress");
-    match dlt::dlt_parse::get_dlt_file_info(&source_file, &tx, shutdown_receiver) {
+    match dlt::dlt_parse::get_dlt_file_info(
+        &source_file,
+        &tx,
+        shutdown_receiver,
+        DltReaderMode::default(),
+        DltFraming::default(),
+    ) {
         Err(why) => {
             error!("couldn't collect statistics: {}", why);
             match tx.send(Err(Notification {
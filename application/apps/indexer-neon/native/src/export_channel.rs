@@ -1,5 +1,7 @@
 use crate::channels::EventEmitterTask;
 use crossbeam_channel as cc;
+use dlt::dlt_parse::DltFraming;
+use dlt::dlt_reader::DltReaderMode;
 use failure::{err_msg, Error};
 use indexer_base::chunks::ChunkResults;
 use indexer_base::config::SectionConfig;
@@ -62,6 +64,8 @@ impl ExporterEventEmitter {
                     destination_path,
                     sections_config,
                     chunk_result_sender,
+                    DltReaderMode::default(),
+                    DltFraming::default(),
                 ) {
                     Ok(_) => {}
                     Err(e) => warn!("error exporting dlt messages: {}", e),
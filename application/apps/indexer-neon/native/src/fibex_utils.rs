@@ -11,7 +11,7 @@ pub(crate) fn gather_fibex_data(fibex: FibexConfig) -> Option<FibexMetadata> {
             .into_iter()
             .map(path::PathBuf::from)
             .collect();
-        match dlt::fibex::read_fibexes(paths) {
+        match dlt::fibex_cache::read_fibexes_cached(paths) {
             Ok(res) => Some(res),
             Err(e) => {
                 warn!("error reading fibex {}", e);
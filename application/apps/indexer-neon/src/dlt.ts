@@ -231,7 +231,8 @@ export function indexDltAsync(
 				params.append,
 				params.chunk_size,
 				params.filterConfig,
-				params.fibex
+				params.fibex,
+				typeof params.jobs === 'number' ? params.jobs : 1
 			);
 			// Create emitter
 			const emitter: NativeEventEmitter = new NativeEventEmitter(channel);
@@ -73,5 +73,160 @@ fn dlt_parse_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, dlt_benchmark, dlt_parse_benchmark);
+fn create_messages(count: usize) -> Vec<u8> {
+    use dlt::dlt::*;
+    let mut bytes = vec![];
+    for i in 0..count {
+        let payload = Payload2 {
+            payload_content: PayloadContent::Verbose(vec![
+                Argument {
+                    type_info: TypeInfo {
+                        kind: TypeInfoKind::StringType,
+                        coding: StringCoding::UTF8,
+                        has_variable_info: false,
+                        has_trace_info: false,
+                    },
+                    name: None,
+                    trace_info: None,
+                    unit: None,
+                    fixed_point: None,
+                    value: Value::StringVal(format!("message number {}", i)),
+                },
+                Argument {
+                    type_info: TypeInfo {
+                        kind: TypeInfoKind::Unsigned(TypeLength::BitLength32),
+                        coding: StringCoding::UTF8,
+                        has_variable_info: true,
+                        has_trace_info: false,
+                    },
+                    name: Some("speed".to_string()),
+                    trace_info: None,
+                    unit: Some("km/h".to_string()),
+                    fixed_point: None,
+                    value: Value::U32(i as u32),
+                },
+            ]),
+        };
+        let message = Message::new(
+            MessageConfig {
+                version: 1,
+                counter: (i % 256) as u8,
+                endianness: Endianness::Big,
+                ecu_id: Some("ECU1".to_string()),
+                session_id: Some(1),
+                timestamp: Some(i as u32),
+                payload,
+                extended_header_info: Some(ExtendedHeaderConfig {
+                    message_type: MessageType::Log(if i % 2 == 0 {
+                        LogLevel::Info
+                    } else {
+                        LogLevel::Debug
+                    }),
+                    app_id: if i % 4 == 0 { "APP1" } else { "APP2" }.to_string(),
+                    context_id: "CTX1".to_string(),
+                }),
+            },
+            None,
+            Some(StorageHeader {
+                timestamp: DltTimeStamp::from_ms(i as u64),
+                ecu_id: "ECU1".to_string(),
+            }),
+        );
+        bytes.extend(message.as_bytes());
+    }
+    bytes
+}
+
+fn dlt_message_view_benchmark(c: &mut Criterion) {
+    use dlt::dlt_parse::{dlt_message, dlt_message_view, DltFraming, ParsedMessage};
+    use indexer_base::chunks::Chunk;
+    let messages = create_messages(1000);
+    let filter_config = dlt::filtering::process_filter_config(dlt::filtering::DltFilterConfig {
+        min_log_level: Some(dlt::dlt::LEVEL_INFO),
+        app_ids: Some(vec!["APP1".to_string()]),
+        ..Default::default()
+    })
+    .expect("invalid filter config");
+    let owned_messages = messages.clone();
+    c.bench_function("parse 1000 owned messages", move |b| {
+        b.iter(|| {
+            let mut input: &[u8] = &owned_messages;
+            let mut parsed = 0usize;
+            while !input.is_empty() {
+                let (rest, msg) =
+                    dlt_message(input, None, 0, None, None, DltFraming::StorageHeader).unwrap();
+                if let ParsedMessage::Item(_) = msg {
+                    parsed += 1;
+                }
+                input = rest;
+            }
+            parsed
+        })
+    });
+    let view_messages = messages.clone();
+    c.bench_function("parse 1000 message views", move |b| {
+        b.iter(|| {
+            let mut input: &[u8] = &view_messages;
+            let mut parsed = 0usize;
+            while !input.is_empty() {
+                let (rest, view) =
+                    dlt_message_view::<Chunk>(input, None, None, DltFraming::StorageHeader)
+                        .unwrap();
+                if view.is_some() {
+                    parsed += 1;
+                }
+                input = rest;
+            }
+            parsed
+        })
+    });
+    let filter_messages = messages.clone();
+    c.bench_function("filter 1000 messages", move |b| {
+        b.iter(|| {
+            let mut input: &[u8] = &filter_messages;
+            let mut matched = 0usize;
+            while !input.is_empty() {
+                let (rest, msg) = dlt_message(
+                    input,
+                    Some(&filter_config),
+                    0,
+                    None,
+                    None,
+                    DltFraming::StorageHeader,
+                )
+                .unwrap();
+                if let ParsedMessage::Item(_) = msg {
+                    matched += 1;
+                }
+                input = rest;
+            }
+            matched
+        })
+    });
+    c.bench_function("statistics of 1000 messages", move |b| {
+        b.iter(|| {
+            let mut input: &[u8] = &messages;
+            let mut rows = 0usize;
+            while !input.is_empty() {
+                let (rest, _row) = dlt::dlt_parse::dlt_statistic_row_info::<Chunk>(
+                    input,
+                    None,
+                    DltFraming::StorageHeader,
+                    None,
+                )
+                .unwrap();
+                rows += 1;
+                input = rest;
+            }
+            rows
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    dlt_benchmark,
+    dlt_parse_benchmark,
+    dlt_message_view_benchmark
+);
 criterion_main!(benches);
@@ -0,0 +1,371 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+
+//! Reader for non-verbose descriptions in AUTOSAR ARXML
+//!
+//! The descriptions are converted into the same frames as the ones of a fibex:
+//! a DLT-MESSAGE becomes the frame `ID_<MESSAGE-ID>` and each of its DLT-ARGUMENTs
+//! becomes a pdu. Arguments with PREDEFINED-TEXT set are not transmitted, their
+//! description (DESC or SHORT-NAME) is shown instead. All other arguments are
+//! decoded with the SW-BASE-TYPE and COMPU-METHOD of their NETWORK-REPRESENTATION.
+//!
+//! Application and context ids are taken from the DLT-CONTEXTs (and their
+//! DLT-APPLICATION) that refer to a message with a DLT-MESSAGE-REF.
+use crate::dlt::{FloatWidth, StringCoding, TypeInfo, TypeInfoKind, TypeLength};
+use crate::fibex::{
+    ApplicationId, CompuConversion, CompuMethod, CompuScale, ContextId, FrameId, FrameMetadata,
+    PduMetadata, SignalConversion,
+};
+use failure::Error;
+use quick_xml::{events::Event as XmlEvent, Reader as XmlReader};
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+type Result<T = ()> = std::result::Result<T, Error>;
+
+/// true for files that have to be read with `read_arxml` instead of the fibex reader
+pub fn is_arxml_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.eq_ignore_ascii_case("arxml"))
+        .unwrap_or(false)
+}
+
+/// reads the frames of all DLT-MESSAGEs in an ARXML file
+pub fn read_arxml(path: &Path) -> Result<Vec<(FrameId, FrameMetadata)>> {
+    debug!("read_arxml from {:?}", path);
+    let root = read_element_tree(path)?;
+    let mut index = ArxmlIndex::default();
+    index.add(&root, "", None);
+    let mut frames = vec![];
+    for (message_path, message) in &index.messages {
+        match message.child_text("MESSAGE-ID").map(str::parse::<u32>) {
+            Some(Ok(id)) => frames.push((
+                FrameId(format!("ID_{}", id)),
+                index.frame(message_path, message)?,
+            )),
+            _ => warn!("DLT-MESSAGE {} has no valid MESSAGE-ID", message_path),
+        }
+    }
+    Ok(frames)
+}
+
+/// an XML element with its children, namespaces are dropped
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    text: String,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+    fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|c| c.text.trim())
+    }
+    /// depth first search
+    fn descendant(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find_map(|c| {
+            if c.name == name {
+                Some(c)
+            } else {
+                c.descendant(name)
+            }
+        })
+    }
+    fn short_name(&self) -> Option<&str> {
+        self.child_text("SHORT-NAME")
+    }
+}
+
+fn read_element_tree(path: &Path) -> Result<Element> {
+    let mut reader = XmlReader::from_file(path)?;
+    reader.trim_text(true);
+    let mut buf = vec![];
+    let mut stack = vec![Element::default()];
+    loop {
+        match reader.read_event(&mut buf)? {
+            XmlEvent::Start(ref e) => stack.push(Element {
+                name: String::from_utf8_lossy(e.local_name()).into_owned(),
+                ..Default::default()
+            }),
+            XmlEvent::Empty(ref e) => {
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(Element {
+                        name: String::from_utf8_lossy(e.local_name()).into_owned(),
+                        ..Default::default()
+                    });
+                }
+            }
+            XmlEvent::Text(ref e) | XmlEvent::CData(ref e) => {
+                let text = e.unescape_and_decode(&reader)?;
+                if let Some(current) = stack.last_mut() {
+                    current.text.push_str(&text);
+                }
+            }
+            XmlEvent::End(_) => {
+                if stack.len() < 2 {
+                    bail!("unbalanced end tag in {:?}", path);
+                }
+                if let Some(element) = stack.pop() {
+                    if let Some(parent) = stack.last_mut() {
+                        parent.children.push(element);
+                    }
+                }
+            }
+            XmlEvent::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    match stack.pop() {
+        Some(document) if stack.is_empty() => Ok(document),
+        _ => bail!("unexpected end of file {:?}", path),
+    }
+}
+
+/// the referable elements of an ARXML file by their path of SHORT-NAMEs
+#[derive(Default)]
+struct ArxmlIndex<'a> {
+    elements: HashMap<String, &'a Element>,
+    messages: Vec<(String, &'a Element)>,
+    message_ids: HashMap<String, (Option<ApplicationId>, ContextId)>,
+}
+
+impl<'a> ArxmlIndex<'a> {
+    fn add(&mut self, element: &'a Element, path: &str, application_id: Option<&'a str>) {
+        let path = match element.short_name() {
+            Some(short_name) => {
+                let path = format!("{}/{}", path, short_name);
+                self.elements.insert(path.clone(), element);
+                path
+            }
+            None => path.to_string(),
+        };
+        let application_id = match element.name.as_str() {
+            "DLT-APPLICATION" => element.child_text("APPLICATION-ID"),
+            "DLT-CONTEXT" => {
+                if let Some(context_id) = element.child_text("CONTEXT-ID") {
+                    for message_ref in element
+                        .children("DLT-MESSAGE-REFS")
+                        .flat_map(|refs| refs.children("DLT-MESSAGE-REF"))
+                    {
+                        self.message_ids.insert(
+                            message_ref.text.trim().to_string(),
+                            (
+                                application_id.map(|id| ApplicationId(id.to_string())),
+                                ContextId(context_id.to_string()),
+                            ),
+                        );
+                    }
+                }
+                application_id
+            }
+            "DLT-MESSAGE" => {
+                self.messages.push((path.clone(), element));
+                application_id
+            }
+            _ => application_id,
+        };
+        for child in &element.children {
+            self.add(child, &path, application_id);
+        }
+    }
+
+    fn resolve(&self, element: &Element, ref_name: &str) -> Option<&'a Element> {
+        let reference = element.descendant(ref_name)?.text.trim();
+        let resolved = self.elements.get(reference).cloned();
+        if resolved.is_none() {
+            warn!("{} {} not found", ref_name, reference);
+        }
+        resolved
+    }
+
+    fn frame(&self, path: &str, message: &Element) -> Result<FrameMetadata> {
+        let mut pdus = vec![];
+        for argument in message
+            .children("DLT-ARGUMENTS")
+            .flat_map(|args| args.children("DLT-ARGUMENT"))
+        {
+            pdus.push(Arc::new(self.pdu(argument)?));
+        }
+        let message_info = message.child_text("MESSAGE-TYPE-INFO").map(str::to_string);
+        let (application_id, context_id) = match self.message_ids.get(path) {
+            Some((application_id, context_id)) => {
+                (application_id.clone(), Some(context_id.clone()))
+            }
+            None => (None, None),
+        };
+        Ok(FrameMetadata {
+            short_name: message.short_name().unwrap_or_default().to_string(),
+            pdus,
+            application_id,
+            context_id,
+            message_type: message_info
+                .as_ref()
+                .filter(|info| info.starts_with("DLT_LOG_"))
+                .map(|_| "DLT_TYPE_LOG".to_string()),
+            message_info,
+        })
+    }
+
+    fn pdu(&self, argument: &Element) -> Result<PduMetadata> {
+        let name = argument.short_name().unwrap_or_default();
+        if argument.child_text("PREDEFINED-TEXT") == Some("true") {
+            let description = argument
+                .child("DESC")
+                .and_then(|desc| desc.child_text("L-2"))
+                .unwrap_or(name);
+            return Ok(PduMetadata {
+                description: Some(description.to_string()),
+                signal_types: vec![],
+                signal_names: vec![],
+                signal_conversions: vec![],
+            });
+        }
+        let mut pdu = PduMetadata {
+            description: None,
+            signal_types: vec![],
+            signal_names: vec![],
+            signal_conversions: vec![],
+        };
+        let representation = match argument.child("NETWORK-REPRESENTATION") {
+            Some(representation) => representation,
+            None => {
+                warn!("DLT-ARGUMENT {} has no NETWORK-REPRESENTATION", name);
+                return Ok(pdu);
+            }
+        };
+        if let Some(type_info) = self
+            .resolve(representation, "BASE-TYPE-REF")
+            .and_then(base_type_info)
+        {
+            let conversion = match self.resolve(representation, "COMPU-METHOD-REF") {
+                Some(compu_method) => Some(SignalConversion {
+                    signal_name: name.to_string(),
+                    compu_method: Arc::new(self.compu_method(compu_method)?),
+                }),
+                None => None,
+            };
+            pdu.signal_types.push(type_info);
+            pdu.signal_names.push(Some(name.to_string()));
+            pdu.signal_conversions.push(conversion);
+        }
+        Ok(pdu)
+    }
+
+    fn compu_method(&self, compu_method: &Element) -> Result<CompuMethod> {
+        let mut scales = vec![];
+        for scale in compu_method
+            .children("COMPU-INTERNAL-TO-PHYS")
+            .flat_map(|c| c.children("COMPU-SCALES"))
+            .flat_map(|c| c.children("COMPU-SCALE"))
+        {
+            let conversion =
+                if let Some(text) = scale.child("COMPU-CONST").and_then(|c| c.child_text("VT")) {
+                    CompuConversion::Text(text.to_string())
+                } else if let Some(coeffs) = scale.child("COMPU-RATIONAL-COEFFS") {
+                    let numerator = values(coeffs.child("COMPU-NUMERATOR"))?;
+                    let denominator = values(coeffs.child("COMPU-DENOMINATOR"))?;
+                    CompuConversion::Linear {
+                        offset: numerator.first().cloned().unwrap_or(0.0),
+                        factor: numerator.get(1).cloned().unwrap_or(0.0),
+                        denominator: denominator.first().cloned().unwrap_or(1.0),
+                    }
+                } else {
+                    warn!(
+                        "unsupported COMPU-SCALE in COMPU-METHOD {}",
+                        compu_method.short_name().unwrap_or_default()
+                    );
+                    continue;
+                };
+            scales.push(CompuScale {
+                lower_limit: scale.child_text("LOWER-LIMIT").map(parse_f64).transpose()?,
+                upper_limit: scale.child_text("UPPER-LIMIT").map(parse_f64).transpose()?,
+                conversion,
+            });
+        }
+        let unit = self.resolve(compu_method, "UNIT-REF").and_then(|unit| {
+            unit.child_text("DISPLAY-NAME")
+                .or_else(|| unit.short_name())
+                .map(str::to_string)
+        });
+        Ok(CompuMethod { scales, unit })
+    }
+}
+
+fn parse_f64(text: &str) -> Result<f64> {
+    text.parse::<f64>()
+        .map_err(|e| format_err!("can't parse f64 {}: {}", text, e))
+}
+
+/// the numbers of the V elements of a COMPU-NUMERATOR or COMPU-DENOMINATOR
+fn values(element: Option<&Element>) -> Result<Vec<f64>> {
+    match element {
+        Some(element) => element
+            .children("V")
+            .map(|v| parse_f64(v.text.trim()))
+            .collect(),
+        None => Ok(vec![]),
+    }
+}
+
+/// strings are transmitted like in fibex described messages, with a 16 bit length
+fn base_type_info(base_type: &Element) -> Option<TypeInfo> {
+    let name = base_type.short_name().unwrap_or_default();
+    let size = base_type
+        .child_text("BASE-TYPE-SIZE")
+        .and_then(|s| s.parse::<u16>().ok());
+    let type_length = match size {
+        Some(8) => Some(TypeLength::BitLength8),
+        Some(16) => Some(TypeLength::BitLength16),
+        Some(32) => Some(TypeLength::BitLength32),
+        Some(64) => Some(TypeLength::BitLength64),
+        Some(128) => Some(TypeLength::BitLength128),
+        _ => None,
+    };
+    let (kind, coding) = match (base_type.child_text("BASE-TYPE-ENCODING"), type_length) {
+        (Some("BOOLEAN"), _) => (TypeInfoKind::Bool, StringCoding::ASCII),
+        (Some("2C"), Some(length)) => (TypeInfoKind::Signed(length), StringCoding::ASCII),
+        (Some("NONE"), Some(length)) | (None, Some(length)) => {
+            (TypeInfoKind::Unsigned(length), StringCoding::ASCII)
+        }
+        (Some("IEEE754"), Some(TypeLength::BitLength32)) => (
+            TypeInfoKind::Float(FloatWidth::Width32),
+            StringCoding::ASCII,
+        ),
+        (Some("IEEE754"), Some(TypeLength::BitLength64)) => (
+            TypeInfoKind::Float(FloatWidth::Width64),
+            StringCoding::ASCII,
+        ),
+        (Some("UTF-8"), _) => (TypeInfoKind::StringType, StringCoding::UTF8),
+        (Some("ISO-8859-1"), _) | (Some("WINDOWS-1252"), _) => {
+            (TypeInfoKind::StringType, StringCoding::ASCII)
+        }
+        (encoding, _) => {
+            warn!(
+                "SW-BASE-TYPE {} not supported (encoding {:?}, size {:?})",
+                name, encoding, size
+            );
+            return None;
+        }
+    };
+    Some(TypeInfo {
+        kind,
+        coding,
+        has_variable_info: false,
+        has_trace_info: false,
+    })
+}
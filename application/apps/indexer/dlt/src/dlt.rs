@@ -15,11 +15,11 @@ use crate::proptest_strategies::*;
 use byteorder::{BigEndian, LittleEndian};
 use bytes::{BufMut, ByteOrder, BytesMut};
 use indexer_base::error_reporter::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::io;
 use std::io::Error;
-use std::rc::Rc;
+use std::sync::Arc;
 
 use proptest::prelude::*;
 use proptest_derive::Arbitrary;
@@ -55,6 +55,9 @@ impl DltTimeStamp {
             microseconds: (us % (1000 * 1000)) as u32 * 1000 * 1000,
         }
     }
+    pub fn as_us(&self) -> u64 {
+        u64::from(self.seconds) * 1000 * 1000 + u64::from(self.microseconds)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Arbitrary, Serialize)]
@@ -99,6 +102,8 @@ pub struct StandardHeader {
     pub session_id: Option<u32>,
     pub timestamp: Option<u32>,
     pub payload_length: u16,
+    /// only present for DLT protocol version 2 messages
+    pub v2: Option<Box<HeaderV2Fields>>,
 }
 
 impl StandardHeader {
@@ -134,7 +139,7 @@ impl StandardHeader {
     }
 }
 
-fn standard_header_type(
+pub(crate) fn standard_header_type(
     has_extended_header: bool,
     endianness: Endianness,
     with_ecu_id: bool,
@@ -184,6 +189,7 @@ impl StandardHeader {
             session_id,
             timestamp,
             payload_length,
+            v2: None,
         }
     }
 
@@ -208,6 +214,80 @@ impl StandardHeader {
     }
 }
 
+/// Timestamp of DLT version 2 messages: 40 bit seconds and 32 bit nanoseconds
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DltTimeStampV2 {
+    pub seconds: u64,
+    pub nanoseconds: u32,
+}
+
+/// Source file name and line number of a DLT version 2 log call (FINA/LINR)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceLocation {
+    pub file_name: String,
+    pub line_number: u32,
+}
+
+/// The fields of a DLT version 2 header (AUTOSAR R19-11+) that have no
+/// counterpart in version 1
+///
+/// All other information of a version 2 header is kept in the common
+/// `StandardHeader` and `ExtendedHeader` so version 1 and version 2
+/// messages can be handled the same way
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HeaderV2Fields {
+    pub timestamp: Option<DltTimeStampV2>,
+    /// version 2 headers might come without application and context id
+    pub with_app_and_context_id: bool,
+    pub source_location: Option<SourceLocation>,
+    pub tags: Vec<String>,
+    pub privacy_level: Option<u8>,
+}
+
+impl HeaderV2Fields {
+    /// the 32 bit header type (HTYP2)
+    pub fn header_type(&self, content_type: u8, with_ecu_id: bool, with_session_id: bool) -> u32 {
+        let mut header_type = u32::from(content_type & 0b11);
+        if with_ecu_id {
+            header_type |= HTYP2_WITH_ECU_ID_FLAG;
+        }
+        if self.with_app_and_context_id {
+            header_type |= HTYP2_WITH_APP_AND_CONTEXT_ID_FLAG;
+        }
+        if with_session_id {
+            header_type |= HTYP2_WITH_SESSION_ID_FLAG;
+        }
+        header_type |= u32::from(DLT_V2_VERSION) << 5;
+        if self.source_location.is_some() {
+            header_type |= HTYP2_WITH_SOURCE_FILE_AND_LINE_FLAG;
+        }
+        if !self.tags.is_empty() {
+            header_type |= HTYP2_WITH_TAGS_FLAG;
+        }
+        if self.privacy_level.is_some() {
+            header_type |= HTYP2_WITH_PRIVACY_LEVEL_FLAG;
+        }
+        header_type
+    }
+}
+
+/// length of a length prefixed string in a version 2 header
+fn v2_string_len(s: &str) -> u16 {
+    1 + std::cmp::min(s.len(), u8::MAX as usize) as u16
+}
+
+trait BytesMutV2Ext {
+    fn put_v2_string(&mut self, s: &str);
+}
+impl BytesMutV2Ext for BytesMut {
+    /// strings in version 2 headers are prefixed with a one byte length
+    fn put_v2_string(&mut self, s: &str) {
+        let bytes = &s.as_bytes()[..std::cmp::min(s.len(), u8::MAX as usize)];
+        self.extend_from_slice(&[bytes.len() as u8]);
+        self.extend_from_slice(bytes);
+    }
+}
+
 #[derive(Debug, PartialEq, PartialOrd, Clone, Copy, Serialize, Arbitrary)]
 pub enum LogLevel {
     Fatal,
@@ -220,7 +300,7 @@ pub enum LogLevel {
     Invalid(u8),
 }
 
-#[derive(Debug, PartialEq, Clone, Arbitrary, Serialize)]
+#[derive(Debug, PartialEq, Clone, Arbitrary, Serialize, Deserialize)]
 pub enum ApplicationTraceType {
     Variable,
     FunctionIn,
@@ -231,7 +311,7 @@ pub enum ApplicationTraceType {
     Invalid(u8),
 }
 
-#[derive(Debug, PartialEq, Clone, Arbitrary, Serialize)]
+#[derive(Debug, PartialEq, Clone, Arbitrary, Serialize, Deserialize)]
 pub enum NetworkTraceType {
     Ipc,
     Can,
@@ -246,7 +326,7 @@ pub enum NetworkTraceType {
 
 const CTRL_TYPE_REQUEST: u8 = 0x1;
 const CTRL_TYPE_RESPONSE: u8 = 0x2;
-#[derive(Debug, PartialEq, Clone, Arbitrary, Serialize)]
+#[derive(Debug, PartialEq, Clone, Arbitrary, Serialize, Deserialize)]
 pub enum ControlType {
     Request,  // represented by 0x1
     Response, // represented by 0x2
@@ -281,6 +361,18 @@ pub enum MessageType {
 }
 
 impl MessageType {
+    /// log messages below the given level can be skipped
+    pub fn skip_with_level(&self, level: LogLevel) -> bool {
+        match self {
+            MessageType::Log(n) => match (*n, level) {
+                (LogLevel::Invalid(a), LogLevel::Invalid(b)) => a < b,
+                (LogLevel::Invalid(_), _) => false,
+                (_, LogLevel::Invalid(_)) => true,
+                _ => level < *n,
+            },
+            _ => false,
+        }
+    }
     pub(crate) fn try_new_from_fibex_message_info(message_info: &str) -> Option<MessageType> {
         Some(MessageType::Log(match message_info {
             "DLT_LOG_FATAL" => LogLevel::Fatal,
@@ -324,29 +416,22 @@ impl ExtendedHeader {
         buf.to_vec()
     }
     pub fn skip_with_level(self: &ExtendedHeader, level: LogLevel) -> bool {
-        match self.message_type {
-            MessageType::Log(n) => match (n, level) {
-                (LogLevel::Invalid(a), LogLevel::Invalid(b)) => a < b,
-                (LogLevel::Invalid(_), _) => false,
-                (_, LogLevel::Invalid(_)) => true,
-                _ => level < n,
-            },
-            _ => false,
-        }
+        self.message_type.skip_with_level(level)
     }
 }
 
-/// Fixed-Point representation. only supports 32 bit and 64 bit values
-/// according to the spec 128 bit are possible but we don't support it
+/// Fixed-Point representation. supports 32 bit, 64 bit and 128 bit values
 #[derive(Debug, PartialEq, Clone, Arbitrary, Serialize)]
 pub enum FixedPointValue {
     I32(i32),
     I64(i64),
+    I128(i128),
 }
 pub fn fixed_point_value_width(v: &FixedPointValue) -> usize {
     match v {
         FixedPointValue::I32(_) => 4,
         FixedPointValue::I64(_) => 8,
+        FixedPointValue::I128(_) => 16,
     }
 }
 #[derive(Debug, PartialEq, Clone, Serialize)]
@@ -366,16 +451,18 @@ pub enum Value {
     F64(f64),
     StringVal(String),
     Raw(Vec<u8>),
+    Array(Vec<u16>, Vec<Value>), // (dimensions, elements in row-major order)
+    Struct(Vec<Argument>),
 }
 
-#[derive(Debug, Clone, PartialEq, Arbitrary, Serialize)]
+#[derive(Debug, Clone, PartialEq, Arbitrary, Serialize, Deserialize)]
 pub enum StringCoding {
     ASCII,
     UTF8,
     #[proptest(strategy = "(2..=7u8).prop_map(StringCoding::Reserved)")]
     Reserved(u8),
 }
-#[derive(Debug, Clone, PartialEq, Copy, Arbitrary, Serialize)]
+#[derive(Debug, Clone, PartialEq, Copy, Arbitrary, Serialize, Deserialize)]
 pub enum FloatWidth {
     Width32 = 32,
     Width64 = 64,
@@ -387,7 +474,22 @@ pub fn float_width_to_type_length(width: FloatWidth) -> TypeLength {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Copy, Arbitrary, Serialize)]
+/// fixed point values can only have a width of 32, 64 or 128 bit
+#[derive(Debug, Clone, PartialEq, Copy, Arbitrary, Serialize, Deserialize)]
+pub enum FixedPointWidth {
+    Width32 = 32,
+    Width64 = 64,
+    Width128 = 128,
+}
+pub fn fixed_point_width_to_type_length(width: FixedPointWidth) -> TypeLength {
+    match width {
+        FixedPointWidth::Width32 => TypeLength::BitLength32,
+        FixedPointWidth::Width64 => TypeLength::BitLength64,
+        FixedPointWidth::Width128 => TypeLength::BitLength128,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Copy, Arbitrary, Serialize, Deserialize)]
 pub enum TypeLength {
     BitLength8 = 8,
     BitLength16 = 16,
@@ -405,6 +507,16 @@ impl FloatWidth {
     }
 }
 
+impl FixedPointWidth {
+    pub fn width_in_bytes(self) -> usize {
+        match self {
+            FixedPointWidth::Width32 => 4,
+            FixedPointWidth::Width64 => 8,
+            FixedPointWidth::Width128 => 16,
+        }
+    }
+}
+
 impl TypeLength {
     pub fn width_in_bytes(self) -> usize {
         match self {
@@ -417,19 +529,73 @@ impl TypeLength {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Arbitrary, Serialize)]
+#[derive(Debug, Clone, PartialEq, Arbitrary, Serialize, Deserialize)]
 pub enum TypeInfoKind {
     Bool,
     #[proptest(strategy = "signed_strategy()")]
     Signed(TypeLength),
-    SignedFixedPoint(FloatWidth),
+    SignedFixedPoint(FixedPointWidth),
     #[proptest(strategy = "unsigned_strategy()")]
     Unsigned(TypeLength),
-    UnsignedFixedPoint(FloatWidth),
+    UnsignedFixedPoint(FixedPointWidth),
     Float(FloatWidth),
-    // Array, NYI
+    Array(ArrayElementKind),
     StringType,
     Raw,
+    Struct,
+}
+
+/// The element type of a DLT array. An array can only hold
+/// bool, signed, unsigned or float values (optionally as fixed point)
+#[derive(Debug, Clone, Copy, PartialEq, Arbitrary, Serialize, Deserialize)]
+pub enum ArrayElementKind {
+    Bool,
+    Signed(TypeLength),
+    SignedFixedPoint(FixedPointWidth),
+    Unsigned(TypeLength),
+    UnsignedFixedPoint(FixedPointWidth),
+    Float(FloatWidth),
+}
+impl ArrayElementKind {
+    pub fn type_info_kind(self) -> TypeInfoKind {
+        match self {
+            ArrayElementKind::Bool => TypeInfoKind::Bool,
+            ArrayElementKind::Signed(v) => TypeInfoKind::Signed(v),
+            ArrayElementKind::SignedFixedPoint(v) => TypeInfoKind::SignedFixedPoint(v),
+            ArrayElementKind::Unsigned(v) => TypeInfoKind::Unsigned(v),
+            ArrayElementKind::UnsignedFixedPoint(v) => TypeInfoKind::UnsignedFixedPoint(v),
+            ArrayElementKind::Float(v) => TypeInfoKind::Float(v),
+        }
+    }
+    pub fn width_in_bytes(self) -> usize {
+        match self {
+            ArrayElementKind::Bool => 1,
+            ArrayElementKind::Signed(v) => v.width_in_bytes(),
+            ArrayElementKind::SignedFixedPoint(v) => v.width_in_bytes(),
+            ArrayElementKind::Unsigned(v) => v.width_in_bytes(),
+            ArrayElementKind::UnsignedFixedPoint(v) => v.width_in_bytes(),
+            ArrayElementKind::Float(v) => v.width_in_bytes(),
+        }
+    }
+    pub fn is_fixed_point(self) -> bool {
+        match self {
+            ArrayElementKind::SignedFixedPoint(_) => true,
+            ArrayElementKind::UnsignedFixedPoint(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// number of elements stored in an array with the given dimensions
+/// (an array without any dimension has no elements)
+pub fn array_element_count(dimensions: &[u16]) -> usize {
+    if dimensions.is_empty() {
+        0
+    } else {
+        dimensions
+            .iter()
+            .fold(1usize, |acc, d| acc.saturating_mul(*d as usize))
+    }
 }
 
 ///
@@ -453,7 +619,10 @@ pub enum TypeInfoKind {
 /// Both always contain a length information field and a field with the text (of name or unit).
 /// The length field contains the number of characters of the associated name or unit filed.
 /// The unit information is to add only in some data types.
-#[derive(Debug, Clone, PartialEq, Arbitrary, Serialize)]
+///
+/// has_trace_info: If Trace Info (TRAI) is set, a length field and a zero terminated
+/// string describing the origin of the argument directly follow the type info.
+#[derive(Debug, Clone, PartialEq, Arbitrary, Serialize, Deserialize)]
 pub struct TypeInfo {
     pub kind: TypeInfoKind,
     pub coding: StringCoding,
@@ -467,6 +636,13 @@ impl TypeInfo {
             FloatWidth::Width64 => 0b100,
         }
     }
+    pub fn type_length_bits_fixed_point(len: FixedPointWidth) -> u32 {
+        match len {
+            FixedPointWidth::Width32 => 0b011,
+            FixedPointWidth::Width64 => 0b100,
+            FixedPointWidth::Width128 => 0b101,
+        }
+    }
     pub fn type_length_bits(len: TypeLength) -> u32 {
         match len {
             TypeLength::BitLength8 => 0b001,
@@ -490,31 +666,45 @@ impl TypeInfo {
         match self.kind {
             TypeInfoKind::SignedFixedPoint(_) => true,
             TypeInfoKind::UnsignedFixedPoint(_) => true,
+            TypeInfoKind::Array(element_kind) => element_kind.is_fixed_point(),
             _ => false,
         }
     }
     pub fn as_bytes<T: ByteOrder>(self: &TypeInfo) -> Vec<u8> {
         // println!("TypeInfo::as_bytes: {:?}", self);
         let mut info: u32 = 0;
+        // arrays are encoded with the type flags of their elements + the array flag
+        let kind = match self.kind {
+            TypeInfoKind::Array(element_kind) => {
+                info |= TYPE_INFO_ARRAY_FLAG;
+                element_kind.type_info_kind()
+            }
+            ref k => k.clone(),
+        };
         // encode length
-        match self.kind {
+        match kind {
             TypeInfoKind::Float(len) => info |= TypeInfo::type_length_bits_float(len),
             TypeInfoKind::Signed(len) => info |= TypeInfo::type_length_bits(len),
-            TypeInfoKind::SignedFixedPoint(len) => info |= TypeInfo::type_length_bits_float(len),
+            TypeInfoKind::SignedFixedPoint(len) => {
+                info |= TypeInfo::type_length_bits_fixed_point(len)
+            }
             TypeInfoKind::Unsigned(len) => info |= TypeInfo::type_length_bits(len),
-            TypeInfoKind::UnsignedFixedPoint(len) => info |= TypeInfo::type_length_bits_float(len),
+            TypeInfoKind::UnsignedFixedPoint(len) => {
+                info |= TypeInfo::type_length_bits_fixed_point(len)
+            }
             _ => (),
         }
-        match self.kind {
+        match kind {
             TypeInfoKind::Bool => info |= TYPE_INFO_BOOL_FLAG,
             TypeInfoKind::Signed(_) => info |= TYPE_INFO_SINT_FLAG,
             TypeInfoKind::SignedFixedPoint(_) => info |= TYPE_INFO_SINT_FLAG,
             TypeInfoKind::Unsigned(_) => info |= TYPE_INFO_UINT_FLAG,
             TypeInfoKind::UnsignedFixedPoint(_) => info |= TYPE_INFO_UINT_FLAG,
             TypeInfoKind::Float(_) => info |= TYPE_INFO_FLOAT_FLAG,
-            // TypeInfoKind::Array => info |= TYPE_INFO_ARRAY_FLAG,
+            TypeInfoKind::Array(_) => info |= TYPE_INFO_ARRAY_FLAG,
             TypeInfoKind::StringType => info |= TYPE_INFO_STRING_FLAG,
             TypeInfoKind::Raw => info |= TYPE_INFO_RAW_FLAG,
+            TypeInfoKind::Struct => info |= TYPE_INFO_STRUCT_FLAG,
         }
         if self.has_variable_info {
             info |= TYPE_INFO_VARIABLE_INFO
@@ -580,22 +770,36 @@ impl TryFrom<u32> for TypeInfo {
                 )),
             }
         }
+        fn type_len_fixed_point(info: u32) -> Result<FixedPointWidth, Error> {
+            match info & 0b1111 {
+                0x03 => Ok(FixedPointWidth::Width32),
+                0x04 => Ok(FixedPointWidth::Width64),
+                0x05 => Ok(FixedPointWidth::Width128),
+                v => Err(Error::other(format!(
+                    "Unknown type_len_fixed_point in TypeInfo {:b}",
+                    v
+                ))),
+            }
+        }
 
         let is_fixed_point = (info & TYPE_INFO_FIXED_POINT_FLAG) != 0;
-        let kind = match (info >> 4) & 0b111_1111 {
+        let is_array = (info & TYPE_INFO_ARRAY_FLAG) != 0;
+        let is_struct = (info & TYPE_INFO_STRUCT_FLAG) != 0;
+        // the array flag is combined with the flag of the element type
+        let kind = match (info >> 4) & 0b110_1111 {
+            0b000_0000 if is_struct => Ok(TypeInfoKind::Struct),
             0b000_0001 => Ok(TypeInfoKind::Bool),
             0b000_0010 => Ok(if is_fixed_point {
-                TypeInfoKind::SignedFixedPoint(type_len_float(info)?)
+                TypeInfoKind::SignedFixedPoint(type_len_fixed_point(info)?)
             } else {
                 TypeInfoKind::Signed(type_len(info)?)
             }),
             0b000_0100 => Ok(if is_fixed_point {
-                TypeInfoKind::UnsignedFixedPoint(type_len_float(info)?)
+                TypeInfoKind::UnsignedFixedPoint(type_len_fixed_point(info)?)
             } else {
                 TypeInfoKind::Unsigned(type_len(info)?)
             }),
             0b000_1000 => Ok(TypeInfoKind::Float(type_len_float(info)?)),
-            // 0b001_0000 => Ok(TypeInfoKind::Array),
             0b010_0000 => Ok(TypeInfoKind::StringType),
             0b100_0000 => Ok(TypeInfoKind::Raw),
             v => Err(Error::new(
@@ -603,6 +807,25 @@ impl TryFrom<u32> for TypeInfo {
                 format!("Unknown TypeInfoKind in TypeInfo {:b}", v),
             )),
         }?;
+        let kind = if is_array {
+            let element_kind = match kind {
+                TypeInfoKind::Bool => ArrayElementKind::Bool,
+                TypeInfoKind::Signed(v) => ArrayElementKind::Signed(v),
+                TypeInfoKind::SignedFixedPoint(v) => ArrayElementKind::SignedFixedPoint(v),
+                TypeInfoKind::Unsigned(v) => ArrayElementKind::Unsigned(v),
+                TypeInfoKind::UnsignedFixedPoint(v) => ArrayElementKind::UnsignedFixedPoint(v),
+                TypeInfoKind::Float(v) => ArrayElementKind::Float(v),
+                k => {
+                    return Err(Error::other(format!(
+                        "Unsupported array element type in TypeInfo {:?}",
+                        k
+                    )))
+                }
+            };
+            TypeInfoKind::Array(element_kind)
+        } else {
+            kind
+        };
         let coding = match (info >> 15) & 0b111 {
             0x00 => (StringCoding::ASCII),
             0x01 => (StringCoding::UTF8),
@@ -632,7 +855,7 @@ impl TryFrom<u32> for TypeInfo {
 /// The width depends on the TYLE value
 ///     * i32 bit if Type Length (TYLE) equals 1,2 or 3
 ///     * i64 bit if Type Length (TYLE) equals 4
-///     * i128 bit if Type Length (TYLE) equals 5 (unsupported)
+///     * i128 bit if Type Length (TYLE) equals 5
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct FixedPoint {
     pub quantization: f32,
@@ -642,47 +865,72 @@ pub struct FixedPoint {
 pub struct Argument {
     pub type_info: TypeInfo,
     pub name: Option<String>,
+    /// origin of the argument (e.g. module or function), only present with TRAI set
+    pub trace_info: Option<String>,
     pub unit: Option<String>,
     pub fixed_point: Option<FixedPoint>,
     pub value: Value,
 }
 impl Argument {
+    /// the physical value of numeric arguments (fixed point values are scaled)
+    pub fn numeric_value(&self) -> Option<f64> {
+        let value = match self.value {
+            Value::Bool(v) => f64::from(v),
+            Value::F32(v) => f64::from(v),
+            Value::F64(v) => v,
+            _ => self.value_as_f64()?,
+        };
+        Some(match &self.fixed_point {
+            Some(FixedPoint {
+                quantization,
+                offset,
+            }) => {
+                let offset = match offset {
+                    FixedPointValue::I32(v) => f64::from(*v),
+                    FixedPointValue::I64(v) => *v as f64,
+                    FixedPointValue::I128(v) => *v as f64,
+                };
+                value * f64::from(*quantization) + offset
+            }
+            None => value,
+        })
+    }
     fn value_as_f64(&self) -> Option<f64> {
         match self.value {
             Value::I8(v) => Some(v as f64),
             Value::I16(v) => Some(v as f64),
             Value::I32(v) => Some(v as f64),
             Value::I64(v) => Some(v as f64),
+            Value::I128(v) => Some(v as f64),
             Value::U8(v) => Some(v as f64),
             Value::U16(v) => Some(v as f64),
             Value::U32(v) => Some(v as f64),
             Value::U64(v) => Some(v as f64),
+            Value::U128(v) => Some(v as f64),
             _ => None,
         }
     }
-    fn log_v(&self) -> Option<u64> {
-        match &self.fixed_point {
-            Some(FixedPoint {
-                quantization,
-                offset,
-            }) => {
-                if let Some(value) = self.value_as_f64() {
-                    match offset {
-                        FixedPointValue::I32(v) => {
-                            Some((value * *quantization as f64) as u64 + *v as u64)
-                        }
-                        FixedPointValue::I64(v) => {
-                            Some((value * *quantization as f64) as u64 + *v as u64)
-                        }
-                    }
-                } else {
-                    None
-                }
-            }
-            _ => None,
-        }
+    /// the scaled value of a fixed point argument, the fraction is cut off
+    ///
+    /// None if the value does not fit into an i128
+    fn log_v(&self) -> Option<i128> {
+        let FixedPoint {
+            quantization,
+            offset,
+        } = self.fixed_point.as_ref()?;
+        let scaled = self.value_as_f64()? * f64::from(*quantization);
+        // i128::MAX is not representable as f64, the next bigger value is 2^127
+        if !scaled.is_finite() || scaled < i128::MIN as f64 || scaled >= i128::MAX as f64 {
+            return None;
+        }
+        let offset = match offset {
+            FixedPointValue::I32(v) => i128::from(*v),
+            FixedPointValue::I64(v) => i128::from(*v),
+            FixedPointValue::I128(v) => *v,
+        };
+        (scaled as i128).checked_add(offset)
     }
-    pub(crate) fn to_real_value(&self) -> Option<u64> {
+    pub(crate) fn to_real_value(&self) -> Option<i128> {
         match (&self.type_info.kind, &self.fixed_point) {
             (TypeInfoKind::SignedFixedPoint(_), Some(_)) => self.log_v(),
             (TypeInfoKind::UnsignedFixedPoint(_), Some(_)) => self.log_v(),
@@ -712,8 +960,8 @@ impl Argument {
     pub fn len_old<T: ByteOrder>(&self) -> usize {
         self.as_bytes::<T>().len()
     }
-    fn fixed_point_capacity(&self, float_width: FloatWidth) -> usize {
-        let mut capacity = float_width.width_in_bytes();
+    fn fixed_point_capacity(&self, width: FixedPointWidth) -> usize {
+        let mut capacity = width.width_in_bytes();
         if let Some(fp) = &self.fixed_point {
             capacity += 4 /*quantixation */ + fixed_point_value_width(&fp.offset);
         }
@@ -727,7 +975,9 @@ impl Argument {
         };
         let unit_space = match &self.unit {
             Some(u) => 2 /* length of unit */ + u.len() + 1,
-            _ => 0,
+            // an empty unit is written for arguments with variable info
+            None if self.type_info.has_variable_info => 2 + 1,
+            None => 0,
         };
         let without_type_info = match self.type_info.kind {
             TypeInfoKind::Bool => name_space + 1,
@@ -735,15 +985,26 @@ impl Argument {
             TypeInfoKind::Unsigned(bit_width) => {
                 name_space + unit_space + bit_width.width_in_bytes()
             }
-            TypeInfoKind::SignedFixedPoint(float_width) => {
-                name_space + unit_space + self.fixed_point_capacity(float_width)
+            TypeInfoKind::SignedFixedPoint(width) => {
+                name_space + unit_space + self.fixed_point_capacity(width)
             }
-            TypeInfoKind::UnsignedFixedPoint(float_width) => {
-                name_space + unit_space + self.fixed_point_capacity(float_width)
+            TypeInfoKind::UnsignedFixedPoint(width) => {
+                name_space + unit_space + self.fixed_point_capacity(width)
             }
             TypeInfoKind::Float(float_width) => {
                 name_space + unit_space + float_width.width_in_bytes()
             }
+            TypeInfoKind::Array(element_kind) => {
+                let mut capacity = 2 /* number of dimensions */ + name_space + unit_space;
+                if let Some(fp) = &self.fixed_point {
+                    capacity += 4 /* quantization */ + fixed_point_value_width(&fp.offset);
+                }
+                if let Value::Array(dimensions, _) = &self.value {
+                    capacity += 2 * dimensions.len()
+                        + array_element_count(dimensions) * element_kind.width_in_bytes();
+                }
+                capacity
+            }
             TypeInfoKind::StringType => {
                 let mut capacity = 2 /* length of string and termination char */ + name_space;
                 match &self.value {
@@ -766,8 +1027,39 @@ impl Argument {
                 }
                 capacity
             }
+            TypeInfoKind::Struct => {
+                let mut capacity = 2 /* number of entries */ + name_space;
+                match &self.value {
+                    Value::Struct(members) => {
+                        capacity += members.iter().map(|m| m.len_new()).sum::<usize>()
+                    }
+                    _ => {
+                        error!("Found typeinfokind Struct but no Struct value!");
+                    }
+                }
+                capacity
+            }
         };
-        without_type_info + TYPE_INFO_LENGTH
+        without_type_info + TYPE_INFO_LENGTH + self.trace_info_len()
+    }
+
+    /// space needed for the trace info that follows the type info if TRAI is set
+    fn trace_info_len(&self) -> usize {
+        if self.type_info.has_trace_info {
+            2 /* length of trace info */ + self.trace_info.as_ref().map_or(0, |t| t.len()) + 1
+        } else {
+            0
+        }
+    }
+    fn put_type_info<T: ByteOrder>(&self, info: &TypeInfo, buf: &mut BytesMut) {
+        buf.extend_from_slice(&info.as_bytes::<T>()[..]);
+        if info.has_trace_info {
+            let trace_info = self.trace_info.as_ref().map_or("", |t| t.as_str());
+            #[allow(deprecated)]
+            buf.put_u16::<T>(trace_info.len() as u16 + 1);
+            buf.extend_from_slice(trace_info.as_bytes());
+            buf.put_u8(0x0); // null termination
+        }
     }
 
     pub fn is_empty<T: ByteOrder>(&self) -> bool {
@@ -782,12 +1074,12 @@ impl Argument {
         info: &TypeInfo,
         name: &Option<String>,
     ) -> BytesMut {
-        let mut capacity = TYPE_INFO_LENGTH + info.type_width();
+        let mut capacity = TYPE_INFO_LENGTH + self.trace_info_len() + info.type_width();
         if let Some(n) = name {
             capacity += 2 /* length name */ + n.len() + 1;
         }
         let mut buf = BytesMut::with_capacity(capacity);
-        buf.extend_from_slice(&info.as_bytes::<T>()[..]);
+        self.put_type_info::<T>(info, &mut buf);
         if let Some(n) = name {
             #[allow(deprecated)]
             buf.put_u16::<T>(n.len() as u16 + 1);
@@ -810,7 +1102,7 @@ impl Argument {
         //     unit,
         //     fixed_point
         // );
-        let mut capacity = TYPE_INFO_LENGTH;
+        let mut capacity = TYPE_INFO_LENGTH + self.trace_info_len();
         if info.has_variable_info {
             if let Some(n) = name {
                 capacity += 2 /* length name */ + n.len() + 1;
@@ -828,7 +1120,18 @@ impl Argument {
         }
         capacity += info.type_width();
         let mut buf = BytesMut::with_capacity(capacity);
-        buf.extend_from_slice(&info.as_bytes::<T>()[..]);
+        self.put_type_info::<T>(info, &mut buf);
+        Argument::put_name_unit_and_fixed_point::<T>(info, name, unit, fixed_point, &mut buf);
+        // println!("typeinfo + name + unit as bytes: {:02X?}", buf.to_vec());
+        buf
+    }
+    fn put_name_unit_and_fixed_point<T: ByteOrder>(
+        info: &TypeInfo,
+        name: &Option<String>,
+        unit: &Option<String>,
+        fixed_point: &Option<FixedPoint>,
+        buf: &mut BytesMut,
+    ) {
         if info.has_variable_info {
             if let Some(n) = name {
                 #[allow(deprecated)]
@@ -873,10 +1176,13 @@ impl Argument {
                     #[allow(deprecated)]
                     buf.put_i64::<T>(v);
                 }
+                FixedPointValue::I128(v) => {
+                    let mut b = [0; 16];
+                    T::write_i128(&mut b, v);
+                    buf.put_slice(&b);
+                }
             }
         }
-        // println!("typeinfo + name + unit as bytes: {:02X?}", buf.to_vec());
-        buf
     }
 
     pub fn as_bytes<T: ByteOrder>(self: &Argument) -> Vec<u8> {
@@ -939,36 +1245,58 @@ impl Argument {
                 buf.to_vec()
             }
             TypeInfoKind::Float(_) => {
-                fn write_value<T: ByteOrder>(value: &Value, buf: &mut BytesMut) {
-                    match value {
-                        Value::F32(v) => {
-                            let mut b = [0; 4];
-                            T::write_f32(&mut b, *v);
-                            buf.put_slice(&b)
-                        }
-                        Value::F64(v) => {
-                            let mut b = [0; 8];
-                            T::write_f64(&mut b, *v);
-                            buf.put_slice(&b)
-                        }
-                        _ => (),
-                    }
-                }
                 let mut buf = self.mut_buf_with_typeinfo_name_unit::<T>(
                     &self.type_info,
                     &self.name,
                     &self.unit,
                     &self.fixed_point,
                 );
-                write_value::<T>(&self.value, &mut buf);
+                put_float_value::<T>(&self.value, &mut buf);
                 dbg_bytes("float argument", &buf.to_vec()[..]);
                 buf.to_vec()
             }
-            // TypeInfoKind::Array => {
-            //     // TODO dlt array type not yet implemented NYI
-            //     eprintln!("found dlt array type...not yet supported");
-            //     BytesMut::with_capacity(STORAGE_HEADER_LENGTH).to_vec()
-            // }
+            TypeInfoKind::Array(element_kind) => match &self.value {
+                Value::Array(dimensions, elements) => {
+                    let mut buf = BytesMut::with_capacity(self.len_new());
+                    self.put_type_info::<T>(&self.type_info, &mut buf);
+                    #[allow(deprecated)]
+                    buf.put_u16::<T>(dimensions.len() as u16);
+                    for d in dimensions {
+                        #[allow(deprecated)]
+                        buf.put_u16::<T>(*d);
+                    }
+                    Argument::put_name_unit_and_fixed_point::<T>(
+                        &self.type_info,
+                        &self.name,
+                        &self.unit,
+                        &self.fixed_point,
+                        &mut buf,
+                    );
+                    for element in elements {
+                        match element_kind {
+                            ArrayElementKind::Bool => {
+                                if let Value::Bool(x) = element {
+                                    buf.put_u8(*x)
+                                }
+                            }
+                            ArrayElementKind::Signed(_) | ArrayElementKind::SignedFixedPoint(_) => {
+                                put_signed_value::<T>(element, &mut buf)
+                            }
+                            ArrayElementKind::Unsigned(_)
+                            | ArrayElementKind::UnsignedFixedPoint(_) => {
+                                put_unsigned_value::<T>(element, &mut buf)
+                            }
+                            ArrayElementKind::Float(_) => put_float_value::<T>(element, &mut buf),
+                        }
+                    }
+                    dbg_bytes("array argument", &buf.to_vec()[..]);
+                    buf.to_vec()
+                }
+                v => {
+                    error!("found invalid dlt entry for Array ({:?}", v);
+                    BytesMut::with_capacity(0).to_vec()
+                }
+            },
             TypeInfoKind::StringType => {
                 match (self.type_info.has_variable_info, &self.name) {
                     (true, Some(var_name)) => {
@@ -977,12 +1305,13 @@ impl Argument {
                                 let name_len_with_termination: u16 = var_name.len() as u16 + 1;
                                 let mut buf = BytesMut::with_capacity(
                                     TYPE_INFO_LENGTH +
+                                    self.trace_info_len() +
                                     2 /* length string */ +
                                     2 /* length name */ +
                                     name_len_with_termination as usize +
                                     s.len() + 1,
                                 );
-                                buf.extend_from_slice(&self.type_info.as_bytes::<T>()[..]);
+                                self.put_type_info::<T>(&self.type_info, &mut buf);
                                 #[allow(deprecated)]
                                 buf.put_u16::<T>(s.len() as u16 + 1);
                                 #[allow(deprecated)]
@@ -1005,10 +1334,11 @@ impl Argument {
                             Value::StringVal(s) => {
                                 let mut buf = BytesMut::with_capacity(
                                     TYPE_INFO_LENGTH +
+                                    self.trace_info_len() +
                                     2 /* length string */ +
                                     s.len() + 1,
                                 );
-                                buf.extend_from_slice(&self.type_info.as_bytes::<T>()[..]);
+                                self.put_type_info::<T>(&self.type_info, &mut buf);
                                 #[allow(deprecated)]
                                 buf.put_u16::<T>(s.len() as u16 + 1);
                                 buf.extend_from_slice(s.as_bytes());
@@ -1040,12 +1370,13 @@ impl Argument {
                                 let name_len_with_termination: u16 = var_name.len() as u16 + 1;
                                 let mut buf = BytesMut::with_capacity(
                                     TYPE_INFO_LENGTH +
+                                    self.trace_info_len() +
                                     2 /* length bytes */ +
                                     2 /* length name */ +
                                     name_len_with_termination as usize +
                                     bytes.len(),
                                 );
-                                buf.extend_from_slice(&self.type_info.as_bytes::<T>()[..]);
+                                self.put_type_info::<T>(&self.type_info, &mut buf);
                                 #[allow(deprecated)]
                                 buf.put_u16::<T>(bytes.len() as u16);
                                 #[allow(deprecated)]
@@ -1067,10 +1398,11 @@ impl Argument {
                             Value::Raw(bytes) => {
                                 let mut buf = BytesMut::with_capacity(
                                     TYPE_INFO_LENGTH +
+                                    self.trace_info_len() +
                                     2 /* length string */ +
                                     bytes.len(),
                                 );
-                                buf.extend_from_slice(&self.type_info.as_bytes::<T>()[..]);
+                                self.put_type_info::<T>(&self.type_info, &mut buf);
                                 #[allow(deprecated)]
                                 buf.put_u16::<T>(bytes.len() as u16);
                                 buf.extend_from_slice(bytes);
@@ -1089,6 +1421,29 @@ impl Argument {
                     }
                 }
             }
+            TypeInfoKind::Struct => match &self.value {
+                Value::Struct(members) => {
+                    let mut buf = BytesMut::with_capacity(self.len_new());
+                    self.put_type_info::<T>(&self.type_info, &mut buf);
+                    #[allow(deprecated)]
+                    buf.put_u16::<T>(members.len() as u16);
+                    if let (true, Some(var_name)) = (self.type_info.has_variable_info, &self.name) {
+                        #[allow(deprecated)]
+                        buf.put_u16::<T>(var_name.len() as u16 + 1);
+                        buf.extend_from_slice(var_name.as_bytes());
+                        buf.put_u8(0x0); // null termination
+                    }
+                    for member in members {
+                        buf.extend_from_slice(&member.as_bytes::<T>()[..]);
+                    }
+                    dbg_bytes("struct argument", &buf.to_vec()[..]);
+                    buf.to_vec()
+                }
+                v => {
+                    error!("found invalid dlt entry for Struct ({:?}", v);
+                    BytesMut::with_capacity(0).to_vec()
+                }
+            },
         }
     }
 }
@@ -1118,6 +1473,21 @@ fn put_unsigned_value<T: ByteOrder>(value: &Value, buf: &mut BytesMut) {
         _ => (),
     }
 }
+fn put_float_value<T: ByteOrder>(value: &Value, buf: &mut BytesMut) {
+    match value {
+        Value::F32(v) => {
+            let mut b = [0; 4];
+            T::write_f32(&mut b, *v);
+            buf.put_slice(&b)
+        }
+        Value::F64(v) => {
+            let mut b = [0; 8];
+            T::write_f64(&mut b, *v);
+            buf.put_slice(&b)
+        }
+        _ => (),
+    }
+}
 fn put_signed_value<T: ByteOrder>(value: &Value, buf: &mut BytesMut) {
     match value {
         Value::I8(v) => buf.put_i8(*v),
@@ -1263,7 +1633,7 @@ pub struct Message {
     pub extended_header: Option<ExtendedHeader>,
     pub payload: Payload2,
     #[serde(skip_serializing)]
-    pub fibex_metadata: Option<Rc<FibexMetadata>>,
+    pub fibex_metadata: Option<Arc<FibexMetadata>>,
 }
 pub const DLT_COLUMN_SENTINAL: char = '\u{0004}';
 pub const DLT_ARGUMENT_SENTINAL: char = '\u{0005}';
@@ -1310,7 +1680,7 @@ fn dbg_bytes(_name: &str, _bytes: &[u8]) {
 impl Message {
     pub fn new(
         conf: MessageConfig,
-        fibex: Option<Rc<FibexMetadata>>,
+        fibex: Option<Arc<FibexMetadata>>,
         storage_header: Option<StorageHeader>,
     ) -> Self {
         // println!("--- Message::new, conf = {:?}", conf);
@@ -1330,6 +1700,7 @@ impl Message {
                 timestamp: conf.timestamp,
                 has_extended_header: conf.extended_header_info.is_some(),
                 payload_length,
+                v2: None,
             },
             extended_header: match conf.extended_header_info {
                 Some(ext_info) => Some(ExtendedHeader {
@@ -1348,7 +1719,7 @@ impl Message {
     }
 
     pub fn as_bytes(self: &Message) -> Vec<u8> {
-        let mut capacity = self.header.overall_length() as usize;
+        let mut capacity = self.byte_len() as usize;
         let mut buf = if let Some(storage_header) = &self.storage_header {
             capacity += STORAGE_HEADER_LENGTH;
             let mut b = BytesMut::with_capacity(capacity);
@@ -1357,6 +1728,10 @@ impl Message {
         } else {
             BytesMut::with_capacity(capacity)
         };
+        if let Some(v2) = &self.header.v2 {
+            self.put_v2_message(v2, &mut buf);
+            return buf.to_vec();
+        }
         dbg_bytes("header", &self.header.as_bytes());
         buf.extend_from_slice(&self.header.as_bytes());
         if let Some(ext_header) = &self.extended_header {
@@ -1378,7 +1753,135 @@ impl Message {
     }
 
     pub fn byte_len(&self) -> u16 {
-        self.header.overall_length()
+        match &self.header.v2 {
+            Some(v2) => self.headers_length_v2(v2) + self.header.payload_length,
+            None => self.header.overall_length(),
+        }
+    }
+
+    /// content type (CNTI) of the version 2 header type
+    pub fn content_type_v2(&self) -> u8 {
+        match self.payload.payload_content {
+            PayloadContent::Verbose(_) => HTYP2_CONTENT_VERBOSE,
+            PayloadContent::NonVerbose(_, _) => HTYP2_CONTENT_NON_VERBOSE,
+            PayloadContent::ControlMsg(_, _) => HTYP2_CONTENT_CONTROL,
+        }
+    }
+
+    /// length of all version 2 header fields
+    /// like in version 1 the message id of non-verbose messages is counted
+    /// as part of the payload
+    fn headers_length_v2(&self, v2: &HeaderV2Fields) -> u16 {
+        let mut length =
+            HEADER_V2_MIN_LENGTH + calculate_conditional_header_length_v2(self.content_type_v2());
+        if let Some(id) = &self.header.ecu_id {
+            length += v2_string_len(id);
+        }
+        if v2.with_app_and_context_id {
+            length += match &self.extended_header {
+                Some(ext) => v2_string_len(&ext.application_id) + v2_string_len(&ext.context_id),
+                None => 2,
+            };
+        }
+        if self.header.session_id.is_some() {
+            length += 4;
+        }
+        if let Some(location) = &v2.source_location {
+            length += v2_string_len(&location.file_name) + 4;
+        }
+        if !v2.tags.is_empty() {
+            length += 1 + v2
+                .tags
+                .iter()
+                .take(u8::MAX as usize)
+                .map(|t| v2_string_len(t))
+                .sum::<u16>();
+        }
+        if v2.privacy_level.is_some() {
+            length += 1;
+        }
+        length
+    }
+
+    /// a version 2 message is always big endian and has no separate
+    /// extended header
+    fn put_v2_message(&self, v2: &HeaderV2Fields, buf: &mut BytesMut) {
+        let content_type = self.content_type_v2();
+        let header_type = v2.header_type(
+            content_type,
+            self.header.ecu_id.is_some(),
+            self.header.session_id.is_some(),
+        );
+        let mut header_type_bytes = [0u8; 4];
+        BigEndian::write_u32(&mut header_type_bytes, header_type);
+        buf.extend_from_slice(&header_type_bytes);
+        buf.extend_from_slice(&[self.header.message_counter]);
+        let mut length_bytes = [0u8; 2];
+        BigEndian::write_u16(&mut length_bytes, self.byte_len());
+        buf.extend_from_slice(&length_bytes);
+
+        let payload = self.payload.as_bytes::<BigEndian>();
+        let (message_id, payload) = if content_type == HTYP2_CONTENT_NON_VERBOSE {
+            payload.split_at(4)
+        } else {
+            payload.split_at(0)
+        };
+        let (message_info, argument_count) = match &self.extended_header {
+            Some(ext) => (
+                u8::from(&ext.message_type) | if ext.verbose { VERBOSE_FLAG } else { 0 },
+                ext.argument_count,
+            ),
+            None => (0, self.payload.arg_count()),
+        };
+        buf.extend_from_slice(&[message_info]);
+        if content_type != HTYP2_CONTENT_NON_VERBOSE {
+            buf.extend_from_slice(&[argument_count]);
+        }
+        if content_type != HTYP2_CONTENT_CONTROL {
+            let (seconds, nanoseconds) = match &v2.timestamp {
+                Some(t) => (t.seconds, t.nanoseconds),
+                None => (0, 0),
+            };
+            let mut timestamp_bytes = [0u8; 9];
+            BigEndian::write_u32(&mut timestamp_bytes[..4], nanoseconds);
+            BigEndian::write_uint(&mut timestamp_bytes[4..], seconds & 0xFF_FFFF_FFFF, 5);
+            buf.extend_from_slice(&timestamp_bytes);
+        }
+        buf.extend_from_slice(message_id);
+        if let Some(id) = &self.header.ecu_id {
+            buf.put_v2_string(id);
+        }
+        if v2.with_app_and_context_id {
+            match &self.extended_header {
+                Some(ext) => {
+                    buf.put_v2_string(&ext.application_id);
+                    buf.put_v2_string(&ext.context_id);
+                }
+                None => buf.extend_from_slice(&[0, 0]),
+            }
+        }
+        if let Some(id) = &self.header.session_id {
+            let mut session_id_bytes = [0u8; 4];
+            BigEndian::write_u32(&mut session_id_bytes, *id);
+            buf.extend_from_slice(&session_id_bytes);
+        }
+        if let Some(location) = &v2.source_location {
+            buf.put_v2_string(&location.file_name);
+            let mut line_bytes = [0u8; 4];
+            BigEndian::write_u32(&mut line_bytes, location.line_number);
+            buf.extend_from_slice(&line_bytes);
+        }
+        if !v2.tags.is_empty() {
+            buf.extend_from_slice(&[std::cmp::min(v2.tags.len(), u8::MAX as usize) as u8]);
+            for tag in v2.tags.iter().take(u8::MAX as usize) {
+                buf.put_v2_string(tag);
+            }
+        }
+        if let Some(level) = v2.privacy_level {
+            buf.extend_from_slice(&[level]);
+        }
+        dbg_bytes("--> v2 payload", payload);
+        buf.extend_from_slice(payload);
     }
 
     pub fn add_storage_header(mut self, time_stamp: Option<DltTimeStamp>) -> Self {
@@ -1431,6 +1934,9 @@ impl Message {
                 )?;
             }
         };
+        if let Some(v2) = &self.header.v2 {
+            write!(f, "{}", v2)?;
+        }
         Ok(())
     }
 }
@@ -1486,6 +1992,25 @@ pub const WITH_SESSION_ID_FLAG: u8 = 1 << 3;
 pub const WITH_TIMESTAMP_FLAG: u8 = 1 << 4;
 pub const HEADER_MIN_LENGTH: u16 = 4;
 
+// Standard header version 2 (AUTOSAR R19-11+)
+// the header type (HTYP2) has 32 bits and is stored big endian like all other
+// fields, the version is in the last byte (see `is_v2_header`)
+pub const DLT_V2_VERSION: u8 = 2;
+pub const HTYP2_CONTENT_VERBOSE: u8 = 0b00;
+pub const HTYP2_CONTENT_NON_VERBOSE: u8 = 0b01;
+pub const HTYP2_CONTENT_CONTROL: u8 = 0b10;
+pub const HTYP2_WITH_ECU_ID_FLAG: u32 = 1 << 2;
+pub const HTYP2_WITH_APP_AND_CONTEXT_ID_FLAG: u32 = 1 << 3;
+pub const HTYP2_WITH_SESSION_ID_FLAG: u32 = 1 << 4;
+pub const HTYP2_WITH_SOURCE_FILE_AND_LINE_FLAG: u32 = 1 << 8;
+pub const HTYP2_WITH_TAGS_FLAG: u32 = 1 << 9;
+pub const HTYP2_WITH_PRIVACY_LEVEL_FLAG: u32 = 1 << 10;
+pub const HTYP2_WITH_SEGMENTATION_FLAG: u32 = 1 << 11;
+/// header type (4 bytes), message counter and length
+pub const HEADER_V2_MIN_LENGTH: u16 = 7;
+/// nanoseconds (32 bit) and seconds (40 bit)
+pub const TIMESTAMP_V2_LENGTH: u16 = 9;
+
 // Verbose Mode
 
 // Extended header
@@ -1498,13 +2023,12 @@ pub const TYPE_INFO_BOOL_FLAG: u32 = 1 << 4;
 pub const TYPE_INFO_SINT_FLAG: u32 = 1 << 5;
 pub const TYPE_INFO_UINT_FLAG: u32 = 1 << 6;
 pub const TYPE_INFO_FLOAT_FLAG: u32 = 1 << 7;
-pub const _TYPE_INFO_ARRAY_FLAG: u32 = 1 << 8;
+pub const TYPE_INFO_ARRAY_FLAG: u32 = 1 << 8;
 pub const TYPE_INFO_STRING_FLAG: u32 = 1 << 9;
 pub const TYPE_INFO_RAW_FLAG: u32 = 1 << 10;
 pub const TYPE_INFO_VARIABLE_INFO: u32 = 1 << 11;
 pub const TYPE_INFO_FIXED_POINT_FLAG: u32 = 1 << 12;
 pub const TYPE_INFO_TRACE_INFO_FLAG: u32 = 1 << 13;
-#[allow(dead_code)]
 pub const TYPE_INFO_STRUCT_FLAG: u32 = 1 << 14;
 
 // TODO use header struct not u8
@@ -1531,6 +2055,19 @@ pub fn calculate_all_headers_length(header_type: u8) -> u16 {
     length
 }
 
+/// length of the conditional part of a version 2 header without the
+/// message id of non-verbose messages
+pub fn calculate_conditional_header_length_v2(content_type: u8) -> u16 {
+    match content_type {
+        // message info, number of arguments, timestamp
+        HTYP2_CONTENT_VERBOSE => 2 + TIMESTAMP_V2_LENGTH,
+        // message info, timestamp
+        HTYP2_CONTENT_NON_VERBOSE => 1 + TIMESTAMP_V2_LENGTH,
+        // message info, number of arguments
+        _ => 2,
+    }
+}
+
 pub fn zero_terminated_string(raw: &[u8]) -> Result<String, Error> {
     let nul_range_end = raw
         .iter()
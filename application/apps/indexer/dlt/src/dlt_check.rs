@@ -0,0 +1,437 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+
+//! Checks DLT files for damaged content
+//!
+//! The file is parsed like the indexer does it, but every byte that cannot be
+//! attributed to a valid message is recorded in a `CorruptionReport`.
+use crate::dlt::DltTimeStamp;
+use crate::dlt_parse::{
+    dlt_message_view, forward_to_next_message, DltFraming, DltParseError, DLT_PATTERN_SIZE,
+    DLT_READER_CAPACITY,
+};
+use crate::dlt_reader::{DltReader, DltReaderMode};
+use crossbeam_channel as cc;
+use failure::{err_msg, Error};
+use indexer_base::{progress::*, utils};
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::io::BufRead;
+use std::path::PathBuf;
+
+const PROGRESS_MESSAGE_THRESHOLD: usize = 250_000;
+
+/// why a range of bytes could not be used
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub enum CorruptionReason {
+    /// bytes that do not belong to any message
+    Garbage,
+    /// a message header could not be parsed
+    InvalidHeader(String),
+    /// the length in the header is smaller than the header itself
+    InvalidLength,
+    /// the headers were fine but the payload could not be parsed
+    InvalidPayload(String),
+    /// the message could not be parsed, used where headers and payload are parsed at once
+    InvalidMessage(String),
+    /// the file ends in the middle of a message
+    Truncated,
+}
+
+/// the last message that could be parsed before a corrupted range
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MessageLocation {
+    /// number of the message, the same as the line in the index
+    pub index: usize,
+    /// offset of the message in the file
+    pub offset: u64,
+    pub length: usize,
+    pub message_counter: u8,
+    pub timestamp: Option<DltTimeStamp>,
+}
+
+/// a range of bytes that was skipped while parsing
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CorruptedRange {
+    pub start: u64,
+    /// offset of the first byte after the range
+    pub end: u64,
+    pub reason: CorruptionReason,
+    /// messages that started in this range but could not be parsed
+    pub messages_lost: usize,
+    pub last_valid_message: Option<MessageLocation>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct CorruptionReport {
+    pub file_size: u64,
+    pub valid_messages: usize,
+    pub messages_lost: usize,
+    pub corrupted_bytes: u64,
+    pub ranges: Vec<CorruptedRange>,
+}
+
+impl CorruptionReport {
+    pub fn is_corrupted(&self) -> bool {
+        !self.ranges.is_empty()
+    }
+}
+
+pub type CorruptionResults = std::result::Result<IndexingProgress<CorruptionReport>, Notification>;
+
+impl fmt::Display for CorruptionReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CorruptionReason::Garbage => write!(f, "garbage"),
+            CorruptionReason::InvalidHeader(reason) => write!(f, "invalid header: {}", reason),
+            CorruptionReason::InvalidLength => write!(f, "invalid length"),
+            CorruptionReason::InvalidPayload(reason) => write!(f, "invalid payload: {}", reason),
+            CorruptionReason::InvalidMessage(reason) => write!(f, "invalid message: {}", reason),
+            CorruptionReason::Truncated => write!(f, "truncated message"),
+        }
+    }
+}
+
+impl fmt::Display for CorruptedRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "skipped bytes {}..{} ({}), {} message(s) lost",
+            self.start, self.end, self.reason, self.messages_lost
+        )?;
+        match &self.last_valid_message {
+            Some(msg) => write!(
+                f,
+                ", last valid message #{} at offset {}",
+                msg.index, msg.offset
+            ),
+            None => write!(f, ", no valid message before"),
+        }
+    }
+}
+
+/// collects the corrupted ranges, adjacent ranges are merged into one
+///
+/// shared by the check, the indexer and the statistics, every range is sent as a
+/// warning on the `update_channel` as soon as it is complete
+pub(crate) struct CorruptionCollector<T> {
+    report: CorruptionReport,
+    open_range: Option<CorruptedRange>,
+    last_valid_message: Option<MessageLocation>,
+    update_channel: cc::Sender<IndexingResults<T>>,
+}
+
+impl<T> CorruptionCollector<T> {
+    pub(crate) fn new(file_size: u64, update_channel: cc::Sender<IndexingResults<T>>) -> Self {
+        CorruptionCollector {
+            report: CorruptionReport {
+                file_size,
+                ..Default::default()
+            },
+            open_range: None,
+            last_valid_message: None,
+            update_channel,
+        }
+    }
+
+    /// true if the file ends within the next `available` bytes after `offset`
+    pub(crate) fn at_end(&self, offset: u64, available: usize) -> bool {
+        offset + available as u64 >= self.report.file_size
+    }
+
+    pub(crate) fn skipped(
+        &mut self,
+        start: u64,
+        length: usize,
+        reason: CorruptionReason,
+        lost: usize,
+    ) {
+        self.report.corrupted_bytes += length as u64;
+        self.report.messages_lost += lost;
+        if let Some(range) = self.open_range.as_mut() {
+            if range.end == start {
+                range.end += length as u64;
+                range.messages_lost += lost;
+                // garbage is usually the consequence of a broken message
+                if range.reason == CorruptionReason::Garbage {
+                    range.reason = reason;
+                }
+                return;
+            }
+        }
+        self.close_range();
+        self.open_range = Some(CorruptedRange {
+            start,
+            end: start + length as u64,
+            reason,
+            messages_lost: lost,
+            last_valid_message: self.last_valid_message.clone(),
+        });
+    }
+
+    pub(crate) fn valid_message(&mut self, location: MessageLocation) {
+        self.close_range();
+        self.report.valid_messages += 1;
+        self.last_valid_message = Some(location);
+    }
+
+    /// a valid message that is not known in detail (e.g. it was filtered out),
+    /// the last valid message stays the one before
+    pub(crate) fn other_valid_message(&mut self) {
+        self.close_range();
+        self.report.valid_messages += 1;
+    }
+
+    fn close_range(&mut self) {
+        if let Some(range) = self.open_range.take() {
+            let _ = self.update_channel.send(Err(Notification {
+                severity: Severity::WARNING,
+                content: range.to_string(),
+                line: range.last_valid_message.as_ref().map(|msg| msg.index),
+            }));
+            self.report.ranges.push(range);
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> CorruptionReport {
+        self.close_range();
+        self.report
+    }
+}
+
+/// a part of a file as it was found by the parser
+#[derive(Debug, Clone)]
+pub(crate) enum ParsedPart {
+    /// consecutive valid messages, with the location of the last one that is known in detail
+    Messages {
+        count: usize,
+        last: Option<MessageLocation>,
+    },
+    Skipped {
+        start: u64,
+        length: usize,
+        reason: CorruptionReason,
+        lost: usize,
+    },
+}
+
+/// records the parts of a file so the corrupted ranges can be collected later
+///
+/// used when a file is parsed in partitions: the parts of all partitions are
+/// replayed in order, so ranges that span partitions are merged as well
+#[derive(Debug)]
+pub(crate) struct ParsedParts {
+    file_size: u64,
+    parts: Vec<ParsedPart>,
+}
+
+impl ParsedParts {
+    pub(crate) fn new(file_size: u64) -> Self {
+        ParsedParts {
+            file_size,
+            parts: vec![],
+        }
+    }
+
+    /// true if the file ends within the next `available` bytes after `offset`
+    pub(crate) fn at_end(&self, offset: u64, available: usize) -> bool {
+        offset + available as u64 >= self.file_size
+    }
+
+    pub(crate) fn skipped(
+        &mut self,
+        start: u64,
+        length: usize,
+        reason: CorruptionReason,
+        lost: usize,
+    ) {
+        self.parts.push(ParsedPart::Skipped {
+            start,
+            length,
+            reason,
+            lost,
+        });
+    }
+
+    /// a valid message, `location` is `None` if it is not known in detail
+    pub(crate) fn valid_message(&mut self, location: Option<MessageLocation>) {
+        if let Some(ParsedPart::Messages { count, last }) = self.parts.last_mut() {
+            *count += 1;
+            if location.is_some() {
+                *last = location;
+            }
+            return;
+        }
+        self.parts.push(ParsedPart::Messages {
+            count: 1,
+            last: location,
+        });
+    }
+
+    /// feeds the recorded parts to `collector`, the message indices are shifted by `first_index`
+    pub(crate) fn replay<T>(self, collector: &mut CorruptionCollector<T>, first_index: usize) {
+        for part in self.parts {
+            match part {
+                ParsedPart::Messages { count, last } => {
+                    collector.close_range();
+                    collector.report.valid_messages += count;
+                    if let Some(location) = last {
+                        collector.last_valid_message = Some(MessageLocation {
+                            index: location.index + first_index,
+                            ..location
+                        });
+                    }
+                }
+                ParsedPart::Skipped {
+                    start,
+                    length,
+                    reason,
+                    lost,
+                } => collector.skipped(start, length, reason, lost),
+            }
+        }
+    }
+}
+
+/// check a dlt file for corrupted content
+///
+/// every corrupted range is reported as a notification as soon as it is complete,
+/// the complete `CorruptionReport` is sent at the end and also returned
+pub fn check_dlt_file(
+    in_file: &PathBuf,
+    framing: DltFraming,
+    reader_mode: DltReaderMode,
+    update_channel: &cc::Sender<CorruptionResults>,
+    shutdown_receiver: Option<cc::Receiver<()>>,
+) -> Result<CorruptionReport, Error> {
+    let file_size = fs::metadata(in_file)?.len();
+    let mut reader = DltReader::open(in_file, 0, DLT_READER_CAPACITY, reader_mode)
+        .map_err(|e| err_msg(format!("could not open {:?} ({})", in_file, e)))?;
+    let mut collector = CorruptionCollector::new(file_size, update_channel.clone());
+    let mut offset = 0u64;
+    let mut attempts = 0usize;
+    loop {
+        let content = reader.fill_buf()?;
+        if content.is_empty() {
+            break;
+        }
+        let available = content.len();
+        let at_end = collector.at_end(offset, available);
+        let dropped = garbage_length(content, framing, at_end);
+        if dropped > 0 {
+            collector.skipped(offset, dropped, CorruptionReason::Garbage, 0);
+            reader.consume(dropped);
+            offset += dropped as u64;
+            continue;
+        }
+        let consumed = match dlt_message_view::<()>(content, None, None, framing) {
+            Ok((rest, Some(view))) => {
+                let length = available - rest.len();
+                match view.payload() {
+                    Ok(_) => {
+                        collector.valid_message(MessageLocation {
+                            index: collector.report.valid_messages,
+                            offset,
+                            length,
+                            message_counter: view.header.message_counter,
+                            timestamp: view.storage_header.map(|h| h.timestamp),
+                        });
+                        length
+                    }
+                    Err(e) => {
+                        collector.skipped(
+                            offset,
+                            length,
+                            CorruptionReason::InvalidPayload(error_reason(e)),
+                            1,
+                        );
+                        length
+                    }
+                }
+            }
+            Ok((rest, None)) => {
+                let length = available - rest.len();
+                collector.skipped(offset, length, CorruptionReason::InvalidLength, 1);
+                length
+            }
+            Err(DltParseError::IncompleteParse { .. }) if at_end => {
+                // the length might be broken, so look for more messages after the header
+                let length = std::cmp::min(DLT_PATTERN_SIZE, available);
+                collector.skipped(offset, length, CorruptionReason::Truncated, 1);
+                length
+            }
+            Err(DltParseError::IncompleteParse { needed }) => {
+                // the message does not fit into the buffer of the reader
+                update_channel.send(Err(Notification {
+                    severity: Severity::ERROR,
+                    content: format!(
+                        "cannot check message at offset {} (needs {:?} more bytes)",
+                        offset, needed
+                    ),
+                    line: None,
+                }))?;
+                break;
+            }
+            Err(e) => {
+                let length = std::cmp::min(DLT_PATTERN_SIZE, available);
+                collector.skipped(
+                    offset,
+                    length,
+                    CorruptionReason::InvalidHeader(error_reason(e)),
+                    1,
+                );
+                length
+            }
+        };
+        reader.consume(consumed);
+        offset += consumed as u64;
+        attempts += 1;
+        if attempts == PROGRESS_MESSAGE_THRESHOLD {
+            attempts = 0;
+            if utils::check_if_stop_was_requested(&shutdown_receiver, "dlt check") {
+                update_channel.send(Ok(IndexingProgress::Stopped))?;
+                break;
+            }
+            update_channel.send(Ok(IndexingProgress::Progress {
+                ticks: (offset as usize, file_size as usize),
+            }))?;
+        }
+    }
+    let report = collector.finish();
+    update_channel.send(Ok(IndexingProgress::GotItem {
+        item: report.clone(),
+    }))?;
+    update_channel.send(Ok(IndexingProgress::Finished))?;
+    Ok(report)
+}
+
+/// number of bytes before the next storage (or serial) header in `content`
+///
+/// `at_end` tells if `content` reaches up to the end of the file, otherwise the
+/// last bytes are kept because the header pattern could start there
+pub(crate) fn garbage_length(content: &[u8], framing: DltFraming, at_end: bool) -> usize {
+    let available = content.len();
+    match forward_to_next_message(content, framing) {
+        Some((dropped, _)) => dropped,
+        None if at_end => available,
+        None => available.saturating_sub(DLT_PATTERN_SIZE - 1),
+    }
+}
+
+pub(crate) fn error_reason(e: DltParseError) -> String {
+    match e {
+        DltParseError::Unrecoverable { cause } => cause,
+        DltParseError::ParsingHickup { reason } => reason,
+        DltParseError::IncompleteParse { needed } => format!("incomplete, needed {:?}", needed),
+    }
+}
@@ -0,0 +1,656 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+
+//! Decoding of the payload of DLT control messages
+//!
+//! The payload of a control message starts with the service id (uint32 in the
+//! byte order of the message). Requests and responses of the standard services
+//! are decoded into a `ControlPayload`, responses always start with a status byte.
+use crate::dlt::*;
+use crate::dlt_parse::{dlt_zero_terminated_string, DltParseError, NomByteOrder};
+use crate::dlt_view::{payload_parse_error, MessageView};
+use crate::service_id::SERVICE_ID_MAPPING;
+use byteorder::{BigEndian, LittleEndian};
+use nom::{combinator::rest, number::streaming::be_u8, IResult};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+
+pub const SERVICE_SET_LOG_LEVEL: u32 = 0x01;
+pub const SERVICE_GET_LOG_INFO: u32 = 0x03;
+pub const SERVICE_GET_DEFAULT_LOG_LEVEL: u32 = 0x04;
+pub const SERVICE_GET_SOFTWARE_VERSION: u32 = 0x13;
+pub const SERVICE_BUFFER_OVERFLOW_NOTIFICATION: u32 = 0x23;
+
+/// get_log_info options: ids with log level and trace status and descriptions
+pub const LOG_INFO_WITH_DESCRIPTIONS: u8 = 7;
+
+/// status byte at the start of every control response
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum ResponseStatus {
+    Ok,
+    NotSupported,
+    Error,
+    /// get_log_info succeeded, the value tells which information is included (3..=7)
+    LogInfo(u8),
+    /// get_log_info found no matching context
+    NoMatchingContextId,
+    /// get_log_info response would have been too big
+    Overflow,
+    Unknown(u8),
+}
+
+impl From<u8> for ResponseStatus {
+    fn from(v: u8) -> Self {
+        match v {
+            0 => ResponseStatus::Ok,
+            1 => ResponseStatus::NotSupported,
+            2 => ResponseStatus::Error,
+            3..=7 => ResponseStatus::LogInfo(v),
+            8 => ResponseStatus::NoMatchingContextId,
+            9 => ResponseStatus::Overflow,
+            n => ResponseStatus::Unknown(n),
+        }
+    }
+}
+
+impl ResponseStatus {
+    pub fn is_ok(self) -> bool {
+        matches!(self, ResponseStatus::Ok | ResponseStatus::LogInfo(_))
+    }
+}
+
+/// log level or trace status of a control message
+///
+/// besides the log levels, -1 stands for the default value and 0 for off
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ControlLogLevel(pub i8);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContextInfo {
+    pub context_id: String,
+    pub log_level: Option<ControlLogLevel>,
+    pub trace_status: Option<ControlLogLevel>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ApplicationInfo {
+    pub application_id: String,
+    pub contexts: Vec<ContextInfo>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum ControlPayload {
+    SetLogLevelRequest {
+        application_id: String,
+        context_id: String,
+        log_level: ControlLogLevel,
+        com_interface: String,
+    },
+    GetLogInfoRequest {
+        options: u8,
+        application_id: String,
+        context_id: String,
+        com_interface: String,
+    },
+    GetLogInfoResponse {
+        status: ResponseStatus,
+        applications: Vec<ApplicationInfo>,
+        com_interface: Option<String>,
+    },
+    GetDefaultLogLevelRequest,
+    GetDefaultLogLevelResponse {
+        status: ResponseStatus,
+        log_level: Option<ControlLogLevel>,
+    },
+    GetSoftwareVersionRequest,
+    GetSoftwareVersionResponse {
+        status: ResponseStatus,
+        version: Option<String>,
+    },
+    BufferOverflowNotification {
+        status: ResponseStatus,
+        overflow_counter: Option<u32>,
+    },
+    /// response of a service that has no further data to decode
+    Response {
+        service_id: u32,
+        status: ResponseStatus,
+    },
+    /// request of a service that is not decoded
+    Request {
+        service_id: u32,
+        data: Vec<u8>,
+    },
+}
+
+impl ControlPayload {
+    pub fn service_id(&self) -> u32 {
+        match self {
+            ControlPayload::SetLogLevelRequest { .. } => SERVICE_SET_LOG_LEVEL,
+            ControlPayload::GetLogInfoRequest { .. }
+            | ControlPayload::GetLogInfoResponse { .. } => SERVICE_GET_LOG_INFO,
+            ControlPayload::GetDefaultLogLevelRequest
+            | ControlPayload::GetDefaultLogLevelResponse { .. } => SERVICE_GET_DEFAULT_LOG_LEVEL,
+            ControlPayload::GetSoftwareVersionRequest
+            | ControlPayload::GetSoftwareVersionResponse { .. } => SERVICE_GET_SOFTWARE_VERSION,
+            ControlPayload::BufferOverflowNotification { .. } => {
+                SERVICE_BUFFER_OVERFLOW_NOTIFICATION
+            }
+            ControlPayload::Response { service_id, .. }
+            | ControlPayload::Request { service_id, .. } => *service_id,
+        }
+    }
+
+    /// the name of the service as listed in `SERVICE_ID_MAPPING`
+    pub fn service_name(&self) -> Option<&'static str> {
+        service_name(self.service_id())
+    }
+}
+
+pub fn service_name(service_id: u32) -> Option<&'static str> {
+    u8::try_from(service_id)
+        .ok()
+        .and_then(|id| SERVICE_ID_MAPPING.get(&id))
+        .map(|(name, _desc)| *name)
+}
+
+/// decode the payload of a control message
+///
+/// `payload` is the complete payload including the service id
+pub fn decode_control_payload(
+    control_type: &ControlType,
+    endianness: Endianness,
+    payload: &[u8],
+) -> Result<ControlPayload, DltParseError> {
+    let res = if endianness == Endianness::Big {
+        control_payload::<BigEndian>(control_type, payload)
+    } else {
+        control_payload::<LittleEndian>(control_type, payload)
+    };
+    res.map(|(_, payload)| payload).map_err(payload_parse_error)
+}
+
+fn control_payload<'a, T: NomByteOrder>(
+    control_type: &ControlType,
+    input: &'a [u8],
+) -> IResult<&'a [u8], ControlPayload> {
+    let (input, service_id) = T::parse_u32(input)?;
+    match control_type {
+        ControlType::Response => control_response::<T>(service_id, input),
+        _ => control_request(service_id, input),
+    }
+}
+
+fn control_id(input: &[u8]) -> IResult<&[u8], String> {
+    let (rest, id) = dlt_zero_terminated_string(input, 4)?;
+    Ok((rest, id.to_string()))
+}
+
+fn control_log_level(input: &[u8]) -> IResult<&[u8], ControlLogLevel> {
+    let (rest, level) = be_u8(input)?;
+    Ok((rest, ControlLogLevel(level as i8)))
+}
+
+fn control_request(service_id: u32, input: &[u8]) -> IResult<&[u8], ControlPayload> {
+    match service_id {
+        SERVICE_SET_LOG_LEVEL => {
+            let (input, application_id) = control_id(input)?;
+            let (input, context_id) = control_id(input)?;
+            let (input, log_level) = control_log_level(input)?;
+            let (input, com_interface) = control_id(input)?;
+            Ok((
+                input,
+                ControlPayload::SetLogLevelRequest {
+                    application_id,
+                    context_id,
+                    log_level,
+                    com_interface,
+                },
+            ))
+        }
+        SERVICE_GET_LOG_INFO => {
+            let (input, options) = be_u8(input)?;
+            let (input, application_id) = control_id(input)?;
+            let (input, context_id) = control_id(input)?;
+            let (input, com_interface) = control_id(input)?;
+            Ok((
+                input,
+                ControlPayload::GetLogInfoRequest {
+                    options,
+                    application_id,
+                    context_id,
+                    com_interface,
+                },
+            ))
+        }
+        SERVICE_GET_DEFAULT_LOG_LEVEL => Ok((input, ControlPayload::GetDefaultLogLevelRequest)),
+        SERVICE_GET_SOFTWARE_VERSION => Ok((input, ControlPayload::GetSoftwareVersionRequest)),
+        _ => {
+            let (input, data) = rest(input)?;
+            Ok((
+                input,
+                ControlPayload::Request {
+                    service_id,
+                    data: data.to_vec(),
+                },
+            ))
+        }
+    }
+}
+
+fn control_response<T: NomByteOrder>(
+    service_id: u32,
+    input: &[u8],
+) -> IResult<&[u8], ControlPayload> {
+    let (input, status) = be_u8(input)?;
+    let status = ResponseStatus::from(status);
+    match service_id {
+        SERVICE_GET_LOG_INFO => {
+            let (input, applications) = match status {
+                ResponseStatus::LogInfo(options) => log_info::<T>(options, input)?,
+                _ => (input, vec![]),
+            };
+            let (input, com_interface) = if input.len() >= 4 {
+                let (input, com_interface) = control_id(input)?;
+                (input, Some(com_interface))
+            } else {
+                (input, None)
+            };
+            Ok((
+                input,
+                ControlPayload::GetLogInfoResponse {
+                    status,
+                    applications,
+                    com_interface,
+                },
+            ))
+        }
+        SERVICE_GET_DEFAULT_LOG_LEVEL => {
+            let (input, log_level) = if status.is_ok() {
+                let (input, level) = control_log_level(input)?;
+                (input, Some(level))
+            } else {
+                (input, None)
+            };
+            Ok((
+                input,
+                ControlPayload::GetDefaultLogLevelResponse { status, log_level },
+            ))
+        }
+        SERVICE_GET_SOFTWARE_VERSION => {
+            let (input, version) = if status.is_ok() {
+                let (input, length) = T::parse_u32(input)?;
+                let (input, version) = dlt_zero_terminated_string(input, length as usize)?;
+                (input, Some(version.to_string()))
+            } else {
+                (input, None)
+            };
+            Ok((
+                input,
+                ControlPayload::GetSoftwareVersionResponse { status, version },
+            ))
+        }
+        SERVICE_BUFFER_OVERFLOW_NOTIFICATION => {
+            let (input, overflow_counter) = if input.len() >= 4 {
+                let (input, counter) = T::parse_u32(input)?;
+                (input, Some(counter))
+            } else {
+                (input, None)
+            };
+            Ok((
+                input,
+                ControlPayload::BufferOverflowNotification {
+                    status,
+                    overflow_counter,
+                },
+            ))
+        }
+        _ => Ok((input, ControlPayload::Response { service_id, status })),
+    }
+}
+
+/// the application and context information of a get_log_info response
+///
+/// `options` tells which information is included:
+/// 4: log level, 5: trace status, 6: both, 7: both and descriptions
+fn log_info<T: NomByteOrder>(options: u8, input: &[u8]) -> IResult<&[u8], Vec<ApplicationInfo>> {
+    let with_log_level = matches!(options, 4 | 6 | 7);
+    let with_trace_status = matches!(options, 5..=7);
+    let with_descriptions = options == LOG_INFO_WITH_DESCRIPTIONS;
+    let (mut input, app_count) = T::parse_u16(input)?;
+    let mut applications = Vec::with_capacity(app_count as usize);
+    for _ in 0..app_count {
+        let (i, application_id) = control_id(input)?;
+        let (i, context_count) = T::parse_u16(i)?;
+        input = i;
+        let mut contexts = Vec::with_capacity(context_count as usize);
+        for _ in 0..context_count {
+            let (i, context_id) = control_id(input)?;
+            let (i, log_level) = if with_log_level {
+                let (i, level) = control_log_level(i)?;
+                (i, Some(level))
+            } else {
+                (i, None)
+            };
+            let (i, trace_status) = if with_trace_status {
+                let (i, status) = control_log_level(i)?;
+                (i, Some(status))
+            } else {
+                (i, None)
+            };
+            let (i, description) = if with_descriptions {
+                let (i, description) = description::<T>(i)?;
+                (i, Some(description))
+            } else {
+                (i, None)
+            };
+            input = i;
+            contexts.push(ContextInfo {
+                context_id,
+                log_level,
+                trace_status,
+                description,
+            });
+        }
+        let (i, description) = if with_descriptions {
+            let (i, description) = description::<T>(input)?;
+            (i, Some(description))
+        } else {
+            (input, None)
+        };
+        input = i;
+        applications.push(ApplicationInfo {
+            application_id,
+            contexts,
+            description,
+        });
+    }
+    Ok((input, applications))
+}
+
+fn description<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], String> {
+    let (input, length) = T::parse_u16(input)?;
+    let (input, description) = dlt_zero_terminated_string(input, length as usize)?;
+    Ok((input, description.to_string()))
+}
+
+impl Message {
+    /// decode the payload if this is a control message
+    pub fn control_payload(&self) -> Option<Result<ControlPayload, DltParseError>> {
+        match (&self.payload.payload_content, &self.extended_header) {
+            (PayloadContent::ControlMsg(first, data), Some(ext)) => match &ext.message_type {
+                MessageType::Control(control_type) => {
+                    // the parser keeps the first byte of the payload separately
+                    let mut payload = Vec::with_capacity(data.len() + 1);
+                    payload.push(first.value());
+                    payload.extend_from_slice(data);
+                    Some(decode_control_payload(
+                        control_type,
+                        self.header.endianness,
+                        &payload,
+                    ))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl<'a> MessageView<'a> {
+    /// decode the payload if this is a control message
+    pub fn control_payload(&self) -> Option<Result<ControlPayload, DltParseError>> {
+        match self.extended_header.as_ref().map(|ext| &ext.message_type) {
+            Some(MessageType::Control(control_type)) => Some(decode_control_payload(
+                control_type,
+                self.header.endianness,
+                self.payload,
+            )),
+            _ => None,
+        }
+    }
+
+    /// the applications of a successful get_log_info response
+    pub fn log_info(&self) -> Option<Vec<ApplicationInfo>> {
+        match self.extended_header.as_ref().map(|ext| &ext.message_type) {
+            Some(MessageType::Control(ControlType::Response)) => (),
+            _ => return None,
+        }
+        match self.control_payload() {
+            Some(Ok(ControlPayload::GetLogInfoResponse { applications, .. })) => Some(applications),
+            _ => None,
+        }
+    }
+}
+
+/// what is known about an application from get_log_info responses
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct RegisteredApplication {
+    pub description: Option<String>,
+    pub contexts: BTreeMap<String, RegisteredContext>,
+}
+
+/// what is known about a context from get_log_info responses
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct RegisteredContext {
+    pub description: Option<String>,
+    pub log_level: Option<ControlLogLevel>,
+    pub trace_status: Option<ControlLogLevel>,
+}
+
+/// applications and contexts per ECU as reported in get_log_info responses
+///
+/// later responses update what was reported before, descriptions are only
+/// replaced by non-empty descriptions
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct EcuRegistry {
+    ecus: BTreeMap<String, BTreeMap<String, RegisteredApplication>>,
+}
+
+impl EcuRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ecus.is_empty()
+    }
+
+    pub fn add_log_info(&mut self, ecu_id: &str, applications: &[ApplicationInfo]) {
+        let registered_apps = self.ecus.entry(ecu_id.to_string()).or_default();
+        for app in applications {
+            let registered_app = registered_apps
+                .entry(app.application_id.clone())
+                .or_default();
+            update_description(&mut registered_app.description, &app.description);
+            for context in &app.contexts {
+                let registered_context = registered_app
+                    .contexts
+                    .entry(context.context_id.clone())
+                    .or_default();
+                update_description(&mut registered_context.description, &context.description);
+                if context.log_level.is_some() {
+                    registered_context.log_level = context.log_level;
+                }
+                if context.trace_status.is_some() {
+                    registered_context.trace_status = context.trace_status;
+                }
+            }
+        }
+    }
+
+    pub fn applications(&self, ecu_id: &str) -> Option<&BTreeMap<String, RegisteredApplication>> {
+        self.ecus.get(ecu_id)
+    }
+
+    pub fn application_description(&self, ecu_id: &str, app_id: &str) -> Option<&str> {
+        self.ecus
+            .get(ecu_id)
+            .and_then(|apps| apps.get(app_id))
+            .and_then(|app| app.description.as_deref())
+    }
+
+    pub fn context_description(
+        &self,
+        ecu_id: &str,
+        app_id: &str,
+        context_id: &str,
+    ) -> Option<&str> {
+        self.ecus
+            .get(ecu_id)
+            .and_then(|apps| apps.get(app_id))
+            .and_then(|app| app.contexts.get(context_id))
+            .and_then(|context| context.description.as_deref())
+    }
+
+    /// the app id together with its description, e.g. "APP1 – Navigation service"
+    pub fn application_label(&self, ecu_id: &str, app_id: &str) -> String {
+        match self.application_description(ecu_id, app_id) {
+            Some(description) => format!("{} \u{2013} {}", app_id, description),
+            None => app_id.to_string(),
+        }
+    }
+}
+
+fn update_description(registered: &mut Option<String>, reported: &Option<String>) {
+    if let Some(description) = reported {
+        if !description.is_empty() {
+            *registered = Some(description.clone());
+        }
+    }
+}
+
+impl fmt::Display for ResponseStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResponseStatus::Ok => f.write_str("ok"),
+            ResponseStatus::NotSupported => f.write_str("not supported"),
+            ResponseStatus::Error => f.write_str("error"),
+            ResponseStatus::LogInfo(_) => f.write_str("ok"),
+            ResponseStatus::NoMatchingContextId => f.write_str("no matching context id"),
+            ResponseStatus::Overflow => f.write_str("overflow"),
+            ResponseStatus::Unknown(n) => write!(f, "unknown status {}", n),
+        }
+    }
+}
+
+impl fmt::Display for ControlLogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            -1 => f.write_str("default"),
+            0 => f.write_str("off"),
+            n => match u8_to_log_level(n as u8) {
+                Some(level) => write!(f, "{}", level),
+                None => write!(f, "{}", n),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ContextInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.context_id)?;
+        if let Some(level) = &self.log_level {
+            write!(f, " level={}", level)?;
+        }
+        match self.trace_status {
+            Some(ControlLogLevel(-1)) => write!(f, " trace=default")?,
+            Some(ControlLogLevel(0)) => write!(f, " trace=off")?,
+            Some(ControlLogLevel(1)) => write!(f, " trace=on")?,
+            Some(ControlLogLevel(n)) => write!(f, " trace={}", n)?,
+            None => (),
+        }
+        if let Some(description) = &self.description {
+            write!(f, " \"{}\"", description)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ApplicationInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.application_id)?;
+        if let Some(description) = &self.description {
+            write!(f, " \"{}\"", description)?;
+        }
+        write!(f, " (")?;
+        for (i, context) in self.contexts.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", context)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// the details of the payload, the service name is not included
+impl fmt::Display for ControlPayload {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ControlPayload::SetLogLevelRequest {
+                application_id,
+                context_id,
+                log_level,
+                com_interface,
+            } => write!(
+                f,
+                "{}/{} level={} interface={}",
+                application_id, context_id, log_level, com_interface
+            ),
+            ControlPayload::GetLogInfoRequest {
+                options,
+                application_id,
+                context_id,
+                com_interface,
+            } => write!(
+                f,
+                "{}/{} options={} interface={}",
+                application_id, context_id, options, com_interface
+            ),
+            ControlPayload::GetLogInfoResponse {
+                status,
+                applications,
+                ..
+            } => {
+                write!(f, "{}", status)?;
+                for app in applications {
+                    write!(f, " {}", app)?;
+                }
+                Ok(())
+            }
+            ControlPayload::GetDefaultLogLevelRequest
+            | ControlPayload::GetSoftwareVersionRequest => Ok(()),
+            ControlPayload::GetDefaultLogLevelResponse { status, log_level } => match log_level {
+                Some(level) => write!(f, "{} level={}", status, level),
+                None => write!(f, "{}", status),
+            },
+            ControlPayload::GetSoftwareVersionResponse { status, version } => match version {
+                Some(version) => write!(f, "{} {}", status, version),
+                None => write!(f, "{}", status),
+            },
+            ControlPayload::BufferOverflowNotification {
+                status,
+                overflow_counter,
+            } => match overflow_counter {
+                Some(counter) => write!(f, "{} overflows={}", status, counter),
+                None => write!(f, "{}", status),
+            },
+            ControlPayload::Response { status, .. } => write!(f, "{}", status),
+            ControlPayload::Request { data, .. } => write!(f, "{:02X?}", data),
+        }
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+
+//! Conversion of non-verbose DLT files to verbose DLT
+//!
+//! Tools without the fibex can't decode non-verbose messages. Every non-verbose
+//! message that is described in the fibex is rewritten as a verbose message with
+//! one argument per pdu, all other bytes of the file are copied unchanged.
+use crate::dlt::{
+    Endianness, ExtendedHeader, LogLevel, Message, MessageType, Payload2, PayloadContent,
+    EXTENDED_HEADER_LENGTH,
+};
+use crate::dlt_check::garbage_length;
+use crate::dlt_parse::{
+    dlt_message, DltFraming, DltParseError, ParsedMessage, DLT_PATTERN_SIZE, DLT_READER_CAPACITY,
+    DLT_SERIAL_PATTERN,
+};
+use crate::dlt_reader::{DltReader, DltReaderMode};
+use crate::fibex::FibexMetadata;
+use byteorder::{BigEndian, LittleEndian};
+use crossbeam_channel as cc;
+use failure::{err_msg, Error};
+use indexer_base::{progress::*, utils};
+use serde::Serialize;
+use std::fs;
+use std::io::{BufRead, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const PROGRESS_MESSAGE_THRESHOLD: usize = 250_000;
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ConversionReport {
+    /// all messages that could be parsed
+    pub messages: usize,
+    /// non-verbose messages that were written as verbose messages
+    pub converted: usize,
+    /// non-verbose messages that are not described in the fibex, they are kept unchanged
+    pub unresolved: usize,
+    /// bytes that do not belong to a valid message, they are copied unchanged
+    pub skipped_bytes: u64,
+}
+
+pub type ConversionResults = std::result::Result<IndexingProgress<ConversionReport>, Notification>;
+
+/// the verbose form of a non-verbose message
+///
+/// the pdus of the frame in the fibex become the arguments: descriptions are
+/// written as strings, signals keep their types and raw values and are named
+/// after the signal. None for verbose and control messages and for non-verbose
+/// messages that can't be decoded with the fibex of the message.
+pub fn to_verbose_message(message: &Message) -> Option<Message> {
+    let (id, data) = match &message.payload.payload_content {
+        PayloadContent::NonVerbose(id, data) => (*id, data),
+        _ => return None,
+    };
+    let frame_metadata = message.fibex_frame(id)?;
+    let mut arguments = message.raw_fibex_arguments(frame_metadata, data).ok()?;
+    for arg in &mut arguments {
+        // names of verbose arguments are only written with VARI set
+        arg.type_info.has_variable_info = arg.name.is_some();
+    }
+    let payload = Payload2 {
+        payload_content: PayloadContent::Verbose(arguments),
+    };
+    let mut header = message.header.clone();
+    header.payload_length = payload_length(message, &payload)?;
+    header.has_extended_header = true;
+    let extended_header = match &message.extended_header {
+        Some(extended_header) => ExtendedHeader {
+            verbose: true,
+            argument_count: payload.arg_count(),
+            ..extended_header.clone()
+        },
+        None => ExtendedHeader {
+            verbose: true,
+            argument_count: payload.arg_count(),
+            message_type: frame_metadata
+                .message_info
+                .as_ref()
+                .and_then(|info| MessageType::try_new_from_fibex_message_info(info))
+                .unwrap_or(MessageType::Log(LogLevel::Info)),
+            application_id: frame_metadata
+                .application_id
+                .as_ref()
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            context_id: frame_metadata
+                .context_id
+                .as_ref()
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+        },
+    };
+    Some(Message {
+        storage_header: message.storage_header.clone(),
+        header,
+        extended_header: Some(extended_header),
+        payload,
+        fibex_metadata: message.fibex_metadata.clone(),
+    })
+}
+
+/// None if the payload does not fit into the length field
+fn payload_length(message: &Message, payload: &Payload2) -> Option<u16> {
+    // version 2 messages are always big endian
+    let length = if message.header.v2.is_some() || message.header.endianness == Endianness::Big {
+        payload.as_bytes::<BigEndian>().len()
+    } else {
+        payload.as_bytes::<LittleEndian>().len()
+    };
+    let mut headers_length = (message.byte_len() - message.header.payload_length) as usize;
+    if message.extended_header.is_none() {
+        headers_length += EXTENDED_HEADER_LENGTH as usize;
+    }
+    if headers_length + length > u16::MAX as usize {
+        warn!("verbose message too long, keeping non-verbose message");
+        return None;
+    }
+    Some(length as u16)
+}
+
+/// writes a copy of `in_file` to `out_file` with all non-verbose messages that are
+/// described in `fibex_metadata` converted to verbose messages
+///
+/// the `ConversionReport` is sent at the end and also returned
+pub fn convert_to_verbose(
+    in_file: &PathBuf,
+    out_file: &PathBuf,
+    fibex_metadata: Arc<FibexMetadata>,
+    framing: DltFraming,
+    reader_mode: DltReaderMode,
+    update_channel: &cc::Sender<ConversionResults>,
+    shutdown_receiver: Option<cc::Receiver<()>>,
+) -> Result<ConversionReport, Error> {
+    let file_size = fs::metadata(in_file)?.len();
+    let mut reader = DltReader::open(in_file, 0, DLT_READER_CAPACITY, reader_mode)
+        .map_err(|e| err_msg(format!("could not open {:?} ({})", in_file, e)))?;
+    let mut writer = BufWriter::new(
+        fs::File::create(out_file)
+            .map_err(|e| err_msg(format!("could not create {:?} ({})", out_file, e)))?,
+    );
+    let mut report = ConversionReport::default();
+    let mut offset = 0u64;
+    let mut attempts = 0usize;
+    loop {
+        let content = reader.fill_buf()?;
+        if content.is_empty() {
+            break;
+        }
+        let available = content.len();
+        let at_end = offset + available as u64 >= file_size;
+        let dropped = garbage_length(content, framing, at_end);
+        if dropped > 0 {
+            writer.write_all(&content[..dropped])?;
+            report.skipped_bytes += dropped as u64;
+            reader.consume(dropped);
+            offset += dropped as u64;
+            continue;
+        }
+        let consumed = match dlt_message(
+            content,
+            None,
+            report.messages,
+            None,
+            Some(fibex_metadata.clone()),
+            framing,
+        ) {
+            Ok((rest, ParsedMessage::Item(message))) => {
+                let length = available - rest.len();
+                report.messages += 1;
+                match to_verbose_message(&message) {
+                    Some(verbose) => {
+                        if framing == DltFraming::SerialHeader {
+                            writer.write_all(DLT_SERIAL_PATTERN)?;
+                        }
+                        writer.write_all(&verbose.as_bytes())?;
+                        report.converted += 1;
+                    }
+                    None => {
+                        if let PayloadContent::NonVerbose(_, _) = message.payload.payload_content {
+                            report.unresolved += 1;
+                        }
+                        writer.write_all(&content[..length])?;
+                    }
+                }
+                length
+            }
+            Ok((rest, _)) => {
+                let length = available - rest.len();
+                writer.write_all(&content[..length])?;
+                report.skipped_bytes += length as u64;
+                length
+            }
+            Err(DltParseError::IncompleteParse { .. }) if at_end => {
+                // truncated message at the end of the file
+                writer.write_all(content)?;
+                report.skipped_bytes += available as u64;
+                available
+            }
+            Err(DltParseError::IncompleteParse { needed }) => {
+                return Err(err_msg(format!(
+                    "cannot convert message at offset {} (needs {:?} more bytes)",
+                    offset, needed
+                )));
+            }
+            Err(_) => {
+                let length = std::cmp::min(DLT_PATTERN_SIZE, available);
+                writer.write_all(&content[..length])?;
+                report.skipped_bytes += length as u64;
+                length
+            }
+        };
+        reader.consume(consumed);
+        offset += consumed as u64;
+        attempts += 1;
+        if attempts == PROGRESS_MESSAGE_THRESHOLD {
+            attempts = 0;
+            if utils::check_if_stop_was_requested(&shutdown_receiver, "dlt conversion") {
+                update_channel.send(Ok(IndexingProgress::Stopped))?;
+                break;
+            }
+            update_channel.send(Ok(IndexingProgress::Progress {
+                ticks: (offset as usize, file_size as usize),
+            }))?;
+        }
+    }
+    writer.flush()?;
+    update_channel.send(Ok(IndexingProgress::GotItem {
+        item: report.clone(),
+    }))?;
+    update_channel.send(Ok(IndexingProgress::Finished))?;
+    Ok(report)
+}
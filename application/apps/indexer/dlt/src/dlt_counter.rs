@@ -0,0 +1,253 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+
+//! Detection of lost messages
+//!
+//! Every sender increments the 8 bit message counter of the standard header
+//! with each message. The counters are tracked per ECU and session id, a jump
+//! of the counter means that messages were lost on the way (dropped UDP packets,
+//! overflows of the buffer of the dlt daemon).
+//!
+//! Messages without a session id share one counter per ECU, that is what a
+//! dlt daemon without session ids sends.
+//!
+//! Some senders do not increment the counter at all, so a message with the same
+//! counter as the message before is no gap. Together with the 8 bits of the counter
+//! this means that only gaps of 1 to 254 messages are detected: 255 lost messages
+//! look like a repeated counter and 256 lost messages like no gap at all.
+use crate::dlt_view::MessageView;
+use indexer_base::progress::{Notification, Severity};
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// only the first gaps of a session are recorded, all lost messages are counted
+pub const MAX_RECORDED_GAPS: usize = 1000;
+
+/// a jump of the message counter
+///
+/// the counter only has 8 bits, so `lost` is between 1 and 254 (see the module docs)
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CounterGap {
+    /// index of the first message after the gap
+    pub index: usize,
+    pub expected: u8,
+    pub received: u8,
+    pub lost: usize,
+}
+
+/// message counters of one session of an ECU
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SessionCounters {
+    pub messages: usize,
+    pub lost_messages: usize,
+    /// the first `MAX_RECORDED_GAPS` gaps
+    pub gaps: Vec<CounterGap>,
+    #[serde(skip)]
+    first: (usize, u8),
+    #[serde(skip)]
+    last: u8,
+}
+
+impl SessionCounters {
+    fn new(index: usize, counter: u8) -> Self {
+        SessionCounters {
+            messages: 1,
+            lost_messages: 0,
+            gaps: vec![],
+            first: (index, counter),
+            last: counter,
+        }
+    }
+
+    fn next(&mut self, index: usize, counter: u8) -> Option<CounterGap> {
+        let gap = counter_gap(self.last, index, counter);
+        self.messages += 1;
+        self.last = counter;
+        if let Some(gap) = &gap {
+            self.add_gap(gap.clone());
+        }
+        gap
+    }
+
+    fn add_gap(&mut self, gap: CounterGap) {
+        self.lost_messages += gap.lost;
+        if self.gaps.len() < MAX_RECORDED_GAPS {
+            self.gaps.push(gap);
+        }
+    }
+}
+
+/// None if `counter` follows `last` or repeats it
+fn counter_gap(last: u8, index: usize, counter: u8) -> Option<CounterGap> {
+    let expected = last.wrapping_add(1);
+    if counter == expected || counter == last {
+        return None;
+    }
+    Some(CounterGap {
+        index,
+        expected,
+        received: counter,
+        lost: counter.wrapping_sub(expected) as usize,
+    })
+}
+
+/// keeps track of the message counters of all sessions
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct MessageCounterTracker {
+    #[serde(serialize_with = "serialize_ecus")]
+    ecus: BTreeMap<String, BTreeMap<Option<u32>, SessionCounters>>,
+}
+
+/// json keys have to be strings, messages without session id are listed as "NONE"
+fn serialize_ecus<S: Serializer>(
+    ecus: &BTreeMap<String, BTreeMap<Option<u32>, SessionCounters>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(Some(ecus.len()))?;
+    for (ecu_id, sessions) in ecus {
+        let sessions: BTreeMap<String, &SessionCounters> = sessions
+            .iter()
+            .map(|(session_id, session)| (session_name(*session_id), session))
+            .collect();
+        map.serialize_entry(ecu_id, &sessions)?;
+    }
+    map.end()
+}
+
+fn session_name(session_id: Option<u32>) -> String {
+    session_id.map_or_else(|| "NONE".to_string(), |id| id.to_string())
+}
+
+impl MessageCounterTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// returns the gap if messages were lost before this message
+    pub fn track(
+        &mut self,
+        ecu_id: Option<&str>,
+        session_id: Option<u32>,
+        counter: u8,
+        index: usize,
+    ) -> Option<CounterGap> {
+        let ecu_id = ecu_id.unwrap_or("NONE");
+        if let Some(session) = self
+            .ecus
+            .get_mut(ecu_id)
+            .and_then(|sessions| sessions.get_mut(&session_id))
+        {
+            return session.next(index, counter);
+        }
+        self.ecus
+            .entry(ecu_id.to_string())
+            .or_default()
+            .insert(session_id, SessionCounters::new(index, counter));
+        None
+    }
+
+    /// like `track`, but returns a warning that can be sent to the user
+    pub fn track_message(&mut self, view: &MessageView, index: usize) -> Option<Notification> {
+        self.track(
+            view.header.ecu_id,
+            view.header.session_id,
+            view.header.message_counter,
+            index,
+        )
+        .map(|gap| {
+            gap_notification(
+                view.header.ecu_id.unwrap_or("NONE"),
+                view.header.session_id,
+                &gap,
+            )
+        })
+    }
+
+    /// the counters of the messages of `ecu_id` without session id for `None`
+    pub fn session(&self, ecu_id: &str, session_id: Option<u32>) -> Option<&SessionCounters> {
+        self.ecus
+            .get(ecu_id)
+            .and_then(|sessions| sessions.get(&session_id))
+    }
+
+    /// number of lost messages of all sessions
+    pub fn lost_messages(&self) -> usize {
+        self.ecus
+            .values()
+            .flat_map(|sessions| sessions.values())
+            .map(|session| session.lost_messages)
+            .sum()
+    }
+
+    /// add the counters of messages that follow the messages tracked so far
+    ///
+    /// the indices of `other` are shifted by `index_offset`, gaps between the last
+    /// message here and the first message in `other` are returned as warnings
+    pub(crate) fn append(
+        &mut self,
+        other: MessageCounterTracker,
+        index_offset: usize,
+    ) -> Vec<Notification> {
+        let mut notifications = vec![];
+        for (ecu_id, sessions) in other.ecus {
+            let known_sessions = self.ecus.entry(ecu_id.clone()).or_default();
+            for (session_id, mut session) in sessions {
+                session.first.0 += index_offset;
+                for gap in session.gaps.iter_mut() {
+                    gap.index += index_offset;
+                }
+                let known = match known_sessions.get_mut(&session_id) {
+                    Some(known) => known,
+                    None => {
+                        known_sessions.insert(session_id, session);
+                        continue;
+                    }
+                };
+                let (first_index, first_counter) = session.first;
+                if let Some(gap) = counter_gap(known.last, first_index, first_counter) {
+                    notifications.push(gap_notification(&ecu_id, session_id, &gap));
+                    known.add_gap(gap);
+                }
+                known.messages += session.messages;
+                known.last = session.last;
+                known.lost_messages += session.lost_messages;
+                let free = MAX_RECORDED_GAPS.saturating_sub(known.gaps.len());
+                known.gaps.extend(session.gaps.into_iter().take(free));
+            }
+        }
+        notifications
+    }
+}
+
+fn gap_notification(ecu_id: &str, session_id: Option<u32>, gap: &CounterGap) -> Notification {
+    Notification {
+        severity: Severity::WARNING,
+        content: format!(
+            "ECU {} session {}: {}",
+            ecu_id,
+            session_name(session_id),
+            gap
+        ),
+        line: Some(gap.index),
+    }
+}
+
+impl fmt::Display for CounterGap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} message(s) lost (message counter {}, expected {})",
+            self.lost, self.received, self.expected
+        )
+    }
+}
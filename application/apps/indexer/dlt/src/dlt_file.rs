@@ -10,56 +10,66 @@
 // is strictly forbidden unless prior written permission is obtained
 // from E.S.R.Labs.
 use crate::dlt::Message;
-use crate::dlt_parse::forward_to_next_storage_header;
-use crate::dlt_parse::skip_storage_header;
+use crate::dlt_check::{
+    garbage_length, CorruptionCollector, CorruptionReason, MessageLocation, ParsedParts,
+};
+use crate::dlt_counter::MessageCounterTracker;
 use crate::dlt_parse::{
-    dlt_message, DltParseError, ParsedMessage, DLT_MIN_BUFFER_SPACE, DLT_PATTERN_SIZE,
-    DLT_READER_CAPACITY,
+    dlt_message_tracked, dlt_message_view, forward_to_next_message, skip_framing_header,
+    DltFraming, DltParseError, ParsedMessage, DLT_PATTERN_SIZE, DLT_READER_CAPACITY,
 };
+use crate::dlt_reader::{DltReader, DltReaderMode};
 use crate::filtering;
-use buf_redux::policy::MinBuffered;
-use buf_redux::BufReader as ReduxReader;
+use crate::payload_search::{IndexLine, PayloadMatch};
 use crossbeam_channel as cc;
 use crossbeam_channel::unbounded;
 use failure::{err_msg, Error};
 use futures::stream::StreamExt;
 use indexer_base::{
-    chunks::{ChunkFactory, ChunkResults},
+    chunks::{Chunk, ChunkFactory, ChunkResults},
     config::*,
     progress::*,
     utils,
 };
 use std::fs;
-use std::io::{BufRead, BufWriter, Write};
-use std::path::PathBuf;
-use std::rc::Rc;
+use std::io::{BufRead, BufWriter, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use crate::fibex::FibexMetadata;
 
 pub async fn parse_dlt_file(
     in_file: PathBuf,
     filter_config: Option<filtering::ProcessedDltFilterConfig>,
-    fibex_metadata: Option<Rc<FibexMetadata>>,
+    fibex_metadata: Option<Arc<FibexMetadata>>,
 ) -> Result<Vec<Message>, Error> {
     trace!("parse_dlt_file");
     let source_file_size = fs::metadata(&in_file)?.len() as usize;
     let (update_channel, _rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = unbounded();
     let mut progress_reporter = ProgressReporter::new(source_file_size, update_channel.clone());
     let mut messages: Vec<Message> = Vec::new();
+    let search = filter_config
+        .as_ref()
+        .and_then(|c| c.payload_search.clone());
     let mut message_stream = FileMessageProducer::new(
         &in_file,
         filter_config,
         update_channel.clone(),
-        true,
+        DltFraming::StorageHeader,
         fibex_metadata,
+        DltReaderMode::default(),
     )?;
+    progress_reporter.make_progress(message_stream.start_offset as usize);
     // type Item = Result<Option<Message>, DltParseError>;
     while let Some(msg_result) = message_stream.next().await {
         trace!("got message from stream: {:?}", msg_result);
         match msg_result {
             Ok((consumed, Some(msg))) => {
                 progress_reporter.make_progress(consumed);
-                messages.push(msg)
+                match &search {
+                    Some(search) if !search.is_match(&msg.payload_text()) => (),
+                    _ => messages.push(msg),
+                }
             }
             Ok((consumed, None)) => {
                 if consumed == 0 {
@@ -70,9 +80,11 @@ pub async fn parse_dlt_file(
             Err(e) => warn!("could not produce message: {}", e),
         }
     }
+    message_stream.finish_corruption_report();
     Ok(messages)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_index_and_mapping_dlt(
     config: IndexingConfig,
     source_file_size: usize,
@@ -80,16 +92,20 @@ pub fn create_index_and_mapping_dlt(
     update_channel: &cc::Sender<ChunkResults>,
     shutdown_receiver: Option<cc::Receiver<()>>,
     fibex_metadata: Option<FibexMetadata>,
-) -> Result<(), Error> {
+    reader_mode: DltReaderMode,
+    framing: DltFraming,
+) -> Result<Vec<PayloadMatch>, Error> {
     trace!("create_index_and_mapping_dlt");
-    let filter_config: Option<filtering::ProcessedDltFilterConfig> =
-        dlt_filter.map(filtering::process_filter_config);
+    let filter_config: Option<filtering::ProcessedDltFilterConfig> = dlt_filter
+        .map(filtering::process_filter_config)
+        .transpose()?;
     let mut message_producer = FileMessageProducer::new(
         &config.in_file,
         filter_config,
         update_channel.clone(),
-        true,
-        fibex_metadata.map(Rc::new),
+        framing,
+        fibex_metadata.map(Arc::new),
+        reader_mode,
     )?;
     // TODO do not clone metadata...if we use it in FileMessageProducer, we should not need it in index_dlt_content
     index_dlt_content(
@@ -107,37 +123,101 @@ pub struct MessageStats {
     no_parse: usize,
 }
 pub struct FileMessageProducer {
-    reader: ReduxReader<fs::File, MinBuffered>,
+    reader: DltReader,
     filter_config: Option<filtering::ProcessedDltFilterConfig>,
     stats: MessageStats,
     update_channel: cc::Sender<ChunkResults>,
-    with_storage_header: bool,
-    fibex_metadata: Option<Rc<FibexMetadata>>,
+    framing: DltFraming,
+    fibex_metadata: Option<Arc<FibexMetadata>>,
+    counters: MessageCounterTracker,
+    /// offset in the file of the first message that is read
+    start_offset: u64,
+    /// offset in the file of the next message
+    offset: u64,
+    /// what happens with the bytes that could not be parsed
+    corruption: Option<CorruptionTracking>,
+}
+
+/// how a `FileMessageProducer` keeps track of the corrupted parts of a file
+enum CorruptionTracking {
+    /// the ranges are sent as warnings as soon as they are complete
+    Report(CorruptionCollector<Chunk>),
+    /// the parts are recorded and reported later, for partitions of a file
+    Record(ParsedParts),
+}
+
+impl CorruptionTracking {
+    fn at_end(&self, offset: u64, available: usize) -> bool {
+        match self {
+            CorruptionTracking::Report(collector) => collector.at_end(offset, available),
+            CorruptionTracking::Record(parts) => parts.at_end(offset, available),
+        }
+    }
+
+    fn skipped(&mut self, start: u64, length: usize, reason: CorruptionReason, lost: usize) {
+        match self {
+            CorruptionTracking::Report(collector) => collector.skipped(start, length, reason, lost),
+            CorruptionTracking::Record(parts) => parts.skipped(start, length, reason, lost),
+        }
+    }
+
+    /// a valid message, `location` is `None` if it is not known in detail
+    fn valid_message(&mut self, location: Option<MessageLocation>) {
+        match (self, location) {
+            (CorruptionTracking::Report(collector), Some(location)) => {
+                collector.valid_message(location)
+            }
+            (CorruptionTracking::Report(collector), None) => collector.other_valid_message(),
+            (CorruptionTracking::Record(parts), location) => parts.valid_message(location),
+        }
+    }
 }
 
 impl FileMessageProducer {
+    /// starts at the time range of the filter if it can be found in the file
+    ///
+    /// the corrupted parts of the file are reported as warnings
     fn new(
         in_path: &PathBuf,
         filter_config: Option<filtering::ProcessedDltFilterConfig>,
         update_channel: cc::Sender<ChunkResults>,
-        with_storage_header: bool,
-        fibex_metadata: Option<Rc<FibexMetadata>>,
+        framing: DltFraming,
+        fibex_metadata: Option<Arc<FibexMetadata>>,
+        reader_mode: DltReaderMode,
     ) -> Result<FileMessageProducer, Error> {
-        let f = match fs::File::open(&in_path) {
-            Ok(file) => file,
-            Err(e) => {
-                warn!("could not open {:?}", in_path);
-                let _ = update_channel.try_send(Err(Notification {
-                    severity: Severity::WARNING,
-                    content: format!("could not open file ({})", e),
-                    line: None,
-                }));
-                return Err(err_msg(format!("could not open file ({})", e)));
-            }
-        };
-        let reader = ReduxReader::with_capacity(DLT_READER_CAPACITY, f)
-            .set_policy(MinBuffered(DLT_MIN_BUFFER_SPACE));
-        Ok(FileMessageProducer {
+        let start_offset =
+            time_range_start_offset(in_path, filter_config.as_ref(), framing, reader_mode)?;
+        let reader = open_reader(
+            in_path,
+            start_offset,
+            DLT_READER_CAPACITY,
+            reader_mode,
+            &update_channel,
+        )?;
+        let mut producer = FileMessageProducer::with_reader(
+            reader,
+            filter_config,
+            update_channel,
+            framing,
+            fibex_metadata,
+        );
+        producer.start_offset = start_offset;
+        producer.offset = start_offset;
+        producer.corruption = Some(CorruptionTracking::Report(CorruptionCollector::new(
+            fs::metadata(in_path)?.len(),
+            producer.update_channel.clone(),
+        )));
+        Ok(producer)
+    }
+
+    fn with_reader(
+        reader: DltReader,
+        filter_config: Option<filtering::ProcessedDltFilterConfig>,
+        update_channel: cc::Sender<ChunkResults>,
+        framing: DltFraming,
+        fibex_metadata: Option<Arc<FibexMetadata>>,
+    ) -> FileMessageProducer {
+        FileMessageProducer {
             reader,
             filter_config,
             stats: MessageStats {
@@ -145,12 +225,147 @@ impl FileMessageProducer {
                 no_parse: 0,
             },
             update_channel,
-            with_storage_header,
+            framing,
             fibex_metadata,
-        })
+            counters: MessageCounterTracker::new(),
+            start_offset: 0,
+            offset: 0,
+            corruption: None,
+        }
+    }
+
+    /// sends the corrupted range at the end of the file if there is one
+    fn finish_corruption_report(&mut self) {
+        if let Some(CorruptionTracking::Report(collector)) = self.corruption.take() {
+            collector.finish();
+        }
+    }
+}
+
+/// files are only searched for the start of a time range down to this precision,
+/// the messages before the start are then filtered like all others
+const TIME_SEEK_PRECISION: u64 = 64 * 1024;
+
+/// offset of a message shortly before the time range of the filter
+///
+/// only storage header timestamps can be searched, they are expected to be ascending
+/// like in all recorded files. Returns 0 if there is nothing to search.
+pub(crate) fn time_range_start_offset(
+    in_path: &PathBuf,
+    filter_config: Option<&filtering::ProcessedDltFilterConfig>,
+    framing: DltFraming,
+    reader_mode: DltReaderMode,
+) -> Result<u64, Error> {
+    if framing != DltFraming::StorageHeader {
+        return Ok(0);
+    }
+    let start = match filter_config.and_then(|c| c.time_range.as_ref()) {
+        Some(filtering::ProcessedTimeRange {
+            clock: filtering::TimeClock::StorageHeader,
+            start_us: Some(start),
+            ..
+        }) => *start,
+        _ => return Ok(0),
+    };
+    let mut lower = 0u64;
+    let mut upper = fs::metadata(in_path)?.len();
+    while upper - lower > TIME_SEEK_PRECISION {
+        let middle = lower + (upper - lower) / 2;
+        match message_time_after(in_path, middle, reader_mode)? {
+            Some((offset, time)) if time < start && offset < upper => lower = offset,
+            _ => upper = middle,
+        }
+    }
+    Ok(lower)
+}
+
+/// offset and storage header timestamp (in µs) of the first message after `offset`
+fn message_time_after(
+    in_path: &Path,
+    offset: u64,
+    reader_mode: DltReaderMode,
+) -> Result<Option<(u64, u64)>, Error> {
+    let framing = DltFraming::StorageHeader;
+    let mut reader = DltReader::open(in_path, offset, PARTITION_READER_CAPACITY, reader_mode)?;
+    let mut position = offset;
+    loop {
+        let content = reader.fill_buf()?;
+        if content.len() < DLT_PATTERN_SIZE {
+            return Ok(None);
+        }
+        let consumed = match forward_to_next_message(content, framing) {
+            Some((dropped, _)) => {
+                match dlt_message_view::<()>(&content[dropped..], None, None, framing) {
+                    Ok((_, Some(view))) => {
+                        if let Some(h) = view.storage_header {
+                            return Ok(Some((position + dropped as u64, h.timestamp.as_us())));
+                        }
+                        dropped + DLT_PATTERN_SIZE
+                    }
+                    // a header pattern inside of a payload
+                    _ => dropped + DLT_PATTERN_SIZE,
+                }
+            }
+            // the pattern could start in the last bytes of the buffer
+            None => content.len() - (DLT_PATTERN_SIZE - 1),
+        };
+        reader.consume(consumed);
+        position += consumed as u64;
+    }
+}
+
+fn open_reader(
+    in_path: &PathBuf,
+    offset: u64,
+    capacity: usize,
+    reader_mode: DltReaderMode,
+    update_channel: &cc::Sender<ChunkResults>,
+) -> Result<DltReader, Error> {
+    match DltReader::open(in_path, offset, capacity, reader_mode) {
+        Ok(reader) => Ok(reader),
+        Err(e) => {
+            warn!("could not open {:?}", in_path);
+            let _ = update_channel.try_send(Err(Notification {
+                severity: Severity::WARNING,
+                content: format!("could not open file ({})", e),
+                line: None,
+            }));
+            Err(err_msg(format!("could not open file ({})", e)))
+        }
     }
 }
+
 impl FileMessageProducer {
+    /// skip everything up to the next storage or serial header, returns the number of skipped bytes
+    ///
+    /// nothing is skipped if the messages are not framed
+    fn forward_to_next_message(&mut self) -> Result<usize, Error> {
+        if self.framing.pattern().is_none() {
+            return Ok(0);
+        }
+        let mut skipped = 0usize;
+        loop {
+            let content = self.reader.fill_buf()?;
+            if content.len() < DLT_PATTERN_SIZE {
+                let available = content.len();
+                self.reader.consume(available);
+                return Ok(skipped + available);
+            }
+            match forward_to_next_message(content, self.framing) {
+                Some((dropped, _)) => {
+                    self.reader.consume(dropped);
+                    return Ok(skipped + dropped);
+                }
+                None => {
+                    // the pattern could start in the last bytes of the buffer
+                    let dropped = content.len() - (DLT_PATTERN_SIZE - 1);
+                    self.reader.consume(dropped);
+                    skipped += dropped;
+                }
+            }
+        }
+    }
+
     fn produce_next_message(&mut self) -> (usize, Result<ParsedMessage, DltParseError>) {
         #[allow(clippy::never_loop)]
         let consume_and_parse_result = loop {
@@ -161,22 +376,73 @@ impl FileMessageProducer {
                         return (0, Ok(ParsedMessage::Invalid));
                     }
                     let available = content.len();
+                    // only known if the corrupted parts are reported
+                    let at_end =
+                        matches!(&self.corruption, Some(c) if c.at_end(self.offset, available));
+                    if let Some(collector) = self.corruption.as_mut() {
+                        let dropped = garbage_length(content, self.framing, at_end);
+                        if dropped > 0 {
+                            collector.skipped(self.offset, dropped, CorruptionReason::Garbage, 0);
+                            break (dropped, Ok(ParsedMessage::Invalid));
+                        }
+                    }
+                    let index = self.stats.parsed + self.stats.no_parse;
 
-                    let parse_result: Result<(&[u8], ParsedMessage), DltParseError> = dlt_message(
-                        content,
-                        self.filter_config.as_ref(),
-                        self.stats.parsed + self.stats.no_parse,
-                        Some(&self.update_channel),
-                        self.fibex_metadata.clone(),
-                        self.with_storage_header,
-                    );
+                    let parse_result: Result<(&[u8], ParsedMessage), DltParseError> =
+                        dlt_message_tracked(
+                            content,
+                            self.filter_config.as_ref(),
+                            index,
+                            Some(&self.update_channel),
+                            self.fibex_metadata.clone(),
+                            self.framing,
+                            Some(&mut self.counters),
+                        );
 
                     match parse_result {
                         Ok((rest, maybe_msg)) => {
                             let consumed = available - rest.len();
                             self.stats.parsed += 1;
+                            if let Some(collector) = self.corruption.as_mut() {
+                                match &maybe_msg {
+                                    ParsedMessage::Item(msg) => {
+                                        collector.valid_message(Some(MessageLocation {
+                                            index,
+                                            offset: self.offset,
+                                            length: consumed,
+                                            message_counter: msg.header.message_counter,
+                                            timestamp: msg
+                                                .storage_header
+                                                .as_ref()
+                                                .map(|h| h.timestamp.clone()),
+                                        }))
+                                    }
+                                    ParsedMessage::FilteredOut => collector.valid_message(None),
+                                    ParsedMessage::Invalid => collector.skipped(
+                                        self.offset,
+                                        consumed,
+                                        CorruptionReason::InvalidLength,
+                                        1,
+                                    ),
+                                }
+                            }
                             break (consumed, Ok(maybe_msg));
                         }
+                        Err(DltParseError::IncompleteParse { .. }) if at_end => {
+                            // the file ends in the middle of the message, the length might
+                            // be broken, so look for more messages after the header
+                            self.stats.no_parse += 1;
+                            let length = std::cmp::min(DLT_PATTERN_SIZE, available);
+                            if let Some(collector) = self.corruption.as_mut() {
+                                collector.skipped(
+                                    self.offset,
+                                    length,
+                                    CorruptionReason::Truncated,
+                                    1,
+                                );
+                            }
+                            break (length, Ok(ParsedMessage::Invalid));
+                        }
                         Err(DltParseError::IncompleteParse { needed }) => {
                             debug!("parse incomplete");
                             self.stats.no_parse += 1;
@@ -195,6 +461,14 @@ impl FileMessageProducer {
                         Err(DltParseError::ParsingHickup { reason }) => {
                             warn!("parse error");
                             self.stats.no_parse += 1;
+                            if let Some(collector) = self.corruption.as_mut() {
+                                collector.skipped(
+                                    self.offset,
+                                    DLT_PATTERN_SIZE,
+                                    CorruptionReason::InvalidMessage(reason.clone()),
+                                    1,
+                                );
+                            }
                             break (
                                 DLT_PATTERN_SIZE,
                                 Err(DltParseError::ParsingHickup {
@@ -232,6 +506,7 @@ impl FileMessageProducer {
             }
         };
         self.reader.consume(consume_and_parse_result.0);
+        self.offset += consume_and_parse_result.0 as u64;
         trace!(
             "produce_next_message (consumed {})",
             consume_and_parse_result.0
@@ -259,33 +534,123 @@ impl futures::Stream for FileMessageProducer {
     }
 }
 
+/// writes the formatted messages of an index and produces the `Chunk` mapping
+///
+/// shared by the sequential and the parallel indexer so both create exactly the same output
+struct DltIndexWriter<'a> {
+    tag: &'a str,
+    buf_writer: BufWriter<fs::File>,
+    chunk_factory: ChunkFactory,
+    line_nr: usize,
+    chunk_count: usize,
+    last_byte_index: usize,
+    matches: Vec<PayloadMatch>,
+}
+
+impl<'a> DltIndexWriter<'a> {
+    fn new(config: &IndexingConfig<'a>) -> Result<DltIndexWriter<'a>, Error> {
+        let (out_file, current_out_file_size) =
+            utils::get_out_file_and_size(config.append, &config.out_path)?;
+        let line_nr = if config.append {
+            utils::next_line_nr(config.out_path)?
+        } else {
+            0
+        };
+        Ok(DltIndexWriter {
+            tag: config.tag,
+            buf_writer: BufWriter::with_capacity(DLT_READER_CAPACITY, out_file),
+            chunk_factory: ChunkFactory::new(config.chunk_size, current_out_file_size),
+            line_nr,
+            chunk_count: 0,
+            last_byte_index: 0,
+            matches: vec![],
+        })
+    }
+
+    /// returns true if a new chunk was completed and sent
+    fn write_line(
+        &mut self,
+        line: IndexLine,
+        update_channel: &cc::Sender<ChunkResults>,
+    ) -> Result<bool, Error> {
+        if !line.positions.is_empty() {
+            self.matches.push(PayloadMatch {
+                line: self.line_nr,
+                positions: line.positions,
+            });
+        }
+        let written_bytes_len = utils::create_tagged_line_d(
+            self.tag,
+            &mut self.buf_writer,
+            line.text,
+            self.line_nr,
+            true,
+        )?;
+        self.line_nr += 1;
+        if let Some(chunk) = self
+            .chunk_factory
+            .add_bytes(self.line_nr, written_bytes_len)
+        {
+            self.chunk_count += 1;
+            self.last_byte_index = chunk.b.1;
+            update_channel.send(Ok(IndexingProgress::GotItem { item: chunk }))?;
+            self.buf_writer.flush()?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// returns the matches of the payload search
+    fn finish(
+        mut self,
+        out_path: &PathBuf,
+        update_channel: &cc::Sender<ChunkResults>,
+    ) -> Result<Vec<PayloadMatch>, Error> {
+        trace!("buf_writer.flush()");
+        self.buf_writer.flush()?;
+        if let Some(chunk) = self
+            .chunk_factory
+            .create_last_chunk(self.line_nr, self.chunk_count == 0)
+        {
+            trace!("send chunk {:?}", chunk);
+            update_channel.send(Ok(IndexingProgress::GotItem {
+                item: chunk.clone(),
+            }))?;
+            self.chunk_count += 1;
+            self.last_byte_index = chunk.b.1;
+        }
+        if self.chunk_count > 0 {
+            let last_expected_byte_index = fs::metadata(out_path).map(|md| md.len() as usize)?;
+            if last_expected_byte_index != self.last_byte_index {
+                update_channel.send(Err(Notification {
+                    severity: Severity::ERROR,
+                    content: format!(
+                        "error in computation! last byte in chunks is {} but should be {}",
+                        self.last_byte_index, last_expected_byte_index
+                    ),
+                    line: Some(self.line_nr),
+                }))?;
+            }
+        }
+        Ok(self.matches)
+    }
+}
+
 /// create index for a dlt file
 /// source_file_size: if progress updates should be made, add this value
-#[allow(clippy::cognitive_complexity)]
+///
+/// returns the matches of the payload search of the filter
 pub fn index_dlt_content(
     config: IndexingConfig,
     source_file_size: usize,
     update_channel: &cc::Sender<ChunkResults>,
     shutdown_receiver: Option<cc::Receiver<()>>,
     message_producer: &mut FileMessageProducer,
-) -> Result<(), Error> {
+) -> Result<Vec<PayloadMatch>, Error> {
     trace!("index_dlt_file {:?}", config);
-    let (out_file, current_out_file_size) =
-        utils::get_out_file_and_size(config.append, &config.out_path)?;
-
-    let mut chunk_count = 0usize;
-    let mut last_byte_index = 0usize;
-    let mut chunk_factory = ChunkFactory::new(config.chunk_size, current_out_file_size);
-    let mut line_nr = if config.append {
-        utils::next_line_nr(config.out_path)?
-    } else {
-        0
-    };
-    // let tmp_file = create_dlt_tmp_file("file")?;
-    // let mut tmp_writer = BufWriter::with_capacity(DLT_READER_CAPACITY, tmp_file);
-    let mut buf_writer = BufWriter::with_capacity(DLT_READER_CAPACITY, out_file);
-
+    let mut writer = DltIndexWriter::new(&config)?;
     let mut progress_reporter = ProgressReporter::new(source_file_size, update_channel.clone());
+    progress_reporter.make_progress(message_producer.start_offset as usize);
 
     let mut stopped = false;
     let mut skipped = 0usize;
@@ -302,17 +667,20 @@ pub fn index_dlt_content(
         }
         match next {
             Ok(ParsedMessage::Item(msg)) => {
-                trace!("[line:{}] next was Ok(ParsedMessage::Item(msg))", line_nr);
-                let written_bytes_len =
-                    utils::create_tagged_line_d(config.tag, &mut buf_writer, &msg, line_nr, true)?;
-                // tmp_writer.write_all(&msg.as_bytes())?;
-                line_nr += 1;
-                if let Some(chunk) = chunk_factory.add_bytes(line_nr, written_bytes_len) {
-                    stopped = utils::check_if_stop_was_requested(&shutdown_receiver, "dlt indexer");
-                    chunk_count += 1;
-                    last_byte_index = chunk.b.1;
-                    update_channel.send(Ok(IndexingProgress::GotItem { item: chunk }))?;
-                    buf_writer.flush()?;
+                trace!(
+                    "[line:{}] next was Ok(ParsedMessage::Item(msg))",
+                    writer.line_nr
+                );
+                match IndexLine::new(message_producer.filter_config.as_ref(), &msg) {
+                    Some(line) => {
+                        if writer.write_line(line, update_channel)? {
+                            stopped = utils::check_if_stop_was_requested(
+                                &shutdown_receiver,
+                                "dlt indexer",
+                            );
+                        }
+                    }
+                    None => skipped += 1,
                 }
             }
             Ok(ParsedMessage::Invalid) => {
@@ -322,68 +690,416 @@ pub fn index_dlt_content(
                 trace!("next was Ok(ParsedMessage::Skipped)");
                 skipped += 1;
             }
-            Err(e) => match e {
-                DltParseError::ParsingHickup { reason } => {
-                    warn!(
-                        "error parsing 1 dlt message, try to continue parsing: {}",
-                        reason
-                    );
+            Err(e) => {
+                if let Some(notification) = parse_error_notification(e) {
+                    update_channel.send(Err(notification))?;
+                    break;
                 }
-                DltParseError::Unrecoverable { cause } => {
-                    warn!("dlt_file: cannot continue parsing: {}", cause);
-                    update_channel.send(Err(Notification {
-                        severity: Severity::ERROR,
-                        content: format!("error parsing dlt file: {}", cause),
-                        line: None,
-                    }))?;
+            }
+        }
+    }
+
+    message_producer.finish_corruption_report();
+    let matches = writer.finish(config.out_path, update_channel)?;
+    debug!(
+        "sending IndexingProgress::Finished (skipped {} msgs)",
+        skipped
+    );
+    update_channel.send(Ok(IndexingProgress::Finished))?;
+    Ok(matches)
+}
+
+/// returns a notification if the error does not allow to continue parsing
+fn parse_error_notification(e: DltParseError) -> Option<Notification> {
+    match e {
+        DltParseError::ParsingHickup { reason } => {
+            warn!(
+                "error parsing 1 dlt message, try to continue parsing: {}",
+                reason
+            );
+            None
+        }
+        DltParseError::Unrecoverable { cause } => {
+            warn!("dlt_file: cannot continue parsing: {}", cause);
+            Some(Notification {
+                severity: Severity::ERROR,
+                content: format!("error parsing dlt file: {}", cause),
+                line: None,
+            })
+        }
+        DltParseError::IncompleteParse { needed } => {
+            warn!(
+                "dlt_file: cannot continue parsing, was incomplete: needed {:?}",
+                needed
+            );
+            Some(Notification {
+                severity: Severity::ERROR,
+                content: format!("error parsing dlt file (incomplete parse): {:?}", needed),
+                line: None,
+            })
+        }
+    }
+}
+
+/// size of the byte ranges that are parsed by one worker at a time
+const PARTITION_SIZE: u64 = 4 * 1024 * 1024;
+const PARTITION_READER_CAPACITY: usize = 1024 * 1024;
+
+/// a byte range of the input file that should be parsed by a worker
+///
+/// if `search_start` is set, the worker first forwards to the next storage (or serial) header
+#[derive(Debug, Clone)]
+struct PartitionJob {
+    index: usize,
+    start: u64,
+    end: u64,
+    search_start: bool,
+    reader_mode: DltReaderMode,
+    framing: DltFraming,
+    file_size: u64,
+}
+
+/// everything a worker found in its partition, in the order of the input
+///
+/// all messages that start before the end of the partition belong to it, so
+/// `stop` is the offset of the first message of the following partition
+struct ParsedPartition {
+    start: u64,
+    stop: u64,
+    /// the formatted messages with the matches of the payload search
+    lines: Vec<IndexLine>,
+    parse_attempts: usize,
+    skipped: usize,
+    notifications: Vec<ChunkResults>,
+    counters: MessageCounterTracker,
+    /// the corrupted ranges are reported when the partitions are put together
+    parts: ParsedParts,
+    reached_end: bool,
+}
+
+fn parse_partition(
+    in_file: &PathBuf,
+    job: &PartitionJob,
+    filter_config: Option<&filtering::ProcessedDltFilterConfig>,
+    fibex_metadata: Option<Arc<FibexMetadata>>,
+) -> Result<ParsedPartition, Error> {
+    let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = unbounded();
+    let reader = open_reader(
+        in_file,
+        job.start,
+        PARTITION_READER_CAPACITY,
+        job.reader_mode,
+        &tx,
+    )?;
+    let mut producer = FileMessageProducer::with_reader(
+        reader,
+        filter_config.cloned(),
+        tx,
+        job.framing,
+        fibex_metadata,
+    );
+    let mut position = job.start;
+    if job.search_start {
+        position += producer.forward_to_next_message()? as u64;
+    }
+    let start = position;
+    producer.offset = start;
+    producer.corruption = Some(CorruptionTracking::Record(ParsedParts::new(job.file_size)));
+    let mut lines = vec![];
+    let mut skipped = 0usize;
+    let mut reached_end = false;
+    while position < job.end {
+        let (consumed, next) = producer.produce_next_message();
+        if consumed == 0 {
+            reached_end = true;
+            break;
+        }
+        position += consumed as u64;
+        match next {
+            Ok(ParsedMessage::Item(msg)) => match IndexLine::new(filter_config, &msg) {
+                Some(line) => lines.push(line),
+                None => skipped += 1,
+            },
+            Ok(ParsedMessage::Invalid) => (),
+            Ok(ParsedMessage::FilteredOut) => skipped += 1,
+            Err(e) => {
+                if let Some(notification) = parse_error_notification(e) {
+                    let _ = producer.update_channel.send(Err(notification));
+                    reached_end = true;
                     break;
                 }
-                DltParseError::IncompleteParse { needed } => {
-                    warn!(
-                        "dlt_file: cannot continue parsing, was incomplete: needed {:?}",
-                        needed
-                    );
-                    update_channel.send(Err(Notification {
-                        severity: Severity::ERROR,
-                        content: format!("error parsing dlt file (incomplete parse): {:?}", needed),
-                        line: None,
-                    }))?;
+            }
+        }
+    }
+    Ok(ParsedPartition {
+        start,
+        stop: position,
+        lines,
+        parse_attempts: producer.stats.parsed + producer.stats.no_parse,
+        skipped,
+        notifications: rx.try_iter().collect(),
+        counters: producer.counters,
+        parts: match producer.corruption {
+            Some(CorruptionTracking::Record(parts)) => parts,
+            _ => ParsedParts::new(job.file_size),
+        },
+        reached_end,
+    })
+}
+
+/// create index for a dlt file using `worker_count` threads for parsing
+///
+/// the file is split into partitions at storage (or serial) headers. Each partition is parsed
+/// on a worker thread and the results are written in order, so the output and the
+/// chunks are the same as with `create_index_and_mapping_dlt`. If a partition did not
+/// start where the previous one ended (e.g. a storage header pattern inside of a payload),
+/// it is parsed again starting at the correct offset.
+#[allow(clippy::too_many_arguments)]
+pub fn create_index_and_mapping_dlt_parallel(
+    config: IndexingConfig,
+    source_file_size: usize,
+    dlt_filter: Option<filtering::DltFilterConfig>,
+    update_channel: &cc::Sender<ChunkResults>,
+    shutdown_receiver: Option<cc::Receiver<()>>,
+    fibex_metadata: Option<FibexMetadata>,
+    reader_mode: DltReaderMode,
+    framing: DltFraming,
+    worker_count: usize,
+) -> Result<Vec<PayloadMatch>, Error> {
+    trace!(
+        "create_index_and_mapping_dlt_parallel ({} workers)",
+        worker_count
+    );
+    // without framing there is no way to find the start of a message in a partition
+    if worker_count < 2 || framing.pattern().is_none() {
+        return create_index_and_mapping_dlt(
+            config,
+            source_file_size,
+            dlt_filter,
+            update_channel,
+            shutdown_receiver,
+            fibex_metadata,
+            reader_mode,
+            framing,
+        );
+    }
+    index_dlt_file_parallel(
+        config,
+        source_file_size,
+        dlt_filter
+            .map(filtering::process_filter_config)
+            .transpose()?,
+        update_channel,
+        shutdown_receiver,
+        fibex_metadata,
+        reader_mode,
+        framing,
+        worker_count,
+        PARTITION_SIZE,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn index_dlt_file_parallel(
+    config: IndexingConfig,
+    source_file_size: usize,
+    filter_config: Option<filtering::ProcessedDltFilterConfig>,
+    update_channel: &cc::Sender<ChunkResults>,
+    shutdown_receiver: Option<cc::Receiver<()>>,
+    fibex_metadata: Option<FibexMetadata>,
+    reader_mode: DltReaderMode,
+    framing: DltFraming,
+    worker_count: usize,
+    partition_size: u64,
+) -> Result<Vec<PayloadMatch>, Error> {
+    let file_size = fs::metadata(&config.in_file)?.len();
+    let first_offset = time_range_start_offset(
+        &config.in_file,
+        filter_config.as_ref(),
+        framing,
+        reader_mode,
+    )?;
+    // the last partition also takes the remaining bytes
+    let partition_count = std::cmp::max(1, ((file_size - first_offset) / partition_size) as usize);
+    let (job_tx, job_rx): (cc::Sender<PartitionJob>, cc::Receiver<PartitionJob>) = unbounded();
+    let (result_tx, result_rx) = unbounded();
+    // shared by the workers, the metadata of a big fibex catalogue is not copied
+    let fibex_metadata = fibex_metadata.map(Arc::new);
+    let mut workers = vec![];
+    for _ in 0..worker_count {
+        let job_rx = job_rx.clone();
+        let result_tx: cc::Sender<(usize, Result<ParsedPartition, Error>)> = result_tx.clone();
+        let in_file = config.in_file.clone();
+        let filter_config = filter_config.clone();
+        let fibex_metadata = fibex_metadata.clone();
+        workers.push(std::thread::spawn(move || {
+            for job in job_rx.iter() {
+                let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    parse_partition(
+                        &in_file,
+                        &job,
+                        filter_config.as_ref(),
+                        fibex_metadata.clone(),
+                    )
+                }))
+                .unwrap_or_else(|_| Err(err_msg("dlt indexer worker panicked")));
+                if result_tx.send((job.index, res)).is_err() {
                     break;
                 }
-            },
+            }
+        }));
+    }
+    drop(result_tx);
+    let res = write_partitions(
+        &config,
+        source_file_size,
+        update_channel,
+        &shutdown_receiver,
+        PartitionScheduler {
+            job_tx,
+            result_rx,
+            partition_count,
+            partition_size,
+            first_offset,
+            file_size,
+            max_pending: 2 * worker_count,
+            reader_mode,
+            framing,
+        },
+        filter_config.as_ref(),
+        fibex_metadata,
+    );
+    for worker in workers {
+        if worker.join().is_err() {
+            warn!("dlt indexer worker panicked");
         }
     }
+    res
+}
 
-    trace!("buf_writer.flush()");
-    buf_writer.flush()?;
-    // tmp_writer.flush()?;
-    if let Some(chunk) = chunk_factory.create_last_chunk(line_nr, chunk_count == 0) {
-        trace!("send chunk {:?}", chunk);
-        update_channel.send(Ok(IndexingProgress::GotItem {
-            item: chunk.clone(),
-        }))?;
-        chunk_count += 1;
-        last_byte_index = chunk.b.1;
+struct PartitionScheduler {
+    job_tx: cc::Sender<PartitionJob>,
+    result_rx: cc::Receiver<(usize, Result<ParsedPartition, Error>)>,
+    partition_count: usize,
+    partition_size: u64,
+    /// offset of the first message of the first partition
+    first_offset: u64,
+    file_size: u64,
+    max_pending: usize,
+    reader_mode: DltReaderMode,
+    framing: DltFraming,
+}
+
+impl PartitionScheduler {
+    fn job(&self, index: usize) -> PartitionJob {
+        PartitionJob {
+            index,
+            start: self.first_offset + index as u64 * self.partition_size,
+            end: if index + 1 == self.partition_count {
+                self.file_size
+            } else {
+                self.first_offset + (index as u64 + 1) * self.partition_size
+            },
+            search_start: index > 0,
+            reader_mode: self.reader_mode,
+            framing: self.framing,
+            file_size: self.file_size,
+        }
     }
-    if chunk_count > 0 {
-        let last_expected_byte_index = fs::metadata(config.out_path).map(|md| md.len() as usize)?;
-        if last_expected_byte_index != last_byte_index {
-            update_channel.send(Err(Notification {
-                severity: Severity::ERROR,
-                content: format!(
-                    "error in computation! last byte in chunks is {} but should be {}",
-                    last_byte_index, last_expected_byte_index
-                ),
-                line: Some(line_nr),
+}
+
+fn write_partitions(
+    config: &IndexingConfig,
+    source_file_size: usize,
+    update_channel: &cc::Sender<ChunkResults>,
+    shutdown_receiver: &Option<cc::Receiver<()>>,
+    scheduler: PartitionScheduler,
+    filter_config: Option<&filtering::ProcessedDltFilterConfig>,
+    fibex_metadata: Option<Arc<FibexMetadata>>,
+) -> Result<Vec<PayloadMatch>, Error> {
+    let mut writer = DltIndexWriter::new(config)?;
+    let mut progress_reporter = ProgressReporter::new(source_file_size, update_channel.clone());
+    let mut finished_partitions: std::collections::HashMap<usize, Result<ParsedPartition, Error>> =
+        std::collections::HashMap::new();
+    progress_reporter.make_progress(scheduler.first_offset as usize);
+    let mut next_job = 0usize;
+    let mut expected_start = scheduler.first_offset;
+    let mut parse_attempts = 0usize;
+    let mut skipped = 0usize;
+    let mut counters = MessageCounterTracker::new();
+    let mut corruption = CorruptionCollector::new(scheduler.file_size, update_channel.clone());
+    let mut stopped = false;
+    for index in 0..scheduler.partition_count {
+        while next_job < scheduler.partition_count && next_job < index + scheduler.max_pending {
+            scheduler.job_tx.send(scheduler.job(next_job))?;
+            next_job += 1;
+        }
+        let parsed = loop {
+            if let Some(res) = finished_partitions.remove(&index) {
+                break res;
+            }
+            let (finished_index, res) = scheduler.result_rx.recv()?;
+            finished_partitions.insert(finished_index, res);
+        };
+        // a worker might have started at a header pattern that was part of a
+        // payload, the partition then has to be parsed from where the previous one ended
+        let partition = match parsed {
+            Ok(partition) if partition.start == expected_start => partition,
+            parsed => {
+                debug!(
+                    "partition {} did not start at {} ({:?}), parse it again",
+                    index,
+                    expected_start,
+                    parsed.map(|p| p.start)
+                );
+                let job = PartitionJob {
+                    start: expected_start,
+                    search_start: false,
+                    ..scheduler.job(index)
+                };
+                parse_partition(&config.in_file, &job, filter_config, fibex_metadata.clone())?
+            }
+        };
+        // gaps between the last message of the previous partition and the first of this one
+        for notification in counters.append(partition.counters, parse_attempts) {
+            update_channel.send(Err(notification))?;
+        }
+        for notification in partition.notifications {
+            update_channel.send(notification.map_err(|n| Notification {
+                line: n.line.map(|l| l + parse_attempts),
+                ..n
             }))?;
         }
+        partition.parts.replay(&mut corruption, parse_attempts);
+        for line in partition.lines {
+            if stopped {
+                break;
+            }
+            if writer.write_line(line, update_channel)? {
+                stopped = utils::check_if_stop_was_requested(shutdown_receiver, "dlt indexer");
+            }
+        }
+        progress_reporter.make_progress((partition.stop - expected_start) as usize);
+        expected_start = partition.stop;
+        parse_attempts += partition.parse_attempts;
+        skipped += partition.skipped;
+        if stopped {
+            info!("we were stopped in dlt-indexer",);
+            break;
+        }
+        if partition.reached_end {
+            break;
+        }
     }
+    drop(scheduler);
+    corruption.finish();
+    let matches = writer.finish(config.out_path, update_channel)?;
     debug!(
         "sending IndexingProgress::Finished (skipped {} msgs)",
         skipped
     );
     update_channel.send(Ok(IndexingProgress::Finished))?;
-    Ok(())
+    Ok(matches)
 }
 
 pub fn export_session_file(
@@ -404,6 +1120,8 @@ pub fn export_session_file(
         destination_path,
         sections,
         update_channel,
+        DltReaderMode::default(),
+        DltFraming::StorageHeader,
     )
 }
 
@@ -412,9 +1130,10 @@ pub fn export_as_dlt_file(
     destination_path: PathBuf,
     sections: SectionConfig,
     update_channel: cc::Sender<ChunkResults>,
+    reader_mode: DltReaderMode,
+    framing: DltFraming,
 ) -> Result<(), Error> {
     use std::io::Read;
-    use std::io::Seek;
     trace!(
         "export_as_dlt_file {:?} to file: {:?}, exporting {:?}",
         dlt_file_path,
@@ -423,20 +1142,34 @@ pub fn export_as_dlt_file(
     );
     if dlt_file_path.exists() {
         trace!("found file to export: {:?}", &dlt_file_path);
-        let f = fs::File::open(&dlt_file_path)?;
-        let mut reader = &mut std::io::BufReader::new(f);
         let out_file = std::fs::File::create(destination_path)?;
         trace!("created out_file: {:?}", &out_file);
-        let partitioner = FilePartitioner::new(&dlt_file_path, sections)?;
+        let partitioner = FilePartitioner::new(&dlt_file_path, sections, reader_mode, framing)?;
         let mut out_writer = BufWriter::new(out_file);
 
-        for part in partitioner.get_parts() {
-            trace!("copy part {:?}", part);
-            reader.seek(std::io::SeekFrom::Start(part.offset as u64))?;
-            let mut take = reader.take(part.length as u64);
-            std::io::copy(&mut take, &mut out_writer)?;
-            reader = take.into_inner();
-            out_writer.flush()?;
+        match DltReader::open(&dlt_file_path, 0, DLT_READER_CAPACITY, reader_mode)? {
+            DltReader::Mapped(mapped) => {
+                let content = mapped.as_slice();
+                for part in partitioner.get_parts() {
+                    trace!("copy part {:?}", part);
+                    let start = std::cmp::min(part.offset as usize, content.len());
+                    let end = std::cmp::min(start + part.length as usize, content.len());
+                    out_writer.write_all(&content[start..end])?;
+                }
+                out_writer.flush()?;
+            }
+            DltReader::Buffered(_) => {
+                let f = fs::File::open(&dlt_file_path)?;
+                let mut reader = &mut std::io::BufReader::new(f);
+                for part in partitioner.get_parts() {
+                    trace!("copy part {:?}", part);
+                    reader.seek(std::io::SeekFrom::Start(part.offset as u64))?;
+                    let mut take = reader.take(part.length as u64);
+                    std::io::copy(&mut take, &mut out_writer)?;
+                    reader = take.into_inner();
+                    out_writer.flush()?;
+                }
+            }
         }
         let _ = update_channel.send(Ok(IndexingProgress::Finished));
         Ok(())
@@ -466,20 +1199,25 @@ pub(crate) fn create_dlt_session_file(session_id: &str) -> Result<std::fs::File,
 }
 
 struct FilePartitioner {
-    reader: ReduxReader<fs::File, MinBuffered>,
+    reader: DltReader,
     offset: u64,
     section_config: SectionConfig,
     file_size: u64,
+    framing: DltFraming,
 }
 impl FilePartitioner {
-    fn new(in_path: &PathBuf, c: SectionConfig) -> Result<Self, Error> {
-        let f = fs::File::open(&in_path)?;
+    fn new(
+        in_path: &PathBuf,
+        c: SectionConfig,
+        reader_mode: DltReaderMode,
+        framing: DltFraming,
+    ) -> Result<Self, Error> {
         Ok(FilePartitioner {
-            reader: ReduxReader::with_capacity(DLT_READER_CAPACITY, f)
-                .set_policy(MinBuffered(DLT_MIN_BUFFER_SPACE)),
+            reader: DltReader::open(in_path, 0, DLT_READER_CAPACITY, reader_mode)?,
             offset: 0,
             section_config: c,
             file_size: fs::metadata(in_path)?.len(),
+            framing,
         })
     }
     fn get_parts(mut self) -> Vec<FilePart> {
@@ -519,14 +1257,8 @@ impl FilePartitioner {
                             trace!("0, Ok(ParsedMessage::Invalid)");
                             break;
                         }
-                        match skip_storage_header(content) {
-                            Ok((rest, skipped_bytes)) => {
-                                let (len_without_storage_header, _was_last) =
-                                    match forward_to_next_storage_header(rest) {
-                                        Some((dropped, _)) => (dropped, false),
-                                        None => (rest.len(), true),
-                                    };
-                                let consumed = skipped_bytes + len_without_storage_header;
+                        match message_length(content, self.framing) {
+                            Ok(consumed) => {
                                 if state.index == section.first_line {
                                     trace!("---> enter section: {:?}) ({:?})", section, state);
                                     state.in_section = true;
@@ -571,6 +1303,24 @@ impl FilePartitioner {
         result_vec
     }
 }
+/// length of the message at the start of `content` including its storage or serial header
+///
+/// the message is parsed so that a header pattern inside of its payload does not split it.
+/// If it cannot be parsed, the message ends where the next header starts.
+fn message_length(content: &[u8], framing: DltFraming) -> Result<usize, DltParseError> {
+    match dlt_message_view::<()>(content, None, None, framing) {
+        Ok((rest, _)) => return Ok(content.len() - rest.len()),
+        Err(e) if framing.pattern().is_none() => return Err(e),
+        Err(e) => trace!("could not parse message, forward to next header ({})", e),
+    }
+    let (rest, skipped_bytes) = skip_framing_header(content, framing)?;
+    let len_without_header = match forward_to_next_message(rest, framing) {
+        Some((dropped, _)) => dropped,
+        None => rest.len(),
+    };
+    Ok(skipped_bytes + len_without_header)
+}
+
 #[derive(Debug)]
 struct FilePart {
     offset: u64,
@@ -6,8 +6,10 @@ use byteorder::{BigEndian, LittleEndian};
 use bytes::ByteOrder;
 use chrono::prelude::{DateTime, Utc};
 use chrono::NaiveDateTime;
+use std::borrow::Cow;
 use std::fmt::{self, Formatter};
 use std::str;
+use std::sync::Arc;
 
 lazy_static! {
     static ref DLT_NEWLINE_SENTINAL_STR: &'static str =
@@ -27,6 +29,9 @@ lazy_static! {
 /// EColumn.CTID,
 /// EColumn.MSTP,
 /// EColumn.PAYLOAD,
+///
+/// the alternate form (`{:#}`) adds one more column after the payload that holds
+/// the trace info (TRAI) of the verbose arguments
 impl fmt::Display for Message {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if let Some(h) = &self.storage_header {
@@ -36,6 +41,41 @@ impl fmt::Display for Message {
         write!(f, "{}", self.header)?;
         write!(f, "{}", DLT_COLUMN_SENTINAL,)?;
 
+        self.write_payload(f)?;
+        if f.alternate() {
+            write!(f, "{}", DLT_COLUMN_SENTINAL)?;
+            self.write_trace_info(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// the payload text (see `Message::payload_text`) of a formatted message and its
+/// byte offset in `line`
+///
+/// the argument separators are replaced by spaces of the same length, so a
+/// position in the text plus the offset is the position in the line.
+/// `with_trace_info` tells if the line was formatted with the trace info column (`{:#}`)
+pub(crate) fn payload_text_of_line(line: &str, with_trace_info: bool) -> (usize, String) {
+    let column_end = if with_trace_info {
+        line.rfind(DLT_COLUMN_SENTINAL).unwrap_or(0)
+    } else {
+        line.len()
+    };
+    let column_start = line[..column_end]
+        .rfind(DLT_COLUMN_SENTINAL)
+        .map_or(0, |i| i + DLT_COLUMN_SENTINAL.len_utf8());
+    let column = line[column_start..column_end].replace(DLT_ARGUMENT_SENTINAL, " ");
+    let leading_spaces = column.len() - column.trim_start().len();
+    (column_start + leading_spaces, column.trim().to_string())
+}
+
+impl Message {
+    /// the payload as it is shown in the payload column, arguments are separated by spaces
+    pub fn payload_text(&self) -> String {
+        payload_text_of_line(&self.to_string(), false).1
+    }
+    fn write_payload(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match &self.payload.payload_content {
             PayloadContent::Verbose(arguments) => {
                 self.write_app_id_context_id_and_message_type(f)?;
@@ -53,13 +93,32 @@ impl fmt::Display for Message {
             }
             PayloadContent::ControlMsg(ctrl_id, _data) => {
                 self.write_app_id_context_id_and_message_type(f)?;
-                match SERVICE_ID_MAPPING.get(&ctrl_id.value()) {
-                    Some((name, _desc)) => write!(f, "[{}]", name),
-                    None => write!(f, "[Unknown CtrlCommand]"),
+                match self.control_payload() {
+                    Some(Ok(payload)) => match payload.service_name() {
+                        Some(name) => write!(f, "[{}] {}", name, payload),
+                        None => write!(f, "[Unknown CtrlCommand] {}", payload),
+                    },
+                    // payload too short for a complete service id
+                    _ => match SERVICE_ID_MAPPING.get(&ctrl_id.value()) {
+                        Some((name, _desc)) => write!(f, "[{}]", name),
+                        None => write!(f, "[Unknown CtrlCommand]"),
+                    },
                 }
             }
         }
     }
+    fn write_trace_info(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let PayloadContent::Verbose(arguments) = &self.payload.payload_content {
+            let mut trace_infos: Vec<&str> = vec![];
+            for t in arguments.iter().filter_map(|arg| arg.trace_info.as_ref()) {
+                if !trace_infos.contains(&t.as_str()) {
+                    trace_infos.push(t);
+                }
+            }
+            write!(f, "{}", trace_infos.join(","))?;
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Display for MessageType {
@@ -147,8 +206,47 @@ impl fmt::Display for Value {
                     .join(&DLT_NEWLINE_SENTINAL_STR)
             ),
             Value::Raw(value) => write!(f, "{:02X?}", value),
+            Value::Array(dimensions, elements) => write_array(f, dimensions, elements),
+            Value::Struct(members) => {
+                f.write_str("{")?;
+                for (i, member) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", member)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// writes the elements of a (possibly multidimensional) array as nested lists,
+/// e.g. dimensions [2, 3] are written as [[1, 2, 3], [4, 5, 6]]
+fn write_array(f: &mut Formatter, dimensions: &[u16], elements: &[Value]) -> fmt::Result {
+    f.write_str("[")?;
+    match dimensions.split_first() {
+        Some((_, inner_dimensions)) if !inner_dimensions.is_empty() => {
+            let inner_len = array_element_count(inner_dimensions);
+            if inner_len > 0 {
+                for (i, chunk) in elements.chunks(inner_len).enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write_array(f, inner_dimensions, chunk)?;
+                }
+            }
+        }
+        _ => {
+            for (i, element) in elements.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{}", element)?;
+            }
         }
     }
+    f.write_str("]")
 }
 
 impl fmt::Display for Argument {
@@ -171,8 +269,10 @@ impl fmt::Display for Argument {
 
 impl fmt::Display for DltTimeStamp {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
-        let naive: Option<NaiveDateTime> =
-            NaiveDateTime::from_timestamp_opt(i64::from(self.seconds), self.microseconds * 1000);
+        let naive: Option<NaiveDateTime> = self
+            .microseconds
+            .checked_mul(1000)
+            .and_then(|nanos| NaiveDateTime::from_timestamp_opt(i64::from(self.seconds), nanos));
         match naive {
             Some(n) => {
                 let datetime: DateTime<Utc> = DateTime::from_utc(n, Utc);
@@ -203,6 +303,8 @@ impl fmt::Display for StandardHeader {
         )?;
         if let Some(t) = &self.timestamp {
             write!(f, "{}", t)?;
+        } else if let Some(t) = self.v2.as_ref().and_then(|v2| v2.timestamp.as_ref()) {
+            write!(f, "{}", t)?;
         }
         write!(f, "{}", DLT_COLUMN_SENTINAL,)?;
         if let Some(id) = &self.ecu_id {
@@ -212,6 +314,36 @@ impl fmt::Display for StandardHeader {
     }
 }
 
+impl fmt::Display for DltTimeStampV2 {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}.{:09}", self.seconds, self.nanoseconds)
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}:{}", self.file_name, self.line_number)
+    }
+}
+
+/// the fields of a version 2 header that have no column of their own
+/// are written in front of the payload: [file:line] [tags: a,b] [privacy: n]
+impl fmt::Display for HeaderV2Fields {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        let mut parts: Vec<String> = vec![];
+        if let Some(location) = &self.source_location {
+            parts.push(format!("[{}]", location));
+        }
+        if !self.tags.is_empty() {
+            parts.push(format!("[tags: {}]", self.tags.join(",")));
+        }
+        if let Some(level) = self.privacy_level {
+            parts.push(format!("[privacy: {}]", level));
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
 impl fmt::Display for LogLevel {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
         match self {
@@ -234,249 +366,62 @@ impl Message {
         f: &mut fmt::Formatter,
     ) -> fmt::Result {
         let mut is_written = false;
-        if let Some(fibex_metadata) = &self.fibex_metadata {
-            let id_text = format!("ID_{}", id);
-            let frame_metadata = if let Some(extended_header) = &self.extended_header {
-                fibex_metadata.frame_map_with_key.get(&(
-                    ContextId(extended_header.context_id.clone()),
-                    ApplicationId(extended_header.application_id.clone()),
-                    FrameId(id_text),
-                )) // TODO: avoid cloning here (Cow or Borrow)
-            } else {
-                fibex_metadata.frame_map.get(&FrameId(id_text))
-            };
-            if let Some(frame_metadata) = frame_metadata {
-                let FrameMetadata {
-                    application_id,
-                    context_id,
-                    message_info,
-                    ..
-                } = &**frame_metadata;
-                write!(
-                    f,
-                    "{}{}{}{}",
-                    application_id
-                        .as_ref()
-                        .map(|id| &**id)
-                        .or_else(|| self
-                            .extended_header
-                            .as_ref()
-                            .map(|h| h.application_id.as_ref()))
-                        .unwrap_or("-"),
-                    DLT_COLUMN_SENTINAL,
-                    context_id
+        if let Some(frame_metadata) = self.fibex_frame(id) {
+            let FrameMetadata {
+                application_id,
+                context_id,
+                message_info,
+                ..
+            } = &**frame_metadata;
+            write!(
+                f,
+                "{}{}{}{}",
+                application_id
+                    .as_ref()
+                    .map(|id| &**id)
+                    .or_else(|| self
+                        .extended_header
                         .as_ref()
-                        .map(|id| &**id)
-                        .or_else(|| self.extended_header.as_ref().map(|h| h.context_id.as_ref()))
-                        .unwrap_or("-"),
-                    DLT_COLUMN_SENTINAL
-                )?;
-                if let Some(v) = message_info
+                        .map(|h| h.application_id.as_ref()))
+                    .unwrap_or("-"),
+                DLT_COLUMN_SENTINAL,
+                context_id
                     .as_ref()
-                    .and_then(|mi| MessageType::try_new_from_fibex_message_info(&*mi))
-                {
-                    write!(f, "{}", v)?;
-                } else if let Some(message_type) =
-                    self.extended_header.as_ref().map(|h| &h.message_type)
-                {
-                    write!(f, "{}", message_type)?;
-                } else {
-                    write!(f, "-")?;
-                }
-                write!(f, "{}", DLT_COLUMN_SENTINAL)?;
-                let mut offset = 0;
-                for pdu in &frame_metadata.pdus {
-                    if let Some(description) = &pdu.description {
-                        let arg = Argument {
-                            type_info: TypeInfo {
-                                kind: TypeInfoKind::StringType,
-                                coding: StringCoding::UTF8,
-                                has_trace_info: false,
-                                has_variable_info: false,
-                            },
-                            name: None,
-                            unit: None,
-                            fixed_point: None,
-                            value: Value::StringVal(description.to_string()),
-                        };
-                        write!(f, "{}{} ", DLT_ARGUMENT_SENTINAL, arg)?;
-                    } else {
-                        for signal_type in &pdu.signal_types {
-                            let mut fixed_point = None;
-                            let value = match signal_type.kind {
-                                TypeInfoKind::StringType | TypeInfoKind::Raw => {
-                                    if data.len() < offset + 2 {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    let length = if self.header.endianness == Endianness::Big {
-                                        BigEndian::read_u16(&data[offset..offset + 2]) as usize
-                                    } else {
-                                        LittleEndian::read_u16(&data[offset..offset + 2]) as usize
-                                    };
-                                    offset += 2;
-                                    if data.len() < offset + length {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    let v = if signal_type.kind == TypeInfoKind::StringType {
-                                        Value::StringVal(
-                                            String::from_utf8(
-                                                data[offset..offset + length].to_vec(),
-                                            )
-                                            .map_err(|_| fmt::Error)?,
-                                        )
-                                    } else {
-                                        Value::Raw(Vec::from(&data[offset..offset + length]))
-                                    };
-                                    offset += length;
-                                    v
-                                }
-                                TypeInfoKind::Bool => {
-                                    offset += 1;
-                                    if data.len() < offset {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    Value::Bool(data[offset - 1])
-                                }
-                                TypeInfoKind::Float(width) => {
-                                    let length = width as usize / 8;
-                                    if data.len() < offset + length {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    let v = if self.header.endianness == Endianness::Big {
-                                        dlt_fint::<BigEndian>(width)(&data[offset..offset + length])
-                                    } else {
-                                        dlt_fint::<LittleEndian>(width)(
-                                            &data[offset..offset + length],
-                                        )
-                                    }
-                                    .map_err(|_| fmt::Error)?
-                                    .1;
-                                    offset += length;
-                                    v
-                                }
-                                TypeInfoKind::Signed(length) => {
-                                    let byte_length = length as usize / 8;
-                                    if data.len() < offset + byte_length {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    let value_offset = &data[offset..];
-                                    let (_, v) = if self.header.endianness == Endianness::Big {
-                                        dlt_sint::<BigEndian>(length)(value_offset)
-                                    } else {
-                                        dlt_sint::<LittleEndian>(length)(value_offset)
-                                    }
-                                    .map_err(|_| fmt::Error)?;
-                                    offset += byte_length;
-                                    v
-                                }
-                                TypeInfoKind::SignedFixedPoint(length) => {
-                                    let byte_length = length as usize / 8;
-                                    if data.len() < offset + byte_length {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    let (value_offset, fp) =
-                                        if self.header.endianness == Endianness::Big {
-                                            dlt_fixed_point::<BigEndian>(
-                                                &data[offset..offset + byte_length],
-                                                length,
-                                            )
-                                        } else {
-                                            dlt_fixed_point::<LittleEndian>(
-                                                &data[offset..offset + byte_length],
-                                                length,
-                                            )
-                                        }
-                                        .map_err(|_| fmt::Error)?;
-                                    fixed_point = Some(fp);
-                                    let (_, v) =
-                                        if self.header.endianness == Endianness::Big {
-                                            dlt_sint::<BigEndian>(float_width_to_type_length(
-                                                length,
-                                            ))(
-                                                value_offset
-                                            )
-                                        } else {
-                                            dlt_sint::<LittleEndian>(float_width_to_type_length(
-                                                length,
-                                            ))(
-                                                value_offset
-                                            )
-                                        }
-                                        .map_err(|_| fmt::Error)?;
-                                    offset += byte_length;
-                                    v
-                                }
-                                TypeInfoKind::Unsigned(length) => {
-                                    let byte_length = length as usize / 8;
-                                    if data.len() < offset + byte_length {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    let value_offset = &data[offset..];
-                                    let (_, v) = if self.header.endianness == Endianness::Big {
-                                        dlt_uint::<BigEndian>(length)(value_offset)
-                                    } else {
-                                        dlt_uint::<LittleEndian>(length)(value_offset)
-                                    }
-                                    .map_err(|_| fmt::Error)?;
-                                    offset += byte_length;
-                                    v
-                                }
-                                TypeInfoKind::UnsignedFixedPoint(length) => {
-                                    let byte_length = length as usize / 8;
-                                    if data.len() < offset + byte_length {
-                                        return fmt::Result::Err(fmt::Error);
-                                    }
-                                    let value_offset = {
-                                        let (r, fp) =
-                                            if self.header.endianness == Endianness::Big {
-                                                dlt_fixed_point::<BigEndian>(
-                                                    &data[offset..offset + byte_length],
-                                                    length,
-                                                )
-                                            } else {
-                                                dlt_fixed_point::<LittleEndian>(
-                                                    &data[offset..offset + byte_length],
-                                                    length,
-                                                )
-                                            }
-                                            .map_err(|_| fmt::Error)?;
-                                        fixed_point = Some(fp);
-                                        r
-                                    };
-                                    let (_, v) =
-                                        if self.header.endianness == Endianness::Big {
-                                            dlt_uint::<BigEndian>(float_width_to_type_length(
-                                                length,
-                                            ))(
-                                                value_offset
-                                            )
-                                        } else {
-                                            dlt_uint::<LittleEndian>(float_width_to_type_length(
-                                                length,
-                                            ))(
-                                                value_offset
-                                            )
-                                        }
-                                        .map_err(|_| fmt::Error)?;
-                                    offset += byte_length;
-                                    v
-                                }
-                            };
-                            let arg = Argument {
-                                type_info: signal_type.clone(),
-                                name: None,
-                                unit: None,
-                                fixed_point,
-                                value,
-                            };
-                            write!(f, "{}{} ", DLT_ARGUMENT_SENTINAL, arg)?;
+                    .map(|id| &**id)
+                    .or_else(|| self.extended_header.as_ref().map(|h| h.context_id.as_ref()))
+                    .unwrap_or("-"),
+                DLT_COLUMN_SENTINAL
+            )?;
+            if let Some(v) = message_info
+                .as_ref()
+                .and_then(|mi| MessageType::try_new_from_fibex_message_info(&*mi))
+            {
+                write!(f, "{}", v)?;
+            } else if let Some(message_type) =
+                self.extended_header.as_ref().map(|h| &h.message_type)
+            {
+                write!(f, "{}", message_type)?;
+            } else {
+                write!(f, "-")?;
+            }
+            write!(f, "{}", DLT_COLUMN_SENTINAL)?;
+            if let Some(v2) = &self.header.v2 {
+                write!(f, "{}", v2)?;
+            }
+            for arg in self.fibex_arguments(frame_metadata, data)? {
+                match &arg.name {
+                    // converted signals are rendered as name=value
+                    Some(name) => {
+                        write!(f, "{}{}={}", DLT_ARGUMENT_SENTINAL, name, arg.value)?;
+                        if let Some(unit) = &arg.unit {
+                            write!(f, " {}", unit)?;
                         }
-                    };
-                    is_written = true;
+                        f.write_str(" ")?;
+                    }
+                    None => write!(f, "{}{} ", DLT_ARGUMENT_SENTINAL, arg)?,
                 }
-            } else {
-                self.write_app_id_context_id_and_message_type(f)?;
             }
+            is_written = !frame_metadata.pdus.is_empty();
         } else {
             self.write_app_id_context_id_and_message_type(f)?;
         }
@@ -493,6 +438,296 @@ impl Message {
         }
         Ok(())
     }
+
+    /// the frame of a non-verbose message in the fibex
+    pub(crate) fn fibex_frame(&self, id: u32) -> Option<&Arc<FrameMetadata>> {
+        let fibex_metadata = self.fibex_metadata.as_ref()?;
+        let id_text = format!("ID_{}", id);
+        if let Some(extended_header) = &self.extended_header {
+            fibex_metadata.frame_map_with_key.get(&(
+                ContextId(extended_header.context_id.clone()),
+                ApplicationId(extended_header.application_id.clone()),
+                FrameId(id_text),
+            )) // TODO: avoid cloning here (Cow or Borrow)
+        } else {
+            fibex_metadata.frame_map.get(&FrameId(id_text))
+        }
+    }
+
+    /// the arguments of a verbose message or the signals of a non-verbose message
+    ///
+    /// None for control messages and non-verbose messages that are not described in the fibex
+    pub fn arguments(&self) -> Option<Cow<'_, [Argument]>> {
+        match &self.payload.payload_content {
+            PayloadContent::Verbose(arguments) => Some(Cow::Borrowed(arguments)),
+            PayloadContent::NonVerbose(id, data) => {
+                let frame_metadata = self.fibex_frame(*id)?;
+                self.fibex_arguments(frame_metadata, data)
+                    .ok()
+                    .map(Cow::Owned)
+            }
+            PayloadContent::ControlMsg(_, _) => None,
+        }
+    }
+
+    /// decodes the payload of a non-verbose message with the pdus of its frame in the fibex
+    ///
+    /// descriptions of the pdus become string arguments
+    pub(crate) fn fibex_arguments(
+        &self,
+        frame_metadata: &FrameMetadata,
+        data: &[u8],
+    ) -> Result<Vec<Argument>, fmt::Error> {
+        self.decode_fibex_arguments(frame_metadata, data, true)
+    }
+
+    /// like `fibex_arguments`, but the signals keep the types and raw values of the
+    /// fibex and are named after their SHORT-NAMEs
+    pub(crate) fn raw_fibex_arguments(
+        &self,
+        frame_metadata: &FrameMetadata,
+        data: &[u8],
+    ) -> Result<Vec<Argument>, fmt::Error> {
+        self.decode_fibex_arguments(frame_metadata, data, false)
+    }
+
+    fn decode_fibex_arguments(
+        &self,
+        frame_metadata: &FrameMetadata,
+        data: &[u8],
+        convert_signals: bool,
+    ) -> Result<Vec<Argument>, fmt::Error> {
+        let mut arguments = vec![];
+        let mut offset = 0;
+        for pdu in &frame_metadata.pdus {
+            if let Some(description) = &pdu.description {
+                arguments.push(text_argument(description.to_string()));
+            } else {
+                for (index, signal_type) in pdu.signal_types.iter().enumerate() {
+                    let mut fixed_point = None;
+                    let value = match signal_type.kind {
+                        TypeInfoKind::StringType | TypeInfoKind::Raw => {
+                            if data.len() < offset + 2 {
+                                return Err(fmt::Error);
+                            }
+                            let length = if self.header.endianness == Endianness::Big {
+                                BigEndian::read_u16(&data[offset..offset + 2]) as usize
+                            } else {
+                                LittleEndian::read_u16(&data[offset..offset + 2]) as usize
+                            };
+                            offset += 2;
+                            if data.len() < offset + length {
+                                return Err(fmt::Error);
+                            }
+                            let v = if signal_type.kind == TypeInfoKind::StringType {
+                                Value::StringVal(
+                                    String::from_utf8(data[offset..offset + length].to_vec())
+                                        .map_err(|_| fmt::Error)?,
+                                )
+                            } else {
+                                Value::Raw(Vec::from(&data[offset..offset + length]))
+                            };
+                            offset += length;
+                            v
+                        }
+                        TypeInfoKind::Bool => {
+                            offset += 1;
+                            if data.len() < offset {
+                                return Err(fmt::Error);
+                            }
+                            Value::Bool(data[offset - 1])
+                        }
+                        TypeInfoKind::Float(width) => {
+                            let length = width as usize / 8;
+                            if data.len() < offset + length {
+                                return Err(fmt::Error);
+                            }
+                            let v = if self.header.endianness == Endianness::Big {
+                                dlt_fint::<BigEndian>(width)(&data[offset..offset + length])
+                            } else {
+                                dlt_fint::<LittleEndian>(width)(&data[offset..offset + length])
+                            }
+                            .map_err(|_| fmt::Error)?
+                            .1;
+                            offset += length;
+                            v
+                        }
+                        TypeInfoKind::Signed(length) => {
+                            let byte_length = length as usize / 8;
+                            if data.len() < offset + byte_length {
+                                return Err(fmt::Error);
+                            }
+                            let value_offset = &data[offset..];
+                            let (_, v) = if self.header.endianness == Endianness::Big {
+                                dlt_sint::<BigEndian>(length)(value_offset)
+                            } else {
+                                dlt_sint::<LittleEndian>(length)(value_offset)
+                            }
+                            .map_err(|_| fmt::Error)?;
+                            offset += byte_length;
+                            v
+                        }
+                        TypeInfoKind::SignedFixedPoint(length) => {
+                            let byte_length = length as usize / 8;
+                            if data.len() < offset + byte_length {
+                                return Err(fmt::Error);
+                            }
+                            let (value_offset, fp) = if self.header.endianness == Endianness::Big {
+                                dlt_fixed_point::<BigEndian>(
+                                    &data[offset..offset + byte_length],
+                                    length,
+                                )
+                            } else {
+                                dlt_fixed_point::<LittleEndian>(
+                                    &data[offset..offset + byte_length],
+                                    length,
+                                )
+                            }
+                            .map_err(|_| fmt::Error)?;
+                            fixed_point = Some(fp);
+                            let (_, v) = if self.header.endianness == Endianness::Big {
+                                dlt_sint::<BigEndian>(fixed_point_width_to_type_length(length))(
+                                    value_offset,
+                                )
+                            } else {
+                                dlt_sint::<LittleEndian>(fixed_point_width_to_type_length(length))(
+                                    value_offset,
+                                )
+                            }
+                            .map_err(|_| fmt::Error)?;
+                            offset += byte_length;
+                            v
+                        }
+                        TypeInfoKind::Unsigned(length) => {
+                            let byte_length = length as usize / 8;
+                            if data.len() < offset + byte_length {
+                                return Err(fmt::Error);
+                            }
+                            let value_offset = &data[offset..];
+                            let (_, v) = if self.header.endianness == Endianness::Big {
+                                dlt_uint::<BigEndian>(length)(value_offset)
+                            } else {
+                                dlt_uint::<LittleEndian>(length)(value_offset)
+                            }
+                            .map_err(|_| fmt::Error)?;
+                            offset += byte_length;
+                            v
+                        }
+                        TypeInfoKind::UnsignedFixedPoint(length) => {
+                            let byte_length = length as usize / 8;
+                            if data.len() < offset + byte_length {
+                                return Err(fmt::Error);
+                            }
+                            let value_offset = {
+                                let (r, fp) = if self.header.endianness == Endianness::Big {
+                                    dlt_fixed_point::<BigEndian>(
+                                        &data[offset..offset + byte_length],
+                                        length,
+                                    )
+                                } else {
+                                    dlt_fixed_point::<LittleEndian>(
+                                        &data[offset..offset + byte_length],
+                                        length,
+                                    )
+                                }
+                                .map_err(|_| fmt::Error)?;
+                                fixed_point = Some(fp);
+                                r
+                            };
+                            let (_, v) = if self.header.endianness == Endianness::Big {
+                                dlt_uint::<BigEndian>(fixed_point_width_to_type_length(length))(
+                                    value_offset,
+                                )
+                            } else {
+                                dlt_uint::<LittleEndian>(fixed_point_width_to_type_length(length))(
+                                    value_offset,
+                                )
+                            }
+                            .map_err(|_| fmt::Error)?;
+                            offset += byte_length;
+                            v
+                        }
+                        // fibex signals are never described as arrays or structs, their
+                        // length is unknown and the rest of the frame can't be decoded
+                        TypeInfoKind::Array(_) => {
+                            arguments.push(text_argument("<array>".to_string()));
+                            return Ok(arguments);
+                        }
+                        TypeInfoKind::Struct => {
+                            arguments.push(text_argument("<struct>".to_string()));
+                            return Ok(arguments);
+                        }
+                    };
+                    let mut arg = Argument {
+                        type_info: signal_type.clone(),
+                        name: None,
+                        trace_info: None,
+                        unit: None,
+                        fixed_point,
+                        value,
+                    };
+                    if !convert_signals {
+                        arg.name = pdu.signal_names.get(index).cloned().flatten();
+                    } else if let Some(conversion) =
+                        pdu.signal_conversions.get(index).and_then(Option::as_ref)
+                    {
+                        convert_signal(&mut arg, conversion);
+                    }
+                    arguments.push(arg);
+                }
+            };
+        }
+        Ok(arguments)
+    }
+}
+/// a string argument without name, e.g. for the description of a pdu
+fn text_argument(text: String) -> Argument {
+    Argument {
+        type_info: TypeInfo {
+            kind: TypeInfoKind::StringType,
+            coding: StringCoding::UTF8,
+            has_trace_info: false,
+            has_variable_info: false,
+        },
+        name: None,
+        trace_info: None,
+        unit: None,
+        fixed_point: None,
+        value: Value::StringVal(text),
+    }
+}
+/// names the signal and applies the COMPU-METHOD of its coding to the raw value
+///
+/// values that are not covered by a scale of the COMPU-METHOD stay unchanged
+fn convert_signal(arg: &mut Argument, conversion: &SignalConversion) {
+    arg.name = Some(conversion.signal_name.clone());
+    let physical = arg
+        .numeric_value()
+        .and_then(|raw| conversion.compu_method.convert(raw));
+    match physical {
+        Some(PhysicalValue::Text(text)) => {
+            arg.type_info = TypeInfo {
+                kind: TypeInfoKind::StringType,
+                coding: StringCoding::UTF8,
+                has_trace_info: false,
+                has_variable_info: false,
+            };
+            arg.fixed_point = None;
+            arg.value = Value::StringVal(text);
+        }
+        Some(PhysicalValue::Number(number)) => {
+            arg.type_info = TypeInfo {
+                kind: TypeInfoKind::Float(FloatWidth::Width64),
+                coding: StringCoding::ASCII,
+                has_trace_info: false,
+                has_variable_info: false,
+            };
+            arg.fixed_point = None;
+            arg.value = Value::F64(number);
+            arg.unit = conversion.compu_method.unit.clone();
+        }
+        None => arg.unit = conversion.compu_method.unit.clone(),
+    }
 }
 impl fmt::Display for TypeInfo {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
@@ -505,8 +740,10 @@ impl fmt::Display for TypeInfo {
                 format!("Signed FP ({:?})", float_width)
             }
             TypeInfoKind::Float(float_width) => format!("Float ({:?})", float_width),
+            TypeInfoKind::Array(element_kind) => format!("Array ({:?})", element_kind),
             TypeInfoKind::StringType => "String".into(),
             TypeInfoKind::Raw => "Raw".into(),
+            TypeInfoKind::Struct => "Struct".into(),
         };
         write!(f, "TypeInfo {}", kind)
     }
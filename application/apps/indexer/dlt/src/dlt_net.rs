@@ -1,10 +1,11 @@
 extern crate dirs;
 use crate::dlt::*;
+use crate::dlt_counter::MessageCounterTracker;
 use crate::dlt_file::create_dlt_session_file;
-use crate::dlt_parse::dlt_message;
 use crate::dlt_parse::*;
 use crate::fibex::FibexMetadata;
 use crate::filtering;
+use crate::payload_search::IndexLine;
 use async_std::net::{Ipv4Addr, UdpSocket};
 use crossbeam_channel as cc;
 use failure::err_msg;
@@ -17,7 +18,7 @@ use indexer_base::progress::*;
 use indexer_base::utils;
 use std::io::{BufWriter, Write};
 use std::net::SocketAddr;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Debug, Fail)]
 pub enum ConnectionError {
@@ -103,12 +104,12 @@ pub async fn index_from_socket2(
             b: (0, 0),
         },
     }));
-    let udp_msg_producer = UdpMessageProducer {
+    let udp_msg_producer = UdpMessageProducer::new(
         socket,
-        update_channel: update_channel.clone(),
-        fibex_metadata: fibex_metadata.map(Rc::new),
-        filter_config,
-    };
+        update_channel.clone(),
+        fibex_metadata.map(Arc::new),
+        filter_config.clone(),
+    );
     // listen for both a shutdown request and incomming messages
     // to do this we need to select over streams of the same type
     // the type we use to unify is this Event enum
@@ -146,9 +147,18 @@ pub async fn index_from_socket2(
             Some(msgs) => {
                 trace!("socket: got {} messages ...", msgs.len());
                 for m in msgs {
+                    let line = match IndexLine::new(filter_config.as_ref(), &m) {
+                        Some(line) => line,
+                        None => continue,
+                    };
                     tmp_writer.write_all(&m.as_bytes())?;
-                    let written_bytes_len =
-                        utils::create_tagged_line_d(tag, &mut buf_writer, &m, line_nr, true)?;
+                    let written_bytes_len = utils::create_tagged_line_d(
+                        tag,
+                        &mut buf_writer,
+                        line.text,
+                        line_nr,
+                        true,
+                    )?;
                     line_nr += 1;
                     if let Some(chunk) = chunk_factory.add_bytes(line_nr, written_bytes_len) {
                         buf_writer.flush()?;
@@ -179,8 +189,9 @@ pub async fn create_index_and_mapping_dlt_from_socket(
     trace!("create_index_and_mapping_dlt_from_socket");
     let res = match utils::next_line_nr(out_path) {
         Ok(initial_line_nr) => {
-            let filter_config: Option<filtering::ProcessedDltFilterConfig> =
-                dlt_filter.map(filtering::process_filter_config);
+            let filter_config: Option<filtering::ProcessedDltFilterConfig> = dlt_filter
+                .map(filtering::process_filter_config)
+                .transpose()?;
             match index_from_socket2(
                 session_id,
                 socket_config,
@@ -240,14 +251,16 @@ pub async fn create_index_and_mapping_dlt_from_socket(
 pub struct UdpMessageProducer {
     socket: UdpSocket,
     update_channel: cc::Sender<ChunkResults>,
-    fibex_metadata: Option<Rc<FibexMetadata>>,
+    fibex_metadata: Option<Arc<FibexMetadata>>,
     filter_config: Option<filtering::ProcessedDltFilterConfig>,
+    counters: MessageCounterTracker,
+    index: usize,
 }
 impl UdpMessageProducer {
     pub fn new(
         socket: UdpSocket,
         update_channel: cc::Sender<ChunkResults>,
-        fibex_metadata: Option<Rc<FibexMetadata>>,
+        fibex_metadata: Option<Arc<FibexMetadata>>,
         filter_config: Option<filtering::ProcessedDltFilterConfig>,
     ) -> Self {
         UdpMessageProducer {
@@ -255,6 +268,8 @@ impl UdpMessageProducer {
             update_channel,
             fibex_metadata,
             filter_config,
+            counters: MessageCounterTracker::new(),
+            index: 0,
         }
     }
 }
@@ -264,9 +279,10 @@ impl futures::Stream for UdpMessageProducer {
         self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context,
     ) -> futures::task::Poll<Option<Self::Item>> {
+        let this = self.get_mut();
         let mut buf = [0u8; 65535];
         let (pending, received_bytes) = {
-            let mut f = self.socket.recv_from(&mut buf).boxed();
+            let mut f = this.socket.recv_from(&mut buf).boxed();
             match f.as_mut().poll(cx) {
                 futures::task::Poll::Pending => (true, 0),
                 futures::task::Poll::Ready(Err(e)) => {
@@ -286,13 +302,16 @@ impl futures::Stream for UdpMessageProducer {
             let mut messages: Vec<Message> = vec![];
             let mut consumed = 0usize;
             loop {
-                match dlt_message(
+                let index = this.index;
+                this.index += 1;
+                match dlt_message_tracked(
                     &buf[consumed..],
-                    self.filter_config.as_ref(),
-                    0,
-                    Some(&self.update_channel),
-                    self.fibex_metadata.clone(),
-                    false,
+                    this.filter_config.as_ref(),
+                    index,
+                    Some(&this.update_channel),
+                    this.fibex_metadata.clone(),
+                    DltFraming::None,
+                    Some(&mut this.counters),
                 ) {
                     Ok((_, ParsedMessage::Invalid)) => {
                         warn!("invalid message received");
@@ -301,11 +320,14 @@ impl futures::Stream for UdpMessageProducer {
                         }
                         return futures::task::Poll::Ready(None);
                     }
-                    Ok((_, ParsedMessage::FilteredOut)) => {
-                        continue;
+                    Ok((rest, ParsedMessage::FilteredOut)) => {
+                        consumed = buf.len() - rest.len();
+                        if consumed >= received_bytes {
+                            return futures::task::Poll::Ready(Some(Ok(Some(messages))));
+                        }
                     }
-                    Ok((_, ParsedMessage::Item(m))) => {
-                        consumed += m.byte_len() as usize;
+                    Ok((rest, ParsedMessage::Item(m))) => {
+                        consumed = buf.len() - rest.len();
                         let msg_with_storage_header = match m.storage_header {
                             Some(_) => m,
                             None => m.add_storage_header(None),
@@ -10,21 +10,26 @@
 // is strictly forbidden unless prior written permission is obtained
 // from E.S.R.Labs.
 use crate::dlt::*;
+use crate::dlt_check::{garbage_length, CorruptionCollector, CorruptionReason, MessageLocation};
+use crate::dlt_control::{ApplicationInfo, EcuRegistry};
+use crate::dlt_counter::MessageCounterTracker;
+use crate::dlt_reader::{DltReader, DltReaderMode};
+use crate::dlt_view::*;
 use crate::filtering;
 use crossbeam_channel as cc;
 use indexer_base::{chunks::ChunkResults, error_reporter::*, progress::*, utils};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use buf_redux::policy::MinBuffered;
-use buf_redux::BufReader as ReduxReader;
 use byteorder::{BigEndian, LittleEndian};
 use failure::{err_msg, Error};
 use nom::bytes::streaming::{tag, take, take_while_m_n};
-use nom::{combinator::map, multi::count, number::streaming, sequence::tuple, IResult};
+use nom::{
+    combinator::cond, combinator::map, multi::count, number::streaming, sequence::tuple, IResult,
+};
 use rustc_hash::FxHashMap;
 use std::fs;
-use std::io::{BufRead, Read};
-use std::rc::Rc;
+use std::io::BufRead;
+use std::sync::Arc;
 
 use crate::fibex::FibexMetadata;
 use std::str;
@@ -34,6 +39,46 @@ pub(crate) const DLT_READER_CAPACITY: usize = 10 * 1024 * 1024;
 pub(crate) const DLT_MIN_BUFFER_SPACE: usize = 10 * 1024;
 pub(crate) const DLT_PATTERN_SIZE: usize = 4;
 pub(crate) const DLT_PATTERN: &[u8] = &[0x44, 0x4C, 0x54, 0x01];
+pub(crate) const DLT_SERIAL_PATTERN: &[u8] = &[0x44, 0x4C, 0x53, 0x01];
+
+/// how the messages of a dlt stream are separated from each other
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum DltFraming {
+    /// messages follow each other without any header in between (UDP, pcap)
+    None,
+    /// every message starts with a storage header (`DLT\x01`), as written by dlt-viewer
+    StorageHeader,
+    /// every message starts with the serial header (`DLS\x01`) as sent over serial lines
+    SerialHeader,
+}
+
+// not derived, `#[default]` on an enum variant needs Rust 1.62
+#[allow(clippy::derivable_impls)]
+impl Default for DltFraming {
+    fn default() -> Self {
+        DltFraming::StorageHeader
+    }
+}
+
+impl DltFraming {
+    /// the marker every message starts with, `None` if messages are not framed
+    pub fn pattern(self) -> Option<&'static [u8]> {
+        match self {
+            DltFraming::None => None,
+            DltFraming::StorageHeader => Some(DLT_PATTERN),
+            DltFraming::SerialHeader => Some(DLT_SERIAL_PATTERN),
+        }
+    }
+
+    /// number of bytes in front of the standard header
+    pub fn header_length(self) -> usize {
+        match self {
+            DltFraming::None => 0,
+            DltFraming::StorageHeader => STORAGE_HEADER_LENGTH,
+            DltFraming::SerialHeader => DLT_PATTERN_SIZE,
+        }
+    }
+}
 
 pub(crate) fn parse_ecu_id(input: &[u8]) -> IResult<&[u8], &str> {
     dlt_zero_terminated_string(input, 4)
@@ -44,10 +89,25 @@ pub(crate) fn parse_ecu_id(input: &[u8]) -> IResult<&[u8], &str> {
 /// note: we won't skip anything if the input already begins
 /// with a storage header
 pub(crate) fn forward_to_next_storage_header(input: &[u8]) -> Option<(usize, &[u8])> {
+    forward_to_next_pattern(input, DLT_PATTERN)
+}
+
+/// skip ahead in input array till we reach the start of the next message
+/// (see `forward_to_next_storage_header`)
+///
+/// without framing the input is expected to start with a message
+pub(crate) fn forward_to_next_message(input: &[u8], framing: DltFraming) -> Option<(usize, &[u8])> {
+    match framing.pattern() {
+        Some(pattern) => forward_to_next_pattern(input, pattern),
+        None => Some((0, input)),
+    }
+}
+
+fn forward_to_next_pattern<'a>(input: &'a [u8], pattern: &[u8]) -> Option<(usize, &'a [u8])> {
     let mut found = false;
     let mut to_drop = 0usize;
-    for v in input.windows(DLT_PATTERN_SIZE) {
-        if v == DLT_PATTERN {
+    for v in input.windows(pattern.len()) {
+        if v == pattern {
             found = true;
             break;
         }
@@ -56,7 +116,8 @@ pub(crate) fn forward_to_next_storage_header(input: &[u8]) -> Option<(usize, &[u
 
     if !found {
         debug!(
-            "forward but no more storage header (input left {})",
+            "forward but no more {:?} pattern (input left {})",
+            pattern,
             input.len()
         );
         return None;
@@ -71,7 +132,7 @@ pub(crate) fn dlt_storage_header<'a, T>(
     input: &'a [u8],
     index: Option<usize>,
     update_channel: Option<&cc::Sender<IndexingResults<T>>>,
-) -> IResult<&'a [u8], Option<StorageHeader>> {
+) -> IResult<&'a [u8], Option<StorageHeaderView<'a>>> {
     // println!("dlt_storage_header (left: {} bytes)", input.len());
     match forward_to_next_storage_header(input) {
         Some((consumed, rest)) => {
@@ -94,12 +155,12 @@ pub(crate) fn dlt_storage_header<'a, T>(
             // println!("after_stringA (left: {} bytes)", after_string.len());
             Ok((
                 after_string,
-                Some(StorageHeader {
+                Some(StorageHeaderView {
                     timestamp: DltTimeStamp {
                         seconds,
                         microseconds,
                     },
-                    ecu_id: ecu_id.to_string(),
+                    ecu_id,
                 }),
             ))
         }
@@ -116,6 +177,39 @@ pub(crate) fn dlt_storage_header<'a, T>(
     }
 }
 
+/// skip to the next serial header (`DLS\x01`) and consume it
+pub(crate) fn dlt_serial_header<'a, T>(
+    input: &'a [u8],
+    index: Option<usize>,
+    update_channel: Option<&cc::Sender<IndexingResults<T>>>,
+) -> IResult<&'a [u8], ()> {
+    match forward_to_next_pattern(input, DLT_SERIAL_PATTERN) {
+        Some((consumed, rest)) => {
+            if consumed > 0 {
+                if let Some(tx) = update_channel {
+                    let _ = tx.send(Err(Notification {
+                        severity: Severity::WARNING,
+                        content: format!("dropped {} to get to next message", consumed),
+                        line: index,
+                    }));
+                }
+            }
+            let (after_serial_header, _) = tag(DLT_SERIAL_PATTERN)(rest)?;
+            Ok((after_serial_header, ()))
+        }
+        None => {
+            if let Some(tx) = update_channel {
+                let _ = tx.send(Err(Notification {
+                    severity: Severity::ERROR,
+                    content: "did not find another serial header".to_string(),
+                    line: index,
+                }));
+            }
+            Err(nom::Err::Failure((&[], nom::error::ErrorKind::Verify)))
+        }
+    }
+}
+
 fn maybe_parse_ecu_id(a: bool) -> impl Fn(&[u8]) -> IResult<&[u8], Option<&str>> {
     fn parse_ecu_id_to_option(input: &[u8]) -> IResult<&[u8], Option<&str>> {
         map(parse_ecu_id, Some)(input)
@@ -145,7 +239,7 @@ fn maybe_parse_u32(a: bool) -> impl Fn(&[u8]) -> IResult<&[u8], Option<u32>> {
 
 /// The standard header is part of every DLT message
 /// all big endian format [PRS_Dlt_00091]
-pub(crate) fn dlt_standard_header(input: &[u8]) -> IResult<&[u8], StandardHeader> {
+pub(crate) fn dlt_standard_header(input: &[u8]) -> IResult<&[u8], StandardHeaderView<'_>> {
     let (rest, header_type_byte) = streaming::be_u8(input)?;
     let has_ecu_id = (header_type_byte & WITH_ECU_ID_FLAG) != 0;
     let has_session_id = (header_type_byte & WITH_SESSION_ID_FLAG) != 0;
@@ -158,24 +252,27 @@ pub(crate) fn dlt_standard_header(input: &[u8]) -> IResult<&[u8], StandardHeader
         maybe_parse_u32(has_timestamp),
     ))(rest)?;
     let has_extended_header = (header_type_byte & WITH_EXTENDED_HEADER_FLAG) != 0;
-    let payload_length = overall_length - calculate_all_headers_length(header_type_byte);
+    // an invalid length is detected later with `validated_payload_length`
+    let payload_length =
+        overall_length.wrapping_sub(calculate_all_headers_length(header_type_byte));
 
     Ok((
         i,
-        StandardHeader::new(
-            header_type_byte >> 5 & 0b111,
-            if (header_type_byte & BIG_ENDIAN_FLAG) != 0 {
+        StandardHeaderView {
+            version: header_type_byte >> 5 & 0b111,
+            endianness: if (header_type_byte & BIG_ENDIAN_FLAG) != 0 {
                 Endianness::Big
             } else {
                 Endianness::Little
             },
-            message_counter,
             has_extended_header,
-            payload_length,
-            ecu_id.map(|r| r.to_string()),
+            message_counter,
+            ecu_id,
             session_id,
             timestamp,
-        ),
+            payload_length,
+            v2: None,
+        },
     ))
 }
 
@@ -183,7 +280,7 @@ pub(crate) fn dlt_extended_header<'a, T>(
     input: &'a [u8],
     index: Option<usize>,
     update_channel: Option<&cc::Sender<IndexingResults<T>>>,
-) -> IResult<&'a [u8], ExtendedHeader> {
+) -> IResult<&'a [u8], ExtendedHeaderView<'a>> {
     let (i, (message_info, argument_count, app_id, context_id)) = tuple((
         streaming::be_u8,
         streaming::be_u8,
@@ -193,54 +290,15 @@ pub(crate) fn dlt_extended_header<'a, T>(
     let verbose = (message_info & VERBOSE_FLAG) != 0;
     match MessageType::try_from(message_info) {
         Ok(message_type) => {
-            if let Some(tx) = update_channel {
-                match message_type {
-                    MessageType::Unknown(n) => {
-                        let _ = tx.send(Err(Notification {
-                            severity: Severity::WARNING,
-                            content: format!("unknown message type {:?}", n),
-                            line: index,
-                        }));
-                    }
-                    MessageType::Log(LogLevel::Invalid(n)) => {
-                        let _ = tx.send(Err(Notification {
-                            severity: Severity::WARNING,
-                            content: format!("unknown log level {}", n),
-                            line: index,
-                        }));
-                    }
-                    MessageType::Control(ControlType::Unknown(n)) => {
-                        let _ = tx.send(Err(Notification {
-                            severity: Severity::WARNING,
-                            content: format!("unknown control type {}", n),
-                            line: index,
-                        }));
-                    }
-                    MessageType::ApplicationTrace(ApplicationTraceType::Invalid(n)) => {
-                        let _ = tx.send(Err(Notification {
-                            severity: Severity::WARNING,
-                            content: format!("invalid application-trace type {}", n),
-                            line: index,
-                        }));
-                    }
-                    MessageType::NetworkTrace(NetworkTraceType::Invalid) => {
-                        let _ = tx.send(Err(Notification {
-                            severity: Severity::WARNING,
-                            content: "invalid application-trace type 0".to_string(),
-                            line: index,
-                        }));
-                    }
-                    _ => (),
-                };
-            };
+            report_message_type_warnings(&message_type, index, update_channel);
             Ok((
                 i,
-                ExtendedHeader {
+                ExtendedHeaderView {
                     verbose,
                     argument_count,
                     message_type,
-                    application_id: app_id.to_string(),
-                    context_id: context_id.to_string(),
+                    application_id: app_id,
+                    context_id,
                 },
             ))
         }
@@ -259,6 +317,258 @@ pub(crate) fn dlt_extended_header<'a, T>(
         }
     }
 }
+/// decides which header layout to use per message
+///
+/// the version 1 header type is the first byte. The version 2 header type is a big
+/// endian u32 with the version in the last byte, its first byte only has reserved
+/// bits, which would be a version 0 header type without any flags in version 1
+pub(crate) fn is_v2_header(input: &[u8]) -> bool {
+    match input {
+        [0, _, _, header_type, ..] => (header_type >> 5) & 0b111 == DLT_V2_VERSION,
+        _ => false,
+    }
+}
+
+/// strings in version 2 headers are prefixed with their length (1 byte)
+fn dlt_v2_string(input: &[u8]) -> IResult<&[u8], &str> {
+    let (rest, length) = streaming::be_u8(input)?;
+    let (rest, content) = take(length)(rest)?;
+    let without_null = match content.iter().position(|b| *b == 0) {
+        Some(end) => &content[..end],
+        None => content,
+    };
+    let res_str = match str::from_utf8(without_null) {
+        Ok(content) => content,
+        Err(e) => {
+            let (valid, _) = without_null.split_at(e.valid_up_to());
+            unsafe { str::from_utf8_unchecked(valid) }
+        }
+    };
+    Ok((rest, res_str))
+}
+
+/// the timestamp of version 2 headers: nanoseconds (32 bit) followed by seconds (40 bit)
+fn dlt_v2_timestamp(input: &[u8]) -> IResult<&[u8], DltTimeStampV2> {
+    let (rest, (nanoseconds, seconds)) = tuple((streaming::be_u32, take(5usize)))(input)?;
+    let seconds = seconds
+        .iter()
+        .fold(0u64, |acc, byte| (acc << 8) | u64::from(*byte));
+    Ok((
+        rest,
+        DltTimeStampV2 {
+            seconds,
+            nanoseconds,
+        },
+    ))
+}
+
+/// the tags field of a version 2 header (the number of tags and the tags), it is
+/// only split into the tags by `dlt_v2_tag_list` when they are needed
+fn dlt_v2_tags(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    let (mut rest, tag_count) = streaming::be_u8(input)?;
+    for _ in 0..tag_count {
+        rest = dlt_v2_string(rest)?.0;
+    }
+    Ok((rest, &input[..input.len() - rest.len()]))
+}
+
+/// the tags of a tags field parsed by `dlt_v2_tags`
+pub(crate) fn dlt_v2_tag_list(tags: &[u8]) -> Vec<&str> {
+    match tags.split_first() {
+        Some((tag_count, rest)) => count(dlt_v2_string, *tag_count as usize)(rest)
+            .map(|(_, tags)| tags)
+            .unwrap_or_default(),
+        None => vec![],
+    }
+}
+
+/// version 2 headers hold the message id of non-verbose messages
+#[derive(Debug)]
+pub(crate) struct HeaderV2<'a> {
+    pub(crate) header: StandardHeaderView<'a>,
+    pub(crate) extended_header: ExtendedHeaderView<'a>,
+    pub(crate) message_id: Option<u32>,
+}
+
+/// The version 2 header (AUTOSAR R19-11+) replaces standard and extended header
+/// of version 1. It is always big endian.
+/// [HTYP2 (4 byte)][MCNT][LEN (2 byte)][conditional fields][extension fields]
+/// conditional fields depend on the content type:
+///   verbose: MSIN, NOAR, timestamp
+///   non-verbose: MSIN, timestamp, message id
+///   control: MSIN, NOAR
+/// extension fields depend on the flags in the header type:
+///   ECU id, app id + context id, session id, file name + line number, tags, privacy level
+///
+/// returns None if the length in the header does not cover all header fields
+pub(crate) fn dlt_header_v2<'a, T>(
+    input: &'a [u8],
+    index: Option<usize>,
+    update_channel: Option<&cc::Sender<IndexingResults<T>>>,
+) -> IResult<&'a [u8], Option<HeaderV2<'a>>> {
+    let (rest, header_type) = streaming::be_u32(input)?;
+    if (header_type & HTYP2_WITH_SEGMENTATION_FLAG) != 0 {
+        if let Some(tx) = update_channel {
+            let _ = tx.send(Err(Notification {
+                severity: Severity::WARNING,
+                content: "segmented DLT v2 messages are not supported".to_string(),
+                line: index,
+            }));
+        }
+        return Err(nom::Err::Error((input, nom::error::ErrorKind::Verify)));
+    }
+    let content_type = (header_type & 0b11) as u8;
+    if content_type > HTYP2_CONTENT_CONTROL {
+        if let Some(tx) = update_channel {
+            let _ = tx.send(Err(Notification {
+                severity: Severity::ERROR,
+                content: format!("invalid DLT v2 content type {}", content_type),
+                line: index,
+            }));
+        }
+        return Err(nom::Err::Error((input, nom::error::ErrorKind::Verify)));
+    }
+    let has = |flag: u32| (header_type & flag) != 0;
+    let (rest, (message_counter, overall_length, message_info)) =
+        tuple((streaming::be_u8, streaming::be_u16, streaming::be_u8))(rest)?;
+    let (rest, argument_count) = if content_type == HTYP2_CONTENT_NON_VERBOSE {
+        (rest, 0)
+    } else {
+        streaming::be_u8(rest)?
+    };
+    let (rest, timestamp) = cond(content_type != HTYP2_CONTENT_CONTROL, dlt_v2_timestamp)(rest)?;
+    let (rest, message_id) =
+        cond(content_type == HTYP2_CONTENT_NON_VERBOSE, streaming::be_u32)(rest)?;
+    let (rest, ecu_id) = cond(has(HTYP2_WITH_ECU_ID_FLAG), dlt_v2_string)(rest)?;
+    let (rest, app_and_context_id) = cond(
+        has(HTYP2_WITH_APP_AND_CONTEXT_ID_FLAG),
+        tuple((dlt_v2_string, dlt_v2_string)),
+    )(rest)?;
+    let (rest, session_id) = maybe_parse_u32(has(HTYP2_WITH_SESSION_ID_FLAG))(rest)?;
+    let (rest, source_location) = cond(
+        has(HTYP2_WITH_SOURCE_FILE_AND_LINE_FLAG),
+        map(
+            tuple((dlt_v2_string, streaming::be_u32)),
+            |(file_name, line_number)| SourceLocationView {
+                file_name,
+                line_number,
+            },
+        ),
+    )(rest)?;
+    let (rest, tags) = cond(has(HTYP2_WITH_TAGS_FLAG), dlt_v2_tags)(rest)?;
+    let (rest, privacy_level) = cond(has(HTYP2_WITH_PRIVACY_LEVEL_FLAG), streaming::be_u8)(rest)?;
+
+    // like in version 1 the message id is counted as part of the payload
+    let headers_length =
+        (input.len() - rest.len()) as u16 - if message_id.is_some() { 4 } else { 0 };
+    if overall_length < headers_length {
+        if let Some(tx) = update_channel {
+            let _ = tx.send(Err(Notification {
+                severity: Severity::ERROR,
+                content: format!(
+                    "Invalid header length {} (message only has {} bytes)",
+                    headers_length, overall_length
+                ),
+                line: index,
+            }));
+        }
+        return Ok((rest, None));
+    }
+    let message_type = match MessageType::try_from(message_info) {
+        Ok(message_type) => message_type,
+        Err(e) => {
+            if let Some(tx) = update_channel {
+                let _ = tx.send(Err(Notification {
+                    severity: Severity::ERROR,
+                    content: format!("lineInvalid message type: {}", e),
+                    line: index,
+                }));
+            }
+            return Err(nom::Err::Error((&[], nom::error::ErrorKind::Verify)));
+        }
+    };
+    report_message_type_warnings(&message_type, index, update_channel);
+    let with_app_and_context_id = app_and_context_id.is_some();
+    let (application_id, context_id) = app_and_context_id.unwrap_or(("", ""));
+    Ok((
+        rest,
+        Some(HeaderV2 {
+            header: StandardHeaderView {
+                version: DLT_V2_VERSION,
+                endianness: Endianness::Big,
+                has_extended_header: true,
+                message_counter,
+                ecu_id,
+                session_id,
+                timestamp: None,
+                payload_length: overall_length - headers_length,
+                v2: Some(HeaderV2FieldsView {
+                    timestamp,
+                    with_app_and_context_id,
+                    source_location,
+                    tags: tags.unwrap_or_default(),
+                    privacy_level,
+                }),
+            },
+            extended_header: ExtendedHeaderView {
+                verbose: content_type == HTYP2_CONTENT_VERBOSE,
+                argument_count,
+                message_type,
+                application_id,
+                context_id,
+            },
+            message_id,
+        }),
+    ))
+}
+
+/// warn about message types that are not defined by the standard
+fn report_message_type_warnings<T>(
+    message_type: &MessageType,
+    index: Option<usize>,
+    update_channel: Option<&cc::Sender<IndexingResults<T>>>,
+) {
+    if let Some(tx) = update_channel {
+        match message_type {
+            MessageType::Unknown(n) => {
+                let _ = tx.send(Err(Notification {
+                    severity: Severity::WARNING,
+                    content: format!("unknown message type {:?}", n),
+                    line: index,
+                }));
+            }
+            MessageType::Log(LogLevel::Invalid(n)) => {
+                let _ = tx.send(Err(Notification {
+                    severity: Severity::WARNING,
+                    content: format!("unknown log level {}", n),
+                    line: index,
+                }));
+            }
+            MessageType::Control(ControlType::Unknown(n)) => {
+                let _ = tx.send(Err(Notification {
+                    severity: Severity::WARNING,
+                    content: format!("unknown control type {}", n),
+                    line: index,
+                }));
+            }
+            MessageType::ApplicationTrace(ApplicationTraceType::Invalid(n)) => {
+                let _ = tx.send(Err(Notification {
+                    severity: Severity::WARNING,
+                    content: format!("invalid application-trace type {}", n),
+                    line: index,
+                }));
+            }
+            MessageType::NetworkTrace(NetworkTraceType::Invalid) => {
+                let _ = tx.send(Err(Notification {
+                    severity: Severity::WARNING,
+                    content: "invalid application-trace type 0".to_string(),
+                    line: index,
+                }));
+            }
+            _ => (),
+        };
+    };
+}
 #[inline]
 pub fn is_not_null(chr: u8) -> bool {
     chr != 0x0
@@ -444,12 +754,12 @@ pub(crate) fn dlt_type_info<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Typ
 }
 pub(crate) fn dlt_fixed_point<T: NomByteOrder>(
     input: &[u8],
-    width: FloatWidth,
+    width: FixedPointWidth,
 ) -> IResult<&[u8], FixedPoint> {
     // println!("width {:?} dlt_fixedpoint,input: \t{:02X?}", width, input);
     let (i, quantization) = T::parse_f32(input)?;
     // println!("parsed quantization: {:?}", quantization);
-    if width == FloatWidth::Width32 {
+    if width == FixedPointWidth::Width32 {
         let (rest, offset) = T::parse_i32(i)?;
         // println!("parsed offset: {:?}", offset);
         Ok((
@@ -459,7 +769,7 @@ pub(crate) fn dlt_fixed_point<T: NomByteOrder>(
                 offset: FixedPointValue::I32(offset),
             },
         ))
-    } else if width == FloatWidth::Width64 {
+    } else if width == FixedPointWidth::Width64 {
         let (rest, offset) = T::parse_i64(i)?;
         Ok((
             rest,
@@ -469,13 +779,36 @@ pub(crate) fn dlt_fixed_point<T: NomByteOrder>(
             },
         ))
     } else {
-        report_error("error in dlt_fixed_point");
-        Err(nom::Err::Error((&[], nom::error::ErrorKind::Verify)))
+        let (rest, offset) = T::parse_i128(i)?;
+        Ok((
+            rest,
+            FixedPoint {
+                quantization,
+                offset: FixedPointValue::I128(offset),
+            },
+        ))
     }
 }
+/// structs can contain other structs, limit how deep we follow them
+/// so corrupted data cannot exhaust the stack
+pub(crate) const DLT_MAX_STRUCT_NESTING: usize = 16;
+
 pub(crate) fn dlt_argument<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Argument> {
+    dlt_nested_argument::<T>(input, 0)
+}
+fn dlt_nested_argument<T: NomByteOrder>(
+    input: &[u8],
+    nesting_level: usize,
+) -> IResult<&[u8], Argument> {
     let (i, type_info) = dlt_type_info::<T>(input)?;
     dbg_parsed("type info", input, i, &type_info);
+    let (i, trace_info) = if type_info.has_trace_info {
+        let (after_trace_info, trace_info) = dlt_variable_name::<T>(i)?;
+        dbg_parsed("trace info", i, after_trace_info, &trace_info);
+        (after_trace_info, Some(trace_info))
+    } else {
+        (i, None)
+    };
     // println!("type info: {:?}", type_info);
     match type_info.kind {
         TypeInfoKind::Signed(width) => {
@@ -487,6 +820,7 @@ pub(crate) fn dlt_argument<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Argu
                 rest,
                 Argument {
                     name: name_unit.0,
+                    trace_info,
                     unit: name_unit.1,
                     value,
                     fixed_point: None,
@@ -502,11 +836,12 @@ pub(crate) fn dlt_argument<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Argu
             let (after_fixed_point, fixed_point) = (r, Some(fp));
             dbg_parsed("fixed_point", before_val, after_fixed_point, &fixed_point);
             let (rest, value) =
-                dlt_sint::<T>(float_width_to_type_length(width))(after_fixed_point)?;
+                dlt_sint::<T>(fixed_point_width_to_type_length(width))(after_fixed_point)?;
             Ok((
                 rest,
                 Argument {
                     name: name_unit.0,
+                    trace_info,
                     unit: name_unit.1,
                     value,
                     fixed_point,
@@ -523,6 +858,7 @@ pub(crate) fn dlt_argument<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Argu
                 rest,
                 Argument {
                     name,
+                    trace_info,
                     unit,
                     value,
                     fixed_point: None,
@@ -541,11 +877,12 @@ pub(crate) fn dlt_argument<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Argu
             //     before_val
             // );
             let (rest, value) =
-                dlt_uint::<T>(float_width_to_type_length(width))(after_fixed_point)?;
+                dlt_uint::<T>(fixed_point_width_to_type_length(width))(after_fixed_point)?;
             Ok((
                 rest,
                 Argument {
                     name,
+                    trace_info,
                     unit,
                     value,
                     fixed_point,
@@ -562,6 +899,7 @@ pub(crate) fn dlt_argument<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Argu
                 rest,
                 Argument {
                     name,
+                    trace_info,
                     unit,
                     value,
                     fixed_point: None,
@@ -581,6 +919,7 @@ pub(crate) fn dlt_argument<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Argu
                 rest,
                 Argument {
                     name,
+                    trace_info,
                     unit: None,
                     value,
                     fixed_point: None,
@@ -602,6 +941,7 @@ pub(crate) fn dlt_argument<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Argu
                 Argument {
                     type_info,
                     name,
+                    trace_info,
                     unit: None,
                     fixed_point: None,
                     value: Value::Bool(bool_value),
@@ -625,6 +965,7 @@ pub(crate) fn dlt_argument<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Argu
                 rest,
                 Argument {
                     name,
+                    trace_info,
                     unit: None,
                     fixed_point: None,
                     value: Value::StringVal(value.to_string()),
@@ -632,6 +973,89 @@ pub(crate) fn dlt_argument<T: NomByteOrder>(input: &[u8]) -> IResult<&[u8], Argu
                 },
             ))
         }
+        TypeInfoKind::Array(element_kind) => {
+            let (i2, dimension_cnt) = T::parse_u16(i)?;
+            let (i3, dimensions) = count(T::parse_u16, dimension_cnt as usize)(i2)?;
+            dbg_parsed("array dimensions", i2, i3, &dimensions);
+            let (i4, (name, unit)) = dlt_variable_name_and_unit::<T>(&type_info)(i3)?;
+            let (i5, fixed_point) = match element_kind {
+                ArrayElementKind::SignedFixedPoint(width)
+                | ArrayElementKind::UnsignedFixedPoint(width) => {
+                    let (r, fp) = dlt_fixed_point::<T>(i4, width)?;
+                    (r, Some(fp))
+                }
+                _ => (i4, None),
+            };
+            // take all bytes of the elements first so we never try to
+            // collect more elements than there is data available
+            let element_cnt = array_element_count(&dimensions);
+            let (rest, element_bytes) =
+                take(element_cnt.saturating_mul(element_kind.width_in_bytes()))(i5)?;
+            let (_, elements) =
+                count(dlt_array_element::<T>(element_kind), element_cnt)(element_bytes)?;
+            dbg_parsed("array elements", i5, rest, &elements);
+            Ok((
+                rest,
+                Argument {
+                    name,
+                    trace_info,
+                    unit,
+                    value: Value::Array(dimensions, elements),
+                    fixed_point,
+                    type_info,
+                },
+            ))
+        }
+        TypeInfoKind::Struct => {
+            if nesting_level >= DLT_MAX_STRUCT_NESTING {
+                report_error(format!(
+                    "dlt struct nested deeper than {} levels",
+                    DLT_MAX_STRUCT_NESTING
+                ));
+                return Err(nom::Err::Error((i, nom::error::ErrorKind::TooLarge)));
+            }
+            let (i2, member_cnt) = T::parse_u16(i)?;
+            let (i3, name) = if type_info.has_variable_info {
+                map(dlt_variable_name::<T>, Some)(i2)?
+            } else {
+                (i2, None)
+            };
+            let mut members = vec![];
+            let mut rest = i3;
+            for _ in 0..member_cnt {
+                let (r, member) = dlt_nested_argument::<T>(rest, nesting_level + 1)?;
+                members.push(member);
+                rest = r;
+            }
+            dbg_parsed("struct members", i3, rest, &members);
+            Ok((
+                rest,
+                Argument {
+                    name,
+                    trace_info,
+                    unit: None,
+                    value: Value::Struct(members),
+                    fixed_point: None,
+                    type_info,
+                },
+            ))
+        }
+    }
+}
+fn dlt_array_element<T: NomByteOrder>(
+    element_kind: ArrayElementKind,
+) -> fn(&[u8]) -> IResult<&[u8], Value> {
+    match element_kind {
+        ArrayElementKind::Bool => |i| map(streaming::be_u8, Value::Bool)(i),
+        ArrayElementKind::Signed(width) => dlt_sint::<T>(width),
+        ArrayElementKind::SignedFixedPoint(width) => {
+            dlt_sint::<T>(fixed_point_width_to_type_length(width))
+        }
+        ArrayElementKind::Unsigned(width) => dlt_uint::<T>(width),
+        ArrayElementKind::UnsignedFixedPoint(width) => {
+            dlt_uint::<T>(fixed_point_width_to_type_length(width))
+        }
+        ArrayElementKind::Float(width) => dlt_fint::<T>(width),
     }
 }
 
@@ -640,7 +1064,7 @@ struct DltArgumentParser {
     current_index: Option<usize>,
 }
 
-fn dlt_payload<T: NomByteOrder>(
+pub(crate) fn dlt_payload<T: NomByteOrder>(
     input: &[u8],
     verbose: bool,
     payload_length: u16,
@@ -737,6 +1161,8 @@ pub enum ParsedMessage {
 
 /// a DLT message looks like this: [STANDARD-HEADER][EXTENDED-HEADER][PAYLOAD]
 /// if stored, an additional header is placed BEFORE all of this [storage-header][...]
+/// DLT version 2 messages ([V2-HEADER][PAYLOAD]) are detected by the version bits
+/// of the header type and parsed with `dlt_header_v2`
 /// example: 444C5401 262CC94D D8A20C00 45435500 3500001F 45435500 3F88623A 16014150 5000434F 4E001100 00000472 656D6F
 /// --------------------------------------------
 /// [STORAGE-HEADER]: 444C5401 262CC94D D8A20C00 45435500
@@ -777,171 +1203,195 @@ pub fn dlt_message<'a>(
     filter_config_opt: Option<&filtering::ProcessedDltFilterConfig>,
     index: usize,
     update_channel: Option<&cc::Sender<ChunkResults>>,
-    fibex_metadata: Option<Rc<FibexMetadata>>,
-    with_storage_header: bool,
+    fibex_metadata: Option<Arc<FibexMetadata>>,
+    framing: DltFraming,
 ) -> Result<(&'a [u8], ParsedMessage), DltParseError> {
-    // trace!("starting to parse dlt_message==================");
-    let (after_storage_header, storage_header) = if with_storage_header {
-        dlt_storage_header(input, Some(index), update_channel)?
-    } else {
-        (input, None)
+    dlt_message_tracked(
+        input,
+        filter_config_opt,
+        index,
+        update_channel,
+        fibex_metadata,
+        framing,
+        None,
+    )
+}
+
+/// like `dlt_message`, the message counters of all messages are tracked
+/// before they are filtered
+///
+/// lost messages are reported as warnings on the `update_channel`
+pub(crate) fn dlt_message_tracked<'a>(
+    input: &'a [u8],
+    filter_config_opt: Option<&filtering::ProcessedDltFilterConfig>,
+    index: usize,
+    update_channel: Option<&cc::Sender<ChunkResults>>,
+    fibex_metadata: Option<Arc<FibexMetadata>>,
+    framing: DltFraming,
+    counters: Option<&mut MessageCounterTracker>,
+) -> Result<(&'a [u8], ParsedMessage), DltParseError> {
+    let (rest, view) = dlt_message_view(input, Some(index), update_channel, framing)?;
+    let view = match view {
+        Some(view) => view,
+        None => return Ok((rest, ParsedMessage::Invalid)),
+    };
+    if let Some(counters) = counters {
+        if let Some(notification) = counters.track_message(&view, index) {
+            if let Some(tx) = update_channel {
+                let _ = tx.send(Err(notification));
+            }
+        }
+    }
+    let mut payload_expr = None;
+    if let Some(filter_config) = filter_config_opt {
+        if view.is_filtered_out(filter_config) {
+            // trace!("no need to parse further, skip payload");
+            return Ok((rest, ParsedMessage::FilteredOut));
+        }
+        match &filter_config.filter_expr {
+            Some(expr) if expr.uses_payload() => payload_expr = Some(expr),
+            Some(expr) if !expr.matches(&view) => return Ok((rest, ParsedMessage::FilteredOut)),
+            _ => (),
+        }
+    }
+    let payload = view.payload()?;
+    dbg_parsed("payload", view.payload_bytes(), &[], &payload);
+    if filtered_out_by_payload(filter_config_opt, &payload) {
+        return Ok((rest, ParsedMessage::FilteredOut));
+    }
+    let message = view.to_message_with_payload(payload, fibex_metadata);
+    if let Some(expr) = payload_expr {
+        if !expr.matches(&message) {
+            return Ok((rest, ParsedMessage::FilteredOut));
+        }
+    }
+    Ok((rest, ParsedMessage::Item(message)))
+}
+
+/// parses all headers of the next message and borrows its payload
+/// without decoding it (see `dlt_message` for the layout)
+///
+/// returns None for messages with an invalid length
+pub fn dlt_message_view<'a, T>(
+    input: &'a [u8],
+    index: Option<usize>,
+    update_channel: Option<&cc::Sender<IndexingResults<T>>>,
+    framing: DltFraming,
+) -> Result<(&'a [u8], Option<MessageView<'a>>), DltParseError> {
+    let (after_storage_header, storage_header) = match framing {
+        DltFraming::StorageHeader => dlt_storage_header(input, index, update_channel)?,
+        DltFraming::SerialHeader => {
+            let (rest, ()) = dlt_serial_header(input, index, update_channel)?;
+            (rest, None)
+        }
+        DltFraming::None => (input, None),
     };
     dbg_parsed(
         "storage header",
-        &input,
-        &after_storage_header,
+        input,
+        after_storage_header,
         &storage_header,
     );
+    if is_v2_header(after_storage_header) {
+        let (after_headers, parsed_header) =
+            dlt_header_v2(after_storage_header, index, update_channel)?;
+        dbg_parsed(
+            "v2 header",
+            after_storage_header,
+            after_headers,
+            &parsed_header,
+        );
+        let HeaderV2 {
+            header,
+            extended_header,
+            message_id,
+        } = match parsed_header {
+            Some(h) => h,
+            None => return Ok((after_headers, None)),
+        };
+        // the message id of non-verbose messages was already consumed with the header
+        let remaining_payload_length = match message_id {
+            Some(_) => header.payload_length.saturating_sub(4),
+            None => header.payload_length,
+        };
+        let (after_message, payload) = take(remaining_payload_length)(after_headers)?;
+        return Ok((
+            after_message,
+            Some(MessageView {
+                storage_header,
+                header,
+                extended_header: Some(extended_header),
+                message_id,
+                payload,
+            }),
+        ));
+    }
     let (after_storage_and_normal_header, header) = dlt_standard_header(after_storage_header)?;
-    // trace!(
-    //     "parsed header is {}",
-    //     if header.endianness == Endianness::Big {
-    //         "big endian"
-    //     } else {
-    //         "little endian"
-    //     }
-    // );
     dbg_parsed(
         "normal header",
-        &after_storage_header,
-        &after_storage_and_normal_header,
+        after_storage_header,
+        after_storage_and_normal_header,
         &header,
     );
-    // trace!("dlt_msg 3, header: {:?}", serde_json::to_string(&header));
-
-    // trace!("parsing 2...let's validate the payload length");
-    let payload_length = match validated_payload_length(&header, Some(index), update_channel) {
+    let payload_length = match validated_payload_length(&header, index, update_channel) {
         Some(length) => length,
         None => {
-            return Ok((after_storage_and_normal_header, ParsedMessage::Invalid));
+            return Ok((after_storage_and_normal_header, None));
         }
     };
-
-    // trace!("dlt_msg 4, payload_length: {}", payload_length);
-    let mut verbose: bool = false;
-    let mut is_controll_msg = false;
-    let mut arg_count = 0;
     let (after_headers, extended_header) = if header.has_extended_header {
-        // trace!("try to parse extended header");
         let (rest, ext_header) =
-            dlt_extended_header(after_storage_and_normal_header, Some(index), update_channel)?;
-        verbose = ext_header.verbose;
-        arg_count = ext_header.argument_count;
-        // trace!(
-        //     "did parse extended header (type: {})",
-        //     ext_header.message_type
-        // );
-        is_controll_msg = match ext_header.message_type {
-            MessageType::Control(_) => true,
-            _ => false,
-        };
-        // trace!(
-        //     "did parse extended header, verbose: {}, arg_count: {}, is_controll: {}",
-        //     verbose,
-        //     arg_count,
-        //     is_controll_msg
-        // );
+            dlt_extended_header(after_storage_and_normal_header, index, update_channel)?;
         dbg_parsed(
             "extended header",
-            &after_storage_and_normal_header,
-            &rest,
+            after_storage_and_normal_header,
+            rest,
             &ext_header,
         );
         (rest, Some(ext_header))
     } else {
         (after_storage_and_normal_header, None)
     };
-    // trace!(
-    //     "extended header: {:?}",
-    //     serde_json::to_string(&extended_header)
-    // );
-    // trace!("dlt_msg 5");
-    if let Some(filter_config) = filter_config_opt {
-        // trace!("dlt_msg 6");
-        if let Some(h) = &extended_header {
-            // trace!("dlt_msg 7");
-            if let Some(min_filter_level) = filter_config.min_log_level {
-                if h.skip_with_level(min_filter_level) {
-                    // trace!("no need to parse further, skip payload (skipped level)");
-                    let (after_message, _) = take(payload_length)(after_headers)?;
-                    return Ok((after_message, ParsedMessage::FilteredOut));
-                }
-            }
-            if let Some(only_these_components) = &filter_config.app_ids {
-                if !only_these_components.contains(&h.application_id) {
-                    // trace!("no need to parse further, skip payload (skipped app id)");
-                    let (after_message, _) = take(payload_length)(after_headers)?;
-                    return Ok((after_message, ParsedMessage::FilteredOut));
-                }
-            }
-            if let Some(only_these_context_ids) = &filter_config.context_ids {
-                if !only_these_context_ids.contains(&h.context_id) {
-                    // trace!("no need to parse further, skip payload (skipped context id)");
-                    let (after_message, _) = take(payload_length)(after_headers)?;
-                    return Ok((after_message, ParsedMessage::FilteredOut));
-                }
-            }
-            if let Some(only_these_ecu_ids) = &filter_config.ecu_ids {
-                if let Some(ecu_id) = &header.ecu_id {
-                    if !only_these_ecu_ids.contains(ecu_id) {
-                        // trace!("no need to parse further, skip payload (skipped ecu id)");
-                        let (after_message, _) = take(payload_length)(after_headers)?;
-                        return Ok((after_message, ParsedMessage::FilteredOut));
-                    }
-                }
-            }
-        }
-    }
-    // trace!("about to parse payload, left: {}", after_headers.len());
-    // trace!("after_headers: {} bytes left", after_headers.len());
-    // trace!(
-    //     "parsing payload (header is {})",
-    //     if header.endianness == Endianness::Big {
-    //         "big endian"
-    //     } else {
-    //         "little endian"
-    //     }
-    // );
-    let (i, payload) = if header.endianness == Endianness::Big {
-        // trace!("parsing payload big endian");
-        dlt_payload::<BigEndian>(
-            after_headers,
-            verbose,
-            payload_length,
-            arg_count,
-            is_controll_msg,
-        )?
-    } else {
-        // trace!("parsing payload little endian");
-        dlt_payload::<LittleEndian>(
-            after_headers,
-            verbose,
-            payload_length,
-            arg_count,
-            is_controll_msg,
-        )?
-    };
-    dbg_parsed("payload", &after_headers, &i, &payload);
-    // trace!("after payload: {} bytes left", i.len());
+    let (after_message, payload) = take(payload_length)(after_headers)?;
     Ok((
-        i,
-        ParsedMessage::Item(Message {
+        after_message,
+        Some(MessageView {
             storage_header,
             header,
             extended_header,
+            message_id: None,
             payload,
-            fibex_metadata,
         }),
     ))
 }
 
+/// the trace info is part of the arguments so we can only check after parsing the payload
+fn filtered_out_by_payload(
+    filter_config_opt: Option<&filtering::ProcessedDltFilterConfig>,
+    payload: &Payload2,
+) -> bool {
+    if let Some(only_these_trace_infos) = filter_config_opt.and_then(|c| c.trace_infos.as_ref()) {
+        let has_trace_info = match &payload.payload_content {
+            PayloadContent::Verbose(arguments) => {
+                arguments.iter().any(|arg| match &arg.trace_info {
+                    Some(t) => only_these_trace_infos.contains(t),
+                    None => false,
+                })
+            }
+            _ => false,
+        };
+        return !has_trace_info;
+    }
+    false
+}
+
 fn validated_payload_length<T>(
-    header: &StandardHeader,
+    header: &StandardHeaderView,
     index: Option<usize>,
     update_channel: Option<&cc::Sender<IndexingResults<T>>>,
 ) -> Option<u16> {
-    let message_length = header.overall_length();
     let headers_length = calculate_all_headers_length(header.header_type_byte());
+    let message_length = header.payload_length.wrapping_add(headers_length);
     if message_length < headers_length {
         if let Some(tx) = update_channel {
             let _ = tx.send(Err(Notification {
@@ -958,14 +1408,17 @@ fn validated_payload_length<T>(
     Some(message_length - headers_length)
 }
 
-fn skip_till_after_next_storage_header(input: &[u8]) -> Result<(&[u8], usize), DltParseError> {
-    match forward_to_next_storage_header(input) {
+fn skip_till_after_next_header(
+    input: &[u8],
+    framing: DltFraming,
+) -> Result<(&[u8], usize), DltParseError> {
+    match forward_to_next_message(input, framing) {
         Some((consumed, rest)) => {
-            let (after_storage_header, skipped_bytes) = skip_storage_header(rest)?;
-            Ok((after_storage_header, consumed + skipped_bytes))
+            let (after_header, skipped_bytes) = skip_framing_header(rest, framing)?;
+            Ok((after_header, consumed + skipped_bytes))
         }
         None => Err(DltParseError::ParsingHickup {
-            reason: "did not find another storage header".into(),
+            reason: "did not find another message header".into(),
         }),
     }
 }
@@ -983,68 +1436,111 @@ pub(crate) fn skip_storage_header(input: &[u8]) -> Result<(&[u8], usize), DltPar
     }
 }
 
+/// skip the storage header or the serial header if the input starts with it
+pub(crate) fn skip_framing_header(
+    input: &[u8],
+    framing: DltFraming,
+) -> Result<(&[u8], usize), DltParseError> {
+    match framing {
+        DltFraming::StorageHeader => skip_storage_header(input),
+        DltFraming::SerialHeader => {
+            let (i, _): (&[u8], _) = tag(DLT_SERIAL_PATTERN)(input)?;
+            Ok((i, DLT_PATTERN_SIZE))
+        }
+        DltFraming::None => Ok((input, 0)),
+    }
+}
+
 pub fn dlt_statistic_row_info<'a, T>(
     input: &'a [u8],
     index: Option<usize>,
-    with_storage_header: bool,
+    framing: DltFraming,
     update_channel: Option<&cc::Sender<IndexingResults<T>>>,
-) -> Result<(&'a [u8], StatisticRowInfo), DltParseError> {
-    let update_channel_ref = update_channel;
-    // let (after_storage_header, _) = skip_till_after_next_storage_header(input)?;
-    let (after_storage_header, _) = if with_storage_header {
-        skip_till_after_next_storage_header(input)?
-    } else {
-        (input, 0)
-    };
-    let (after_storage_and_normal_header, header) = dlt_standard_header(after_storage_header)?;
-
-    let payload_length = match validated_payload_length(&header, index, update_channel_ref) {
-        Some(length) => length,
+) -> Result<(&'a [u8], StatisticRowInfo<'a>), DltParseError> {
+    let (after_storage_header, skipped) = skip_till_after_next_header(input, framing)?;
+    let (after_message, view) = dlt_message_view(
+        after_storage_header,
+        index,
+        update_channel,
+        DltFraming::None,
+    )?;
+    let storage_header = framing_storage_header(&input[..skipped], framing);
+    let view = match view {
+        Some(view) => MessageView {
+            storage_header,
+            ..view
+        },
         None => {
             return Ok((
-                after_storage_and_normal_header,
+                after_message,
                 StatisticRowInfo {
                     app_id_context_id: None,
-                    ecu_id: header.ecu_id,
+                    ecu_id: ecu_id_of_invalid_message(after_storage_header, storage_header),
+                    session_id: None,
+                    message_counter: None,
                     level: None,
                     verbose: false,
+                    log_info: None,
                 },
             ));
         }
     };
-    if !header.has_extended_header {
-        // no app id, skip rest
-        let (after_message, _) = take(payload_length)(after_storage_and_normal_header)?;
-        return Ok((
-            after_message,
-            StatisticRowInfo {
-                app_id_context_id: None,
-                ecu_id: header.ecu_id,
-                level: None,
-                verbose: false,
-            },
-        ));
-    }
-
-    let (after_headers, extended_header) =
-        dlt_extended_header(after_storage_and_normal_header, index, update_channel)?;
-    // skip payload
-    let (after_message, _) = take(payload_length)(after_headers)?;
-    let level = match extended_header.message_type {
-        MessageType::Log(level) => Some(level),
-        _ => None,
+    // version 2 headers might come without app id and context id
+    let with_app_and_context_id = match &view.header.v2 {
+        Some(v2) => v2.with_app_and_context_id,
+        None => true,
     };
     Ok((
         after_message,
         StatisticRowInfo {
-            app_id_context_id: Some((extended_header.application_id, extended_header.context_id)),
-            ecu_id: header.ecu_id,
-            level,
-            verbose: extended_header.verbose,
+            app_id_context_id: match &view.extended_header {
+                Some(ext) if with_app_and_context_id => Some((ext.application_id, ext.context_id)),
+                _ => None,
+            },
+            ecu_id: view.ecu_id(),
+            session_id: view.header.session_id,
+            message_counter: Some(view.header.message_counter),
+            level: view.log_level(),
+            verbose: view.is_verbose(),
+            log_info: view.log_info(),
         },
     ))
 }
 
+/// the storage header at the end of `framing_header`, the bytes skipped up to the message
+fn framing_storage_header(
+    framing_header: &[u8],
+    framing: DltFraming,
+) -> Option<StorageHeaderView<'_>> {
+    match framing {
+        DltFraming::StorageHeader if framing_header.len() >= STORAGE_HEADER_LENGTH => {
+            let storage_header = &framing_header[framing_header.len() - STORAGE_HEADER_LENGTH..];
+            match dlt_storage_header::<()>(storage_header, None, None) {
+                Ok((_, header)) => header,
+                Err(_) => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// the ECU id of a message with an invalid length, only the length is broken so
+/// the ECU id is taken like `MessageView::ecu_id` does, from the standard header
+/// or else from the storage header
+fn ecu_id_of_invalid_message<'a>(
+    message: &'a [u8],
+    storage_header: Option<StorageHeaderView<'a>>,
+) -> Option<&'a str> {
+    let header_ecu_id = if is_v2_header(message) {
+        None
+    } else {
+        dlt_standard_header(message)
+            .ok()
+            .and_then(|(_, header)| header.ecu_id)
+    };
+    header_ecu_id.or_else(|| storage_header.map(|header| header.ecu_id))
+}
+
 #[derive(Debug, Fail, PartialEq)]
 pub enum DltParseError {
     #[fail(display = "parsing stopped, cannot continue: {}", cause)]
@@ -1133,8 +1629,8 @@ impl LevelDistribution {
 }
 type IdMap = FxHashMap<String, LevelDistribution>;
 
-fn add_for_level(level: Option<LogLevel>, ids: &mut IdMap, id: String) {
-    if let Some(n) = ids.get_mut(&id) {
+fn add_for_level(level: Option<LogLevel>, ids: &mut IdMap, id: &str) {
+    if let Some(n) = ids.get_mut(id) {
         match level {
             Some(LogLevel::Fatal) => {
                 *n = LevelDistribution {
@@ -1186,7 +1682,7 @@ fn add_for_level(level: Option<LogLevel>, ids: &mut IdMap, id: String) {
             }
         }
     } else {
-        ids.insert(id, LevelDistribution::new(level));
+        ids.insert(id.to_string(), LevelDistribution::new(level));
     }
 }
 #[derive(Serialize, Debug)]
@@ -1195,15 +1691,21 @@ pub struct StatisticInfo {
     context_ids: Vec<(String, LevelDistribution)>,
     ecu_ids: Vec<(String, LevelDistribution)>,
     contained_non_verbose: bool,
+    /// applications and contexts with descriptions found in get_log_info responses
+    ecu_registry: EcuRegistry,
+    /// message counters and lost messages per ECU and session id
+    message_counters: MessageCounterTracker,
 }
 pub type StatisticsResults = std::result::Result<IndexingProgress<StatisticInfo>, Notification>;
 pub fn get_dlt_file_info(
     in_file: &std::path::PathBuf,
     update_channel: &cc::Sender<StatisticsResults>,
     shutdown_receiver: Option<cc::Receiver<()>>,
+    reader_mode: DltReaderMode,
+    framing: DltFraming,
 ) -> Result<(), Error> {
-    let f = match fs::File::open(in_file) {
-        Ok(file) => file,
+    let mut reader = match DltReader::open(in_file, 0, DLT_READER_CAPACITY, reader_mode) {
+        Ok(reader) => reader,
         Err(e) => {
             error!("could not open {:?}", in_file);
             return Err(err_msg(format!("could not open {:?} ({})", in_file, e)));
@@ -1211,8 +1713,6 @@ pub fn get_dlt_file_info(
     };
 
     let source_file_size: usize = fs::metadata(&in_file)?.len() as usize;
-    let mut reader = ReduxReader::with_capacity(DLT_READER_CAPACITY, f)
-        .set_policy(MinBuffered(DLT_MIN_BUFFER_SPACE));
 
     let mut app_ids: IdMap = FxHashMap::default();
     let mut context_ids: IdMap = FxHashMap::default();
@@ -1220,87 +1720,115 @@ pub fn get_dlt_file_info(
     let mut index = 0usize;
     let mut processed_bytes = 0usize;
     let mut contained_non_verbose = false;
+    let mut ecu_registry = EcuRegistry::new();
+    let mut message_counters = MessageCounterTracker::new();
+    let mut corruption = CorruptionCollector::new(source_file_size as u64, update_channel.clone());
     loop {
-        match read_one_dlt_message_info(&mut reader, Some(index), true, Some(update_channel)) {
-            Ok(Some((
-                consumed,
-                StatisticRowInfo {
-                    app_id_context_id: Some((app_id, context_id)),
-                    ecu_id: ecu,
-                    level,
-                    verbose,
-                },
-            ))) => {
-                contained_non_verbose = contained_non_verbose || !verbose;
-                reader.consume(consumed);
-                add_for_level(level, &mut app_ids, app_id);
-                add_for_level(level, &mut context_ids, context_id);
-                match ecu {
-                    Some(id) => add_for_level(level, &mut ecu_ids, id),
-                    None => add_for_level(level, &mut ecu_ids, "NONE".to_string()),
-                };
-                processed_bytes += consumed;
-            }
-            Ok(Some((
-                consumed,
-                StatisticRowInfo {
-                    app_id_context_id: None,
-                    ecu_id: ecu,
-                    level,
-                    verbose,
-                },
-            ))) => {
-                contained_non_verbose = contained_non_verbose || !verbose;
-                reader.consume(consumed);
-                add_for_level(level, &mut app_ids, "NONE".to_string());
-                add_for_level(level, &mut context_ids, "NONE".to_string());
-                match ecu {
-                    Some(id) => add_for_level(level, &mut ecu_ids, id),
-                    None => add_for_level(level, &mut ecu_ids, "NONE".to_string()),
-                };
-                processed_bytes += consumed;
-            }
-            Ok(None) => {
-                break;
-            }
-            // Err(e) => {
-            //     return Err(err_msg(format!(
-            //         "error while parsing dlt messages[{}]: {}",
-            //         index, e
-            //     )))
-            Err(e) => {
-                // we couldn't parse the message. try to skip it and find the next.
-                debug!("stats...try to skip and continue parsing: {}", e);
-                match e {
-                    DltParseError::ParsingHickup { reason } => {
-                        // we couldn't parse the message. try to skip it and find the next.
-                        reader.consume(4); // at least skip the magic DLT pattern
-                        debug!(
-                            "error parsing 1 dlt message, try to continue parsing: {}",
-                            reason
-                        );
-                    }
-                    DltParseError::Unrecoverable { cause } => {
-                        warn!("cannot continue parsing: {}", cause);
-                        update_channel.send(Err(Notification {
-                            severity: Severity::ERROR,
-                            content: format!("error parsing dlt file: {}", cause),
-                            line: None,
-                        }))?;
-                        break;
+        let (consumed, parse_result) = match reader.fill_buf() {
+            Ok(content) => {
+                if content.is_empty() {
+                    break;
+                }
+                let available = content.len();
+                let offset = processed_bytes as u64;
+                let at_end = corruption.at_end(offset, available);
+                let dropped = garbage_length(content, framing, at_end);
+                if dropped > 0 {
+                    corruption.skipped(offset, dropped, CorruptionReason::Garbage, 0);
+                    reader.consume(dropped);
+                    processed_bytes += dropped;
+                    continue;
+                }
+                match dlt_statistic_row_info(content, Some(index), framing, Some(update_channel)) {
+                    Ok((rest, row)) => {
+                        let length = available - rest.len();
+                        match row.message_counter {
+                            Some(message_counter) => corruption.valid_message(MessageLocation {
+                                index,
+                                offset,
+                                length,
+                                message_counter,
+                                timestamp: None,
+                            }),
+                            None => corruption.skipped(
+                                offset,
+                                length,
+                                CorruptionReason::InvalidLength,
+                                1,
+                            ),
+                        }
+                        contained_non_verbose = contained_non_verbose || !row.verbose;
+                        let (app_id, context_id) =
+                            row.app_id_context_id.unwrap_or(("NONE", "NONE"));
+                        add_for_level(row.level, &mut app_ids, app_id);
+                        add_for_level(row.level, &mut context_ids, context_id);
+                        add_for_level(row.level, &mut ecu_ids, row.ecu_id.unwrap_or("NONE"));
+                        if let Some(applications) = &row.log_info {
+                            ecu_registry.add_log_info(row.ecu_id.unwrap_or("NONE"), applications);
+                        }
+                        if let Some(counter) = row.message_counter {
+                            message_counters.track(row.ecu_id, row.session_id, counter, index);
+                        }
+                        (length, Ok(()))
                     }
-                    DltParseError::IncompleteParse { needed } => {
-                        warn!(
-                            "cannot continue parsing, parse was incomplete: {:?}",
-                            needed
-                        );
-                        update_channel.send(Err(Notification {
-                            severity: Severity::ERROR,
-                            content: format!("parse was incomplete: {:?}", needed),
-                            line: None,
-                        }))?;
-                        break;
+                    Err(DltParseError::IncompleteParse { .. }) if at_end => {
+                        // the file ends in the middle of the message, the length might
+                        // be broken, so look for more messages after the header
+                        let length = std::cmp::min(DLT_PATTERN_SIZE, available);
+                        corruption.skipped(offset, length, CorruptionReason::Truncated, 1);
+                        (length, Ok(()))
                     }
+                    Err(e) => (0, Err(e)),
+                }
+            }
+            Err(e) => (
+                0,
+                Err(DltParseError::ParsingHickup {
+                    reason: format!("error while parsing dlt messages: {}", e),
+                }),
+            ),
+        };
+        reader.consume(consumed);
+        processed_bytes += consumed;
+        if let Err(e) = parse_result {
+            // we couldn't parse the message. try to skip it and find the next.
+            debug!("stats...try to skip and continue parsing: {}", e);
+            match e {
+                DltParseError::ParsingHickup { reason } => {
+                    corruption.skipped(
+                        processed_bytes as u64,
+                        DLT_PATTERN_SIZE,
+                        CorruptionReason::InvalidMessage(reason.clone()),
+                        1,
+                    );
+                    // we couldn't parse the message. try to skip it and find the next.
+                    reader.consume(DLT_PATTERN_SIZE); // at least skip the magic DLT pattern
+                    processed_bytes += DLT_PATTERN_SIZE;
+                    debug!(
+                        "error parsing 1 dlt message, try to continue parsing: {}",
+                        reason
+                    );
+                }
+                DltParseError::Unrecoverable { cause } => {
+                    warn!("cannot continue parsing: {}", cause);
+                    update_channel.send(Err(Notification {
+                        severity: Severity::ERROR,
+                        content: format!("error parsing dlt file: {}", cause),
+                        line: None,
+                    }))?;
+                    break;
+                }
+                DltParseError::IncompleteParse { needed } => {
+                    warn!(
+                        "cannot continue parsing, parse was incomplete: {:?}",
+                        needed
+                    );
+                    update_channel.send(Err(Notification {
+                        severity: Severity::ERROR,
+                        content: format!("parse was incomplete: {:?}", needed),
+                        line: None,
+                    }))?;
+                    break;
                 }
             }
         }
@@ -1315,6 +1843,7 @@ pub fn get_dlt_file_info(
             }))?;
         }
     }
+    corruption.finish();
     let res = StatisticInfo {
         app_ids: app_ids
             .into_iter()
@@ -1326,6 +1855,8 @@ pub fn get_dlt_file_info(
             .into_iter()
             .collect::<Vec<(String, LevelDistribution)>>(),
         contained_non_verbose,
+        ecu_registry,
+        message_counters,
     };
 
     update_channel.send(Ok(IndexingProgress::GotItem { item: res }))?;
@@ -1334,30 +1865,14 @@ pub fn get_dlt_file_info(
 }
 
 #[derive(Serialize, Debug)]
-pub struct StatisticRowInfo {
-    app_id_context_id: Option<(String, String)>,
-    ecu_id: Option<String>,
-    level: Option<LogLevel>,
-    verbose: bool,
-}
-fn read_one_dlt_message_info<T: Read>(
-    reader: &mut ReduxReader<T, MinBuffered>,
-    index: Option<usize>,
-    with_storage_header: bool,
-    update_channel: Option<&cc::Sender<StatisticsResults>>,
-) -> Result<Option<(usize, StatisticRowInfo)>, DltParseError> {
-    match reader.fill_buf() {
-        Ok(content) => {
-            if content.is_empty() {
-                return Ok(None);
-            }
-            let available = content.len();
-            let r = dlt_statistic_row_info(content, index, with_storage_header, update_channel)?;
-            let consumed = available - r.0.len();
-            Ok(Some((consumed, r.1)))
-        }
-        Err(e) => Err(DltParseError::ParsingHickup {
-            reason: format!("error while parsing dlt messages: {}", e),
-        }),
-    }
+pub struct StatisticRowInfo<'a> {
+    pub(crate) app_id_context_id: Option<(&'a str, &'a str)>,
+    pub(crate) ecu_id: Option<&'a str>,
+    pub(crate) session_id: Option<u32>,
+    /// None for messages with an invalid length
+    pub(crate) message_counter: Option<u8>,
+    pub(crate) level: Option<LogLevel>,
+    pub(crate) verbose: bool,
+    /// content of a get_log_info response
+    pub(crate) log_info: Option<Vec<ApplicationInfo>>,
 }
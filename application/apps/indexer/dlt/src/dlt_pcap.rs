@@ -1,7 +1,9 @@
 use crate::dlt::*;
+use crate::dlt_counter::MessageCounterTracker;
 use crate::dlt_parse::*;
 use crate::fibex::FibexMetadata;
 use crate::filtering;
+use crate::payload_search::{IndexLine, PayloadMatch};
 use async_std::task;
 use crossbeam_channel as cc;
 use etherparse::*;
@@ -16,17 +18,18 @@ use pcap_parser::PcapNGReader;
 use pcap_parser::*;
 use std::fs::*;
 use std::io::{BufWriter, Write};
-use std::rc::Rc;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn convert_to_dlt_file(
     pcap_path: std::path::PathBuf,
     dlt_filter: Option<filtering::DltFilterConfig>,
     update_channel: cc::Sender<ChunkResults>,
-    fibex: Option<Rc<FibexMetadata>>,
+    fibex: Option<Arc<FibexMetadata>>,
 ) -> Result<(), Error> {
-    let filter_config: Option<filtering::ProcessedDltFilterConfig> =
-        dlt_filter.map(filtering::process_filter_config);
+    let filter_config: Option<filtering::ProcessedDltFilterConfig> = dlt_filter
+        .map(filtering::process_filter_config)
+        .transpose()?;
     let ending = &pcap_path
         .extension()
         .ok_or_else(|| err_msg("could not get extension"))?;
@@ -63,8 +66,9 @@ struct PcapMessageProducer {
     reader: PcapNGReader<File>,
     update_channel: cc::Sender<ChunkResults>,
     index: usize,
-    fibex_metadata: Option<Rc<FibexMetadata>>,
+    fibex_metadata: Option<Arc<FibexMetadata>>,
     filter_config: Option<filtering::ProcessedDltFilterConfig>,
+    counters: MessageCounterTracker,
 }
 
 impl PcapMessageProducer {
@@ -72,7 +76,7 @@ impl PcapMessageProducer {
     pub fn new(
         pcap_path: &std::path::PathBuf,
         update_channel: cc::Sender<ChunkResults>,
-        fibex_metadata: Option<Rc<FibexMetadata>>,
+        fibex_metadata: Option<Arc<FibexMetadata>>,
         filter_config: Option<filtering::ProcessedDltFilterConfig>,
     ) -> Result<Self, Error> {
         let pcap_file = File::open(&pcap_path)?;
@@ -83,6 +87,7 @@ impl PcapMessageProducer {
                 update_channel,
                 fibex_metadata,
                 filter_config,
+                counters: MessageCounterTracker::new(),
             }),
             Err(e) => Err(err_msg(format!("{:?}", e))),
         }
@@ -108,6 +113,7 @@ impl futures::Stream for PcapMessageProducer {
         let fibex = self.fibex_metadata.clone();
         let index = self.index;
         self.index += 1;
+        let mut counters = std::mem::take(&mut self.counters);
         let now = SystemTime::now();
         let since_the_epoch = now
             .duration_since(UNIX_EPOCH)
@@ -138,13 +144,14 @@ impl futures::Stream for PcapMessageProducer {
                             })))
                         }
                         Ok(value) => {
-                            match dlt_message(
+                            match dlt_message_tracked(
                                 value.payload,
                                 filter_config.as_ref(),
                                 index,
                                 Some(&update_channel),
                                 fibex,
-                                false,
+                                DltFraming::None,
+                                Some(&mut counters),
                             ) {
                                 Ok((_, ParsedMessage::Item(m))) => {
                                     let msg_with_storage_header = m.add_storage_header(Some(
@@ -208,6 +215,7 @@ impl futures::Stream for PcapMessageProducer {
                 })))
             }
         };
+        self.counters = counters;
         self.reader.consume(consumed);
         res
     }
@@ -220,20 +228,21 @@ pub fn index_from_pcap<'a>(
     initial_line_nr: usize,
     update_channel: cc::Sender<ChunkResults>,
     shutdown_receiver: async_std::sync::Receiver<()>,
-    fibex_metadata: Option<Rc<FibexMetadata>>,
-) -> Result<(), Error> {
+    fibex_metadata: Option<Arc<FibexMetadata>>,
+) -> Result<Vec<PayloadMatch>, Error> {
     trace!("index_from_pcap for  conf: {:?}", config);
     let (out_file, current_out_file_size) = utils::get_out_file_and_size(true, config.out_path)?;
     // let out_file_name = format!("{:?}", out_file);
     let mut chunk_factory = ChunkFactory::new(config.chunk_size, current_out_file_size);
     let mut line_nr = initial_line_nr;
     let mut buf_writer = BufWriter::with_capacity(10 * 1024 * 1024, out_file);
+    let mut matches = vec![];
 
     let pcap_msg_producer = PcapMessageProducer::new(
         &config.in_file,
         update_channel.clone(),
         fibex_metadata,
-        filter_config,
+        filter_config.clone(),
     )?;
     // listen for both a shutdown request and incomming messages
     // to do this we need to select over streams of the same type
@@ -260,10 +269,20 @@ pub fn index_from_pcap<'a>(
                     break;
                 }
                 Event::Msg(Ok(MessageStreamItem::Item(msg))) => {
+                    let line = match IndexLine::new(filter_config.as_ref(), &msg) {
+                        Some(line) => line,
+                        None => continue,
+                    };
+                    if !line.positions.is_empty() {
+                        matches.push(PayloadMatch {
+                            line: line_nr,
+                            positions: line.positions,
+                        });
+                    }
                     let written_bytes_len = utils::create_tagged_line_d(
                         config.tag,
                         &mut buf_writer,
-                        &msg,
+                        line.text,
                         line_nr,
                         true,
                     )?;
@@ -314,7 +333,7 @@ pub fn index_from_pcap<'a>(
             };
         }
         trace!("finished index_from_pcap()");
-        Ok(())
+        Ok(matches)
     })
 }
 
@@ -323,13 +342,14 @@ pub fn create_index_and_mapping_dlt_from_pcap<'a>(
     dlt_filter: Option<filtering::DltFilterConfig>,
     update_channel: &cc::Sender<ChunkResults>,
     shutdown_receiver: async_std::sync::Receiver<()>,
-    fibex_metadata: Option<Rc<FibexMetadata>>,
-) -> Result<(), Error> {
+    fibex_metadata: Option<Arc<FibexMetadata>>,
+) -> Result<Vec<PayloadMatch>, Error> {
     trace!("create_index_and_mapping_dlt_from_pcap");
     match utils::next_line_nr(config.out_path) {
         Ok(initial_line_nr) => {
-            let filter_config: Option<filtering::ProcessedDltFilterConfig> =
-                dlt_filter.map(filtering::process_filter_config);
+            let filter_config: Option<filtering::ProcessedDltFilterConfig> = dlt_filter
+                .map(filtering::process_filter_config)
+                .transpose()?;
             match index_from_pcap(
                 config,
                 filter_config,
@@ -338,7 +358,7 @@ pub fn create_index_and_mapping_dlt_from_pcap<'a>(
                 shutdown_receiver,
                 fibex_metadata,
             ) {
-                Ok(()) => Ok(()),
+                Ok(matches) => Ok(matches),
                 Err(e) => {
                     let content = format!("{}", e);
                     let _ = update_channel.send(Err(Notification {
@@ -0,0 +1,137 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+
+//! Readers for DLT input files
+//!
+//! Regular files can be mapped into memory, then the parser always sees the
+//! complete rest of the file and never has to wait for a buffer refill.
+//! Everything that cannot be mapped (pipes, sockets, empty files) is read
+//! through a buffered reader.
+use crate::dlt_parse::DLT_MIN_BUFFER_SPACE;
+use buf_redux::policy::MinBuffered;
+use buf_redux::BufReader as ReduxReader;
+use memmap::Mmap;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// how the content of a DLT file is read
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum DltReaderMode {
+    /// read through a buffer that is refilled from the file
+    Buffered,
+    /// map the file into memory, falls back to `Buffered` if the input cannot be mapped
+    MemoryMapped,
+}
+
+// not derived, `#[default]` on an enum variant needs Rust 1.62
+#[allow(clippy::derivable_impls)]
+impl Default for DltReaderMode {
+    fn default() -> Self {
+        DltReaderMode::Buffered
+    }
+}
+
+/// reader on a memory mapped file
+pub(crate) struct MappedReader {
+    mmap: Mmap,
+    pos: usize,
+}
+
+impl MappedReader {
+    /// the complete content of the file
+    pub(crate) fn as_slice(&self) -> &[u8] {
+        &self.mmap[..]
+    }
+}
+
+impl Read for MappedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = (&self.mmap[self.pos..]).read(buf)?;
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl BufRead for MappedReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(&self.mmap[self.pos..])
+    }
+    fn consume(&mut self, amt: usize) {
+        self.pos = std::cmp::min(self.pos + amt, self.mmap.len());
+    }
+}
+
+pub(crate) enum DltReader {
+    Buffered(ReduxReader<fs::File, MinBuffered>),
+    Mapped(MappedReader),
+}
+
+impl DltReader {
+    /// open `path` and position the reader at `offset`
+    ///
+    /// `capacity` is only used for the buffered reader
+    pub(crate) fn open(
+        path: &Path,
+        offset: u64,
+        capacity: usize,
+        mode: DltReaderMode,
+    ) -> io::Result<DltReader> {
+        let mut f = fs::File::open(path)?;
+        if mode == DltReaderMode::MemoryMapped {
+            let meta = f.metadata()?;
+            if meta.is_file() && meta.len() > 0 {
+                // the file must not be truncated by someone else while it is mapped
+                match unsafe { Mmap::map(&f) } {
+                    Ok(mmap) => {
+                        let pos = std::cmp::min(offset as usize, mmap.len());
+                        return Ok(DltReader::Mapped(MappedReader { mmap, pos }));
+                    }
+                    Err(e) => debug!("could not map {:?}, use buffered reader ({})", path, e),
+                }
+            } else {
+                debug!("{:?} cannot be mapped, use buffered reader", path);
+            }
+        }
+        if offset > 0 {
+            f.seek(SeekFrom::Start(offset))?;
+        }
+        Ok(DltReader::Buffered(
+            ReduxReader::with_capacity(capacity, f).set_policy(MinBuffered(DLT_MIN_BUFFER_SPACE)),
+        ))
+    }
+}
+
+impl Read for DltReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            DltReader::Buffered(reader) => reader.read(buf),
+            DltReader::Mapped(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl BufRead for DltReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match self {
+            DltReader::Buffered(reader) => reader.fill_buf(),
+            DltReader::Mapped(reader) => reader.fill_buf(),
+        }
+    }
+    fn consume(&mut self, amt: usize) {
+        match self {
+            DltReader::Buffered(reader) => reader.consume(amt),
+            DltReader::Mapped(reader) => reader.consume(amt),
+        }
+    }
+}
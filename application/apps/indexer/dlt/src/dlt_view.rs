@@ -0,0 +1,355 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+
+//! Borrowed views on DLT messages
+//!
+//! A `MessageView` only borrows from the input it was parsed from. All headers
+//! are parsed right away, the arguments of the payload are only parsed when
+//! they are requested. This keeps statistics and filtering free of allocations.
+use crate::dlt::*;
+use crate::dlt_parse::{dlt_argument, dlt_payload, dlt_v2_tag_list, DltParseError};
+use crate::fibex::FibexMetadata;
+use crate::filtering;
+use byteorder::{BigEndian, LittleEndian};
+use std::sync::Arc;
+
+/// Borrowed counterpart of `StorageHeader`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageHeaderView<'a> {
+    pub timestamp: DltTimeStamp,
+    pub ecu_id: &'a str,
+}
+
+impl<'a> StorageHeaderView<'a> {
+    pub fn to_storage_header(&self) -> StorageHeader {
+        StorageHeader {
+            timestamp: self.timestamp.clone(),
+            ecu_id: self.ecu_id.to_string(),
+        }
+    }
+}
+
+/// Borrowed counterpart of `StandardHeader`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StandardHeaderView<'a> {
+    pub version: u8,
+    pub endianness: Endianness,
+    pub has_extended_header: bool,
+    pub message_counter: u8,
+    pub ecu_id: Option<&'a str>,
+    pub session_id: Option<u32>,
+    pub timestamp: Option<u32>,
+    pub payload_length: u16,
+    /// only present for DLT protocol version 2 messages
+    pub v2: Option<HeaderV2FieldsView<'a>>,
+}
+
+impl<'a> StandardHeaderView<'a> {
+    pub fn header_type_byte(&self) -> u8 {
+        standard_header_type(
+            self.has_extended_header,
+            self.endianness,
+            self.ecu_id.is_some(),
+            self.session_id.is_some(),
+            self.timestamp.is_some(),
+            self.version,
+        )
+    }
+    pub fn to_standard_header(&self) -> StandardHeader {
+        StandardHeader {
+            version: self.version,
+            endianness: self.endianness,
+            has_extended_header: self.has_extended_header,
+            message_counter: self.message_counter,
+            ecu_id: self.ecu_id.map(|id| id.to_string()),
+            session_id: self.session_id,
+            timestamp: self.timestamp,
+            payload_length: self.payload_length,
+            v2: self
+                .v2
+                .as_ref()
+                .map(|v2| Box::new(v2.to_header_v2_fields())),
+        }
+    }
+}
+
+/// Borrowed counterpart of `SourceLocation`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceLocationView<'a> {
+    pub file_name: &'a str,
+    pub line_number: u32,
+}
+
+/// Borrowed counterpart of `HeaderV2Fields`
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeaderV2FieldsView<'a> {
+    pub timestamp: Option<DltTimeStampV2>,
+    pub with_app_and_context_id: bool,
+    pub source_location: Option<SourceLocationView<'a>>,
+    /// the raw tags field, see `tags`
+    pub(crate) tags: &'a [u8],
+    pub privacy_level: Option<u8>,
+}
+
+impl<'a> HeaderV2FieldsView<'a> {
+    pub fn tags(&self) -> Vec<&'a str> {
+        dlt_v2_tag_list(self.tags)
+    }
+    pub fn to_header_v2_fields(&self) -> HeaderV2Fields {
+        HeaderV2Fields {
+            timestamp: self.timestamp.clone(),
+            with_app_and_context_id: self.with_app_and_context_id,
+            source_location: self
+                .source_location
+                .as_ref()
+                .map(|location| SourceLocation {
+                    file_name: location.file_name.to_string(),
+                    line_number: location.line_number,
+                }),
+            tags: self.tags().into_iter().map(String::from).collect(),
+            privacy_level: self.privacy_level,
+        }
+    }
+}
+
+/// Borrowed counterpart of `ExtendedHeader`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtendedHeaderView<'a> {
+    pub verbose: bool,
+    pub argument_count: u8,
+    pub message_type: MessageType,
+    pub application_id: &'a str,
+    pub context_id: &'a str,
+}
+
+impl<'a> ExtendedHeaderView<'a> {
+    pub fn to_extended_header(&self) -> ExtendedHeader {
+        ExtendedHeader {
+            verbose: self.verbose,
+            argument_count: self.argument_count,
+            message_type: self.message_type.clone(),
+            application_id: self.application_id.to_string(),
+            context_id: self.context_id.to_string(),
+        }
+    }
+}
+
+/// A DLT message that borrows from the input it was parsed from
+///
+/// the payload is kept as raw bytes, use `arguments` or `payload`
+/// to decode it
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageView<'a> {
+    pub storage_header: Option<StorageHeaderView<'a>>,
+    pub header: StandardHeaderView<'a>,
+    pub extended_header: Option<ExtendedHeaderView<'a>>,
+    /// version 2 headers already contain the message id of non-verbose messages
+    pub(crate) message_id: Option<u32>,
+    pub(crate) payload: &'a [u8],
+}
+
+impl<'a> MessageView<'a> {
+    /// the raw bytes of the payload
+    pub fn payload_bytes(&self) -> &'a [u8] {
+        self.payload
+    }
+
+    pub fn is_verbose(&self) -> bool {
+        match &self.extended_header {
+            Some(ext) => ext.verbose,
+            None => false,
+        }
+    }
+
+    /// the ECU id of the standard header, or of the storage header if the
+    /// standard header has none
+    pub fn ecu_id(&self) -> Option<&'a str> {
+        self.header
+            .ecu_id
+            .or_else(|| self.storage_header.as_ref().map(|h| h.ecu_id))
+    }
+
+    pub fn log_level(&self) -> Option<LogLevel> {
+        match self.extended_header.as_ref().map(|ext| &ext.message_type) {
+            Some(MessageType::Log(level)) => Some(*level),
+            _ => None,
+        }
+    }
+
+    /// iterate over the verbose arguments, each argument is parsed
+    /// when it is requested
+    pub fn arguments(&self) -> Arguments<'a> {
+        Arguments {
+            input: self.payload,
+            remaining: match &self.extended_header {
+                Some(ext) if ext.verbose => ext.argument_count,
+                _ => 0,
+            },
+            endianness: self.header.endianness,
+        }
+    }
+
+    /// the time of the message on `clock` in µs, if the message has a timestamp of this clock
+    pub fn time_us(&self, clock: filtering::TimeClock) -> Option<u64> {
+        match clock {
+            filtering::TimeClock::StorageHeader => {
+                self.storage_header.as_ref().map(|h| h.timestamp.as_us())
+            }
+            // the uptime is counted in 0.1 ms
+            filtering::TimeClock::Uptime => self.header.timestamp.map(|t| u64::from(t) * 100),
+        }
+    }
+
+    /// checks all filter conditions that can be decided by only looking at the headers
+    pub fn is_filtered_out(&self, filter_config: &filtering::ProcessedDltFilterConfig) -> bool {
+        if let Some(time_range) = &filter_config.time_range {
+            if let Some(time) = self.time_us(time_range.clock) {
+                if !time_range.contains_us(time) {
+                    return true;
+                }
+            }
+        }
+        // messages without session or ECU id never match an allow-list
+        if let Some(only_these_session_ids) = &filter_config.session_ids {
+            match self.header.session_id {
+                Some(session_id) if only_these_session_ids.contains(&session_id) => (),
+                _ => return true,
+            }
+        }
+        let ecu_id = self.ecu_id();
+        if let (Some(excluded), Some(ecu_id)) = (&filter_config.exclude_ecu_ids, ecu_id) {
+            if excluded.contains(ecu_id) {
+                return true;
+            }
+        }
+        if let Some(only_these_ecu_ids) = &filter_config.ecu_ids {
+            match ecu_id {
+                Some(ecu_id) if only_these_ecu_ids.contains(ecu_id) => (),
+                _ => return true,
+            }
+        }
+        match &self.extended_header {
+            Some(h) => filter_config.is_filtered_out_by_extended_header(
+                ecu_id,
+                h.application_id,
+                h.context_id,
+                &h.message_type,
+            ),
+            None => filter_config.is_filtered_out_without_extended_header(ecu_id),
+        }
+    }
+
+    /// decode the complete payload
+    pub fn payload(&self) -> Result<Payload2, DltParseError> {
+        if let Some(id) = self.message_id {
+            return Ok(Payload2 {
+                payload_content: PayloadContent::NonVerbose(id, self.payload.to_vec()),
+            });
+        }
+        let (verbose, arg_count, is_controll_msg) = match &self.extended_header {
+            Some(ext) => (
+                ext.verbose,
+                ext.argument_count,
+                matches!(ext.message_type, MessageType::Control(_)),
+            ),
+            None => (false, 0, false),
+        };
+        let res = if self.header.endianness == Endianness::Big {
+            dlt_payload::<BigEndian>(
+                self.payload,
+                verbose,
+                self.payload.len() as u16,
+                arg_count,
+                is_controll_msg,
+            )
+        } else {
+            dlt_payload::<LittleEndian>(
+                self.payload,
+                verbose,
+                self.payload.len() as u16,
+                arg_count,
+                is_controll_msg,
+            )
+        };
+        res.map(|(_, payload)| payload).map_err(payload_parse_error)
+    }
+
+    /// create an owned message with an already decoded payload
+    pub fn to_message_with_payload(
+        &self,
+        payload: Payload2,
+        fibex_metadata: Option<Arc<FibexMetadata>>,
+    ) -> Message {
+        Message {
+            storage_header: self.storage_header.as_ref().map(|h| h.to_storage_header()),
+            header: self.header.to_standard_header(),
+            extended_header: self
+                .extended_header
+                .as_ref()
+                .map(|h| h.to_extended_header()),
+            payload,
+            fibex_metadata,
+        }
+    }
+
+    pub fn to_message(
+        &self,
+        fibex_metadata: Option<Arc<FibexMetadata>>,
+    ) -> Result<Message, DltParseError> {
+        Ok(self.to_message_with_payload(self.payload()?, fibex_metadata))
+    }
+}
+
+/// the payload is always complete, if the parser needs more data the
+/// message itself is broken
+pub(crate) fn payload_parse_error(e: nom::Err<(&[u8], nom::error::ErrorKind)>) -> DltParseError {
+    match e {
+        nom::Err::Incomplete(needed) => DltParseError::ParsingHickup {
+            reason: format!("payload is shorter than its content ({:?})", needed),
+        },
+        e => DltParseError::from(e),
+    }
+}
+
+/// Iterator over the verbose arguments of a `MessageView`
+///
+/// stops after the first argument that could not be parsed
+pub struct Arguments<'a> {
+    input: &'a [u8],
+    remaining: u8,
+    endianness: Endianness,
+}
+
+impl<'a> Iterator for Arguments<'a> {
+    type Item = Result<Argument, DltParseError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let res = if self.endianness == Endianness::Big {
+            dlt_argument::<BigEndian>(self.input)
+        } else {
+            dlt_argument::<LittleEndian>(self.input)
+        };
+        match res {
+            Ok((rest, argument)) => {
+                self.remaining -= 1;
+                self.input = rest;
+                Some(Ok(argument))
+            }
+            Err(e) => {
+                self.remaining = 0;
+                Some(Err(payload_parse_error(e)))
+            }
+        }
+    }
+}
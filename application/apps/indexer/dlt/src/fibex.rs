@@ -1,3 +1,4 @@
+use crate::arxml::{is_arxml_file, read_arxml};
 use crate::dlt::{FloatWidth, StringCoding, TypeInfo, TypeInfoKind, TypeLength};
 use derive_more::{Deref, Display};
 use failure::{bail, format_err, Error};
@@ -5,6 +6,7 @@ use quick_xml::{
     events::{attributes::Attributes, BytesStart, Event as XmlEvent},
     Reader as XmlReader,
 };
+use serde::{Deserialize, Serialize};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::{
@@ -12,20 +14,20 @@ use std::{
     io::{BufRead, BufReader},
     mem,
     path::{Path, PathBuf},
-    rc::Rc,
+    sync::Arc,
 };
 
 type Result<T = ()> = std::result::Result<T, Error>;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct FibexMetadata {
-    pub(crate) frame_map_with_key: HashMap<(ContextId, ApplicationId, FrameId), Rc<FrameMetadata>>, // TODO: avoid cloning on .get
-    pub(crate) frame_map: HashMap<FrameId, Rc<FrameMetadata>>,
+    pub(crate) frame_map_with_key: HashMap<(ContextId, ApplicationId, FrameId), Arc<FrameMetadata>>, // TODO: avoid cloning on .get
+    pub(crate) frame_map: HashMap<FrameId, Arc<FrameMetadata>>,
 }
 #[derive(Debug, PartialEq, Clone)]
 pub struct FrameMetadata {
     pub short_name: String,
-    pub pdus: Vec<Rc<PduMetadata>>,
+    pub pdus: Vec<Arc<PduMetadata>>,
     pub application_id: Option<ApplicationId>,
     pub context_id: Option<ContextId>,
     pub message_type: Option<String>,
@@ -35,22 +37,122 @@ pub struct FrameMetadata {
 pub struct PduMetadata {
     pub description: Option<String>,
     pub signal_types: Vec<TypeInfo>,
+    /// the SHORT-NAMEs of the signals, in the same order as `signal_types`
+    ///
+    /// None for the predefined signals of DLT fibexes (S_UINT32, ...)
+    pub signal_names: Vec<Option<String>>,
+    /// the conversions of the signals, in the same order as `signal_types`
+    pub signal_conversions: Vec<Option<SignalConversion>>,
+}
+/// name and COMPU-METHOD of a signal whose coding converts the raw value
+#[derive(Debug, PartialEq, Clone)]
+pub struct SignalConversion {
+    pub signal_name: String,
+    pub compu_method: Arc<CompuMethod>,
+}
+/// the internal-to-physical conversion of a coding (TEXTTABLE or LINEAR)
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct CompuMethod {
+    pub scales: Vec<CompuScale>,
+    /// display name of the unit of the physical value
+    pub unit: Option<String>,
+}
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct CompuScale {
+    /// None if the scale has no lower bound
+    pub lower_limit: Option<f64>,
+    /// None if the scale has no upper bound
+    pub upper_limit: Option<f64>,
+    pub conversion: CompuConversion,
+}
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub enum CompuConversion {
+    /// a TEXTTABLE entry
+    Text(String),
+    /// physical = (offset + factor * raw) / denominator
+    Linear {
+        offset: f64,
+        factor: f64,
+        denominator: f64,
+    },
+}
+/// result of converting a raw signal value with a `CompuMethod`
+#[derive(Debug, PartialEq, Clone)]
+pub enum PhysicalValue {
+    Text(String),
+    Number(f64),
+}
+impl CompuScale {
+    fn contains(&self, raw: f64) -> bool {
+        self.lower_limit.map(|l| l <= raw).unwrap_or(true)
+            && self.upper_limit.map(|u| raw <= u).unwrap_or(true)
+    }
+}
+impl CompuMethod {
+    /// None if no scale covers `raw`
+    pub fn convert(&self, raw: f64) -> Option<PhysicalValue> {
+        self.scales
+            .iter()
+            .find(|scale| scale.contains(raw))
+            .map(|scale| match &scale.conversion {
+                CompuConversion::Text(text) => PhysicalValue::Text(text.clone()),
+                CompuConversion::Linear {
+                    offset,
+                    factor,
+                    denominator,
+                } => PhysicalValue::Number((offset + factor * raw) / denominator),
+            })
+    }
 }
 
-#[derive(Hash, PartialEq, Eq, Clone, Debug, Deref, Display)]
+#[derive(Hash, PartialEq, Eq, Clone, Debug, Deref, Display, Serialize, Deserialize)]
 pub struct FrameId(pub String);
 
-#[derive(Hash, PartialEq, Eq, Clone, Debug, Deref, Display)]
+#[derive(Hash, PartialEq, Eq, Clone, Debug, Deref, Display, Serialize, Deserialize)]
 pub struct ContextId(pub String);
 
-#[derive(Hash, PartialEq, Eq, Clone, Debug, Deref, Display)]
+#[derive(Hash, PartialEq, Eq, Clone, Debug, Deref, Display, Serialize, Deserialize)]
 pub struct ApplicationId(pub String);
 
-fn type_info_for_signal_ref(
-    signal_ref: String,
-    signals: &HashMap<String, String>,
-    codings: &HashMap<String, String>,
-) -> Option<TypeInfo> {
+/// the type of a predefined signal of DLT fibexes (S_UINT32, S_STRG_UTF8, ...)
+///
+/// None if `signal_ref` is not predefined, Some(None) if the signal is not supported
+pub(crate) fn predefined_signal_type(signal_ref: &str) -> Option<Option<TypeInfo>> {
+    Some(match signal_ref {
+        "S_BOOL" => Some(TypeInfo {
+            kind: TypeInfoKind::Bool,
+            coding: StringCoding::ASCII,
+            has_variable_info: false,
+            has_trace_info: false,
+        }),
+        "S_SINT8" => type_info_for_base_data_type("A_INT8"),
+        "S_UINT8" => type_info_for_base_data_type("A_UINT8"),
+        "S_SINT16" => type_info_for_base_data_type("A_INT16"),
+        "S_UINT16" => type_info_for_base_data_type("A_UINT16"),
+        "S_SINT32" => type_info_for_base_data_type("A_INT32"),
+        "S_UINT32" => type_info_for_base_data_type("A_UINT32"),
+        "S_SINT64" => type_info_for_base_data_type("A_INT64"),
+        "S_UINT64" => type_info_for_base_data_type("A_UINT64"),
+        "S_FLOA16" => {
+            warn!("16-bit float not supported");
+            None
+        }
+        "S_FLOA32" => type_info_for_base_data_type("A_FLOAT32"),
+        "S_FLOA64" => type_info_for_base_data_type("A_FLOAT64"),
+        "S_STRG_ASCII" => type_info_for_base_data_type("A_ASCIISTRING"),
+        "S_STRG_UTF8" => type_info_for_base_data_type("A_UNICODE2STRING"),
+        "S_RAWD" | "S_RAW" => Some(TypeInfo {
+            kind: TypeInfoKind::Raw,
+            coding: StringCoding::ASCII,
+            has_variable_info: false,
+            has_trace_info: false,
+        }),
+        _ => return None,
+    })
+}
+
+/// the type of the BASE-DATA-TYPE of a coding, None if it is not supported
+pub(crate) fn type_info_for_base_data_type(base_type: &str) -> Option<TypeInfo> {
     fn sint8() -> Option<TypeInfo> {
         Some(TypeInfo {
             kind: TypeInfoKind::Signed(TypeLength::BitLength8),
@@ -159,62 +261,46 @@ fn type_info_for_signal_ref(
         })
     }
 
-    match signal_ref.as_ref() {
-        "S_BOOL" => Some(TypeInfo {
-            kind: TypeInfoKind::Bool,
-            coding: StringCoding::ASCII,
-            has_variable_info: false,
-            has_trace_info: false,
-        }),
-        "S_SINT8" => sint8(),
-        "S_UINT8" => uint8(),
-        "S_SINT16" => sint16(),
-        "S_UINT16" => uint16(),
-        "S_SINT32" => sint32(),
-        "S_UINT32" => uint32(),
-        "S_SINT64" => sint64(),
-        "S_UINT64" => uint64(),
-        "S_FLOA16" => {
-            warn!("16-bit float not supported");
+    match base_type {
+        "A_UINT8" => uint8(),
+        "A_INT8" | "A_SINT8" => sint8(),
+        "A_UINT16" => uint16(),
+        "A_INT16" | "A_SINT16" => sint16(),
+        "A_UINT32" => uint32(),
+        "A_INT32" | "A_SINT32" => sint32(),
+        "A_UINT64" => uint64(),
+        "A_INT64" | "A_SINT64" => sint64(),
+        "A_FLOAT32" => float32(),
+        "A_FLOAT64" => float64(),
+        "A_ASCIISTRING" => ascii_str(),
+        "A_UNICODE2STRING" => utf8_str(),
+        _ => None,
+    }
+}
+
+fn type_info_for_signal_ref(
+    signal_ref: String,
+    signals: &HashMap<String, String>,
+    codings: &HashMap<String, String>,
+) -> Option<TypeInfo> {
+    if let Some(type_info) = predefined_signal_type(&signal_ref) {
+        return type_info;
+    }
+    match signals.get(&signal_ref).and_then(|s| codings.get(s)) {
+        Some(base_type) => {
+            let type_info = type_info_for_base_data_type(base_type);
+            if type_info.is_none() {
+                warn!(
+                    "type_info_for_signal_ref: Signal found but base_type not known:{}",
+                    base_type
+                );
+            }
+            type_info
+        }
+        None => {
+            warn!("type_info_for_signal_ref not supported for {}", signal_ref);
             None
         }
-        "S_FLOA32" => float32(),
-        "S_FLOA64" => float64(),
-        "S_STRG_ASCII" => ascii_str(),
-        "S_STRG_UTF8" => utf8_str(),
-        "S_RAWD" | "S_RAW" => Some(TypeInfo {
-            kind: TypeInfoKind::Raw,
-            coding: StringCoding::ASCII,
-            has_variable_info: false,
-            has_trace_info: false,
-        }),
-        s => match signals.get(s).and_then(|s| codings.get(s)) {
-            Some(base_type) => match base_type.as_ref() {
-                "A_UINT8" => uint8(),
-                "A_INT8" | "A_SINT8" => sint8(),
-                "A_UINT16" => uint16(),
-                "A_INT16" | "A_SINT16" => sint16(),
-                "A_UINT32" => uint32(),
-                "A_INT32" | "A_SINT32" => sint32(),
-                "A_UINT64" => uint64(),
-                "A_INT64" | "A_SINT64" => sint64(),
-                "A_FLOAT32" => float32(),
-                "A_FLOAT64" => float64(),
-                "A_ASCIISTRING" => ascii_str(),
-                "A_UNICODE2STRING" => utf8_str(),
-                s => {
-                    warn!(
-                        "type_info_for_signal_ref: Signal found but base_type not known:{}",
-                        s
-                    );
-                    None
-                }
-            },
-            None => {
-                warn!("type_info_for_signal_ref not supported for {}", s);
-                None
-            }
-        },
     }
 }
 
@@ -225,8 +311,16 @@ pub fn read_fibexes(files: Vec<PathBuf>) -> Result<FibexMetadata> {
     let mut pdu_by_id = HashMap::new();
     let mut signals_map = HashMap::new();
     let mut codings_map = HashMap::new();
+    let mut signal_names = HashMap::new();
+    let mut coding_compu_methods = HashMap::new();
+    let mut units = HashMap::new();
     let mut pdus = vec![];
+    let mut arxml_frames = vec![];
     for f in files {
+        if is_arxml_file(&f) {
+            arxml_frames.extend(read_arxml(&f)?);
+            continue;
+        }
         debug!("read_fibexe from {:?}", f);
         let mut reader = Reader::from_file(f)?;
         loop {
@@ -238,36 +332,86 @@ pub fn read_fibexes(files: Vec<PathBuf>) -> Result<FibexMetadata> {
                     frames.push((FrameId(id), read_frame(&mut reader)?));
                 }
                 Event::Eof => break,
-                Event::Signal { id, coding_ref } => {
+                Event::Signal {
+                    id,
+                    short_name,
+                    coding_ref,
+                } => {
                     trace!("found signal {} (coding_ref={})", id, coding_ref);
+                    if let Some(short_name) = short_name {
+                        signal_names.insert(id.clone(), short_name);
+                    }
                     signals_map.insert(id, coding_ref);
                 }
-                Event::Coding { id, base_data_type } => {
+                Event::Coding {
+                    id,
+                    base_data_type,
+                    compu_method,
+                } => {
+                    if let Some(compu_method) = compu_method {
+                        coding_compu_methods.insert(id.clone(), compu_method);
+                    }
                     codings_map.insert(id, base_data_type);
                 }
+                Event::Unit { id, display_name } => {
+                    units.insert(id, display_name);
+                }
                 x => {
                     debug!("read_fibex some other event: {:?}", x);
                 }
             }
         }
     }
+    // units can be defined after the codings that refer to them
+    let compu_methods: HashMap<String, Arc<CompuMethod>> = coding_compu_methods
+        .into_iter()
+        .map(|(coding_id, (scales, unit_ref))| {
+            let unit = unit_ref.and_then(|unit_ref| {
+                let unit = units.get(&unit_ref).cloned();
+                if unit.is_none() {
+                    warn!("unit {} of coding {} not found", unit_ref, coding_id);
+                }
+                unit
+            });
+            (coding_id, Arc::new(CompuMethod { scales, unit }))
+        })
+        .collect();
     for (id, (description, signal_refs)) in pdus {
         match pdu_by_id.entry(id.clone()) {
             Entry::Occupied(_) => warn!("duplicate PDU ID {} found in fibexes", id),
             Entry::Vacant(v) => {
-                v.insert(Rc::new(PduMetadata {
+                let mut pdu = PduMetadata {
                     description,
-                    signal_types: signal_refs
-                        .into_iter()
-                        .map(|type_ref| {
-                            type_info_for_signal_ref(type_ref, &signals_map, &codings_map)
-                        })
-                        .flatten()
-                        .collect(),
-                }));
+                    signal_types: vec![],
+                    signal_names: vec![],
+                    signal_conversions: vec![],
+                };
+                for signal_ref in signal_refs {
+                    let type_info = match type_info_for_signal_ref(
+                        signal_ref.clone(),
+                        &signals_map,
+                        &codings_map,
+                    ) {
+                        Some(type_info) => type_info,
+                        None => continue,
+                    };
+                    let signal_name = signal_names.get(&signal_ref).cloned();
+                    let conversion = signals_map
+                        .get(&signal_ref)
+                        .and_then(|coding_ref| compu_methods.get(coding_ref))
+                        .map(|compu_method| SignalConversion {
+                            signal_name: signal_name.clone().unwrap_or_else(|| signal_ref.clone()),
+                            compu_method: compu_method.clone(),
+                        });
+                    pdu.signal_types.push(type_info);
+                    pdu.signal_names.push(signal_name);
+                    pdu.signal_conversions.push(conversion);
+                }
+                v.insert(Arc::new(pdu));
             }
         }
     }
+    let mut fibex_frames = vec![];
     for (
         id,
         FrameReadData {
@@ -280,22 +424,29 @@ pub fn read_fibexes(files: Vec<PathBuf>) -> Result<FibexMetadata> {
         },
     ) in frames
     {
-        let frame = Rc::new(FrameMetadata {
-            short_name,
-            pdus: pdu_refs
-                .into_iter()
-                .map(|r| {
-                    pdu_by_id
-                        .get(&r)
-                        .cloned()
-                        .ok_or_else(|| format_err!("pdu {} not found", &r))
-                })
-                .collect::<Result<Vec<_>>>()?,
-            application_id,
-            context_id,
-            message_type,
-            message_info,
-        });
+        fibex_frames.push((
+            id,
+            FrameMetadata {
+                short_name,
+                pdus: pdu_refs
+                    .into_iter()
+                    .map(|r| {
+                        pdu_by_id
+                            .get(&r)
+                            .cloned()
+                            .ok_or_else(|| format_err!("pdu {} not found", &r))
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+                application_id,
+                context_id,
+                message_type,
+                message_info,
+            },
+        ));
+    }
+    // frames of ARXML files come after all fibex frames, the first of duplicates is used
+    for (id, frame) in fibex_frames.into_iter().chain(arxml_frames) {
+        let frame = Arc::new(frame);
         if let (Some(context_id), Some(application_id)) =
             (frame.context_id.clone(), frame.application_id.clone())
         {
@@ -422,6 +573,17 @@ const B_SIGNAL: &[u8] = b"SIGNAL";
 const B_CODING_REF: &[u8] = b"CODING-REF";
 const B_BASE_DATA_TYPE: &[u8] = b"BASE-DATA-TYPE";
 const B_CODED_TYPE: &[u8] = b"CODED-TYPE";
+const B_COMPU_METHOD: &[u8] = b"COMPU-METHOD";
+const B_COMPU_SCALE: &[u8] = b"COMPU-SCALE";
+const B_LOWER_LIMIT: &[u8] = b"LOWER-LIMIT";
+const B_UPPER_LIMIT: &[u8] = b"UPPER-LIMIT";
+const B_VT: &[u8] = b"VT";
+const B_V: &[u8] = b"V";
+const B_COMPU_NUMERATOR: &[u8] = b"COMPU-NUMERATOR";
+const B_COMPU_DENOMINATOR: &[u8] = b"COMPU-DENOMINATOR";
+const B_UNIT_REF: &[u8] = b"UNIT-REF";
+const B_UNIT: &[u8] = b"UNIT";
+const B_DISPLAY_NAME: &[u8] = b"DISPLAY-NAME";
 
 #[derive(Debug)]
 pub enum Event {
@@ -458,14 +620,41 @@ pub enum Event {
     },
     Signal {
         id: String,
+        short_name: Option<String>,
         coding_ref: String,
     },
     Coding {
         id: String,
         base_data_type: String,
+        /// scales and UNIT-REF of the first COMPU-METHOD of the coding
+        compu_method: Option<(Vec<CompuScale>, Option<String>)>,
+    },
+    Unit {
+        id: String,
+        display_name: String,
     },
     Eof,
 }
+/// line and column of a byte position in the content of an xml file
+///
+/// the column counts characters, the content is expected to be UTF-8
+pub fn line_and_column_at(content: &[u8], position: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut column = 0;
+    for b in content.iter().take(position) {
+        if *b == b'\n' {
+            line += 1;
+            column = 0;
+        } else if !is_utf8_continuation_byte(*b) {
+            column += 1;
+        }
+    }
+    (line, column)
+}
+
+fn is_utf8_continuation_byte(b: u8) -> bool {
+    b & 0b1100_0000 == 0b1000_0000
+}
 pub struct XmlReaderWithContext<B: BufRead> {
     xml_reader: XmlReader<B>,
     file_path: PathBuf,
@@ -481,18 +670,8 @@ impl<B: BufRead> XmlReaderWithContext<B> {
         Ok(self.xml_reader.read_text(tag, buf)?)
     }
     pub fn line_and_column(&self) -> Result<(usize, usize)> {
-        let s = std::fs::read_to_string(self.file_path.clone())?;
-        let mut line = 1;
-        let mut column = 0;
-        for c in s.chars().take(self.buffer_position()) {
-            if c == '\n' {
-                line += 1;
-                column = 0;
-            } else {
-                column += 1;
-            }
-        }
-        Ok((line, column))
+        let content = std::fs::read(&self.file_path)?;
+        Ok(line_and_column_at(&content, self.buffer_position()))
     }
     pub fn read_usize(&mut self, e: &BytesStart<'_>) -> Result<usize> {
         Ok(self.read_text_buf(e)?.parse::<usize>().map_err(|e| {
@@ -500,6 +679,41 @@ impl<B: BufRead> XmlReaderWithContext<B> {
             format_err!("can't parse usize at {}:{}: {}", line, column, e)
         })?)
     }
+    pub fn read_f64(&mut self, e: &BytesStart<'_>) -> Result<f64> {
+        let text = self.read_text_buf(e)?;
+        self.parse_f64(&text)
+    }
+    fn parse_f64(&self, text: &str) -> Result<f64> {
+        text.trim().parse::<f64>().map_err(|e| {
+            let (line, column) = self.line_and_column().unwrap_or((0, 0));
+            format_err!("can't parse f64 at {}:{}: {}", line, column, e)
+        })
+    }
+    /// the numbers of the V elements up to the end of `e`
+    pub fn read_values(&mut self, e: &BytesStart<'_>) -> Result<Vec<f64>> {
+        let end = e.local_name().to_vec();
+        let mut values = vec![];
+        let mut buf = vec![];
+        let mut text_buf = vec![];
+        loop {
+            match self.xml_reader.read_event(&mut buf)? {
+                XmlEvent::Start(ref v) if v.local_name() == B_V => {
+                    let text = self.xml_reader.read_text(v.name(), &mut text_buf)?;
+                    values.push(self.parse_f64(&text)?);
+                    text_buf.clear();
+                }
+                XmlEvent::End(ref e) if e.local_name() == &end[..] => return Ok(values),
+                XmlEvent::Eof => {
+                    bail!(
+                        "unexpected end of file in {}",
+                        String::from_utf8_lossy(&end)
+                    )
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+    }
     pub fn read_text_buf(&mut self, e: &BytesStart<'_>) -> Result<String> {
         Ok(self.read_text(e.name(), &mut Vec::new())?)
     }
@@ -568,6 +782,15 @@ pub struct Reader<B: BufRead> {
     message_type: Option<String>,
     message_info: Option<String>,
     base_data_type: Option<String>,
+    compu_method: Option<(Vec<CompuScale>, Option<String>)>,
+    compu_scales: Vec<CompuScale>,
+    unit_ref: Option<String>,
+    lower_limit: Option<f64>,
+    upper_limit: Option<f64>,
+    compu_const: Option<String>,
+    numerator: Vec<f64>,
+    denominator: Vec<f64>,
+    display_name: Option<String>,
 }
 impl Reader<BufReader<File>> {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
@@ -590,10 +813,23 @@ impl Reader<BufReader<File>> {
             message_type: None,
             message_info: None,
             base_data_type: None,
+            compu_method: None,
+            compu_scales: vec![],
+            unit_ref: None,
+            lower_limit: None,
+            upper_limit: None,
+            compu_const: None,
+            numerator: vec![],
+            denominator: vec![],
+            display_name: None,
         })
     }
 }
 impl<B: BufRead> Reader<B> {
+    /// position in the file after the last event
+    pub fn buffer_position(&self) -> usize {
+        self.xml_reader.buffer_position()
+    }
     #[allow(clippy::cognitive_complexity)]
     pub fn read_event(&mut self) -> Result<Event> {
         loop {
@@ -680,15 +916,46 @@ impl<B: BufRead> Reader<B> {
                     B_CODING => {
                         self.id = Some(self.xml_reader.id_attr(e, B_CODING)?);
                         self.base_data_type = None;
+                        self.compu_method = None;
                     }
                     B_SIGNAL => {
                         self.id = Some(self.xml_reader.id_attr(e, B_SIGNAL)?);
+                        self.short_name = None;
                         self.r#ref = None;
                     }
                     B_CODED_TYPE => {
                         self.base_data_type =
                             self.xml_reader.attr(e, B_BASE_DATA_TYPE, B_CODED_TYPE).ok();
                     }
+                    B_COMPU_METHOD => {
+                        self.compu_scales.clear();
+                        self.unit_ref = None;
+                    }
+                    B_COMPU_SCALE => {
+                        self.lower_limit = None;
+                        self.upper_limit = None;
+                        self.compu_const = None;
+                        self.numerator.clear();
+                        self.denominator.clear();
+                    }
+                    B_LOWER_LIMIT => self.lower_limit = Some(self.xml_reader.read_f64(e)?),
+                    B_UPPER_LIMIT => self.upper_limit = Some(self.xml_reader.read_f64(e)?),
+                    B_VT => {
+                        self.compu_const =
+                            Some(self.xml_reader.read_text(e.name(), &mut self.buf2)?);
+                    }
+                    B_COMPU_NUMERATOR => self.numerator = self.xml_reader.read_values(e)?,
+                    B_COMPU_DENOMINATOR => self.denominator = self.xml_reader.read_values(e)?,
+                    B_UNIT => {
+                        // a unit without ID is skipped at its end
+                        self.id = self.xml_reader.id_attr(e, B_UNIT).ok();
+                        self.short_name = None;
+                        self.display_name = None;
+                    }
+                    B_DISPLAY_NAME => {
+                        self.display_name =
+                            Some(self.xml_reader.read_text(e.name(), &mut self.buf2)?);
+                    }
                     _x => {
                         // trace!("read_event (unknown: {:?})", _x);
                     }
@@ -701,6 +968,7 @@ impl<B: BufRead> Reader<B> {
                     B_CODING_REF => {
                         self.r#ref = Some(self.xml_reader.id_ref_attr(e, B_SIGNAL_REF)?);
                     }
+                    B_UNIT_REF => self.unit_ref = Some(self.xml_reader.id_ref_attr(e, B_UNIT_REF)?),
                     x => {
                         trace!("XmlEvent::Empty (unknown: {:?})", x);
                     }
@@ -808,6 +1076,7 @@ impl<B: BufRead> Reader<B> {
                             id: mem::replace(&mut self.id, None).ok_or_else(|| {
                                 missing_attr_err(B_ID, B_SIGNAL, self.xml_reader.line_and_column())
                             })?,
+                            short_name: self.short_name.take(),
                             coding_ref: mem::replace(&mut self.r#ref, None).ok_or_else(|| {
                                 missing_tag_err(
                                     B_CODING_REF,
@@ -830,8 +1099,65 @@ impl<B: BufRead> Reader<B> {
                                         self.xml_reader.line_and_column(),
                                     )
                                 })?,
+                            compu_method: self.compu_method.take(),
                         });
                     }
+                    B_COMPU_SCALE => {
+                        let conversion = if let Some(text) = self.compu_const.take() {
+                            Ok(CompuConversion::Text(text))
+                        } else if let Some(offset) = self.numerator.first() {
+                            let denominator = self.denominator.first().cloned().unwrap_or(1.0);
+                            if denominator == 0.0 {
+                                Err("COMPU-SCALE with denominator 0")
+                            } else {
+                                Ok(CompuConversion::Linear {
+                                    offset: *offset,
+                                    factor: self.numerator.get(1).cloned().unwrap_or(0.0),
+                                    denominator,
+                                })
+                            }
+                        } else {
+                            Err("unsupported COMPU-SCALE")
+                        };
+                        match conversion {
+                            Ok(conversion) => self.compu_scales.push(CompuScale {
+                                lower_limit: self.lower_limit.take(),
+                                upper_limit: self.upper_limit.take(),
+                                conversion,
+                            }),
+                            Err(reason) => warn!(
+                                "{} at {:?} skipped",
+                                reason,
+                                self.xml_reader.line_and_column().unwrap_or((0, 0))
+                            ),
+                        }
+                    }
+                    B_COMPU_METHOD if self.compu_method.is_none() => {
+                        self.compu_method =
+                            Some((mem::take(&mut self.compu_scales), self.unit_ref.take()));
+                    }
+                    B_UNIT => match self.id.take() {
+                        Some(id) => {
+                            return Ok(Event::Unit {
+                                id,
+                                display_name: self
+                                    .display_name
+                                    .take()
+                                    .or_else(|| self.short_name.take())
+                                    .ok_or_else(|| {
+                                        missing_tag_err(
+                                            B_SHORT_NAME,
+                                            B_UNIT,
+                                            self.xml_reader.line_and_column(),
+                                        )
+                                    })?,
+                            });
+                        }
+                        None => warn!(
+                            "UNIT without ID at {:?} skipped",
+                            self.xml_reader.line_and_column().unwrap_or((0, 0))
+                        ),
+                    },
                     _x => {}
                 },
                 XmlEvent::Eof => return Ok(Event::Eof),
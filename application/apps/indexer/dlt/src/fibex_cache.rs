@@ -0,0 +1,371 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+
+//! Binary cache of parsed fibex files
+//!
+//! Parsing a big fibex catalogue takes long, so the parsed `FibexMetadata` is
+//! stored together with the paths, sizes and modification times of the fibex
+//! files it was read from. The cache is used as long as all of them are unchanged,
+//! otherwise the fibexes are parsed again and the cache is rebuilt.
+//!
+//! Frames, pdus and COMPU-METHODs are shared by the maps of `FibexMetadata`, so
+//! every one of them is stored once and referred to by its index in the cache.
+use crate::fibex::{
+    ApplicationId, CompuMethod, ContextId, FibexMetadata, FrameId, FrameMetadata, PduMetadata,
+    SignalConversion,
+};
+use crate::{dlt::TypeInfo, fibex::read_fibexes};
+use failure::{err_msg, Error};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fs,
+    hash::{Hash, Hasher},
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+/// has to be increased whenever the layout of `CachedFibex` changes
+const FIBEX_CACHE_VERSION: u32 = 3;
+
+/// cache files of other fibex combinations that are kept, older ones are removed
+const MAX_CACHE_FILES: usize = 8;
+
+const CACHE_FILE_EXTENSION: &str = "fibex.bin";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct FibexFileStamp {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// written in front of the metadata, the cache is valid if it equals the current key
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct CacheKey {
+    version: u32,
+    files: Vec<FibexFileStamp>,
+}
+
+impl CacheKey {
+    fn new(files: &[PathBuf]) -> Result<Self, Error> {
+        let files = files
+            .iter()
+            .map(|path| {
+                let metadata = fs::metadata(path)
+                    .map_err(|e| format_err!("can't access fibex {:?}: {}", path, e))?;
+                Ok(FibexFileStamp {
+                    path: path.clone(),
+                    size: metadata.len(),
+                    modified: metadata.modified()?,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(CacheKey {
+            version: FIBEX_CACHE_VERSION,
+            files,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedPdu {
+    description: Option<String>,
+    signal_types: Vec<TypeInfo>,
+    signal_names: Vec<Option<String>>,
+    /// signal name and index of the COMPU-METHOD
+    signal_conversions: Vec<Option<(String, usize)>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedFrame {
+    short_name: String,
+    /// indices of the pdus
+    pdus: Vec<usize>,
+    application_id: Option<ApplicationId>,
+    context_id: Option<ContextId>,
+    message_type: Option<String>,
+    message_info: Option<String>,
+}
+
+/// `FibexMetadata` without shared references, the maps refer to the frames by index
+#[derive(Serialize, Deserialize)]
+struct CachedFibex {
+    compu_methods: Vec<CompuMethod>,
+    pdus: Vec<CachedPdu>,
+    frames: Vec<CachedFrame>,
+    frame_map_with_key: Vec<((ContextId, ApplicationId, FrameId), usize)>,
+    frame_map: Vec<(FrameId, usize)>,
+}
+
+/// assigns an index to every distinct `Arc`, shared values are only collected once
+struct Interner<'a, T> {
+    indices: HashMap<*const T, usize>,
+    values: Vec<&'a T>,
+}
+
+impl<'a, T> Interner<'a, T> {
+    fn new() -> Self {
+        Interner {
+            indices: HashMap::new(),
+            values: vec![],
+        }
+    }
+
+    fn index(&mut self, value: &'a Arc<T>) -> usize {
+        let values = &mut self.values;
+        *self.indices.entry(Arc::as_ptr(value)).or_insert_with(|| {
+            values.push(value);
+            values.len() - 1
+        })
+    }
+}
+
+impl CachedFibex {
+    fn new(metadata: &FibexMetadata) -> Self {
+        let mut frames = Interner::new();
+        let frame_map_with_key = metadata
+            .frame_map_with_key
+            .iter()
+            .map(|(key, frame)| (key.clone(), frames.index(frame)))
+            .collect();
+        let frame_map = metadata
+            .frame_map
+            .iter()
+            .map(|(id, frame)| (id.clone(), frames.index(frame)))
+            .collect();
+        let mut pdus = Interner::new();
+        let frames = frames
+            .values
+            .iter()
+            .map(|frame| CachedFrame {
+                short_name: frame.short_name.clone(),
+                pdus: frame.pdus.iter().map(|pdu| pdus.index(pdu)).collect(),
+                application_id: frame.application_id.clone(),
+                context_id: frame.context_id.clone(),
+                message_type: frame.message_type.clone(),
+                message_info: frame.message_info.clone(),
+            })
+            .collect();
+        let mut compu_methods = Interner::new();
+        let pdus = pdus
+            .values
+            .iter()
+            .map(|pdu| CachedPdu {
+                description: pdu.description.clone(),
+                signal_types: pdu.signal_types.clone(),
+                signal_names: pdu.signal_names.clone(),
+                signal_conversions: pdu
+                    .signal_conversions
+                    .iter()
+                    .map(|conversion| {
+                        conversion
+                            .as_ref()
+                            .map(|c| (c.signal_name.clone(), compu_methods.index(&c.compu_method)))
+                    })
+                    .collect(),
+            })
+            .collect();
+        CachedFibex {
+            compu_methods: compu_methods.values.into_iter().cloned().collect(),
+            pdus,
+            frames,
+            frame_map_with_key,
+            frame_map,
+        }
+    }
+
+    fn into_metadata(self) -> Result<FibexMetadata, Error> {
+        fn get<T: Clone>(values: &[T], index: usize) -> Result<T, Error> {
+            values
+                .get(index)
+                .cloned()
+                .ok_or_else(|| err_msg("invalid index in fibex cache"))
+        }
+        let compu_methods: Vec<Arc<CompuMethod>> =
+            self.compu_methods.into_iter().map(Arc::new).collect();
+        let pdus = self
+            .pdus
+            .into_iter()
+            .map(|pdu| {
+                let signal_conversions = pdu
+                    .signal_conversions
+                    .into_iter()
+                    .map(|conversion| match conversion {
+                        Some((signal_name, index)) => Ok(Some(SignalConversion {
+                            signal_name,
+                            compu_method: get(&compu_methods, index)?,
+                        })),
+                        None => Ok(None),
+                    })
+                    .collect::<Result<_, Error>>()?;
+                Ok(Arc::new(PduMetadata {
+                    description: pdu.description,
+                    signal_types: pdu.signal_types,
+                    signal_names: pdu.signal_names,
+                    signal_conversions,
+                }))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let frames = self
+            .frames
+            .into_iter()
+            .map(|frame| {
+                Ok(Arc::new(FrameMetadata {
+                    short_name: frame.short_name,
+                    pdus: frame
+                        .pdus
+                        .into_iter()
+                        .map(|index| get(&pdus, index))
+                        .collect::<Result<_, Error>>()?,
+                    application_id: frame.application_id,
+                    context_id: frame.context_id,
+                    message_type: frame.message_type,
+                    message_info: frame.message_info,
+                }))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(FibexMetadata {
+            frame_map_with_key: self
+                .frame_map_with_key
+                .into_iter()
+                .map(|(key, index)| Ok((key, get(&frames, index)?)))
+                .collect::<Result<_, Error>>()?,
+            frame_map: self
+                .frame_map
+                .into_iter()
+                .map(|(id, index)| Ok((id, get(&frames, index)?)))
+                .collect::<Result<_, Error>>()?,
+        })
+    }
+}
+
+/// `~/.chipmunk/fibex_cache`
+pub fn default_cache_dir() -> Result<PathBuf, Error> {
+    let home_dir = dirs::home_dir().ok_or_else(|| err_msg("couldn't get home directory"))?;
+    Ok(home_dir.join(".chipmunk").join("fibex_cache"))
+}
+
+/// like `read_fibexes`, but uses the cache in the default cache directory
+pub fn read_fibexes_cached(files: Vec<PathBuf>) -> Result<FibexMetadata, Error> {
+    match default_cache_dir() {
+        Ok(cache_dir) => read_fibexes_with_cache(files, &cache_dir),
+        Err(e) => {
+            warn!("fibex cache not available: {}", e);
+            read_fibexes(files)
+        }
+    }
+}
+
+/// loads the metadata of `files` from `cache_dir` or parses the fibexes and
+/// rebuilds the cache if it is missing or outdated
+///
+/// problems with the cache itself are only logged, the fibexes are parsed then
+pub fn read_fibexes_with_cache(
+    files: Vec<PathBuf>,
+    cache_dir: &Path,
+) -> Result<FibexMetadata, Error> {
+    // the same fibexes use the same cache however their paths are written
+    let canonical_files: Vec<PathBuf> = files
+        .iter()
+        .map(|path| fs::canonicalize(path).unwrap_or_else(|_| path.clone()))
+        .collect();
+    let key = CacheKey::new(&canonical_files)?;
+    let cache_file = cache_dir.join(cache_file_name(&canonical_files));
+    match load_cache(&cache_file, &key) {
+        Ok(Some(metadata)) => {
+            debug!("loaded fibex metadata from cache {:?}", cache_file);
+            return Ok(metadata);
+        }
+        Ok(None) => debug!("fibex cache {:?} is outdated", cache_file),
+        Err(e) => debug!("fibex cache {:?} not usable: {}", cache_file, e),
+    }
+    let metadata = read_fibexes(files)?;
+    if let Err(e) = store_cache(cache_dir, &cache_file, &key, &metadata) {
+        warn!("could not write fibex cache {:?}: {}", cache_file, e);
+    }
+    Ok(metadata)
+}
+
+/// one cache file per combination of fibex paths
+///
+/// only the paths select the file, so a rebuilt cache (changed fibexes or a new
+/// cache version) replaces the old one. The key stored in it decides if it is valid.
+fn cache_file_name(files: &[PathBuf]) -> String {
+    let mut hasher = DefaultHasher::new();
+    files.hash(&mut hasher);
+    format!("{:016x}.{}", hasher.finish(), CACHE_FILE_EXTENSION)
+}
+
+/// removes the oldest cache files if there are more than `MAX_CACHE_FILES`
+///
+/// `current` is never removed
+fn prune_cache(cache_dir: &Path, current: &Path) -> Result<(), Error> {
+    let mut cache_files = vec![];
+    for entry in fs::read_dir(cache_dir)? {
+        let path = entry?.path();
+        let is_cache_file = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| name.ends_with(CACHE_FILE_EXTENSION))
+            .unwrap_or(false);
+        if is_cache_file && path != current {
+            let modified = fs::metadata(&path)?.modified()?;
+            cache_files.push((modified, path));
+        }
+    }
+    if cache_files.len() < MAX_CACHE_FILES {
+        return Ok(());
+    }
+    cache_files.sort();
+    let remove_count = cache_files.len() + 1 - MAX_CACHE_FILES;
+    for (_, path) in cache_files.into_iter().take(remove_count) {
+        debug!("removing old fibex cache {:?}", path);
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+/// None if the cache was written for other versions of the fibex files
+fn load_cache(cache_file: &Path, key: &CacheKey) -> Result<Option<FibexMetadata>, Error> {
+    let mut reader = BufReader::new(fs::File::open(cache_file)?);
+    let cached_key: CacheKey = bincode::deserialize_from(&mut reader)?;
+    if cached_key != *key {
+        return Ok(None);
+    }
+    let cached: CachedFibex = bincode::deserialize_from(reader)?;
+    Ok(Some(cached.into_metadata()?))
+}
+
+fn store_cache(
+    cache_dir: &Path,
+    cache_file: &Path,
+    key: &CacheKey,
+    metadata: &FibexMetadata,
+) -> Result<(), Error> {
+    fs::create_dir_all(cache_dir)?;
+    // other processes must never see a partially written cache
+    let tmp_file = cache_file.with_extension(format!("{}.tmp", std::process::id()));
+    {
+        let mut writer = BufWriter::new(fs::File::create(&tmp_file)?);
+        bincode::serialize_into(&mut writer, key)?;
+        bincode::serialize_into(&mut writer, &CachedFibex::new(metadata))?;
+        writer.flush()?;
+    }
+    if let Err(e) = fs::rename(&tmp_file, cache_file) {
+        let _ = fs::remove_file(&tmp_file);
+        return Err(e.into());
+    }
+    prune_cache(cache_dir, cache_file)
+}
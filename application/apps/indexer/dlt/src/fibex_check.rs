@@ -0,0 +1,396 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+
+//! Validation of fibex files
+//!
+//! `read_fibexes` skips everything it can't resolve, which makes broken fibexes
+//! hard to spot: the affected non-verbose messages are just decoded wrongly.
+//! `check_fibexes` reads the same fibexes and reports all of these places.
+use crate::arxml::is_arxml_file;
+use crate::dlt::{TypeInfo, TypeInfoKind};
+use crate::fibex::{
+    line_and_column_at, predefined_signal_type, type_info_for_base_data_type, Event, Reader,
+};
+use failure::Error;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub enum FibexIssue {
+    UnresolvedPduRef {
+        frame_id: String,
+        pdu_ref: String,
+    },
+    UnresolvedSignalRef {
+        pdu_id: String,
+        signal_ref: String,
+    },
+    UnresolvedCodingRef {
+        signal_id: String,
+        coding_ref: String,
+    },
+    DuplicateFrameId {
+        frame_id: String,
+    },
+    DuplicatePduId {
+        pdu_id: String,
+    },
+    UnsupportedBaseDataType {
+        id: String,
+        base_data_type: String,
+    },
+    /// the signals of the pdu need a different number of bytes than declared
+    ByteLengthMismatch {
+        pdu_id: String,
+        declared: usize,
+        signals: usize,
+    },
+    /// the checks only support fibex files, ARXML files are not checked
+    UnsupportedFile,
+}
+
+impl fmt::Display for FibexIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FibexIssue::UnresolvedPduRef { frame_id, pdu_ref } => {
+                write!(f, "frame {} refers to unknown pdu {}", frame_id, pdu_ref)
+            }
+            FibexIssue::UnresolvedSignalRef { pdu_id, signal_ref } => {
+                write!(f, "pdu {} refers to unknown signal {}", pdu_id, signal_ref)
+            }
+            FibexIssue::UnresolvedCodingRef {
+                signal_id,
+                coding_ref,
+            } => write!(
+                f,
+                "signal {} refers to unknown coding {}",
+                signal_id, coding_ref
+            ),
+            FibexIssue::DuplicateFrameId { frame_id } => {
+                write!(f, "duplicate frame id {}", frame_id)
+            }
+            FibexIssue::DuplicatePduId { pdu_id } => write!(f, "duplicate pdu id {}", pdu_id),
+            FibexIssue::UnsupportedBaseDataType { id, base_data_type } => write!(
+                f,
+                "{} has unsupported base data type {}",
+                id, base_data_type
+            ),
+            FibexIssue::ByteLengthMismatch {
+                pdu_id,
+                declared,
+                signals,
+            } => write!(
+                f,
+                "pdu {} declares {} bytes but its signals need {} bytes",
+                pdu_id, declared, signals
+            ),
+            FibexIssue::UnsupportedFile => write!(f, "ARXML files can not be checked"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FibexFinding {
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub issue: FibexIssue,
+}
+
+impl fmt::Display for FibexFinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.file.display(),
+            self.line,
+            self.column,
+            self.issue
+        )
+    }
+}
+
+/// index of the file and byte position in it
+///
+/// references are only complete at the end of their element, so that's
+/// where they are reported
+#[derive(Debug, Clone, Copy)]
+struct Location {
+    file: usize,
+    position: usize,
+}
+
+struct PduRecord {
+    id: String,
+    location: Location,
+    byte_length: usize,
+    signal_refs: Vec<(String, Location)>,
+}
+
+struct FrameRecord {
+    id: String,
+    location: Location,
+    pdu_refs: Vec<(String, Location)>,
+}
+
+#[derive(Default)]
+struct FibexRecords {
+    pdus: Vec<PduRecord>,
+    frames: Vec<FrameRecord>,
+    signals: Vec<(String, String, Location)>,
+    codings: Vec<(String, String, Location)>,
+}
+
+/// checks fibex files, ARXML files are reported as `UnsupportedFile`
+///
+/// the findings are sorted by file and position, errors are only returned
+/// for files that can't be read at all
+pub fn check_fibexes(files: &[PathBuf]) -> Result<Vec<FibexFinding>, Error> {
+    let mut records = FibexRecords::default();
+    let mut unsupported = vec![];
+    for (index, path) in files.iter().enumerate() {
+        if is_arxml_file(path) {
+            debug!("check_fibexes: can't check ARXML file {:?}", path);
+            unsupported.push((
+                Location {
+                    file: index,
+                    position: 0,
+                },
+                FibexIssue::UnsupportedFile,
+            ));
+            continue;
+        }
+        read_records(index, path, &mut records)?;
+    }
+    let mut issues = check_records(&records);
+    issues.append(&mut unsupported);
+    issues.sort_by_key(|(location, _)| (location.file, location.position));
+    let mut findings = vec![];
+    let mut content: Option<(usize, Vec<u8>)> = None;
+    for (location, issue) in issues {
+        let (line, column) = if location.position == 0 {
+            // the start of the file, no need to read it
+            (1, 0)
+        } else {
+            if content.as_ref().map(|(file, _)| *file) != Some(location.file) {
+                content = Some((location.file, fs::read(&files[location.file])?));
+            }
+            content
+                .as_ref()
+                .map(|(_, bytes)| line_and_column_at(bytes, location.position))
+                .unwrap_or((0, 0))
+        };
+        findings.push(FibexFinding {
+            file: files[location.file].clone(),
+            line,
+            column,
+            issue,
+        });
+    }
+    Ok(findings)
+}
+
+fn read_records(file: usize, path: &Path, records: &mut FibexRecords) -> Result<(), Error> {
+    let mut reader = Reader::from_file(path)?;
+    let mut pdu: Option<PduRecord> = None;
+    let mut frame: Option<FrameRecord> = None;
+    loop {
+        let event = reader.read_event()?;
+        let location = Location {
+            file,
+            position: reader.buffer_position(),
+        };
+        match event {
+            Event::PduStart { id } => {
+                pdu = Some(PduRecord {
+                    id,
+                    location,
+                    byte_length: 0,
+                    signal_refs: vec![],
+                })
+            }
+            Event::SignalInstance { signal_ref, .. } => {
+                if let Some(pdu) = pdu.as_mut() {
+                    pdu.signal_refs.push((signal_ref, location));
+                }
+            }
+            Event::PduEnd { byte_length, .. } => {
+                if let Some(mut pdu) = pdu.take() {
+                    pdu.byte_length = byte_length;
+                    records.pdus.push(pdu);
+                }
+            }
+            Event::FrameStart { id } => {
+                frame = Some(FrameRecord {
+                    id,
+                    location,
+                    pdu_refs: vec![],
+                })
+            }
+            Event::PduInstance { pdu_ref, .. } => {
+                if let Some(frame) = frame.as_mut() {
+                    frame.pdu_refs.push((pdu_ref, location));
+                }
+            }
+            Event::FrameEnd { .. } => {
+                if let Some(frame) = frame.take() {
+                    records.frames.push(frame);
+                }
+            }
+            Event::Signal { id, coding_ref, .. } => {
+                records.signals.push((id, coding_ref, location))
+            }
+            Event::Coding {
+                id, base_data_type, ..
+            } => records.codings.push((id, base_data_type, location)),
+            Event::Eof => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+fn check_records(records: &FibexRecords) -> Vec<(Location, FibexIssue)> {
+    let mut issues = vec![];
+
+    let mut codings: HashMap<&str, &str> = HashMap::new();
+    for (id, base_data_type, location) in &records.codings {
+        codings.insert(id, base_data_type);
+        if type_info_for_base_data_type(base_data_type).is_none() {
+            issues.push((
+                *location,
+                FibexIssue::UnsupportedBaseDataType {
+                    id: id.clone(),
+                    base_data_type: base_data_type.clone(),
+                },
+            ));
+        }
+    }
+
+    let mut signals: HashMap<&str, Option<&str>> = HashMap::new();
+    for (id, coding_ref, location) in &records.signals {
+        let base_data_type = codings.get(coding_ref.as_str()).cloned();
+        if base_data_type.is_none() {
+            issues.push((
+                *location,
+                FibexIssue::UnresolvedCodingRef {
+                    signal_id: id.clone(),
+                    coding_ref: coding_ref.clone(),
+                },
+            ));
+        }
+        signals.insert(id, base_data_type);
+    }
+
+    let mut pdu_ids: HashSet<&str> = HashSet::new();
+    for pdu in &records.pdus {
+        if !pdu_ids.insert(&pdu.id) {
+            issues.push((
+                pdu.location,
+                FibexIssue::DuplicatePduId {
+                    pdu_id: pdu.id.clone(),
+                },
+            ));
+        }
+        // None as soon as one signal has no fixed size
+        let mut signals_length = Some(0);
+        for (signal_ref, location) in &pdu.signal_refs {
+            let type_info = match predefined_signal_type(signal_ref) {
+                Some(type_info) => {
+                    if type_info.is_none() {
+                        issues.push((
+                            *location,
+                            FibexIssue::UnsupportedBaseDataType {
+                                id: signal_ref.clone(),
+                                base_data_type: signal_ref.clone(),
+                            },
+                        ));
+                    }
+                    type_info
+                }
+                None => match signals.get(signal_ref.as_str()) {
+                    // unresolved codings and unsupported types are reported with the signal
+                    Some(base_data_type) => base_data_type.and_then(type_info_for_base_data_type),
+                    None => {
+                        issues.push((
+                            *location,
+                            FibexIssue::UnresolvedSignalRef {
+                                pdu_id: pdu.id.clone(),
+                                signal_ref: signal_ref.clone(),
+                            },
+                        ));
+                        None
+                    }
+                },
+            };
+            signals_length = match (signals_length, type_info.as_ref().and_then(fixed_length)) {
+                (Some(total), Some(length)) => Some(total + length),
+                _ => None,
+            };
+        }
+        if let Some(signals_length) = signals_length {
+            if signals_length != pdu.byte_length {
+                issues.push((
+                    pdu.location,
+                    FibexIssue::ByteLengthMismatch {
+                        pdu_id: pdu.id.clone(),
+                        declared: pdu.byte_length,
+                        signals: signals_length,
+                    },
+                ));
+            }
+        }
+    }
+
+    let mut frame_ids: HashSet<&str> = HashSet::new();
+    for frame in &records.frames {
+        if !frame_ids.insert(&frame.id) {
+            issues.push((
+                frame.location,
+                FibexIssue::DuplicateFrameId {
+                    frame_id: frame.id.clone(),
+                },
+            ));
+        }
+        for (pdu_ref, location) in &frame.pdu_refs {
+            if !pdu_ids.contains(pdu_ref.as_str()) {
+                issues.push((
+                    *location,
+                    FibexIssue::UnresolvedPduRef {
+                        frame_id: frame.id.clone(),
+                        pdu_ref: pdu_ref.clone(),
+                    },
+                ));
+            }
+        }
+    }
+    issues
+}
+
+/// bytes of a signal in the payload, None for strings and raw data
+fn fixed_length(type_info: &TypeInfo) -> Option<usize> {
+    match type_info.kind {
+        TypeInfoKind::Bool => Some(1),
+        TypeInfoKind::Signed(length) | TypeInfoKind::Unsigned(length) => Some(length as usize / 8),
+        TypeInfoKind::Float(width) => Some(width as usize / 8),
+        TypeInfoKind::SignedFixedPoint(width) | TypeInfoKind::UnsignedFixedPoint(width) => {
+            Some(width as usize / 8)
+        }
+        TypeInfoKind::StringType
+        | TypeInfoKind::Raw
+        | TypeInfoKind::Array(_)
+        | TypeInfoKind::Struct => None,
+    }
+}
@@ -0,0 +1,470 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+
+//! compiles a `FilterExpr` and evaluates it for a message
+use super::{
+    invalid, ArgumentSelector, CompareOp, FilterExpr, FilterExprError, FilterField, FilterValue,
+};
+use crate::dlt::{Argument, LogLevel, Message, MessageType, Value};
+use crate::dlt_view::MessageView;
+use crate::filtering::MessageTypeFilter;
+use regex::Regex;
+use std::borrow::Cow;
+
+pub trait FilterSubject {
+    fn app_id(&self) -> Option<&str>;
+    fn context_id(&self) -> Option<&str>;
+    fn ecu_id(&self) -> Option<&str>;
+    fn message_type(&self) -> Option<&MessageType>;
+    fn session_id(&self) -> Option<u32>;
+    /// None if the payload was not parsed yet
+    fn payload_text(&self) -> Option<Cow<'_, str>>;
+    /// None if the payload was not parsed yet or has no arguments
+    fn arguments(&self) -> Option<Cow<'_, [Argument]>>;
+}
+
+impl FilterSubject for Message {
+    fn app_id(&self) -> Option<&str> {
+        self.extended_header
+            .as_ref()
+            .map(|h| h.application_id.as_str())
+    }
+    fn context_id(&self) -> Option<&str> {
+        self.extended_header.as_ref().map(|h| h.context_id.as_str())
+    }
+    fn ecu_id(&self) -> Option<&str> {
+        self.header
+            .ecu_id
+            .as_deref()
+            .or_else(|| self.storage_header.as_ref().map(|h| h.ecu_id.as_str()))
+    }
+    fn message_type(&self) -> Option<&MessageType> {
+        self.extended_header.as_ref().map(|h| &h.message_type)
+    }
+    fn session_id(&self) -> Option<u32> {
+        self.header.session_id
+    }
+    fn payload_text(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Owned(Message::payload_text(self)))
+    }
+    fn arguments(&self) -> Option<Cow<'_, [Argument]>> {
+        Message::arguments(self)
+    }
+}
+
+impl<'a> FilterSubject for MessageView<'a> {
+    fn app_id(&self) -> Option<&str> {
+        self.extended_header.as_ref().map(|h| h.application_id)
+    }
+    fn context_id(&self) -> Option<&str> {
+        self.extended_header.as_ref().map(|h| h.context_id)
+    }
+    fn ecu_id(&self) -> Option<&str> {
+        MessageView::ecu_id(self)
+    }
+    fn message_type(&self) -> Option<&MessageType> {
+        self.extended_header.as_ref().map(|h| &h.message_type)
+    }
+    fn session_id(&self) -> Option<u32> {
+        self.header.session_id
+    }
+    fn payload_text(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+    fn arguments(&self) -> Option<Cow<'_, [Argument]>> {
+        None
+    }
+}
+
+impl FilterExpr {
+    /// checks all values and compiles the regular expressions
+    pub fn compile(&self) -> Result<CompiledFilterExpr, FilterExprError> {
+        let root = compile_node(self)?;
+        Ok(CompiledFilterExpr {
+            uses_payload: root.uses_payload(),
+            root,
+        })
+    }
+}
+
+/// a filter expression that is ready to be evaluated
+#[derive(Debug, Clone)]
+pub struct CompiledFilterExpr {
+    root: Node,
+    uses_payload: bool,
+}
+
+impl CompiledFilterExpr {
+    /// true if the payload has to be parsed to evaluate the expression
+    pub fn uses_payload(&self) -> bool {
+        self.uses_payload
+    }
+
+    pub fn matches<S: FilterSubject>(&self, subject: &S) -> bool {
+        self.root.evaluate(subject) == Some(true)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    And(Vec<Node>),
+    Or(Vec<Node>),
+    Not(Box<Node>),
+    /// equal to one of the values (or to none of them if negated)
+    Text {
+        field: FilterField,
+        values: Vec<String>,
+        negate: bool,
+    },
+    Regex {
+        field: FilterField,
+        regex: Regex,
+    },
+    Number {
+        field: FilterField,
+        op: CompareOp,
+        value: u64,
+    },
+    NumberIn {
+        field: FilterField,
+        values: Vec<u64>,
+    },
+    ArgumentNumber {
+        selector: ArgumentSelector,
+        op: CompareOp,
+        value: f64,
+    },
+    Type {
+        types: Vec<MessageTypeFilter>,
+        negate: bool,
+    },
+}
+
+fn is_text_field(field: &FilterField) -> bool {
+    match field {
+        FilterField::App
+        | FilterField::Context
+        | FilterField::Ecu
+        | FilterField::Payload
+        | FilterField::Argument(_)
+        | FilterField::Unit(_) => true,
+        FilterField::Level | FilterField::Session | FilterField::Type => false,
+    }
+}
+
+/// the field can only be evaluated after the payload was parsed
+fn is_payload_field(field: &FilterField) -> bool {
+    matches!(
+        field,
+        FilterField::Payload | FilterField::Argument(_) | FilterField::Unit(_)
+    )
+}
+
+fn compile_node(expr: &FilterExpr) -> Result<Node, FilterExprError> {
+    Ok(match expr {
+        FilterExpr::And(operands) => Node::And(
+            operands
+                .iter()
+                .map(compile_node)
+                .collect::<Result<Vec<Node>, FilterExprError>>()?,
+        ),
+        FilterExpr::Or(operands) => Node::Or(
+            operands
+                .iter()
+                .map(compile_node)
+                .collect::<Result<Vec<Node>, FilterExprError>>()?,
+        ),
+        FilterExpr::Not(operand) => Node::Not(Box::new(compile_node(operand)?)),
+        FilterExpr::Matches { field, pattern } => {
+            if !is_text_field(field) {
+                return invalid(format!("{:?} cannot be matched with ~", field));
+            }
+            match Regex::new(pattern) {
+                Ok(regex) => Node::Regex {
+                    field: field.clone(),
+                    regex,
+                },
+                Err(e) => {
+                    return invalid(format!("invalid regular expression /{}/: {}", pattern, e))
+                }
+            }
+        }
+        FilterExpr::Compare { field, op, value } => {
+            compile_condition(field, *op, std::slice::from_ref(value))?
+        }
+        FilterExpr::In { field, values } => {
+            if values.is_empty() {
+                return invalid(format!("empty list for {:?}", field));
+            }
+            compile_condition(field, CompareOp::Eq, values)?
+        }
+    })
+}
+
+/// `values` contains more than one value only for `in` conditions
+fn compile_condition(
+    field: &FilterField,
+    op: CompareOp,
+    values: &[FilterValue],
+) -> Result<Node, FilterExprError> {
+    if let FilterField::Argument(selector) = field {
+        if let Some(numbers) = values.iter().map(float_value).collect::<Option<Vec<f64>>>() {
+            let mut nodes: Vec<Node> = numbers
+                .into_iter()
+                .map(|value| Node::ArgumentNumber {
+                    selector: selector.clone(),
+                    op,
+                    value,
+                })
+                .collect();
+            return Ok(match nodes.len() {
+                1 => nodes.remove(0),
+                _ => Node::Or(nodes),
+            });
+        }
+    }
+    let negate = match op {
+        CompareOp::Eq => false,
+        CompareOp::Ne => true,
+        _ if *field == FilterField::Level || *field == FilterField::Session => false,
+        _ => return invalid(format!("{:?} can only be compared with == and !=", field)),
+    };
+    match field {
+        FilterField::App
+        | FilterField::Context
+        | FilterField::Ecu
+        | FilterField::Payload
+        | FilterField::Argument(_)
+        | FilterField::Unit(_) => Ok(Node::Text {
+            field: field.clone(),
+            values: values
+                .iter()
+                .map(|v| match v {
+                    FilterValue::Text(text) => text.clone(),
+                    FilterValue::Number(n) => n.to_string(),
+                    FilterValue::Float(n) => n.to_string(),
+                })
+                .collect(),
+            negate,
+        }),
+        FilterField::Type => Ok(Node::Type {
+            types: values
+                .iter()
+                .map(type_value)
+                .collect::<Result<Vec<MessageTypeFilter>, FilterExprError>>()?,
+            negate,
+        }),
+        FilterField::Level | FilterField::Session => {
+            let numbers = values
+                .iter()
+                .map(|v| number_value(field, v))
+                .collect::<Result<Vec<u64>, FilterExprError>>()?;
+            if numbers.len() == 1 {
+                Ok(Node::Number {
+                    field: field.clone(),
+                    op,
+                    value: numbers[0],
+                })
+            } else {
+                Ok(Node::NumberIn {
+                    field: field.clone(),
+                    values: numbers,
+                })
+            }
+        }
+    }
+}
+
+fn float_value(value: &FilterValue) -> Option<f64> {
+    match value {
+        FilterValue::Number(n) => Some(*n as f64),
+        FilterValue::Float(n) => Some(*n),
+        FilterValue::Text(_) => None,
+    }
+}
+
+fn number_value(field: &FilterField, value: &FilterValue) -> Result<u64, FilterExprError> {
+    match (field, value) {
+        (_, FilterValue::Number(n)) => Ok(*n),
+        (_, FilterValue::Float(n)) => {
+            invalid(format!("{:?} needs an unsigned integer, not {}", field, n))
+        }
+        (FilterField::Level, FilterValue::Text(name)) => match name.to_uppercase().as_str() {
+            "FATAL" => Ok(1),
+            "ERROR" => Ok(2),
+            "WARN" | "WARNING" => Ok(3),
+            "INFO" => Ok(4),
+            "DEBUG" => Ok(5),
+            "VERBOSE" => Ok(6),
+            _ => invalid(format!("unknown log level {}", name)),
+        },
+        (_, FilterValue::Text(text)) => match text.parse() {
+            Ok(n) => Ok(n),
+            Err(_) => invalid(format!("{:?} needs a number, not {}", field, text)),
+        },
+    }
+}
+
+fn type_value(value: &FilterValue) -> Result<MessageTypeFilter, FilterExprError> {
+    let name = match value {
+        FilterValue::Text(name) => name.to_uppercase(),
+        FilterValue::Number(n) => return invalid(format!("unknown message type {}", n)),
+        FilterValue::Float(n) => return invalid(format!("unknown message type {}", n)),
+    };
+    match name.as_str() {
+        "LOG" => Ok(MessageTypeFilter::Log),
+        "APP_TRACE" => Ok(MessageTypeFilter::ApplicationTrace(None)),
+        "NW_TRACE" => Ok(MessageTypeFilter::NetworkTrace(None)),
+        "CONTROL" => Ok(MessageTypeFilter::Control(None)),
+        _ => invalid(format!("unknown message type {}", name)),
+    }
+}
+
+fn level_number(level: &LogLevel) -> u64 {
+    u64::from(u8::from(level) >> 4)
+}
+
+impl Node {
+    fn uses_payload(&self) -> bool {
+        match self {
+            Node::And(operands) | Node::Or(operands) => operands.iter().any(Node::uses_payload),
+            Node::Not(operand) => operand.uses_payload(),
+            Node::Text { field, .. } | Node::Regex { field, .. } => is_payload_field(field),
+            Node::ArgumentNumber { .. } => true,
+            Node::Number { .. } | Node::NumberIn { .. } | Node::Type { .. } => false,
+        }
+    }
+
+    /// None if the result depends on a field the message does not have
+    ///
+    /// such a condition is neither true nor false, it stays unknown under `!`
+    /// and only decides `&&` and `||` if no other operand does.
+    fn evaluate<S: FilterSubject>(&self, subject: &S) -> Option<bool> {
+        match self {
+            Node::And(operands) => {
+                let mut result = Some(true);
+                for operand in operands {
+                    match operand.evaluate(subject) {
+                        Some(false) => return Some(false),
+                        Some(true) => (),
+                        None => result = None,
+                    }
+                }
+                result
+            }
+            Node::Or(operands) => {
+                let mut result = Some(false);
+                for operand in operands {
+                    match operand.evaluate(subject) {
+                        Some(true) => return Some(true),
+                        Some(false) => (),
+                        None => result = None,
+                    }
+                }
+                result
+            }
+            Node::Not(operand) => operand.evaluate(subject).map(|matches| !matches),
+            Node::Text {
+                field,
+                values,
+                negate,
+            } => text_field(subject, field)
+                .map(|text| values.iter().any(|v| v == text.as_ref()) != *negate),
+            Node::Regex { field, regex } => {
+                text_field(subject, field).map(|text| regex.is_match(&text))
+            }
+            Node::Number { field, op, value } => {
+                number_field(subject, field).map(|n| compare(*op, n, *value))
+            }
+            Node::NumberIn { field, values } => {
+                number_field(subject, field).map(|n| values.contains(&n))
+            }
+            Node::ArgumentNumber {
+                selector,
+                op,
+                value,
+            } => subject
+                .arguments()
+                .and_then(|args| select_argument(&args, selector)?.numeric_value())
+                .map(|n| compare(*op, n, *value)),
+            Node::Type { types, negate } => subject
+                .message_type()
+                .map(|t| types.iter().any(|filter| filter.matches(t)) != *negate),
+        }
+    }
+}
+
+fn compare<T: PartialOrd>(op: CompareOp, a: T, b: T) -> bool {
+    match op {
+        CompareOp::Eq => a == b,
+        CompareOp::Ne => a != b,
+        CompareOp::Lt => a < b,
+        CompareOp::Le => a <= b,
+        CompareOp::Gt => a > b,
+        CompareOp::Ge => a >= b,
+    }
+}
+
+fn select_argument<'a>(
+    arguments: &'a [Argument],
+    selector: &ArgumentSelector,
+) -> Option<&'a Argument> {
+    match selector {
+        ArgumentSelector::Index(index) => arguments.get(*index),
+        ArgumentSelector::Name(name) => {
+            arguments.iter().find(|arg| arg.name.as_ref() == Some(name))
+        }
+    }
+}
+
+/// strings as they are, numbers with their physical value
+fn argument_text(argument: &Argument) -> String {
+    match (&argument.value, &argument.fixed_point) {
+        (Value::StringVal(text), _) => text.clone(),
+        (_, Some(_)) => match argument.numeric_value() {
+            Some(n) => n.to_string(),
+            None => argument.value.to_string(),
+        },
+        (value, None) => value.to_string(),
+    }
+}
+
+fn text_field<'a, S: FilterSubject>(subject: &'a S, field: &FilterField) -> Option<Cow<'a, str>> {
+    match field {
+        FilterField::App => subject.app_id().map(Cow::Borrowed),
+        FilterField::Context => subject.context_id().map(Cow::Borrowed),
+        FilterField::Ecu => subject.ecu_id().map(Cow::Borrowed),
+        FilterField::Payload => subject.payload_text(),
+        FilterField::Argument(selector) => {
+            let arguments = subject.arguments()?;
+            select_argument(&arguments, selector).map(|arg| Cow::Owned(argument_text(arg)))
+        }
+        FilterField::Unit(selector) => {
+            let arguments = subject.arguments()?;
+            select_argument(&arguments, selector)
+                .and_then(|arg| arg.unit.clone())
+                .map(Cow::Owned)
+        }
+        _ => None,
+    }
+}
+
+fn number_field<S: FilterSubject>(subject: &S, field: &FilterField) -> Option<u64> {
+    match field {
+        FilterField::Level => match subject.message_type() {
+            Some(MessageType::Log(level)) => Some(level_number(level)),
+            _ => None,
+        },
+        FilterField::Session => subject.session_id().map(u64::from),
+        _ => None,
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+
+//! Boolean filter expressions for DLT messages
+//!
+//! ```text
+//! (app == "NAV" && level <= WARN) || ctx in ["DIAG","UDS"] || payload ~ /timeout/
+//! ```
+//!
+//! expression  := or
+//! or          := and ("||" and)*
+//! and         := unary ("&&" unary)*
+//! unary       := "!" unary | "(" expression ")" | condition
+//! condition   := field op value | field "in" "[" value ("," value)* "]" | field "~" regex
+//! op          := "==" | "!=" | "<" | "<=" | ">" | ">="
+//! value       := "string" | number | NAME
+//! regex       := /pattern/ | "pattern"
+//!
+//! fields: `app`, `ctx`, `ecu`, `level`, `session`, `type` and `payload`.
+//! Levels are compared by their number, `level <= WARN` selects FATAL, ERROR and WARN.
+//! Types are LOG, APP_TRACE, NW_TRACE and CONTROL. A condition on a field that
+//! the message does not have (e.g. the level of a control message) is false, also
+//! when it is negated: `!(level > FATAL)` does not select control messages.
+//!
+//! Arguments are selected by position (`arg[0]`) or by the name of the variable
+//! (`arg.speed` or `arg["vehicle speed"]`), `arg[0].unit` is the unit of an argument.
+//! Numbers are compared with the physical value, `arg.speed > 120`. The signals of
+//! non-verbose messages are the arguments if the message is described in the fibex.
+//!
+//! The parsed `FilterExpr` is also the JSON form of an expression.
+mod eval;
+mod parser;
+
+pub use self::eval::{CompiledFilterExpr, FilterSubject};
+use failure::Fail;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum FilterField {
+    App,
+    Context,
+    Ecu,
+    Level,
+    Session,
+    Type,
+    Payload,
+    Argument(ArgumentSelector),
+    Unit(ArgumentSelector),
+}
+
+/// an argument of the payload, by position or by the name of its variable info
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ArgumentSelector {
+    Index(usize),
+    Name(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum FilterValue {
+    Number(u64),
+    /// negative and fractional numbers
+    Float(f64),
+    Text(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    And(Vec<FilterExpr>),
+    Or(Vec<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Compare {
+        field: FilterField,
+        op: CompareOp,
+        value: FilterValue,
+    },
+    In {
+        field: FilterField,
+        values: Vec<FilterValue>,
+    },
+    Matches {
+        field: FilterField,
+        pattern: String,
+    },
+}
+
+/// an expression as it can be given in a `DltFilterConfig`, either as text or as JSON
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum FilterExprSource {
+    Text(String),
+    Expr(FilterExpr),
+}
+
+impl FilterExprSource {
+    pub fn compile(&self) -> Result<CompiledFilterExpr, FilterExprError> {
+        match self {
+            FilterExprSource::Text(text) => FilterExpr::parse(text)?.compile(),
+            FilterExprSource::Expr(expr) => expr.compile(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FilterExprError {
+    Syntax { position: usize, reason: String },
+    Invalid { reason: String },
+}
+
+impl fmt::Display for FilterExprError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FilterExprError::Syntax { position, reason } => {
+                write!(f, "syntax error at {}: {}", position, reason)
+            }
+            FilterExprError::Invalid { reason } => {
+                write!(f, "invalid filter expression: {}", reason)
+            }
+        }
+    }
+}
+
+// not derived, the derive of failure puts the impl into a function
+impl Fail for FilterExprError {}
+
+fn invalid<T>(reason: String) -> Result<T, FilterExprError> {
+    Err(FilterExprError::Invalid { reason })
+}
+
+impl fmt::Display for FilterField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            FilterField::App => "app",
+            FilterField::Context => "ctx",
+            FilterField::Ecu => "ecu",
+            FilterField::Level => "level",
+            FilterField::Session => "session",
+            FilterField::Type => "type",
+            FilterField::Payload => "payload",
+            FilterField::Argument(selector) => return write!(f, "{}", selector),
+            FilterField::Unit(selector) => return write!(f, "{}.unit", selector),
+        })
+    }
+}
+
+impl fmt::Display for ArgumentSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArgumentSelector::Index(index) => write!(f, "arg[{}]", index),
+            ArgumentSelector::Name(name)
+                if name.chars().all(|c| c.is_alphanumeric() || c == '_')
+                    && name.starts_with(|c: char| c.is_alphabetic() || c == '_') =>
+            {
+                write!(f, "arg.{}", name)
+            }
+            ArgumentSelector::Name(name) => write!(f, "arg[{}]", FilterValue::Text(name.clone())),
+        }
+    }
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        })
+    }
+}
+
+impl fmt::Display for FilterValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FilterValue::Number(n) => write!(f, "{}", n),
+            // keeps the decimal point
+            FilterValue::Float(n) => write!(f, "{:?}", n),
+            FilterValue::Text(text) => {
+                write!(f, "\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+        }
+    }
+}
+
+/// writes the expression in the syntax that `FilterExpr::parse` reads
+impl fmt::Display for FilterExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FilterExpr::And(operands) => write_operands(f, operands, " && "),
+            FilterExpr::Or(operands) => write_operands(f, operands, " || "),
+            FilterExpr::Not(operand) => write!(f, "!({})", operand),
+            FilterExpr::Compare { field, op, value } => write!(f, "{} {} {}", field, op, value),
+            FilterExpr::In { field, values } => {
+                write!(f, "{} in [", field)?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_str("]")
+            }
+            FilterExpr::Matches { field, pattern } => {
+                write!(f, "{} ~ /{}/", field, pattern.replace('/', "\\/"))
+            }
+        }
+    }
+}
+
+fn write_operands(f: &mut fmt::Formatter, operands: &[FilterExpr], separator: &str) -> fmt::Result {
+    for (i, operand) in operands.iter().enumerate() {
+        if i > 0 {
+            f.write_str(separator)?;
+        }
+        match operand {
+            FilterExpr::And(_) | FilterExpr::Or(_) => write!(f, "({})", operand)?,
+            _ => write!(f, "{}", operand)?,
+        }
+    }
+    Ok(())
+}
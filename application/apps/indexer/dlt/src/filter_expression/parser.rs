@@ -0,0 +1,332 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+
+//! tokenizer and recursive descent parser for the grammar in the module docs
+use super::{ArgumentSelector, CompareOp, FilterExpr, FilterExprError, FilterField, FilterValue};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Text(String),
+    Number(u64),
+    Float(f64),
+    Regex(String),
+    Op(CompareOp),
+    And,
+    Or,
+    Not,
+    Tilde,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Dot,
+}
+
+fn syntax_error<T>(position: usize, reason: impl Into<String>) -> Result<T, FilterExprError> {
+    Err(FilterExprError::Syntax {
+        position,
+        reason: reason.into(),
+    })
+}
+
+/// splits the input into tokens, each with its position in the input
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, FilterExprError> {
+    let chars: Vec<(usize, char)> = input.char_indices().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        let next = chars.get(i + 1).map(|(_, c)| *c);
+        let (token, length) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Op(CompareOp::Eq), 2),
+            ('!', Some('=')) => (Token::Op(CompareOp::Ne), 2),
+            ('<', Some('=')) => (Token::Op(CompareOp::Le), 2),
+            ('>', Some('=')) => (Token::Op(CompareOp::Ge), 2),
+            ('<', _) => (Token::Op(CompareOp::Lt), 1),
+            ('>', _) => (Token::Op(CompareOp::Gt), 1),
+            ('!', _) => (Token::Not, 1),
+            ('~', _) => (Token::Tilde, 1),
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            ('[', _) => (Token::LBracket, 1),
+            (']', _) => (Token::RBracket, 1),
+            (',', _) => (Token::Comma, 1),
+            ('.', _) => (Token::Dot, 1),
+            ('"', _) | ('/', _) => {
+                let (text, length) = delimited(&chars[i..], c)?;
+                if c == '"' {
+                    (Token::Text(text), length)
+                } else {
+                    (Token::Regex(text), length)
+                }
+            }
+            (c, next) if c.is_ascii_digit() || (c == '-' && matches!(next, Some('0'..='9'))) => {
+                let length = 1 + chars[i + 1..]
+                    .iter()
+                    .take_while(|(_, c)| c.is_ascii_alphanumeric() || *c == '.')
+                    .count();
+                let text: String = chars[i..i + length].iter().map(|(_, c)| c).collect();
+                match number_token(&text) {
+                    Some(token) => (token, length),
+                    None => return syntax_error(pos, format!("invalid number {}", text)),
+                }
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let length = chars[i..]
+                    .iter()
+                    .take_while(|(_, c)| c.is_alphanumeric() || *c == '_')
+                    .count();
+                let name: String = chars[i..i + length].iter().map(|(_, c)| c).collect();
+                (Token::Name(name), length)
+            }
+            (c, _) => return syntax_error(pos, format!("unexpected character '{}'", c)),
+        };
+        tokens.push((pos, token));
+        i += length;
+    }
+    Ok(tokens)
+}
+
+fn number_token(text: &str) -> Option<Token> {
+    if text.starts_with("0x") || text.starts_with("0X") {
+        u64::from_str_radix(&text[2..], 16).ok().map(Token::Number)
+    } else if text.starts_with('-') || text.contains('.') {
+        text.parse().ok().map(Token::Float)
+    } else {
+        text.parse().ok().map(Token::Number)
+    }
+}
+
+/// reads a string or regex that is enclosed in `delimiter`, a backslash escapes the delimiter
+fn delimited(chars: &[(usize, char)], delimiter: char) -> Result<(String, usize), FilterExprError> {
+    let mut text = String::new();
+    let mut i = 1;
+    while i < chars.len() {
+        match chars[i].1 {
+            '\\' if chars.get(i + 1).map(|(_, c)| *c) == Some(delimiter) => {
+                text.push(delimiter);
+                i += 2;
+            }
+            // escapes in strings, regular expressions keep their escapes
+            '\\' if delimiter == '"' && i + 1 < chars.len() => {
+                text.push(chars[i + 1].1);
+                i += 2;
+            }
+            c if c == delimiter => return Ok((text, i + 1)),
+            c => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+    syntax_error(chars[0].0, format!("missing closing {}", delimiter))
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    input_length: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map(|(p, _)| *p)
+            .unwrap_or(self.input_length)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(_, t)| t.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token, what: &str) -> Result<(), FilterExprError> {
+        let position = self.position();
+        match self.next() {
+            Some(ref token) if *token == expected => Ok(()),
+            _ => syntax_error(position, format!("expected {}", what)),
+        }
+    }
+
+    fn expression(&mut self) -> Result<FilterExpr, FilterExprError> {
+        let mut operands = vec![self.and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            operands.push(self.and()?);
+        }
+        Ok(match operands.len() {
+            1 => operands.remove(0),
+            _ => FilterExpr::Or(operands),
+        })
+    }
+
+    fn and(&mut self) -> Result<FilterExpr, FilterExprError> {
+        let mut operands = vec![self.unary()?];
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            operands.push(self.unary()?);
+        }
+        Ok(match operands.len() {
+            1 => operands.remove(0),
+            _ => FilterExpr::And(operands),
+        })
+    }
+
+    fn unary(&mut self) -> Result<FilterExpr, FilterExprError> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.next();
+                Ok(FilterExpr::Not(Box::new(self.unary()?)))
+            }
+            Some(Token::LParen) => {
+                self.next();
+                let expr = self.expression()?;
+                self.expect(Token::RParen, "')'")?;
+                Ok(expr)
+            }
+            _ => self.condition(),
+        }
+    }
+
+    fn condition(&mut self) -> Result<FilterExpr, FilterExprError> {
+        let field = self.field()?;
+        let position = self.position();
+        match self.next() {
+            Some(Token::Op(op)) => Ok(FilterExpr::Compare {
+                field,
+                op,
+                value: self.value()?,
+            }),
+            Some(Token::Tilde) => {
+                let position = self.position();
+                match self.next() {
+                    Some(Token::Regex(pattern)) | Some(Token::Text(pattern)) => {
+                        Ok(FilterExpr::Matches { field, pattern })
+                    }
+                    _ => syntax_error(position, "expected a regular expression"),
+                }
+            }
+            Some(Token::Name(ref name)) if name == "in" => {
+                self.expect(Token::LBracket, "'['")?;
+                let mut values = vec![self.value()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.next();
+                    values.push(self.value()?);
+                }
+                self.expect(Token::RBracket, "']'")?;
+                Ok(FilterExpr::In { field, values })
+            }
+            _ => syntax_error(position, "expected an operator"),
+        }
+    }
+
+    fn field(&mut self) -> Result<FilterField, FilterExprError> {
+        let position = self.position();
+        let name = match self.next() {
+            Some(Token::Name(name)) => name,
+            _ => return syntax_error(position, "expected a field"),
+        };
+        if !name.eq_ignore_ascii_case("arg") {
+            return match field_by_name(&name) {
+                Some(field) => Ok(field),
+                None => syntax_error(position, format!("unknown field {}", name)),
+            };
+        }
+        let position = self.position();
+        let selector = match (self.next(), self.next()) {
+            (Some(Token::LBracket), Some(Token::Number(index))) => {
+                self.expect(Token::RBracket, "']'")?;
+                ArgumentSelector::Index(index as usize)
+            }
+            (Some(Token::LBracket), Some(Token::Text(name))) => {
+                self.expect(Token::RBracket, "']'")?;
+                ArgumentSelector::Name(name)
+            }
+            (Some(Token::Dot), Some(Token::Name(name))) => ArgumentSelector::Name(name),
+            _ => return syntax_error(position, "expected arg[index], arg[\"name\"] or arg.name"),
+        };
+        if self.peek() != Some(&Token::Dot) {
+            return Ok(FilterField::Argument(selector));
+        }
+        self.next();
+        let position = self.position();
+        match self.next() {
+            Some(Token::Name(ref name)) if name.eq_ignore_ascii_case("unit") => {
+                Ok(FilterField::Unit(selector))
+            }
+            _ => syntax_error(position, "expected unit"),
+        }
+    }
+
+    fn value(&mut self) -> Result<FilterValue, FilterExprError> {
+        let position = self.position();
+        match self.next() {
+            Some(Token::Text(text)) | Some(Token::Name(text)) => Ok(FilterValue::Text(text)),
+            Some(Token::Number(n)) => Ok(FilterValue::Number(n)),
+            Some(Token::Float(n)) => Ok(FilterValue::Float(n)),
+            _ => syntax_error(position, "expected a value"),
+        }
+    }
+}
+
+fn field_by_name(name: &str) -> Option<FilterField> {
+    match name.to_lowercase().as_str() {
+        "app" | "apid" | "app_id" => Some(FilterField::App),
+        "ctx" | "ctid" | "context" | "context_id" => Some(FilterField::Context),
+        "ecu" | "ecu_id" => Some(FilterField::Ecu),
+        "level" => Some(FilterField::Level),
+        "session" | "session_id" => Some(FilterField::Session),
+        "type" => Some(FilterField::Type),
+        "payload" => Some(FilterField::Payload),
+        _ => None,
+    }
+}
+
+impl FilterExpr {
+    pub fn parse(input: &str) -> Result<FilterExpr, FilterExprError> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+            input_length: input.len(),
+        };
+        if parser.tokens.is_empty() {
+            return syntax_error(0, "empty expression");
+        }
+        let expr = parser.expression()?;
+        if parser.pos < parser.tokens.len() {
+            return syntax_error(parser.position(), "unexpected input after expression");
+        }
+        Ok(expr)
+    }
+}
+
+impl FromStr for FilterExpr {
+    type Err = FilterExprError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        FilterExpr::parse(s)
+    }
+}
@@ -14,6 +14,10 @@ use std::io::{Read};
 use std::fs;
 use std::collections::HashSet;
 use crate::dlt;
+use crate::filter_expression::{CompiledFilterExpr, FilterExprError, FilterExprSource};
+use crate::payload_search::{PayloadMatcher, PayloadSearch, PayloadSearchError};
+use failure::Fail;
+use std::fmt;
 use std::iter::FromIterator;
 
 /// only select log entries with level MIN_LEVEL and more severe
@@ -23,28 +27,378 @@ use std::iter::FromIterator;
 ///  4 => INFO
 ///  5 => DEBUG
 ///  6 => VERBOSE
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct DltFilterConfig {
     pub min_log_level: Option<u8>,
     pub app_ids: Option<Vec<String>>,
     pub ecu_ids: Option<Vec<String>>,
     pub context_ids: Option<Vec<String>>,
+    /// only select verbose messages with an argument that has one of these trace infos
+    pub trace_infos: Option<Vec<String>>,
+    /// only select messages of these sessions (usually the process id), messages
+    /// without session id are not selected
+    pub session_ids: Option<Vec<u32>>,
+    /// only select messages of one of these types
+    pub message_types: Option<Vec<MessageTypeFilter>>,
+    /// never select messages of these applications
+    pub exclude_app_ids: Option<Vec<String>>,
+    /// never select messages of these contexts
+    pub exclude_context_ids: Option<Vec<String>>,
+    /// never select messages of these ECUs
+    pub exclude_ecu_ids: Option<Vec<String>>,
+    /// the first matching override replaces `min_log_level`
+    pub level_overrides: Option<Vec<LevelOverride>>,
+    /// only select messages that match this expression, see `filter_expression`
+    pub filter_expr: Option<FilterExprSource>,
+    /// only select messages of this time range
+    pub time_range: Option<TimeRange>,
+    /// how messages without extended header are filtered, `Include` if not set
+    pub missing_fields: Option<MissingFieldPolicy>,
+    /// only select messages with this text in the payload, see `payload_search`
+    pub payload_search: Option<PayloadSearch>,
+    /// put out the trace info of the verbose arguments in a column after the payload
+    pub show_trace_info: Option<bool>,
 }
+
+/// how the filters on application id, context id, log level and message type
+/// treat messages without extended header, which do not have these fields
+///
+/// in json: `"Include"`, `"Exclude"` or
+/// `{"Default": {"app_id": "NONV", "context_id": "NONV", "log_level": 4}}`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum MissingFieldPolicy {
+    /// the filters do not apply to these messages
+    Include,
+    /// these messages are filtered out if one of the filters is set
+    Exclude,
+    /// these messages are filtered like log messages with these IDs and level,
+    /// a level outside of 1..=6 is not filtered by level
+    Default {
+        app_id: String,
+        context_id: String,
+        log_level: u8,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum ProcessedMissingFieldPolicy {
+    Include,
+    Exclude,
+    Default {
+        app_id: String,
+        context_id: String,
+        message_type: dlt::MessageType,
+    },
+}
+
+/// the clock that a `TimeRange` refers to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum TimeClock {
+    /// wall clock time of the storage header, in ms since the unix epoch
+    StorageHeader,
+    /// ECU uptime of the timestamp in the standard header, in ms
+    Uptime,
+}
+
+/// selects the messages from `start` (inclusive) to `end` (exclusive)
+///
+/// messages without a timestamp of the clock are not filtered
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TimeRange {
+    pub clock: TimeClock,
+    pub start: Option<u64>,
+    pub end: Option<u64>,
+}
+
+/// a `TimeRange` in µs, the limits saturate at `u64::MAX`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProcessedTimeRange {
+    pub clock: TimeClock,
+    pub start_us: Option<u64>,
+    pub end_us: Option<u64>,
+}
+
+impl ProcessedTimeRange {
+    pub fn contains_us(&self, time_us: u64) -> bool {
+        let after_start = match self.start_us {
+            Some(start) => time_us >= start,
+            None => true,
+        };
+        let before_end = match self.end_us {
+            Some(end) => time_us < end,
+            None => true,
+        };
+        after_start && before_end
+    }
+}
+
+impl From<TimeRange> for ProcessedTimeRange {
+    fn from(range: TimeRange) -> Self {
+        ProcessedTimeRange {
+            clock: range.clock,
+            start_us: range.start.map(|ms| ms.saturating_mul(1000)),
+            end_us: range.end.map(|ms| ms.saturating_mul(1000)),
+        }
+    }
+}
+
+/// a different minimum log level for the messages of some IDs
+///
+/// all IDs that are set have to match. A level outside of 1..=6 selects all levels.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LevelOverride {
+    pub app_id: Option<String>,
+    pub context_id: Option<String>,
+    pub ecu_id: Option<String>,
+    pub min_log_level: u8,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProcessedLevelOverride {
+    pub app_id: Option<String>,
+    pub context_id: Option<String>,
+    pub ecu_id: Option<String>,
+    pub min_log_level: Option<dlt::LogLevel>,
+}
+
+impl ProcessedLevelOverride {
+    pub fn matches(&self, ecu_id: Option<&str>, app_id: &str, context_id: &str) -> bool {
+        id_matches(&self.app_id, Some(app_id))
+            && id_matches(&self.context_id, Some(context_id))
+            && id_matches(&self.ecu_id, ecu_id)
+    }
+}
+
+fn id_matches(expected: &Option<String>, id: Option<&str>) -> bool {
+    match expected {
+        Some(expected) => Some(expected.as_str()) == id,
+        None => true,
+    }
+}
+
+/// selects a message type, optionally only one kind of it
+///
+/// in json: `"Log"`, `{"Control": null}`, `{"Control": "Response"}` or `{"NetworkTrace": "Can"}`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum MessageTypeFilter {
+    Log,
+    ApplicationTrace(Option<dlt::ApplicationTraceType>),
+    NetworkTrace(Option<dlt::NetworkTraceType>),
+    Control(Option<dlt::ControlType>),
+}
+
+impl MessageTypeFilter {
+    pub fn matches(&self, message_type: &dlt::MessageType) -> bool {
+        match (self, message_type) {
+            (MessageTypeFilter::Log, dlt::MessageType::Log(_)) => true,
+            (MessageTypeFilter::ApplicationTrace(kind), dlt::MessageType::ApplicationTrace(t)) => {
+                kind_matches(kind, t)
+            }
+            (MessageTypeFilter::NetworkTrace(kind), dlt::MessageType::NetworkTrace(t)) => {
+                kind_matches(kind, t)
+            }
+            (MessageTypeFilter::Control(kind), dlt::MessageType::Control(t)) => {
+                kind_matches(kind, t)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// no kind selects all kinds
+fn kind_matches<T: PartialEq>(kind: &Option<T>, t: &T) -> bool {
+    match kind {
+        Some(k) => k == t,
+        None => true,
+    }
+}
+
 #[derive(Clone)]
 pub struct ProcessedDltFilterConfig {
     pub min_log_level: Option<dlt::LogLevel>,
     pub app_ids: Option<HashSet<String>>,
     pub ecu_ids: Option<HashSet<String>>,
     pub context_ids: Option<HashSet<String>>,
+    pub trace_infos: Option<HashSet<String>>,
+    pub session_ids: Option<HashSet<u32>>,
+    pub message_types: Option<Vec<MessageTypeFilter>>,
+    pub exclude_app_ids: Option<HashSet<String>>,
+    pub exclude_context_ids: Option<HashSet<String>>,
+    pub exclude_ecu_ids: Option<HashSet<String>>,
+    pub level_overrides: Vec<ProcessedLevelOverride>,
+    pub filter_expr: Option<CompiledFilterExpr>,
+    pub time_range: Option<ProcessedTimeRange>,
+    pub missing_fields: ProcessedMissingFieldPolicy,
+    pub payload_search: Option<PayloadMatcher>,
+    pub show_trace_info: bool,
 }
 
-pub fn process_filter_config(cfg: DltFilterConfig) -> ProcessedDltFilterConfig {
-    ProcessedDltFilterConfig {
+impl ProcessedDltFilterConfig {
+    /// the minimum log level for messages of these IDs
+    pub fn min_log_level_for(
+        &self,
+        ecu_id: Option<&str>,
+        app_id: &str,
+        context_id: &str,
+    ) -> Option<dlt::LogLevel> {
+        match self
+            .level_overrides
+            .iter()
+            .find(|o| o.matches(ecu_id, app_id, context_id))
+        {
+            Some(level_override) => level_override.min_log_level,
+            None => self.min_log_level,
+        }
+    }
+
+    /// true if one of the filters needs the fields of the extended header
+    pub fn filters_extended_header(&self) -> bool {
+        self.min_log_level.is_some()
+            || !self.level_overrides.is_empty()
+            || self.message_types.is_some()
+            || self.app_ids.is_some()
+            || self.context_ids.is_some()
+            || self.exclude_app_ids.is_some()
+            || self.exclude_context_ids.is_some()
+    }
+
+    /// checks the filters on the fields of the extended header
+    pub fn is_filtered_out_by_extended_header(
+        &self,
+        ecu_id: Option<&str>,
+        app_id: &str,
+        context_id: &str,
+        message_type: &dlt::MessageType,
+    ) -> bool {
+        if let Some(excluded) = &self.exclude_app_ids {
+            if excluded.contains(app_id) {
+                return true;
+            }
+        }
+        if let Some(excluded) = &self.exclude_context_ids {
+            if excluded.contains(context_id) {
+                return true;
+            }
+        }
+        if let Some(min_filter_level) = self.min_log_level_for(ecu_id, app_id, context_id) {
+            if message_type.skip_with_level(min_filter_level) {
+                return true;
+            }
+        }
+        if let Some(only_these_types) = &self.message_types {
+            if !only_these_types.iter().any(|t| t.matches(message_type)) {
+                return true;
+            }
+        }
+        if let Some(only_these_components) = &self.app_ids {
+            if !only_these_components.contains(app_id) {
+                return true;
+            }
+        }
+        if let Some(only_these_context_ids) = &self.context_ids {
+            if !only_these_context_ids.contains(context_id) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// checks the filters on the fields of the extended header for a message
+    /// without extended header
+    pub fn is_filtered_out_without_extended_header(&self, ecu_id: Option<&str>) -> bool {
+        match &self.missing_fields {
+            ProcessedMissingFieldPolicy::Include => false,
+            ProcessedMissingFieldPolicy::Exclude => self.filters_extended_header(),
+            ProcessedMissingFieldPolicy::Default {
+                app_id,
+                context_id,
+                message_type,
+            } => self.is_filtered_out_by_extended_header(ecu_id, app_id, context_id, message_type),
+        }
+    }
+}
+
+/// why a `DltFilterConfig` can not be processed
+#[derive(Debug, PartialEq)]
+pub enum FilterConfigError {
+    FilterExpr(FilterExprError),
+    PayloadSearch(PayloadSearchError),
+}
+
+impl fmt::Display for FilterConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FilterConfigError::FilterExpr(e) => write!(f, "{}", e),
+            FilterConfigError::PayloadSearch(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Fail for FilterConfigError {}
+
+impl From<FilterExprError> for FilterConfigError {
+    fn from(err: FilterExprError) -> FilterConfigError {
+        FilterConfigError::FilterExpr(err)
+    }
+}
+
+impl From<PayloadSearchError> for FilterConfigError {
+    fn from(err: PayloadSearchError) -> FilterConfigError {
+        FilterConfigError::PayloadSearch(err)
+    }
+}
+
+pub fn process_filter_config(
+    cfg: DltFilterConfig,
+) -> Result<ProcessedDltFilterConfig, FilterConfigError> {
+    Ok(ProcessedDltFilterConfig {
         min_log_level: cfg.min_log_level.and_then(dlt::u8_to_log_level),
         app_ids: cfg.app_ids.map(HashSet::from_iter),
         ecu_ids: cfg.ecu_ids.map(HashSet::from_iter),
         context_ids: cfg.context_ids.map(HashSet::from_iter),
-    }
+        trace_infos: cfg.trace_infos.map(HashSet::from_iter),
+        session_ids: cfg.session_ids.map(HashSet::from_iter),
+        message_types: cfg.message_types,
+        exclude_app_ids: cfg.exclude_app_ids.map(HashSet::from_iter),
+        exclude_context_ids: cfg.exclude_context_ids.map(HashSet::from_iter),
+        exclude_ecu_ids: cfg.exclude_ecu_ids.map(HashSet::from_iter),
+        level_overrides: cfg
+            .level_overrides
+            .unwrap_or_default()
+            .into_iter()
+            .map(|o| ProcessedLevelOverride {
+                app_id: o.app_id,
+                context_id: o.context_id,
+                ecu_id: o.ecu_id,
+                min_log_level: dlt::u8_to_log_level(o.min_log_level),
+            })
+            .collect(),
+        filter_expr: match cfg.filter_expr {
+            Some(expr) => Some(expr.compile()?),
+            None => None,
+        },
+        time_range: cfg.time_range.map(ProcessedTimeRange::from),
+        missing_fields: match cfg.missing_fields {
+            None | Some(MissingFieldPolicy::Include) => ProcessedMissingFieldPolicy::Include,
+            Some(MissingFieldPolicy::Exclude) => ProcessedMissingFieldPolicy::Exclude,
+            Some(MissingFieldPolicy::Default {
+                app_id,
+                context_id,
+                log_level,
+            }) => ProcessedMissingFieldPolicy::Default {
+                app_id,
+                context_id,
+                message_type: dlt::MessageType::Log(
+                    dlt::u8_to_log_level(log_level).unwrap_or(dlt::LogLevel::Invalid(log_level)),
+                ),
+            },
+        },
+        payload_search: match cfg.payload_search {
+            Some(search) => Some(search.compile()?),
+            None => None,
+        },
+        show_trace_info: cfg.show_trace_info.unwrap_or(false),
+    })
 }
 
 pub fn read_filter_options(f: &mut fs::File) -> Result<DltFilterConfig, failure::Error> {
@@ -19,14 +19,25 @@ extern crate log;
 #[macro_use]
 extern crate failure;
 
+pub mod arxml;
 pub mod dlt;
+pub mod dlt_check;
+pub mod dlt_control;
+pub mod dlt_convert;
+pub mod dlt_counter;
 pub mod dlt_file;
 pub mod dlt_fmt;
 pub mod dlt_net;
 pub mod dlt_parse;
 pub mod dlt_pcap;
+pub mod dlt_reader;
+pub mod dlt_view;
 pub mod fibex;
+pub mod fibex_cache;
+pub mod fibex_check;
+pub mod filter_expression;
 pub mod filtering;
+pub mod payload_search;
 pub mod proptest_strategies;
 pub mod service_id;
 
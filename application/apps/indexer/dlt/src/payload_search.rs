@@ -0,0 +1,158 @@
+// Copyright (c) 2020 E.S.R.Labs. All rights reserved.
+//
+// NOTICE:  All information contained herein is, and remains
+// the property of E.S.R.Labs and its suppliers, if any.
+// The intellectual and technical concepts contained herein are
+// proprietary to E.S.R.Labs and its suppliers and may be covered
+// by German and Foreign Patents, patents in process, and are protected
+// by trade secret or copyright law.
+// Dissemination of this information or reproduction of this material
+// is strictly forbidden unless prior written permission is obtained
+// from E.S.R.Labs.
+
+//! Searching the payload text while indexing
+//!
+//! The text of a payload is the same as in the payload column of the index:
+//! the rendered arguments of verbose messages and the fibex decoded text of
+//! non-verbose messages (see `Message::payload_text`). The indexers format every
+//! message once as an `IndexLine`, messages without a match are filtered out and
+//! the positions of the matches are collected with the line number of the message
+//! in the index.
+use crate::dlt::Message;
+use crate::dlt_fmt::payload_text_of_line;
+use crate::filtering::ProcessedDltFilterConfig;
+use failure::{format_err, Error, Fail};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// what to search for in the payload text
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PayloadSearch {
+    pub pattern: String,
+    /// `pattern` is a regular expression, otherwise it is plain text
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub ignore_case: bool,
+}
+
+impl PayloadSearch {
+    pub fn compile(&self) -> Result<PayloadMatcher, PayloadSearchError> {
+        let pattern = if self.regex {
+            self.pattern.clone()
+        } else {
+            regex::escape(&self.pattern)
+        };
+        match RegexBuilder::new(&pattern)
+            .case_insensitive(self.ignore_case)
+            .build()
+        {
+            Ok(regex) => Ok(PayloadMatcher { regex }),
+            Err(e) => Err(PayloadSearchError::InvalidRegex {
+                pattern: self.pattern.clone(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PayloadSearchError {
+    InvalidRegex { pattern: String, reason: String },
+}
+
+impl fmt::Display for PayloadSearchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PayloadSearchError::InvalidRegex { pattern, reason } => {
+                write!(f, "invalid regular expression {}: {}", pattern, reason)
+            }
+        }
+    }
+}
+
+impl Fail for PayloadSearchError {}
+
+/// a compiled `PayloadSearch`
+#[derive(Clone, Debug)]
+pub struct PayloadMatcher {
+    regex: Regex,
+}
+
+impl PayloadMatcher {
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    /// byte ranges (start inclusive, end exclusive) of all matches in `text`
+    pub fn find(&self, text: &str) -> Vec<(usize, usize)> {
+        self.regex
+            .find_iter(text)
+            .map(|m| (m.start(), m.end()))
+            .collect()
+    }
+}
+
+/// the matches in the payload of one line of the index
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PayloadMatch {
+    pub line: usize,
+    /// byte ranges in the line of the index (the formatted message without
+    /// the tag and the line number)
+    pub positions: Vec<(usize, usize)>,
+}
+
+/// a message formatted for the index
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexLine {
+    pub text: String,
+    /// byte ranges of the matches of the payload search in `text`
+    pub positions: Vec<(usize, usize)>,
+}
+
+impl IndexLine {
+    /// formats `msg` and searches the payload column of the formatted line
+    ///
+    /// the trace info column is added if the filter config asks for it.
+    /// None if the filter has a payload search that does not match
+    pub fn new(
+        filter_config: Option<&ProcessedDltFilterConfig>,
+        msg: &Message,
+    ) -> Option<IndexLine> {
+        let show_trace_info = matches!(filter_config, Some(c) if c.show_trace_info);
+        let text = if show_trace_info {
+            format!("{:#}", msg)
+        } else {
+            msg.to_string()
+        };
+        let positions = match filter_config.and_then(|c| c.payload_search.as_ref()) {
+            Some(matcher) => {
+                let (offset, payload) = payload_text_of_line(&text, show_trace_info);
+                let positions: Vec<(usize, usize)> = matcher
+                    .find(&payload)
+                    .into_iter()
+                    .map(|(start, end)| (start + offset, end + offset))
+                    .collect();
+                if positions.is_empty() {
+                    return None;
+                }
+                positions
+            }
+            None => vec![],
+        };
+        Some(IndexLine { text, positions })
+    }
+}
+
+/// writes the matches as json to `<file>.matches.json` and returns the path
+pub fn write_payload_matches(file: &Path, matches: &[PayloadMatch]) -> Result<PathBuf, Error> {
+    let mut file_name = file.as_os_str().to_owned();
+    file_name.push(".matches.json");
+    let out_path = PathBuf::from(file_name);
+    fs::write(&out_path, serde_json::to_string(matches)?)
+        .map_err(|e| format_err!("could not write {:?}: {}", out_path, e))?;
+    Ok(out_path)
+}
@@ -21,7 +21,11 @@ prop_compose! {
          unit in unit_name_strategy())
             -> (Option<String>, Option<String>) {
         if has_variable_info {
-            if kind == TypeInfoKind::Bool || kind == TypeInfoKind::StringType || kind == TypeInfoKind::Raw {
+            if kind == TypeInfoKind::Bool
+                || kind == TypeInfoKind::StringType
+                || kind == TypeInfoKind::Raw
+                || kind == TypeInfoKind::Struct
+            {
                 (Some(name), None)
             } else {
                 (Some(name), Some(unit))
@@ -33,7 +37,7 @@ prop_compose! {
 }
 prop_compose! {
     pub fn header_strategy(payload_length: u16, endianness: Endianness)(
-        version in 0..8u8,
+        version in (0..8u8).prop_filter("version 2 has a different header layout", |v| *v != DLT_V2_VERSION),
         message_counter in any::<u8>(),
         ecu_id in ecu_id_strategy(),
         session_id in any::<Option<u32>>(),
@@ -49,6 +53,7 @@ prop_compose! {
                 session_id,
                 timestamp,
                 payload_length,
+                v2: None,
         }
     }
 }
@@ -122,12 +127,15 @@ fn value_strategy(info: &TypeInfo) -> impl Strategy<Value = Value> {
         TypeInfoKind::Signed(TypeLength::BitLength128) => {
             any::<i128>().prop_map(Value::I128).boxed()
         }
-        TypeInfoKind::SignedFixedPoint(FloatWidth::Width32) => {
+        TypeInfoKind::SignedFixedPoint(FixedPointWidth::Width32) => {
             any::<i32>().prop_map(Value::I32).boxed()
         }
-        TypeInfoKind::SignedFixedPoint(FloatWidth::Width64) => {
+        TypeInfoKind::SignedFixedPoint(FixedPointWidth::Width64) => {
             any::<i64>().prop_map(Value::I64).boxed()
         }
+        TypeInfoKind::SignedFixedPoint(FixedPointWidth::Width128) => {
+            any::<i128>().prop_map(Value::I128).boxed()
+        }
         // unsigned u8-u64
         TypeInfoKind::Unsigned(TypeLength::BitLength8) => any::<u8>().prop_map(Value::U8).boxed(),
         TypeInfoKind::Unsigned(TypeLength::BitLength16) => {
@@ -142,12 +150,35 @@ fn value_strategy(info: &TypeInfo) -> impl Strategy<Value = Value> {
         TypeInfoKind::Unsigned(TypeLength::BitLength128) => {
             any::<u128>().prop_map(Value::U128).boxed()
         }
-        TypeInfoKind::UnsignedFixedPoint(FloatWidth::Width32) => {
+        TypeInfoKind::UnsignedFixedPoint(FixedPointWidth::Width32) => {
             any::<u32>().prop_map(Value::U32).boxed()
         }
-        TypeInfoKind::UnsignedFixedPoint(FloatWidth::Width64) => {
+        TypeInfoKind::UnsignedFixedPoint(FixedPointWidth::Width64) => {
             any::<u64>().prop_map(Value::U64).boxed()
         }
+        TypeInfoKind::UnsignedFixedPoint(FixedPointWidth::Width128) => {
+            any::<u128>().prop_map(Value::U128).boxed()
+        }
+        // arrays with up to 3 dimensions and up to 4 entries per dimension
+        TypeInfoKind::Array(element_kind) => {
+            let element_info = TypeInfo {
+                kind: element_kind.type_info_kind(),
+                ..info.clone()
+            };
+            prop::collection::vec(0..=4u16, 1..=3)
+                .prop_flat_map(move |dimensions| {
+                    let element_cnt = array_element_count(&dimensions);
+                    (
+                        Just(dimensions),
+                        prop::collection::vec(value_strategy(&element_info), element_cnt),
+                    )
+                })
+                .prop_map(|(dimensions, elements)| Value::Array(dimensions, elements))
+                .boxed()
+        }
+        TypeInfoKind::Struct => prop::collection::vec(argument_strategy(), 0..3)
+            .prop_map(Value::Struct)
+            .boxed(),
     }
 }
 #[allow(dead_code)]
@@ -173,11 +204,11 @@ prop_compose! {
 fn fp_none_strategy() -> impl Strategy<Value = Option<FixedPoint>> {
     Just(None)
 }
-fn fp_strategy(width: FloatWidth) -> impl Strategy<Value = FixedPoint> {
-    let fp_value_strat = if width == FloatWidth::Width32 {
-        any::<i32>().prop_map(FixedPointValue::I32).boxed()
-    } else {
-        any::<i64>().prop_map(FixedPointValue::I64).boxed()
+fn fp_strategy(width: FixedPointWidth) -> impl Strategy<Value = FixedPoint> {
+    let fp_value_strat = match width {
+        FixedPointWidth::Width32 => any::<i32>().prop_map(FixedPointValue::I32).boxed(),
+        FixedPointWidth::Width64 => any::<i64>().prop_map(FixedPointValue::I64).boxed(),
+        FixedPointWidth::Width128 => any::<i128>().prop_map(FixedPointValue::I128).boxed(),
     };
     (any::<f32>(), fp_value_strat).prop_map(|(quantization, offset)| FixedPoint {
         quantization,
@@ -197,6 +228,12 @@ fn type_info_and_fixed_point_strategy() -> impl Strategy<
         let fp_strat = match ti.kind {
             TypeInfoKind::SignedFixedPoint(width) => fp_strategy(width).prop_map(Some).boxed(),
             TypeInfoKind::UnsignedFixedPoint(width) => fp_strategy(width).prop_map(Some).boxed(),
+            TypeInfoKind::Array(ArrayElementKind::SignedFixedPoint(width)) => {
+                fp_strategy(width).prop_map(Some).boxed()
+            }
+            TypeInfoKind::Array(ArrayElementKind::UnsignedFixedPoint(width)) => {
+                fp_strategy(width).prop_map(Some).boxed()
+            }
             _ => fp_none_strategy().boxed(),
         };
         let name_unit_strat = name_and_unit_strategy(ti.has_variable_info, ti.kind.clone());
@@ -207,13 +244,20 @@ fn type_info_and_fixed_point_strategy() -> impl Strategy<
 
 pub fn argument_strategy() -> impl Strategy<Value = Argument> {
     let ti_and_fp_and_val = type_info_and_fixed_point_strategy();
-    ti_and_fp_and_val.prop_map(|(type_info, fixed_point, value, name_and_unit)| Argument {
-        type_info,
-        name: name_and_unit.0,
-        unit: name_and_unit.1,
-        fixed_point,
-        value,
-    })
+    (ti_and_fp_and_val, "[a-zA-Z_:]{0,12}" /*"*/).prop_map(
+        |((type_info, fixed_point, value, name_and_unit), trace)| Argument {
+            trace_info: if type_info.has_trace_info {
+                Some(trace)
+            } else {
+                None
+            },
+            type_info,
+            name: name_and_unit.0,
+            unit: name_and_unit.1,
+            fixed_point,
+            value,
+        },
+    )
     // any::<Argument>()
 }
 pub fn argument_vector_strategy() -> impl Strategy<Value = Vec<Argument>> {
@@ -249,18 +293,19 @@ fn non_verbose_payload_strategy() -> impl Strategy<Value = Payload2> {
             })
     ]
 }
-// strategy to produce signed TypeInfoKinds for only 32 and 64 bit width fixed point or
+// strategy to produce signed TypeInfoKinds for only 32, 64 and 128 bit width fixed point or
 // any other regular signed value
 pub fn signed_strategy() -> impl Strategy<Value = TypeInfoKind> {
     prop_oneof![
-        any::<FloatWidth>().prop_flat_map(|width| Just(TypeInfoKind::SignedFixedPoint(width))),
+        any::<FixedPointWidth>().prop_flat_map(|width| Just(TypeInfoKind::SignedFixedPoint(width))),
         any::<TypeLength>().prop_flat_map(|width| Just(TypeInfoKind::Signed(width)))
     ]
 }
-// strategy to produce unsigned TypeInfoKinds for only 32 and 64 bit width
+// strategy to produce unsigned TypeInfoKinds for only 32, 64 and 128 bit width fixed point
 pub fn unsigned_strategy() -> impl Strategy<Value = TypeInfoKind> {
     prop_oneof![
-        any::<FloatWidth>().prop_flat_map(|width| Just(TypeInfoKind::UnsignedFixedPoint(width))),
+        any::<FixedPointWidth>()
+            .prop_flat_map(|width| Just(TypeInfoKind::UnsignedFixedPoint(width))),
         any::<TypeLength>().prop_flat_map(|width| Just(TypeInfoKind::Unsigned(width)))
     ]
 }
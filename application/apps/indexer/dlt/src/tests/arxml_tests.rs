@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests {
+    use crate::arxml::*;
+    use crate::dlt::*;
+    use crate::fibex::{read_fibexes, ApplicationId, ContextId, FrameId, PhysicalValue};
+    use crate::tests::TestMessage;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+
+    fn test_file(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join(name)
+    }
+
+    #[test]
+    fn test_is_arxml_file() {
+        assert!(is_arxml_file(Path::new("/fibex/ecu.arxml")));
+        assert!(is_arxml_file(Path::new("ECU.ARXML")));
+        assert!(!is_arxml_file(Path::new("/fibex/ecu.xml")));
+        assert!(!is_arxml_file(Path::new("arxml")));
+    }
+
+    #[test]
+    fn test_read_arxml() {
+        let frames = read_arxml(&test_file("dlt-messages.arxml")).expect("can't read arxml");
+        assert_eq!(2, frames.len());
+
+        let (id, frame) = &frames[0];
+        assert_eq!(&FrameId("ID_90".to_string()), id);
+        assert_eq!("VehicleState", frame.short_name);
+        assert_eq!(Some(ApplicationId("VEH".to_string())), frame.application_id);
+        assert_eq!(Some(ContextId("STAT".to_string())), frame.context_id);
+        assert_eq!(Some("DLT_TYPE_LOG".to_string()), frame.message_type);
+        assert_eq!(Some("DLT_LOG_INFO".to_string()), frame.message_info);
+        assert_eq!(4, frame.pdus.len());
+        assert_eq!(Some("state:".to_string()), frame.pdus[0].description);
+        assert!(frame.pdus[0].signal_types.is_empty());
+        assert_eq!(
+            TypeInfoKind::Unsigned(TypeLength::BitLength8),
+            frame.pdus[1].signal_types[0].kind
+        );
+        let gear = frame.pdus[1].signal_conversions[0]
+            .as_ref()
+            .expect("no conversion for Gear");
+        assert_eq!("Gear", gear.signal_name);
+        assert_eq!(
+            Some(PhysicalValue::Text("PARK".to_string())),
+            gear.compu_method.convert(3.0)
+        );
+        let temp = frame.pdus[2].signal_conversions[0]
+            .as_ref()
+            .expect("no conversion for Temp");
+        assert_eq!(Some("°C".to_string()), temp.compu_method.unit);
+        assert_eq!(
+            Some(PhysicalValue::Number(23.5)),
+            temp.compu_method.convert(7350.0)
+        );
+        assert_eq!(vec![None], frame.pdus[3].signal_conversions);
+
+        let (id, frame) = &frames[1];
+        assert_eq!(&FrameId("ID_91".to_string()), id);
+        assert_eq!(None, frame.application_id);
+        assert_eq!(None, frame.context_id);
+        assert_eq!(
+            TypeInfoKind::Signed(TypeLength::BitLength32),
+            frame.pdus[0].signal_types[0].kind
+        );
+    }
+
+    #[test]
+    fn test_mixed_fibex_and_arxml() {
+        let metadata = read_fibexes(vec![
+            test_file("dlt-messages.xml"),
+            test_file("dlt-messages.arxml"),
+        ])
+        .expect("can't read descriptions");
+        assert!(metadata
+            .frame_map
+            .contains_key(&FrameId("ID_64".to_string())));
+        assert!(metadata
+            .frame_map
+            .contains_key(&FrameId("ID_90".to_string())));
+        assert!(metadata.frame_map_with_key.contains_key(&(
+            ContextId("STAT".to_string()),
+            ApplicationId("VEH".to_string()),
+            FrameId("ID_90".to_string())
+        )));
+
+        let mut data = vec![3];
+        data.extend_from_slice(&7350u16.to_be_bytes());
+        data.push(42);
+        let msg = TestMessage::new()
+            .without_extended_header()
+            .payload(PayloadContent::NonVerbose(90, data))
+            .fibex(Arc::new(metadata))
+            .build();
+        let text = msg.payload_text();
+        assert!(text.starts_with("state:"), "{}", text);
+        assert!(text.contains("Gear=PARK"), "{}", text);
+        assert!(text.contains("Temp=23.5 °C"), "{}", text);
+        assert!(text.ends_with(" 42"), "{}", text);
+    }
+}
@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::dlt_check::*;
+    use crate::dlt_parse::DltFraming;
+    use crate::dlt_reader::DltReaderMode;
+    use crate::tests::TestMessage;
+    use crossbeam_channel as cc;
+    use indexer_base::progress::{IndexingProgress, Severity};
+    use pretty_assertions::assert_eq;
+    use std::io::Write;
+    use tempdir::TempDir;
+
+    fn message_bytes(i: usize) -> Vec<u8> {
+        TestMessage::new()
+            .counter(i as u8)
+            .timestamp(Some(i as u32))
+            .text(&format!("message {}", i))
+            .storage_header(DltTimeStamp::from_ms(i as u64 * 1000))
+            .bytes()
+    }
+
+    #[test]
+    fn test_check_damaged_file() {
+        let tmp_dir = TempDir::new("dlt_check").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("damaged.dlt");
+        let mut content: Vec<u8> = vec![];
+        content.extend(message_bytes(0));
+        content.extend(message_bytes(1));
+        let garbage_start = content.len() as u64;
+        content.extend(b"some garbage");
+        let garbage_end = content.len() as u64;
+        let third_offset = content.len() as u64;
+        content.extend(message_bytes(2));
+        let truncated_start = content.len() as u64;
+        let last = message_bytes(3);
+        content.extend(&last[..last.len() / 2]);
+        let mut out = std::fs::File::create(&in_path).expect("could not create file");
+        out.write_all(&content).expect("could not write file");
+        drop(out);
+
+        for reader_mode in &[DltReaderMode::Buffered, DltReaderMode::MemoryMapped] {
+            let (tx, rx) = cc::unbounded();
+            let report =
+                check_dlt_file(&in_path, DltFraming::StorageHeader, *reader_mode, &tx, None)
+                    .expect("could not check file");
+            assert_eq!(content.len() as u64, report.file_size);
+            assert_eq!(3, report.valid_messages);
+            assert_eq!(1, report.messages_lost);
+            assert_eq!(2, report.ranges.len());
+
+            let garbage = &report.ranges[0];
+            assert_eq!((garbage_start, garbage_end), (garbage.start, garbage.end));
+            assert_eq!(CorruptionReason::Garbage, garbage.reason);
+            assert_eq!(0, garbage.messages_lost);
+            assert_eq!(
+                Some(1),
+                garbage.last_valid_message.as_ref().map(|msg| msg.index)
+            );
+
+            let truncated = &report.ranges[1];
+            assert_eq!(
+                (truncated_start, content.len() as u64),
+                (truncated.start, truncated.end)
+            );
+            assert_eq!(CorruptionReason::Truncated, truncated.reason);
+            assert_eq!(1, truncated.messages_lost);
+            let last_valid = truncated
+                .last_valid_message
+                .as_ref()
+                .expect("no valid message before");
+            assert_eq!(2, last_valid.index);
+            assert_eq!(third_offset, last_valid.offset);
+            assert_eq!(2, last_valid.message_counter);
+            assert_eq!(Some(DltTimeStamp::from_ms(2000)), last_valid.timestamp);
+            assert_eq!(
+                report.corrupted_bytes,
+                (garbage_end - garbage_start) + (truncated.end - truncated.start)
+            );
+
+            let results: Vec<CorruptionResults> = rx.try_iter().collect();
+            let warnings: Vec<_> = results
+                .iter()
+                .filter_map(|res| match res {
+                    Err(n) if n.severity == Severity::WARNING => Some(n.line),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(vec![Some(1), Some(2)], warnings);
+            assert!(results.iter().any(|res| match res {
+                Ok(IndexingProgress::GotItem { item }) => item == &report,
+                _ => false,
+            }));
+        }
+    }
+
+    #[test]
+    fn test_garbage_length() {
+        let framing = DltFraming::StorageHeader;
+        let mut content = b"xx".to_vec();
+        content.extend(message_bytes(0));
+        assert_eq!(2, garbage_length(&content, framing, false));
+        // the last bytes could be the start of a header pattern
+        assert_eq!(7, garbage_length(b"garbage DL", framing, false));
+        assert_eq!(10, garbage_length(b"garbage DL", framing, true));
+        // fewer bytes than a header pattern are only dropped at the end of the file
+        assert_eq!(0, garbage_length(b"DL", framing, false));
+        assert_eq!(2, garbage_length(b"DL", framing, true));
+    }
+
+    #[test]
+    fn test_check_valid_file() {
+        let tmp_dir = TempDir::new("dlt_check").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("valid.dlt");
+        let mut out = std::fs::File::create(&in_path).expect("could not create file");
+        for i in 0..10 {
+            out.write_all(&message_bytes(i))
+                .expect("could not write file");
+        }
+        drop(out);
+        let (tx, _rx) = cc::unbounded();
+        let report = check_dlt_file(
+            &in_path,
+            DltFraming::StorageHeader,
+            DltReaderMode::Buffered,
+            &tx,
+            None,
+        )
+        .expect("could not check file");
+        assert!(!report.is_corrupted());
+        assert_eq!(10, report.valid_messages);
+        assert_eq!(0, report.corrupted_bytes);
+    }
+}
@@ -0,0 +1,331 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::dlt_control::*;
+    use crate::dlt_parse::*;
+    use crate::dlt_reader::DltReaderMode;
+    use crate::tests::TestMessage;
+    use crossbeam_channel as cc;
+    use indexer_base::progress::IndexingProgress;
+    use pretty_assertions::assert_eq;
+    use std::io::Write;
+    use tempdir::TempDir;
+
+    fn control_message(
+        control_type: ControlType,
+        endianness: Endianness,
+        payload: &[u8],
+    ) -> Message {
+        TestMessage::new()
+            .endianness(endianness)
+            .counter(1)
+            .timestamp(None)
+            .ids("DA1", "DC1")
+            .message_type(MessageType::Control(control_type))
+            .payload(PayloadContent::ControlMsg(
+                ControlType::from_value(payload[0]),
+                payload[1..].to_vec(),
+            ))
+            .build()
+    }
+
+    fn reparse(msg: &Message) -> Message {
+        match dlt_message(&msg.as_bytes(), None, 0, None, None, DltFraming::None) {
+            Ok((rest, ParsedMessage::Item(parsed))) => {
+                assert!(rest.is_empty());
+                parsed
+            }
+            e => panic!("could not parse control message: {:?}", e),
+        }
+    }
+
+    #[rustfmt::skip]
+    fn get_log_info_response() -> Vec<u8> {
+        vec![
+            0x03, 0x00, 0x00, 0x00, // service id get_log_info
+            0x07, // status: with log level, trace status and descriptions
+            0x01, 0x00, // 1 application
+            b'A', b'P', b'P', b'1',
+            0x02, 0x00, // 2 contexts
+            b'C', b'T', b'X', b'1',
+            0x04, // log level info
+            0x00, // trace status off
+            0x03, 0x00, b'o', b'n', b'e', // description
+            b'C', b'T', b'X', 0x00,
+            0xFF, // default log level
+            0x01, // trace status on
+            0x00, 0x00, // empty description
+            0x04, 0x00, b'a', b'p', b'p', b'1', // application description
+            b'r', b'e', b'm', b'o', // com interface
+        ]
+    }
+
+    #[test]
+    fn test_get_log_info_response() {
+        let msg = reparse(&control_message(
+            ControlType::Response,
+            Endianness::Little,
+            &get_log_info_response(),
+        ));
+        let expected = ControlPayload::GetLogInfoResponse {
+            status: ResponseStatus::LogInfo(7),
+            applications: vec![ApplicationInfo {
+                application_id: "APP1".to_string(),
+                contexts: vec![
+                    ContextInfo {
+                        context_id: "CTX1".to_string(),
+                        log_level: Some(ControlLogLevel(4)),
+                        trace_status: Some(ControlLogLevel(0)),
+                        description: Some("one".to_string()),
+                    },
+                    ContextInfo {
+                        context_id: "CTX".to_string(),
+                        log_level: Some(ControlLogLevel(-1)),
+                        trace_status: Some(ControlLogLevel(1)),
+                        description: Some("".to_string()),
+                    },
+                ],
+                description: Some("app1".to_string()),
+            }],
+            com_interface: Some("remo".to_string()),
+        };
+        let payload = msg
+            .control_payload()
+            .expect("no control message")
+            .expect("could not decode payload");
+        assert_eq!(expected, payload);
+        assert_eq!(Some("get_log_info"), payload.service_name());
+        assert_eq!(
+            "ok APP1 \"app1\" (CTX1 level=INFO trace=off \"one\", CTX level=default trace=on \"\")",
+            payload.to_string()
+        );
+        assert!(msg
+            .to_string()
+            .ends_with("[get_log_info] ok APP1 \"app1\" (CTX1 level=INFO trace=off \"one\", CTX level=default trace=on \"\")"));
+        let json = serde_json::to_value(&payload).expect("could not serialize");
+        assert_eq!(
+            "APP1",
+            json["GetLogInfoResponse"]["applications"][0]["application_id"]
+        );
+        assert_eq!(
+            4,
+            json["GetLogInfoResponse"]["applications"][0]["contexts"][0]["log_level"]
+        );
+    }
+
+    #[test]
+    fn test_get_log_info_response_without_descriptions() {
+        #[rustfmt::skip]
+        let raw = vec![
+            0x03, 0x00, 0x00, 0x00,
+            0x04, // status: with log level
+            0x01, 0x00,
+            b'A', b'P', b'P', b'1',
+            0x01, 0x00,
+            b'C', b'T', b'X', b'1',
+            0x02, // log level error
+        ];
+        let payload = decode_control_payload(&ControlType::Response, Endianness::Little, &raw)
+            .expect("could not decode payload");
+        assert_eq!(
+            ControlPayload::GetLogInfoResponse {
+                status: ResponseStatus::LogInfo(4),
+                applications: vec![ApplicationInfo {
+                    application_id: "APP1".to_string(),
+                    contexts: vec![ContextInfo {
+                        context_id: "CTX1".to_string(),
+                        log_level: Some(ControlLogLevel(2)),
+                        trace_status: None,
+                        description: None,
+                    }],
+                    description: None,
+                }],
+                com_interface: None,
+            },
+            payload
+        );
+    }
+
+    #[test]
+    fn test_truncated_get_log_info_response() {
+        let raw = get_log_info_response();
+        let res = decode_control_payload(
+            &ControlType::Response,
+            Endianness::Little,
+            &raw[..raw.len() - 10],
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_get_software_version_response_big_endian() {
+        #[rustfmt::skip]
+        let raw = vec![
+            0x00, 0x00, 0x00, 0x13, // service id get_software_version
+            0x00, // status ok
+            0x00, 0x00, 0x00, 0x05, // length
+            b'1', b'.', b'2', b'.', b'3',
+        ];
+        let msg = reparse(&control_message(
+            ControlType::Response,
+            Endianness::Big,
+            &raw,
+        ));
+        let payload = msg
+            .control_payload()
+            .expect("no control message")
+            .expect("could not decode payload");
+        assert_eq!(
+            ControlPayload::GetSoftwareVersionResponse {
+                status: ResponseStatus::Ok,
+                version: Some("1.2.3".to_string()),
+            },
+            payload
+        );
+        assert!(msg.to_string().ends_with("[get_software_version] ok 1.2.3"));
+    }
+
+    #[test]
+    fn test_set_log_level_request() {
+        #[rustfmt::skip]
+        let raw = vec![
+            0x01, 0x00, 0x00, 0x00, // service id set_log_level
+            b'A', b'P', b'P', b'1',
+            b'C', b'T', b'X', b'1',
+            0x06, // verbose
+            b'r', b'e', b'm', b'o',
+        ];
+        let msg = reparse(&control_message(
+            ControlType::Request,
+            Endianness::Little,
+            &raw,
+        ));
+        assert!(msg
+            .to_string()
+            .ends_with("[set_log_level] APP1/CTX1 level=VERBOSE interface=remo"));
+    }
+
+    #[test]
+    fn test_status_responses() {
+        let default_level = decode_control_payload(
+            &ControlType::Response,
+            Endianness::Little,
+            &[0x04, 0x00, 0x00, 0x00, 0x00, 0x03],
+        )
+        .expect("could not decode payload");
+        assert_eq!(
+            ControlPayload::GetDefaultLogLevelResponse {
+                status: ResponseStatus::Ok,
+                log_level: Some(ControlLogLevel(3)),
+            },
+            default_level
+        );
+        assert_eq!("ok level=WARN", default_level.to_string());
+
+        let overflow = decode_control_payload(
+            &ControlType::Response,
+            Endianness::Little,
+            &[0x23, 0x00, 0x00, 0x00, 0x00, 0x2A, 0x00, 0x00, 0x00],
+        )
+        .expect("could not decode payload");
+        assert_eq!(
+            ControlPayload::BufferOverflowNotification {
+                status: ResponseStatus::Ok,
+                overflow_counter: Some(42),
+            },
+            overflow
+        );
+        assert_eq!("ok overflows=42", overflow.to_string());
+
+        let not_supported = decode_control_payload(
+            &ControlType::Response,
+            Endianness::Little,
+            &[0x13, 0x00, 0x00, 0x00, 0x01],
+        )
+        .expect("could not decode payload");
+        assert_eq!(
+            ControlPayload::GetSoftwareVersionResponse {
+                status: ResponseStatus::NotSupported,
+                version: None,
+            },
+            not_supported
+        );
+        assert_eq!("not supported", not_supported.to_string());
+    }
+
+    #[test]
+    fn test_ecu_registry_update() {
+        let mut registry = EcuRegistry::new();
+        let mut app = ApplicationInfo {
+            application_id: "APP1".to_string(),
+            contexts: vec![ContextInfo {
+                context_id: "CTX1".to_string(),
+                log_level: Some(ControlLogLevel(4)),
+                trace_status: None,
+                description: Some("Route calculation".to_string()),
+            }],
+            description: Some("Navigation service".to_string()),
+        };
+        registry.add_log_info("ECU1", &[app.clone()]);
+        app.description = Some("".to_string());
+        app.contexts[0].log_level = Some(ControlLogLevel(6));
+        app.contexts[0].description = None;
+        registry.add_log_info("ECU1", &[app]);
+
+        assert_eq!(
+            Some("Navigation service"),
+            registry.application_description("ECU1", "APP1")
+        );
+        assert_eq!(None, registry.application_description("ECU2", "APP1"));
+        assert_eq!(
+            Some("Route calculation"),
+            registry.context_description("ECU1", "APP1", "CTX1")
+        );
+        assert_eq!(
+            "APP1 \u{2013} Navigation service",
+            registry.application_label("ECU1", "APP1")
+        );
+        assert_eq!("APP2", registry.application_label("ECU1", "APP2"));
+        let contexts =
+            &registry.applications("ECU1").expect("ECU1 not registered")["APP1"].contexts;
+        assert_eq!(Some(ControlLogLevel(6)), contexts["CTX1"].log_level);
+    }
+
+    #[test]
+    fn test_ecu_registry_in_statistics() {
+        let tmp_dir = TempDir::new("dlt_ecu_registry").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("log_info.dlt");
+        let msg = control_message(
+            ControlType::Response,
+            Endianness::Little,
+            &get_log_info_response(),
+        )
+        .add_storage_header(Some(DltTimeStamp::from_ms(1000)));
+        let mut out = std::fs::File::create(&in_path).expect("could not create file");
+        out.write_all(&msg.as_bytes())
+            .expect("could not write file");
+        drop(out);
+
+        let (tx, rx) = cc::unbounded();
+        get_dlt_file_info(
+            &in_path,
+            &tx,
+            None,
+            DltReaderMode::Buffered,
+            DltFraming::StorageHeader,
+        )
+        .expect("could not collect statistics");
+        let stats = rx
+            .try_iter()
+            .find_map(|res| match res {
+                Ok(IndexingProgress::GotItem { item }) => Some(item),
+                _ => None,
+            })
+            .expect("no statistics");
+        let json = serde_json::to_value(&stats).expect("could not serialize");
+        let app = &json["ecu_registry"]["ecus"]["ECU1"]["APP1"];
+        assert_eq!("app1", app["description"]);
+        assert_eq!("one", app["contexts"]["CTX1"]["description"]);
+        assert_eq!(4, app["contexts"]["CTX1"]["log_level"]);
+    }
+}
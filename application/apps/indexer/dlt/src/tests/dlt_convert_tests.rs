@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::dlt_convert::*;
+    use crate::dlt_parse::{dlt_message, DltFraming, ParsedMessage};
+    use crate::dlt_reader::DltReaderMode;
+    use crate::fibex::read_fibexes;
+    use crate::tests::TestMessage;
+    use crossbeam_channel as cc;
+    use pretty_assertions::assert_eq;
+    use std::{fs, path::PathBuf, sync::Arc};
+    use tempdir::TempDir;
+
+    fn message(counter: u8) -> TestMessage {
+        TestMessage::new()
+            .counter(counter)
+            .timestamp(Some(u32::from(counter)))
+            .message_type(MessageType::Log(LogLevel::Warn))
+            .storage_header(DltTimeStamp::from_ms(u64::from(counter) * 1000))
+    }
+
+    fn signal_data(gear: u8) -> Vec<u8> {
+        let mut data = vec![gear];
+        data.extend_from_slice(&7350u16.to_be_bytes());
+        data.push(42);
+        data
+    }
+
+    fn parse_all(mut content: &[u8]) -> Vec<Message> {
+        let mut messages = vec![];
+        while !content.is_empty() {
+            let (rest, parsed) =
+                dlt_message(content, None, 0, None, None, DltFraming::StorageHeader)
+                    .expect("could not parse converted message");
+            if let ParsedMessage::Item(message) = parsed {
+                messages.push(message);
+            }
+            content = rest;
+        }
+        messages
+    }
+
+    #[test]
+    fn test_convert_to_verbose() {
+        let fibex = Arc::new(
+            read_fibexes(vec![
+                PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-compu-methods.xml")
+            ])
+            .expect("can't parse fibex"),
+        );
+        let without_extended_header = message(0)
+            .without_extended_header()
+            .payload(PayloadContent::NonVerbose(80, signal_data(3)))
+            .build();
+        let verbose = message(1).text("already verbose").build();
+        let unknown = message(2)
+            .payload(PayloadContent::NonVerbose(4711, vec![1, 2, 3]))
+            .build();
+        let with_extended_header = message(3)
+            .ids("VEH", "STAT")
+            .payload(PayloadContent::NonVerbose(80, signal_data(1)))
+            .build();
+
+        let tmp_dir = TempDir::new("dlt_convert").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("non_verbose.dlt");
+        let out_path = tmp_dir.path().join("verbose.dlt");
+        let mut content = vec![];
+        content.extend(without_extended_header.as_bytes());
+        content.extend(verbose.as_bytes());
+        content.extend(b"garbage");
+        content.extend(unknown.as_bytes());
+        content.extend(with_extended_header.as_bytes());
+        fs::write(&in_path, &content).expect("could not write file");
+
+        let (tx, _rx) = cc::unbounded();
+        let report = convert_to_verbose(
+            &in_path,
+            &out_path,
+            fibex,
+            DltFraming::StorageHeader,
+            DltReaderMode::Buffered,
+            &tx,
+            None,
+        )
+        .expect("could not convert file");
+        assert_eq!(
+            ConversionReport {
+                messages: 4,
+                converted: 2,
+                unresolved: 1,
+                skipped_bytes: 7,
+            },
+            report
+        );
+
+        let converted = fs::read(&out_path).expect("could not read converted file");
+        let messages = parse_all(&converted);
+        assert_eq!(4, messages.len());
+        assert_eq!(verbose, messages[1]);
+        assert_eq!(unknown, messages[2]);
+        assert!(converted
+            .windows(b"garbage".len())
+            .any(|window| window == b"garbage"));
+
+        let first = &messages[0];
+        assert_eq!(without_extended_header.storage_header, first.storage_header);
+        assert_eq!(Some("ECU1".to_string()), first.header.ecu_id);
+        let extended_header = first.extended_header.as_ref().expect("no extended header");
+        assert!(extended_header.verbose);
+        assert_eq!(4, extended_header.argument_count);
+        assert_eq!("VEH", extended_header.application_id);
+        assert_eq!("STAT", extended_header.context_id);
+        let arguments = match &first.payload.payload_content {
+            PayloadContent::Verbose(arguments) => arguments,
+            p => panic!("not verbose: {:?}", p),
+        };
+        let values: Vec<(Option<&str>, &Value)> = arguments
+            .iter()
+            .map(|arg| (arg.name.as_deref(), &arg.value))
+            .collect();
+        assert_eq!(
+            vec![
+                (None, &Value::StringVal("state: ".to_string())),
+                (Some("Gear"), &Value::U8(3)),
+                (Some("Temp"), &Value::U16(7350)),
+                (None, &Value::U8(42)),
+            ],
+            values
+        );
+        assert!(arguments[1].type_info.has_variable_info);
+        assert!(!arguments[3].type_info.has_variable_info);
+
+        // existing extended headers keep their ids and message type
+        let extended_header = messages[3]
+            .extended_header
+            .as_ref()
+            .expect("no extended header");
+        assert!(extended_header.verbose);
+        assert_eq!("VEH", extended_header.application_id);
+        assert_eq!(
+            MessageType::Log(LogLevel::Warn),
+            extended_header.message_type
+        );
+    }
+}
@@ -0,0 +1,253 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::dlt_counter::*;
+    use crate::dlt_file::*;
+    use crate::dlt_parse::{get_dlt_file_info, DltFraming};
+    use crate::dlt_reader::DltReaderMode;
+    use crate::tests::{argument, TestMessage};
+    use crossbeam_channel as cc;
+    use indexer_base::chunks::ChunkResults;
+    use indexer_base::config::IndexingConfig;
+    use indexer_base::progress::{IndexingProgress, Notification};
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_counter_gaps() {
+        let mut tracker = MessageCounterTracker::new();
+        let mut index = 0;
+        let mut track = |tracker: &mut MessageCounterTracker, session: Option<u32>, counter| {
+            index += 1;
+            tracker.track(Some("ECU1"), session, counter, index - 1)
+        };
+        for counter in &[253, 254, 255, 0, 1, 1] {
+            assert_eq!(None, track(&mut tracker, Some(7), *counter));
+        }
+        assert_eq!(
+            Some(CounterGap {
+                index: 6,
+                expected: 2,
+                received: 5,
+                lost: 3,
+            }),
+            track(&mut tracker, Some(7), 5)
+        );
+        // other sessions and messages without session id are independent
+        assert_eq!(None, track(&mut tracker, Some(8), 100));
+        assert_eq!(None, track(&mut tracker, None, 200));
+        // the gap wraps around
+        assert_eq!(
+            Some(CounterGap {
+                index: 9,
+                expected: 6,
+                received: 1,
+                lost: 251,
+            }),
+            track(&mut tracker, Some(7), 1)
+        );
+        let session = tracker
+            .session("ECU1", Some(7))
+            .expect("session not tracked");
+        assert_eq!(8, session.messages);
+        assert_eq!(254, session.lost_messages);
+        assert_eq!(2, session.gaps.len());
+        assert_eq!(254, tracker.lost_messages());
+        assert!(tracker.session("ECU1", Some(8)).is_some());
+        assert!(tracker.session("ECU1", None).is_some());
+    }
+
+    #[test]
+    fn test_counter_gap_limits() {
+        let gap_after = |lost: usize| {
+            let mut tracker = MessageCounterTracker::new();
+            tracker.track(Some("ECU1"), None, 10, 0);
+            tracker
+                .track(Some("ECU1"), None, (11 + lost % 256) as u8, 1)
+                .map(|gap| gap.lost)
+        };
+        assert_eq!(Some(1), gap_after(1));
+        assert_eq!(Some(254), gap_after(254));
+        // 255 lost messages give the same counter again, as if it was not incremented
+        assert_eq!(None, gap_after(255));
+        assert_eq!(None, gap_after(256));
+        assert_eq!(Some(1), gap_after(257));
+    }
+
+    #[test]
+    fn test_counter_gaps_without_session_id() {
+        let mut tracker = MessageCounterTracker::new();
+        assert_eq!(None, tracker.track(Some("ECU1"), None, 10, 0));
+        assert_eq!(None, tracker.track(Some("ECU1"), None, 11, 1));
+        // other ECUs are independent
+        assert_eq!(None, tracker.track(Some("ECU2"), None, 50, 2));
+        assert_eq!(None, tracker.track(None, None, 90, 3));
+        assert_eq!(
+            Some(CounterGap {
+                index: 4,
+                expected: 12,
+                received: 15,
+                lost: 3,
+            }),
+            tracker.track(Some("ECU1"), None, 15, 4)
+        );
+        let session = tracker.session("ECU1", None).expect("messages not tracked");
+        assert_eq!(3, session.messages);
+        assert_eq!(3, session.lost_messages);
+        assert_eq!(1, tracker.session("ECU2", None).map_or(0, |s| s.messages));
+        assert_eq!(1, tracker.session("NONE", None).map_or(0, |s| s.messages));
+
+        let json = serde_json::to_value(&tracker).expect("could not serialize");
+        assert_eq!(3, json["ecus"]["ECU1"]["NONE"]["lost_messages"]);
+    }
+
+    #[test]
+    fn test_append_counters() {
+        let mut first = MessageCounterTracker::new();
+        first.track(Some("ECU1"), Some(1), 10, 0);
+        first.track(Some("ECU1"), Some(1), 11, 1);
+        let mut second = MessageCounterTracker::new();
+        second.track(Some("ECU1"), Some(1), 14, 0);
+        second.track(Some("ECU1"), Some(1), 16, 1);
+        second.track(Some("ECU1"), Some(2), 0, 2);
+
+        let notifications = first.append(second, 2);
+        assert_eq!(1, notifications.len());
+        assert_eq!(Some(2), notifications[0].line);
+        let session = first.session("ECU1", Some(1)).expect("session not tracked");
+        assert_eq!(4, session.messages);
+        assert_eq!(3, session.lost_messages);
+        assert_eq!(
+            vec![(2, 2), (3, 1)],
+            session
+                .gaps
+                .iter()
+                .map(|gap| (gap.index, gap.lost))
+                .collect::<Vec<(usize, usize)>>()
+        );
+        assert!(first.session("ECU1", Some(2)).is_some());
+    }
+
+    /// 2 interleaved sessions, 3 messages of session 1 are missing after message 100
+    /// and 1 message of session 2 after message 401
+    fn create_dlt_file(path: &PathBuf, count: usize) {
+        let mut bytes: Vec<u8> = vec![];
+        for i in 0..count {
+            let session_id = (i % 2) as u32 + 1;
+            let mut counter = i / 2;
+            if session_id == 1 && i > 100 {
+                counter += 3;
+            }
+            if session_id == 2 && i > 401 {
+                counter += 1;
+            }
+            bytes.extend(
+                TestMessage::new()
+                    .counter((counter % 256) as u8)
+                    .session_id(Some(session_id))
+                    .timestamp(Some(i as u32))
+                    .arguments(vec![argument(
+                        TypeInfoKind::Unsigned(TypeLength::BitLength32),
+                        Value::U32(i as u32),
+                    )])
+                    .storage_header(DltTimeStamp::from_ms(i as u64))
+                    .bytes(),
+            );
+        }
+        std::fs::write(path, bytes).expect("could not write dlt file");
+    }
+
+    fn index_warnings(in_path: &PathBuf, out_path: &PathBuf, workers: usize) -> Vec<String> {
+        let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        let config = IndexingConfig {
+            tag: "TAG",
+            chunk_size: 100,
+            in_file: in_path.clone(),
+            out_path,
+            append: false,
+        };
+        let source_file_size = std::fs::metadata(in_path).unwrap().len() as usize;
+        let res = if workers > 1 {
+            index_dlt_file_parallel(
+                config,
+                source_file_size,
+                None,
+                &tx,
+                None,
+                None,
+                DltReaderMode::Buffered,
+                DltFraming::StorageHeader,
+                workers,
+                1000,
+            )
+        } else {
+            create_index_and_mapping_dlt(
+                config,
+                source_file_size,
+                None,
+                &tx,
+                None,
+                None,
+                DltReaderMode::Buffered,
+                DltFraming::StorageHeader,
+            )
+        };
+        assert!(res.is_ok());
+        rx.try_iter()
+            .filter_map(|msg| match msg {
+                Err(Notification { content, line, .. }) => Some(format!("{:?} {}", line, content)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_counter_gaps_while_indexing() {
+        let tmp_dir = TempDir::new("dlt_counter").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("input.dlt");
+        create_dlt_file(&in_path, 600);
+
+        let sequential = index_warnings(&in_path, &tmp_dir.path().join("sequential.out"), 1);
+        assert_eq!(
+            vec![
+                "Some(102) ECU ECU1 session 1: 3 message(s) lost (message counter 54, expected 51)",
+                "Some(403) ECU ECU1 session 2: 1 message(s) lost (message counter 202, expected 201)",
+            ],
+            sequential
+        );
+        let parallel = index_warnings(&in_path, &tmp_dir.path().join("parallel.out"), 3);
+        assert_eq!(sequential, parallel);
+    }
+
+    #[test]
+    fn test_counter_gaps_in_statistics() {
+        let tmp_dir = TempDir::new("dlt_counter").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("input.dlt");
+        create_dlt_file(&in_path, 600);
+
+        let (tx, rx) = cc::unbounded();
+        get_dlt_file_info(
+            &in_path,
+            &tx,
+            None,
+            DltReaderMode::Buffered,
+            DltFraming::StorageHeader,
+        )
+        .expect("could not collect statistics");
+        let stats = rx
+            .try_iter()
+            .find_map(|res| match res {
+                Ok(IndexingProgress::GotItem { item }) => Some(item),
+                _ => None,
+            })
+            .expect("no statistics");
+        let json = serde_json::to_value(&stats).expect("could not serialize");
+        let sessions = &json["message_counters"]["ecus"]["ECU1"];
+        assert_eq!(300, sessions["1"]["messages"]);
+        assert_eq!(3, sessions["1"]["lost_messages"]);
+        assert_eq!(102, sessions["1"]["gaps"][0]["index"]);
+        assert_eq!(1, sessions["2"]["lost_messages"]);
+        assert_eq!(403, sessions["2"]["gaps"][0]["index"]);
+    }
+}
@@ -1,11 +1,18 @@
 #[cfg(test)]
 mod tests {
 
+    use crate::dlt::*;
+    use crate::dlt_check::check_dlt_file;
     use crate::dlt_file::*;
+    use crate::dlt_parse::{get_dlt_file_info, DltFraming, DLT_SERIAL_PATTERN};
+    use crate::dlt_reader::DltReaderMode;
+    use crate::tests::TestMessage;
     use crossbeam_channel as cc;
     use indexer_base::chunks::ChunkResults;
     use indexer_base::config::IndexingConfig;
+    use indexer_base::progress::{IndexingProgress, IndexingResults, Notification, Severity};
     use std::path::PathBuf;
+    use tempdir::TempDir;
     #[test]
     fn test_storage_header_illegeal() {
         let in_path = PathBuf::from("..")
@@ -32,6 +39,537 @@ mod tests {
             &tx,
             None,
             None,
+            DltReaderMode::Buffered,
+            DltFraming::StorageHeader,
         );
     }
+
+    /// the warnings for the corrupted ranges of lukas_crash.dlt as `check_dlt_file` sends them
+    fn lukas_crash_corruption_warnings(in_path: &PathBuf) -> Vec<(String, Option<usize>)> {
+        let (tx, _rx) = cc::unbounded();
+        let report = check_dlt_file(
+            in_path,
+            DltFraming::StorageHeader,
+            DltReaderMode::Buffered,
+            &tx,
+            None,
+        )
+        .expect("could not check file");
+        assert!(report.is_corrupted());
+        report
+            .ranges
+            .iter()
+            .map(|range| {
+                (
+                    range.to_string(),
+                    range.last_valid_message.as_ref().map(|msg| msg.index),
+                )
+            })
+            .collect()
+    }
+
+    fn corruption_warnings<T>(
+        rx: &cc::Receiver<IndexingResults<T>>,
+    ) -> Vec<(String, Option<usize>)> {
+        rx.try_iter()
+            .filter_map(|res| match res {
+                Err(Notification {
+                    severity: Severity::WARNING,
+                    content,
+                    line,
+                }) if content.starts_with("skipped bytes") => Some((content, line)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_corrupted_ranges_while_indexing() {
+        let in_path = PathBuf::from("..")
+            .join("dlt/test_samples")
+            .join("lukas_crash.dlt");
+        let tmp_dir = TempDir::new("dlt_corruption").expect("could not create temp dir");
+        let out_path = tmp_dir.path().join("lukas_crash.dlt.out");
+        let source_file_size = std::fs::metadata(&in_path).unwrap().len() as usize;
+        let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        create_index_and_mapping_dlt(
+            IndexingConfig {
+                tag: "TAG",
+                chunk_size: 500,
+                in_file: in_path.clone(),
+                out_path: &out_path,
+                append: false,
+            },
+            source_file_size,
+            None,
+            &tx,
+            None,
+            None,
+            DltReaderMode::Buffered,
+            DltFraming::StorageHeader,
+        )
+        .expect("indexing failed");
+        assert_eq!(
+            lukas_crash_corruption_warnings(&in_path),
+            corruption_warnings(&rx)
+        );
+    }
+
+    #[test]
+    fn test_corrupted_ranges_in_statistics() {
+        let in_path = PathBuf::from("..")
+            .join("dlt/test_samples")
+            .join("lukas_crash.dlt");
+        let (tx, rx) = cc::unbounded();
+        get_dlt_file_info(
+            &in_path,
+            &tx,
+            None,
+            DltReaderMode::Buffered,
+            DltFraming::StorageHeader,
+        )
+        .expect("could not collect statistics");
+        assert_eq!(
+            lukas_crash_corruption_warnings(&in_path),
+            corruption_warnings(&rx)
+        );
+    }
+
+    /// messages with storage (or serial) headers, some of them contain the header
+    /// pattern in their payload and some are followed by garbage
+    fn create_dlt_file(path: &PathBuf, count: usize, framing: DltFraming) {
+        let mut bytes: Vec<u8> = vec![];
+        for i in 0..count {
+            let text = if i % 7 == 0 {
+                match framing {
+                    DltFraming::SerialHeader => format!("message {} with DLS\x01 in it", i),
+                    _ => format!("message {} with DLT\x01 in it", i),
+                }
+            } else {
+                format!("message {}", i)
+            };
+            let message = TestMessage::new()
+                .counter((i % 256) as u8)
+                .timestamp(Some(i as u32))
+                .text(&text);
+            let message = match framing {
+                DltFraming::StorageHeader => {
+                    message.storage_header(DltTimeStamp::from_ms(i as u64))
+                }
+                _ => message,
+            };
+            if framing == DltFraming::SerialHeader {
+                bytes.extend(DLT_SERIAL_PATTERN);
+            }
+            bytes.extend(message.bytes());
+            if i % 11 == 0 {
+                bytes.extend(b"garbage");
+            }
+        }
+        std::fs::write(path, bytes).expect("could not write dlt file");
+    }
+
+    /// index the file and return the output, the chunks and the notifications
+    fn index_dlt_file(
+        in_path: &PathBuf,
+        out_path: &PathBuf,
+        workers: usize,
+        reader_mode: DltReaderMode,
+        framing: DltFraming,
+    ) -> (Vec<u8>, Vec<String>, Vec<String>) {
+        let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        let config = IndexingConfig {
+            tag: "TAG",
+            chunk_size: 7,
+            in_file: in_path.clone(),
+            out_path,
+            append: false,
+        };
+        let source_file_size = std::fs::metadata(in_path).unwrap().len() as usize;
+        let res = if workers > 1 {
+            index_dlt_file_parallel(
+                config,
+                source_file_size,
+                None,
+                &tx,
+                None,
+                None,
+                reader_mode,
+                framing,
+                workers,
+                1000,
+            )
+        } else {
+            create_index_and_mapping_dlt(
+                config,
+                source_file_size,
+                None,
+                &tx,
+                None,
+                None,
+                reader_mode,
+                framing,
+            )
+        };
+        assert!(res.is_ok());
+        let mut chunks = vec![];
+        let mut notifications = vec![];
+        for msg in rx.try_iter() {
+            match msg {
+                Ok(IndexingProgress::GotItem { item }) => chunks.push(format!("{:?}", item)),
+                Err(Notification {
+                    severity,
+                    content,
+                    line,
+                }) => notifications.push(format!("{:?} {} {:?}", severity, content, line)),
+                _ => (),
+            }
+        }
+        (std::fs::read(out_path).unwrap(), chunks, notifications)
+    }
+
+    #[test]
+    fn test_parallel_index_same_as_sequential() {
+        let tmp_dir = TempDir::new("dlt_parallel").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("input.dlt");
+        create_dlt_file(&in_path, 500, DltFraming::StorageHeader);
+
+        let (sequential_out, sequential_chunks, sequential_notifications) = index_dlt_file(
+            &in_path,
+            &tmp_dir.path().join("sequential.out"),
+            1,
+            DltReaderMode::Buffered,
+            DltFraming::StorageHeader,
+        );
+        let (parallel_out, parallel_chunks, parallel_notifications) = index_dlt_file(
+            &in_path,
+            &tmp_dir.path().join("parallel.out"),
+            3,
+            DltReaderMode::Buffered,
+            DltFraming::StorageHeader,
+        );
+        assert_eq!(500, sequential_out.iter().filter(|b| **b == b'\n').count());
+        assert_eq!(72, sequential_chunks.len());
+        assert!(!sequential_notifications.is_empty());
+        assert_eq!(sequential_out, parallel_out);
+        assert_eq!(sequential_chunks, parallel_chunks);
+        assert_eq!(sequential_notifications, parallel_notifications);
+    }
+
+    #[test]
+    fn test_memory_mapped_index_same_as_buffered() {
+        let tmp_dir = TempDir::new("dlt_mmap").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("input.dlt");
+        create_dlt_file(&in_path, 300, DltFraming::StorageHeader);
+
+        let buffered = index_dlt_file(
+            &in_path,
+            &tmp_dir.path().join("buffered.out"),
+            1,
+            DltReaderMode::Buffered,
+            DltFraming::StorageHeader,
+        );
+        let mapped = index_dlt_file(
+            &in_path,
+            &tmp_dir.path().join("mapped.out"),
+            1,
+            DltReaderMode::MemoryMapped,
+            DltFraming::StorageHeader,
+        );
+        let mapped_parallel = index_dlt_file(
+            &in_path,
+            &tmp_dir.path().join("mapped_parallel.out"),
+            2,
+            DltReaderMode::MemoryMapped,
+            DltFraming::StorageHeader,
+        );
+        assert_eq!(buffered, mapped);
+        assert_eq!(buffered, mapped_parallel);
+    }
+
+    #[test]
+    fn test_memory_mapped_export() {
+        use indexer_base::config::{IndexSection, SectionConfig};
+        let tmp_dir = TempDir::new("dlt_mmap_export").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("input.dlt");
+        create_dlt_file(&in_path, 50, DltFraming::StorageHeader);
+        let export = |mode: DltReaderMode, name: &str| {
+            let out_path = tmp_dir.path().join(name);
+            let (tx, _rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+            export_as_dlt_file(
+                in_path.clone(),
+                out_path.clone(),
+                SectionConfig {
+                    sections: vec![
+                        IndexSection {
+                            first_line: 2,
+                            last_line: 5,
+                        },
+                        IndexSection {
+                            first_line: 20,
+                            last_line: 20,
+                        },
+                    ],
+                },
+                tx,
+                mode,
+                DltFraming::StorageHeader,
+            )
+            .expect("export failed");
+            std::fs::read(out_path).unwrap()
+        };
+        let buffered = export(DltReaderMode::Buffered, "buffered.dlt");
+        let mapped = export(DltReaderMode::MemoryMapped, "mapped.dlt");
+        assert!(!buffered.is_empty());
+        assert_eq!(buffered, mapped);
+    }
+
+    #[test]
+    fn test_serial_header_framing() {
+        use indexer_base::config::{IndexSection, SectionConfig};
+        let tmp_dir = TempDir::new("dlt_serial").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("serial.dlt");
+        create_dlt_file(&in_path, 100, DltFraming::SerialHeader);
+
+        let (sequential_out, _, notifications) = index_dlt_file(
+            &in_path,
+            &tmp_dir.path().join("sequential.out"),
+            1,
+            DltReaderMode::Buffered,
+            DltFraming::SerialHeader,
+        );
+        let (parallel_out, _, _) = index_dlt_file(
+            &in_path,
+            &tmp_dir.path().join("parallel.out"),
+            2,
+            DltReaderMode::MemoryMapped,
+            DltFraming::SerialHeader,
+        );
+        assert_eq!(100, sequential_out.iter().filter(|b| **b == b'\n').count());
+        assert!(String::from_utf8_lossy(&sequential_out).contains("message 99"));
+        // resynced after the 7 bytes of garbage
+        assert!(notifications
+            .iter()
+            .any(|n| n.contains("skipped bytes 58..65 (garbage)")));
+        assert_eq!(sequential_out, parallel_out);
+
+        let (tx, rx) = cc::unbounded();
+        get_dlt_file_info(
+            &in_path,
+            &tx,
+            None,
+            DltReaderMode::Buffered,
+            DltFraming::SerialHeader,
+        )
+        .expect("could not collect statistics");
+        let stats = rx
+            .try_iter()
+            .find_map(|res| match res {
+                Ok(IndexingProgress::GotItem { item }) => Some(item),
+                _ => None,
+            })
+            .expect("no statistics");
+        let json = serde_json::to_value(&stats).expect("could not serialize");
+        assert_eq!("ECU1", json["ecu_ids"][0][0]);
+        assert_eq!(100, json["ecu_ids"][0][1]["log_info"]);
+
+        let out_path = tmp_dir.path().join("export.dlt");
+        let (tx, _rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+        export_as_dlt_file(
+            in_path.clone(),
+            out_path.clone(),
+            SectionConfig {
+                sections: vec![IndexSection {
+                    first_line: 2,
+                    last_line: 5,
+                }],
+            },
+            tx,
+            DltReaderMode::Buffered,
+            DltFraming::SerialHeader,
+        )
+        .expect("export failed");
+        let exported = std::fs::read(out_path).unwrap();
+        let (exported_out, _, _) = {
+            let export_path = tmp_dir.path().join("export.dlt");
+            index_dlt_file(
+                &export_path,
+                &tmp_dir.path().join("export.out"),
+                1,
+                DltReaderMode::Buffered,
+                DltFraming::SerialHeader,
+            )
+        };
+        assert!(exported.starts_with(DLT_SERIAL_PATTERN));
+        let exported_text = String::from_utf8_lossy(&exported_out);
+        assert_eq!(4, exported_text.lines().count());
+        assert!(exported_text.contains("message 2"));
+        assert!(exported_text.contains("message 5"));
+    }
+
+    #[test]
+    fn test_index_time_range() {
+        use crate::filtering::*;
+        let tmp_dir = TempDir::new("dlt_time_range").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("input.dlt");
+        create_dlt_file(&in_path, 20_000, DltFraming::StorageHeader);
+        let dlt_filter = || DltFilterConfig {
+            time_range: Some(TimeRange {
+                clock: TimeClock::StorageHeader,
+                start: Some(15_000),
+                end: Some(15_100),
+            }),
+            ..Default::default()
+        };
+        let filter_config = process_filter_config(dlt_filter()).expect("invalid filter config");
+        let start_offset = time_range_start_offset(
+            &in_path,
+            Some(&filter_config),
+            DltFraming::StorageHeader,
+            DltReaderMode::Buffered,
+        )
+        .expect("could not search time range");
+        assert!(start_offset > 0);
+        let end_offset = time_range_start_offset(
+            &in_path,
+            process_filter_config(DltFilterConfig {
+                time_range: Some(TimeRange {
+                    clock: TimeClock::StorageHeader,
+                    start: Some(u64::MAX),
+                    end: None,
+                }),
+                ..Default::default()
+            })
+            .as_ref()
+            .ok(),
+            DltFraming::StorageHeader,
+            DltReaderMode::Buffered,
+        )
+        .expect("could not search time range");
+        assert!(end_offset > start_offset);
+
+        let mut outputs = vec![];
+        for workers in &[1, 3] {
+            let out_path = tmp_dir.path().join(format!("{}.out", workers));
+            let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+            let config = IndexingConfig {
+                tag: "TAG",
+                chunk_size: 7,
+                in_file: in_path.clone(),
+                out_path: &out_path,
+                append: false,
+            };
+            let source_file_size = std::fs::metadata(&in_path).unwrap().len() as usize;
+            let res = if *workers > 1 {
+                index_dlt_file_parallel(
+                    config,
+                    source_file_size,
+                    Some(filter_config.clone()),
+                    &tx,
+                    None,
+                    None,
+                    DltReaderMode::Buffered,
+                    DltFraming::StorageHeader,
+                    *workers,
+                    10_000,
+                )
+            } else {
+                create_index_and_mapping_dlt(
+                    config,
+                    source_file_size,
+                    Some(dlt_filter()),
+                    &tx,
+                    None,
+                    None,
+                    DltReaderMode::Buffered,
+                    DltFraming::StorageHeader,
+                )
+            };
+            assert!(res.is_ok());
+            // the skipped part counts as progress
+            let first_progress = rx.try_iter().find_map(|msg| match msg {
+                Ok(IndexingProgress::Progress { ticks }) => Some(ticks.0),
+                _ => None,
+            });
+            assert!(first_progress.unwrap_or(0) >= start_offset as usize);
+            let out = String::from_utf8(std::fs::read(&out_path).unwrap()).unwrap();
+            assert_eq!(100, out.lines().count());
+            assert!(out.lines().next().unwrap().contains("message 15000"));
+            assert!(out.lines().last().unwrap().contains("message 15099"));
+            outputs.push(out);
+        }
+        assert_eq!(outputs[0], outputs[1]);
+    }
+
+    #[test]
+    fn test_index_payload_search() {
+        use crate::filtering::*;
+        use crate::payload_search::*;
+        let tmp_dir = TempDir::new("dlt_payload_search").expect("could not create temp dir");
+        let in_path = tmp_dir.path().join("input.dlt");
+        create_dlt_file(&in_path, 2_000, DltFraming::StorageHeader);
+        let dlt_filter = || DltFilterConfig {
+            payload_search: Some(PayloadSearch {
+                pattern: r"^MESSAGE 12\d*".to_string(),
+                regex: true,
+                ignore_case: true,
+            }),
+            ..Default::default()
+        };
+        let source_file_size = std::fs::metadata(&in_path).unwrap().len() as usize;
+        let mut results = vec![];
+        for workers in &[1, 3] {
+            let out_path = tmp_dir.path().join(format!("{}.out", workers));
+            let (tx, _rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = cc::unbounded();
+            let config = IndexingConfig {
+                tag: "TAG",
+                chunk_size: 7,
+                in_file: in_path.clone(),
+                out_path: &out_path,
+                append: false,
+            };
+            let matches = if *workers > 1 {
+                index_dlt_file_parallel(
+                    config,
+                    source_file_size,
+                    Some(process_filter_config(dlt_filter()).expect("invalid filter config")),
+                    &tx,
+                    None,
+                    None,
+                    DltReaderMode::Buffered,
+                    DltFraming::StorageHeader,
+                    *workers,
+                    10_000,
+                )
+            } else {
+                create_index_and_mapping_dlt(
+                    config,
+                    source_file_size,
+                    Some(dlt_filter()),
+                    &tx,
+                    None,
+                    None,
+                    DltReaderMode::Buffered,
+                    DltFraming::StorageHeader,
+                )
+            }
+            .expect("indexing failed");
+            let out = String::from_utf8(std::fs::read(&out_path).unwrap()).unwrap();
+            // message 12, 120..=129 and 1200..=1299
+            assert_eq!(111, out.lines().count());
+            assert_eq!(111, matches.len());
+            // the positions are byte ranges in the lines of the index
+            let matched_text = |m: &PayloadMatch| {
+                assert_eq!(1, m.positions.len());
+                let (start, end) = m.positions[0];
+                out.lines().nth(m.line).unwrap()[start..end].to_string()
+            };
+            assert_eq!(0, matches[0].line);
+            assert_eq!("message 12", matched_text(&matches[0]));
+            assert_eq!(110, matches[110].line);
+            assert_eq!("message 1299", matched_text(&matches[110]));
+            results.push(matches);
+        }
+        assert_eq!(results[0], results[1]);
+    }
 }
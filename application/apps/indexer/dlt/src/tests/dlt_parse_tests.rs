@@ -4,7 +4,9 @@ mod tests {
     use crate::dlt_parse::forward_to_next_storage_header;
     use crate::dlt_parse::DLT_PATTERN;
     use crate::dlt_parse::*;
+    use crate::filtering;
     use crate::proptest_strategies::*;
+    use crate::tests::{argument, TestMessage};
     use dirs;
     use indexer_base::chunks::Chunk;
     use nom::IResult;
@@ -77,7 +79,7 @@ mod tests {
             0x74, 0x68, 0x20, 0x00, 0x44, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00,
         ];
-        match dlt_message(&raw1[..], None, 0, None, None, true) {
+        match dlt_message(&raw1[..], None, 0, None, None, DltFraming::StorageHeader) {
             Ok((_rest, ParsedMessage::Item(msg))) => {
                 let msg_bytes = msg.as_bytes();
                 assert_eq!(raw1, msg_bytes);
@@ -115,7 +117,7 @@ mod tests {
             /* type info 0b0001 0000 => type bool */ 0x10, 0x00, 0x00, 0x00,
              0x6F,
         ];
-        match dlt_message(&raw1[..], None, 0, None, None, true) {
+        match dlt_message(&raw1[..], None, 0, None, None, DltFraming::StorageHeader) {
             Ok((_rest, ParsedMessage::Item(msg))) => {
                 let msg_bytes = msg.as_bytes();
                 assert_eq!(raw1, msg_bytes);
@@ -224,7 +226,7 @@ mod tests {
             0x65, 0x72, 0x3A, 0x3A, 0x70, 0x6F, 0x6C, 0x6C, 0x5D, 0x20, 0x72,
         ];
         raw1.extend_from_slice(&raw2);
-        let res1 = dlt_message(&raw1[..], None, 0, None, None, true);
+        let res1 = dlt_message(&raw1[..], None, 0, None, None, DltFraming::StorageHeader);
         trace!("res1 was: {:?}", res1);
         // let res2: IResult<&[u8], Option<Message>> = dlt_message(&raw2[..], None, 0, 0);
         // trace!("res was: {:?}", res2);
@@ -267,7 +269,8 @@ mod tests {
             let mut header_bytes = header_to_expect.as_bytes();
             trace!("header bytes: {:02X?}", header_bytes);
             header_bytes.extend(b"----");
-            let res: IResult<&[u8], Option<StorageHeader>> = dlt_storage_header::<Chunk>(&header_bytes, None, None);
+            let res: IResult<&[u8], Option<StorageHeader>> = dlt_storage_header::<Chunk>(&header_bytes, None, None)
+                .map(|(rest, h)| (rest, h.map(|h| h.to_storage_header())));
             if let Ok((_, Some(v))) = res.clone() {
                 trace!("parsed header: {}", v)
             }
@@ -280,7 +283,8 @@ mod tests {
             init_logging();
             let mut header_bytes = header_to_expect.as_bytes();
             header_bytes.extend(b"----");
-            let res: IResult<&[u8], StandardHeader> = dlt_standard_header(&header_bytes);
+            let res: IResult<&[u8], StandardHeader> = dlt_standard_header(&header_bytes)
+                .map(|(rest, h)| (rest, h.to_standard_header()));
             let expected: IResult<&[u8], StandardHeader> = Ok((b"----", header_to_expect));
             assert_eq!(expected, res);
         }
@@ -288,7 +292,8 @@ mod tests {
         fn test_extended_header(header_to_expect: ExtendedHeader) {
             let mut header_bytes = header_to_expect.as_bytes();
             header_bytes.extend(b"----");
-            let res: IResult<&[u8], ExtendedHeader> = dlt_extended_header::<Chunk>(&header_bytes, None, None);
+            let res: IResult<&[u8], ExtendedHeader> = dlt_extended_header::<Chunk>(&header_bytes, None, None)
+                .map(|(rest, h)| (rest, h.to_extended_header()));
             let expected: IResult<&[u8], ExtendedHeader> = Ok((b"----", header_to_expect));
             assert_eq!(expected, res);
         }
@@ -336,7 +341,21 @@ mod tests {
             // dump_to_file(&msg_bytes)?;
             let expected: Result<(&[u8], ParsedMessage), DltParseError>  =
                 Ok((b"----", ParsedMessage::Item(msg)));
-            assert_eq!(expected, dlt_message(&msg_bytes, None, 0, None, None, false));
+            assert_eq!(expected, dlt_message(&msg_bytes, None, 0, None, None, DltFraming::None));
+        }
+        #[test]
+        fn test_message_view_matches_message(msg in message_strat()) {
+            let mut msg_bytes = msg.as_bytes();
+            msg_bytes.extend(b"----");
+            let (rest, view) = dlt_message_view::<Chunk>(&msg_bytes, None, None, DltFraming::None)
+                .expect("could not parse view");
+            let view = view.expect("message length should be valid");
+            assert_eq!(b"----", rest);
+            if let PayloadContent::Verbose(expected_args) = &msg.payload.payload_content {
+                let args: Result<Vec<Argument>, DltParseError> = view.arguments().collect();
+                assert_eq!(expected_args, &args.expect("could not parse arguments"));
+            }
+            assert_eq!(msg, view.to_message(None).expect("could not convert view"));
         }
     }
 
@@ -359,6 +378,7 @@ mod tests {
                     has_trace_info: false,
                 },
                 name: Some("UcbfX".to_string()),
+                trace_info: None,
                 unit: Some("seconds".to_string()),
                 fixed_point: None,
                 value: Value::U32(2_063_359_909),
@@ -385,16 +405,279 @@ mod tests {
         println!("--> test_parse_msg: msg_bytes: {:02X?}", msg_bytes);
 
         msg_bytes.extend(b"----");
-        let res = dlt_message(&msg_bytes, None, 0, None, None, false);
+        let res = dlt_message(&msg_bytes, None, 0, None, None, DltFraming::None);
         let expected: Result<(&[u8], ParsedMessage), DltParseError> =
             Ok((b"----", ParsedMessage::Item(msg)));
         assert_eq!(expected, res);
     }
 
+    #[test]
+    fn test_parse_and_filter_trace_info() {
+        init_logging();
+        let speed = argument(
+            TypeInfoKind::Unsigned(TypeLength::BitLength16),
+            Value::U16(55),
+        );
+        let msg = TestMessage::new()
+            .endianness(Endianness::Little)
+            .counter(3)
+            .timestamp(None)
+            .ids("APP", "CTX")
+            .arguments(vec![Argument {
+                type_info: TypeInfo {
+                    has_variable_info: true,
+                    has_trace_info: true,
+                    ..speed.type_info
+                },
+                name: Some("speed".to_string()),
+                trace_info: Some("sensor.c:read_speed".to_string()),
+                unit: Some("mph".to_string()),
+                ..speed
+            }])
+            .build();
+        let msg_bytes = msg.as_bytes();
+        let res = dlt_message(&msg_bytes, None, 0, None, None, DltFraming::None);
+        let expected: Result<(&[u8], ParsedMessage), DltParseError> =
+            Ok((&[], ParsedMessage::Item(msg.clone())));
+        assert_eq!(expected, res);
+        assert!(format!("{:#}", msg).ends_with("\u{0004}sensor.c:read_speed"));
+        assert!(!format!("{}", msg).contains("sensor.c"));
+
+        let filter_config = |trace_info: &str| {
+            filtering::process_filter_config(filtering::DltFilterConfig {
+                trace_infos: Some(vec![trace_info.to_string()]),
+                ..Default::default()
+            })
+            .expect("invalid filter config")
+        };
+        let matching = filter_config("sensor.c:read_speed");
+        assert!(matches!(
+            dlt_message(&msg_bytes, Some(&matching), 0, None, None, DltFraming::None),
+            Ok((_, ParsedMessage::Item(_)))
+        ));
+        let not_matching = filter_config("other.c:main");
+        assert!(matches!(
+            dlt_message(&msg_bytes, Some(&not_matching), 0, None, None, DltFraming::None),
+            Ok((rest, ParsedMessage::FilteredOut)) if rest.is_empty()
+        ));
+    }
+
+    fn v2_message(payload: Payload2, message_type: MessageType, v2: HeaderV2Fields) -> Message {
+        let payload_length = payload.as_bytes::<BigEndian>().len() as u16;
+        Message {
+            storage_header: None,
+            header: StandardHeader {
+                version: DLT_V2_VERSION,
+                endianness: Endianness::Big,
+                has_extended_header: true,
+                message_counter: 7,
+                ecu_id: Some("ECU1".to_string()),
+                session_id: Some(0x1234),
+                timestamp: None,
+                payload_length,
+                v2: Some(Box::new(v2)),
+            },
+            extended_header: Some(ExtendedHeader {
+                verbose: payload.is_verbose(),
+                argument_count: payload.arg_count(),
+                message_type,
+                application_id: "NAVI".to_string(),
+                context_id: "ROUT".to_string(),
+            }),
+            payload,
+            fibex_metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_v2_verbose_message() {
+        init_logging();
+        let payload = Payload2 {
+            payload_content: PayloadContent::Verbose(vec![Argument {
+                type_info: TypeInfo {
+                    kind: TypeInfoKind::Unsigned(TypeLength::BitLength16),
+                    coding: StringCoding::UTF8,
+                    has_variable_info: false,
+                    has_trace_info: false,
+                },
+                name: None,
+                trace_info: None,
+                unit: None,
+                fixed_point: None,
+                value: Value::U16(55),
+            }]),
+        };
+        let msg = v2_message(
+            payload,
+            MessageType::Log(LogLevel::Warn),
+            HeaderV2Fields {
+                timestamp: Some(DltTimeStampV2 {
+                    seconds: 12,
+                    nanoseconds: 500,
+                }),
+                with_app_and_context_id: true,
+                source_location: Some(SourceLocation {
+                    file_name: "main.c".to_string(),
+                    line_number: 42,
+                }),
+                tags: vec!["nav".to_string(), "debug".to_string()],
+                privacy_level: Some(1),
+            },
+        );
+        let msg_bytes = msg.as_bytes();
+        assert_eq!(msg_bytes.len(), msg.byte_len() as usize);
+        let res = dlt_message(&msg_bytes, None, 0, None, None, DltFraming::None);
+        let expected: Result<(&[u8], ParsedMessage), DltParseError> =
+            Ok((&[], ParsedMessage::Item(msg.clone())));
+        assert_eq!(expected, res);
+        let formatted = format!("{}", msg);
+        assert!(formatted.contains("\u{0004}12.000000500\u{0004}ECU1"));
+        assert!(formatted.contains("[main.c:42] [tags: nav,debug] [privacy: 1]\u{0005}55"));
+    }
+
+    #[test]
+    fn test_parse_v2_non_verbose_message() {
+        #[rustfmt::skip]
+        let raw: Vec<u8> = vec![
+            0x00, 0x00, 0x00, 0x45, // HTYP2: non-verbose, WEID, version 2
+            0x03, // message counter
+            0x00, 0x1C, // length
+            0x40, // MSIN: log info
+            0x00, 0x00, 0x01, 0xF4, 0x00, 0x00, 0x00, 0x00, 0x0C, // 500 ns, 12 s
+            0x00, 0x00, 0x00, 0x2A, // message id
+            0x04, 0x45, 0x43, 0x55, 0x31, // ECU id "ECU1"
+            0x01, 0x02, // payload
+        ];
+        let res = dlt_message(&raw, None, 0, None, None, DltFraming::None);
+        let msg = match res {
+            Ok((rest, ParsedMessage::Item(msg))) => {
+                assert!(rest.is_empty());
+                msg
+            }
+            other => panic!("unexpected result: {:?}", other),
+        };
+        assert_eq!(Some("ECU1".to_string()), msg.header.ecu_id);
+        assert_eq!(3, msg.header.message_counter);
+        assert_eq!(
+            PayloadContent::NonVerbose(42, vec![0x01, 0x02]),
+            msg.payload.payload_content
+        );
+        assert_eq!(
+            Some(MessageType::Log(LogLevel::Info)),
+            msg.extended_header.as_ref().map(|h| h.message_type.clone())
+        );
+        assert_eq!(
+            Some(&DltTimeStampV2 {
+                seconds: 12,
+                nanoseconds: 500
+            }),
+            msg.header.v2.as_ref().and_then(|v2| v2.timestamp.as_ref())
+        );
+        assert_eq!(raw, msg.as_bytes());
+
+        let (rest, row) = dlt_statistic_row_info::<()>(&raw, None, DltFraming::None, None)
+            .expect("could not parse statistics");
+        assert!(rest.is_empty());
+        assert_eq!(Some("ECU1"), row.ecu_id);
+        assert_eq!(None, row.app_id_context_id);
+        assert_eq!(Some(LogLevel::Info), row.level);
+    }
+
+    #[test]
+    fn test_statistic_row_info_ecu_id() {
+        #[rustfmt::skip]
+        let with_ecu_id: Vec<u8> = vec![
+            0x44, 0x4C, 0x54, 0x01, // storage header pattern
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // timestamp
+            0x45, 0x43, 0x55, 0x31, // storage header ecu id "ECU1"
+            0x35, // header type: UEH, WEID, WTMS, version 1
+            0x07, // message counter
+            0x00, 0x04, // length, shorter than the headers
+            0x45, 0x43, 0x55, 0x32, // ecu id "ECU2"
+            0x00, 0x00, 0x00, 0x01, // timestamp
+        ];
+        let (_, row) =
+            dlt_statistic_row_info::<()>(&with_ecu_id, None, DltFraming::StorageHeader, None)
+                .expect("could not parse statistics");
+        assert_eq!(None, row.message_counter);
+        assert_eq!(Some("ECU2"), row.ecu_id);
+
+        // without ecu id in the standard header the one of the storage header is used
+        #[rustfmt::skip]
+        let without_ecu_id: Vec<u8> = vec![
+            0x44, 0x4C, 0x54, 0x01, // storage header pattern
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // timestamp
+            0x45, 0x43, 0x55, 0x31, // storage header ecu id "ECU1"
+            0x31, // header type: UEH, WTMS, version 1
+            0x07, // message counter
+            0x00, 0x04, // length, shorter than the headers
+            0x00, 0x00, 0x00, 0x01, // timestamp
+        ];
+        let (_, row) =
+            dlt_statistic_row_info::<()>(&without_ecu_id, None, DltFraming::StorageHeader, None)
+                .expect("could not parse statistics");
+        assert_eq!(None, row.message_counter);
+        assert_eq!(Some("ECU1"), row.ecu_id);
+        let (_, row) =
+            dlt_statistic_row_info::<()>(&without_ecu_id[16..], None, DltFraming::None, None)
+                .expect("could not parse statistics");
+        assert_eq!(None, row.ecu_id);
+
+        // the same for a message with a valid length
+        let mut valid = TestMessage::new().ecu_id(None).build();
+        valid.storage_header = Some(StorageHeader {
+            timestamp: DltTimeStamp::from_ms(0),
+            ecu_id: "ECU1".to_string(),
+        });
+        let valid = valid.as_bytes();
+        let (_, row) = dlt_statistic_row_info::<()>(&valid, None, DltFraming::StorageHeader, None)
+            .expect("could not parse statistics");
+        assert_eq!(Some(0), row.message_counter);
+        assert_eq!(Some("ECU1"), row.ecu_id);
+    }
+
+    #[test]
+    fn test_parse_v1_and_v2_messages_mixed() {
+        let control_payload = Payload2 {
+            payload_content: PayloadContent::ControlMsg(ControlType::Request, vec![0x11, 0x0]),
+        };
+        let v2_msg = v2_message(
+            control_payload.clone(),
+            MessageType::Control(ControlType::Request),
+            HeaderV2Fields {
+                timestamp: None,
+                with_app_and_context_id: true,
+                source_location: None,
+                tags: vec![],
+                privacy_level: None,
+            },
+        )
+        .add_storage_header(Some(DltTimeStamp::from_ms(1000)));
+        let v1_msg = TestMessage::new()
+            .endianness(Endianness::Little)
+            .counter(3)
+            .ids("APP", "CTX")
+            .message_type(MessageType::Control(ControlType::Request))
+            .payload(control_payload.payload_content)
+            .storage_header(DltTimeStamp::from_ms(2000))
+            .build();
+        let mut bytes = v1_msg.as_bytes();
+        bytes.extend(v2_msg.as_bytes());
+        bytes.extend(v1_msg.as_bytes());
+        let mut input: &[u8] = &bytes;
+        for expected in &[&v1_msg, &v2_msg, &v1_msg] {
+            let (rest, parsed) = dlt_message(input, None, 0, None, None, DltFraming::StorageHeader)
+                .expect("could not parse message");
+            assert_eq!(ParsedMessage::Item((*expected).clone()), parsed);
+            input = rest;
+        }
+        assert!(input.is_empty());
+    }
+
     #[test]
     fn test_parse_offending_argument() {
         let type_info = TypeInfo {
-            kind: TypeInfoKind::SignedFixedPoint(FloatWidth::Width64),
+            kind: TypeInfoKind::SignedFixedPoint(FixedPointWidth::Width64),
             coding: StringCoding::UTF8,
             has_variable_info: true,
             has_trace_info: false,
@@ -402,6 +685,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: Some("a".to_string()),
+            trace_info: None,
             unit: Some("a".to_string()),
             fixed_point: Some(FixedPoint {
                 quantization: 1.0,
@@ -420,12 +704,13 @@ mod tests {
     fn test2_parse_offending_argument() {
         let argument = Argument {
             type_info: TypeInfo {
-                kind: TypeInfoKind::SignedFixedPoint(FloatWidth::Width32),
+                kind: TypeInfoKind::SignedFixedPoint(FixedPointWidth::Width32),
                 coding: StringCoding::UTF8,
                 has_variable_info: true,
                 has_trace_info: false,
             },
             name: Some("a".to_string()),
+            trace_info: None,
             unit: Some("A".to_string()),
             fixed_point: Some(FixedPoint {
                 quantization: 0.1,
@@ -452,6 +737,7 @@ mod tests {
             let argument = Argument {
                 type_info,
                 name: None,
+                trace_info: None,
                 unit: None,
                 fixed_point: None,
                 value: Value::Bool(0x1),
@@ -475,6 +761,7 @@ mod tests {
             let argument = Argument {
                 type_info,
                 name: Some("abc".to_string()),
+                trace_info: None,
                 unit: None,
                 fixed_point: None,
                 value: Value::Bool(0x1),
@@ -500,6 +787,7 @@ mod tests {
             let argument = Argument {
                 type_info,
                 name: None,
+                trace_info: None,
                 unit: None,
                 fixed_point: None,
                 value: Value::U32(0x123),
@@ -522,6 +810,7 @@ mod tests {
             let argument = Argument {
                 type_info,
                 name: Some("speed".to_string()),
+                trace_info: None,
                 unit: Some("mph".to_string()),
                 fixed_point: None,
                 value: Value::U32(0x123),
@@ -544,11 +833,12 @@ mod tests {
                 has_trace_info: false,
             },
             name: Some("UcbfX".to_string()),
+            trace_info: None,
             unit: Some("seconds".to_string()),
             fixed_point: None,
             value: Value::U32(2_063_359_909),
             // type_info: TypeInfo {
-            //     kind: TypeInfoKind::UnsignedFixedPoint(FloatWidth::Width32),
+            //     kind: TypeInfoKind::UnsignedFixedPoint(FixedPointWidth::Width32),
             //     coding: StringCoding::ASCII,
             //     has_variable_info: false,
             //     has_trace_info: false,
@@ -579,6 +869,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: None,
+            trace_info: None,
             unit: None,
             fixed_point: None,
             value: Value::I16(-23),
@@ -599,6 +890,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: Some("temperature".to_string()),
+            trace_info: None,
             unit: Some("celcius".to_string()),
             fixed_point: None,
             value: Value::I32(-23),
@@ -621,6 +913,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: None,
+            trace_info: None,
             unit: None,
             fixed_point: None,
             value: Value::F32(123.98f32),
@@ -641,6 +934,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: Some("temperature".to_string()),
+            trace_info: None,
             unit: Some("celcius".to_string()),
             fixed_point: None,
             value: Value::F64(28.3),
@@ -663,6 +957,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: None,
+            trace_info: None,
             unit: None,
             fixed_point: None,
             value: Value::Raw(vec![0xD, 0xE, 0xA, 0xD]),
@@ -682,6 +977,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: Some("payload".to_string()),
+            trace_info: None,
             unit: None,
             fixed_point: None,
             value: Value::Raw(vec![0xD, 0xE, 0xA, 0xD]),
@@ -704,6 +1000,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: None,
+            trace_info: None,
             unit: None,
             fixed_point: None,
             value: Value::StringVal("foo".to_string()),
@@ -776,7 +1073,7 @@ mod tests {
     #[test]
     fn test_parse_fixed_point_argument() {
         let type_info = TypeInfo {
-            kind: TypeInfoKind::SignedFixedPoint(FloatWidth::Width32),
+            kind: TypeInfoKind::SignedFixedPoint(FixedPointWidth::Width32),
             coding: StringCoding::UTF8,
             has_variable_info: true,
             has_trace_info: false,
@@ -784,6 +1081,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: Some("speed".to_string()),
+            trace_info: None,
             unit: Some("mph".to_string()),
             value: Value::I32(-44),
             fixed_point: Some(FixedPoint {
@@ -799,6 +1097,229 @@ mod tests {
         assert_eq!(expected, res);
     }
 
+    #[test]
+    fn test_parse_128_bit_arguments() {
+        let arguments = vec![
+            Argument {
+                type_info: TypeInfo {
+                    kind: TypeInfoKind::Unsigned(TypeLength::BitLength128),
+                    coding: StringCoding::UTF8,
+                    has_variable_info: false,
+                    has_trace_info: false,
+                },
+                name: None,
+                trace_info: None,
+                unit: None,
+                fixed_point: None,
+                value: Value::U128(0x0123_4567_89AB_CDEF_0123_4567_89AB_CDEF),
+            },
+            Argument {
+                type_info: TypeInfo {
+                    kind: TypeInfoKind::Signed(TypeLength::BitLength128),
+                    coding: StringCoding::UTF8,
+                    has_variable_info: false,
+                    has_trace_info: false,
+                },
+                name: None,
+                trace_info: None,
+                unit: None,
+                fixed_point: None,
+                value: Value::I128(i128::MIN),
+            },
+            Argument {
+                type_info: TypeInfo {
+                    kind: TypeInfoKind::SignedFixedPoint(FixedPointWidth::Width128),
+                    coding: StringCoding::UTF8,
+                    has_variable_info: true,
+                    has_trace_info: false,
+                },
+                name: Some("counter".to_string()),
+                trace_info: None,
+                unit: Some("ticks".to_string()),
+                value: Value::I128(-42),
+                fixed_point: Some(FixedPoint {
+                    quantization: 0.5,
+                    offset: FixedPointValue::I128(1 << 100),
+                }),
+            },
+        ];
+        for argument in arguments {
+            let mut argument_bytes = argument.as_bytes::<LittleEndian>();
+            assert_eq!(argument_bytes.len(), argument.len_new());
+            argument_bytes.extend(b"----");
+            let res: IResult<&[u8], Argument> = dlt_argument::<LittleEndian>(&argument_bytes);
+            let expected: IResult<&[u8], Argument> = Ok((b"----", argument));
+            assert_eq!(expected, res);
+        }
+    }
+    #[test]
+    fn test_parse_array_argument() {
+        {
+            let type_info = TypeInfo {
+                kind: TypeInfoKind::Array(ArrayElementKind::Signed(TypeLength::BitLength32)),
+                coding: StringCoding::UTF8,
+                has_variable_info: false,
+                has_trace_info: false,
+            };
+            let argument = Argument {
+                type_info,
+                name: None,
+                trace_info: None,
+                unit: None,
+                fixed_point: None,
+                value: Value::Array(
+                    vec![2, 3],
+                    vec![
+                        Value::I32(1),
+                        Value::I32(-2),
+                        Value::I32(3),
+                        Value::I32(-4),
+                        Value::I32(5),
+                        Value::I32(-6),
+                    ],
+                ),
+            };
+            let mut argument_bytes = argument.as_bytes::<LittleEndian>();
+            argument_bytes.extend(b"----");
+            let res: IResult<&[u8], Argument> = dlt_argument::<LittleEndian>(&argument_bytes);
+            let expected: IResult<&[u8], Argument> = Ok((b"----", argument));
+            assert_eq!(expected, res);
+        }
+        // fixed point array with variable info
+        {
+            let type_info = TypeInfo {
+                kind: TypeInfoKind::Array(ArrayElementKind::UnsignedFixedPoint(
+                    FixedPointWidth::Width32,
+                )),
+                coding: StringCoding::UTF8,
+                has_variable_info: true,
+                has_trace_info: false,
+            };
+            let argument = Argument {
+                type_info,
+                name: Some("speed".to_string()),
+                trace_info: None,
+                unit: Some("mph".to_string()),
+                value: Value::Array(vec![3], vec![Value::U32(1), Value::U32(2), Value::U32(3)]),
+                fixed_point: Some(FixedPoint {
+                    quantization: 1.5,
+                    offset: FixedPointValue::I32(-200),
+                }),
+            };
+            let mut argument_bytes = argument.as_bytes::<BigEndian>();
+            argument_bytes.extend(b"----");
+            let res: IResult<&[u8], Argument> = dlt_argument::<BigEndian>(&argument_bytes);
+            let expected: IResult<&[u8], Argument> = Ok((b"----", argument));
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn test_parse_struct_argument() {
+        let member_type_info = TypeInfo {
+            kind: TypeInfoKind::Unsigned(TypeLength::BitLength8),
+            coding: StringCoding::UTF8,
+            has_variable_info: true,
+            has_trace_info: false,
+        };
+        let inner = Argument {
+            type_info: TypeInfo {
+                kind: TypeInfoKind::Struct,
+                coding: StringCoding::UTF8,
+                has_variable_info: false,
+                has_trace_info: false,
+            },
+            name: None,
+            trace_info: None,
+            unit: None,
+            fixed_point: None,
+            value: Value::Struct(vec![Argument {
+                type_info: TypeInfo {
+                    kind: TypeInfoKind::Bool,
+                    coding: StringCoding::UTF8,
+                    has_variable_info: false,
+                    has_trace_info: false,
+                },
+                name: None,
+                trace_info: None,
+                unit: None,
+                fixed_point: None,
+                value: Value::Bool(1),
+            }]),
+        };
+        let argument = Argument {
+            type_info: TypeInfo {
+                kind: TypeInfoKind::Struct,
+                coding: StringCoding::UTF8,
+                has_variable_info: true,
+                has_trace_info: false,
+            },
+            name: Some("position".to_string()),
+            trace_info: None,
+            unit: None,
+            fixed_point: None,
+            value: Value::Struct(vec![
+                Argument {
+                    type_info: member_type_info.clone(),
+                    name: Some("x".to_string()),
+                    trace_info: None,
+                    unit: Some("cm".to_string()),
+                    fixed_point: None,
+                    value: Value::U8(3),
+                },
+                Argument {
+                    type_info: member_type_info,
+                    name: Some("y".to_string()),
+                    trace_info: None,
+                    unit: Some("cm".to_string()),
+                    fixed_point: None,
+                    value: Value::U8(4),
+                },
+                inner,
+            ]),
+        };
+        let mut argument_bytes = argument.as_bytes::<BigEndian>();
+        assert_eq!(argument_bytes.len(), argument.len_new());
+        argument_bytes.extend(b"----");
+        let res: IResult<&[u8], Argument> = dlt_argument::<BigEndian>(&argument_bytes);
+        let expected: IResult<&[u8], Argument> = Ok((b"----", argument.clone()));
+        assert_eq!(expected, res);
+        assert_eq!("position: {x: cm3, y: cm4, {1}}", format!("{}", argument));
+    }
+    #[test]
+    fn test_parse_too_deeply_nested_struct_argument() {
+        let mut argument = Argument {
+            type_info: TypeInfo {
+                kind: TypeInfoKind::Bool,
+                coding: StringCoding::UTF8,
+                has_variable_info: false,
+                has_trace_info: false,
+            },
+            name: None,
+            trace_info: None,
+            unit: None,
+            fixed_point: None,
+            value: Value::Bool(1),
+        };
+        for _ in 0..=DLT_MAX_STRUCT_NESTING {
+            argument = Argument {
+                type_info: TypeInfo {
+                    kind: TypeInfoKind::Struct,
+                    coding: StringCoding::UTF8,
+                    has_variable_info: false,
+                    has_trace_info: false,
+                },
+                name: None,
+                trace_info: None,
+                unit: None,
+                fixed_point: None,
+                value: Value::Struct(vec![argument]),
+            };
+        }
+        let argument_bytes = argument.as_bytes::<BigEndian>();
+        assert!(dlt_argument::<BigEndian>(&argument_bytes).is_err());
+    }
+
     #[test]
     fn test_dlt_zero_terminated_string_exact() {
         let mut buf = BytesMut::with_capacity(4);
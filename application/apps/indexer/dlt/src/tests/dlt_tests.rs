@@ -53,6 +53,7 @@ mod tests {
             ecu_id: Some("abc".to_string()),
             session_id: None,
             timestamp: Some(5),
+            v2: None,
         };
         assert_eq!(
             vec![
@@ -158,7 +159,7 @@ mod tests {
             has_trace_info: false,
         };
         let type_info2 = TypeInfo {
-            kind: TypeInfoKind::UnsignedFixedPoint(FloatWidth::Width32),
+            kind: TypeInfoKind::UnsignedFixedPoint(FixedPointWidth::Width32),
             coding: StringCoding::ASCII,
             has_variable_info: false,
             has_trace_info: false,
@@ -227,6 +228,7 @@ mod tests {
         let argument = Argument {
             type_info: type_info.clone(),
             name: Some("foo".to_string()),
+            trace_info: None,
             unit: None,
             fixed_point: None,
             value: Value::Bool(0x1),
@@ -248,6 +250,7 @@ mod tests {
         let argument2 = Argument {
             type_info: type_info2,
             name: None,
+            trace_info: None,
             unit: None,
             fixed_point: None,
             value: Value::Bool(0x1),
@@ -267,6 +270,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: Some("speed".to_string()),
+            trace_info: None,
             unit: Some("mph".to_string()),
             fixed_point: None,
             value: Value::U32(0x33),
@@ -291,6 +295,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: None,
+            trace_info: None,
             unit: None,
             fixed_point: None,
             value: Value::U32(0x33),
@@ -313,6 +318,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: Some("speed".to_string()),
+            trace_info: None,
             unit: Some("mph".to_string()),
             fixed_point: None,
             value: Value::I32(-0x33),
@@ -337,6 +343,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: None,
+            trace_info: None,
             unit: None,
             fixed_point: None,
             value: Value::I32(-0x33),
@@ -359,6 +366,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: Some("speed".to_string()),
+            trace_info: None,
             unit: Some("mph".to_string()),
             fixed_point: None,
             value: Value::F32(123.98f32),
@@ -383,6 +391,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: None,
+            trace_info: None,
             unit: None,
             fixed_point: None,
             value: Value::F64(123.98f64),
@@ -404,6 +413,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: Some("speed".to_string()),
+            trace_info: None,
             unit: None,
             fixed_point: None,
             value: Value::StringVal("foo".to_string()),
@@ -425,6 +435,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: None,
+            trace_info: None,
             unit: None,
             fixed_point: None,
             value: Value::StringVal("foo".to_string()),
@@ -436,9 +447,38 @@ mod tests {
         assert_eq!(expected, argument_bytes);
     }
     #[test]
+    fn test_fixed_point_real_value() {
+        let argument = |value: Value, offset: FixedPointValue| Argument {
+            type_info: TypeInfo {
+                kind: TypeInfoKind::SignedFixedPoint(FixedPointWidth::Width64),
+                coding: StringCoding::UTF8,
+                has_variable_info: false,
+                has_trace_info: false,
+            },
+            name: None,
+            trace_info: None,
+            unit: None,
+            value,
+            fixed_point: Some(FixedPoint {
+                quantization: 0.5,
+                offset,
+            }),
+        };
+        let negative = argument(Value::I64(-1000), FixedPointValue::I32(-200));
+        assert_eq!(Some(-700), negative.to_real_value());
+        assert_eq!("-700", negative.to_string());
+
+        let large_offset = argument(Value::I64(-1000), FixedPointValue::I128(1 << 100));
+        assert_eq!(Some((1i128 << 100) - 500), large_offset.to_real_value());
+
+        let overflow = argument(Value::I64(10), FixedPointValue::I128(i128::MAX));
+        assert_eq!(None, overflow.to_real_value());
+        assert_eq!("10", overflow.to_string());
+    }
+    #[test]
     fn test_convert_fixedpoint_argument_to_bytes() {
         let type_info = TypeInfo {
-            kind: TypeInfoKind::SignedFixedPoint(FloatWidth::Width32),
+            kind: TypeInfoKind::SignedFixedPoint(FixedPointWidth::Width32),
             coding: StringCoding::UTF8,
             has_variable_info: true,
             has_trace_info: false,
@@ -447,6 +487,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: Some("speed".to_string()),
+            trace_info: None,
             unit: Some("mph".to_string()),
             value: Value::I32(-44),
             fixed_point: Some(FixedPoint {
@@ -469,7 +510,7 @@ mod tests {
 
         // now without variable info
         let type_info = TypeInfo {
-            kind: TypeInfoKind::SignedFixedPoint(FloatWidth::Width32),
+            kind: TypeInfoKind::SignedFixedPoint(FixedPointWidth::Width32),
             coding: StringCoding::UTF8,
             has_variable_info: false,
             has_trace_info: false,
@@ -478,6 +519,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: None,
+            trace_info: None,
             unit: None,
             value: Value::I32(-44),
             fixed_point: Some(FixedPoint {
@@ -506,6 +548,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: Some("foo".to_string()),
+            trace_info: None,
             unit: None,
             value: Value::Raw(vec![0xD, 0xE, 0xA, 0xD]),
             fixed_point: Some(FixedPoint {
@@ -530,6 +573,7 @@ mod tests {
         let argument = Argument {
             type_info,
             name: None,
+            trace_info: None,
             unit: None,
             value: Value::Raw(vec![0xD, 0xE, 0xA, 0xD]),
             fixed_point: Some(FixedPoint {
@@ -541,4 +585,39 @@ mod tests {
         expected.extend(vec![0xD, 0xE, 0xA, 0xD]);
         assert_eq!(expected, argument.as_bytes::<BigEndian>());
     }
+    #[test]
+    fn test_convert_array_argument_to_bytes() {
+        let type_info = TypeInfo {
+            kind: TypeInfoKind::Array(ArrayElementKind::Unsigned(TypeLength::BitLength16)),
+            coding: StringCoding::UTF8,
+            has_variable_info: true,
+            has_trace_info: false,
+        };
+        let mut expected = type_info.as_bytes::<BigEndian>();
+        let argument = Argument {
+            type_info,
+            name: Some("foo".to_string()),
+            trace_info: None,
+            unit: Some("cm".to_string()),
+            fixed_point: None,
+            value: Value::Array(
+                vec![2, 2],
+                vec![
+                    Value::U16(1),
+                    Value::U16(2),
+                    Value::U16(3),
+                    Value::U16(0xABCD),
+                ],
+            ),
+        };
+        expected.extend(vec![0x0, 0x2]); // number of dimensions
+        expected.extend(vec![0x0, 0x2, 0x0, 0x2]); // entries per dimension
+        expected.extend(vec![0x0, 0x4]); // length of name + zero
+        expected.extend(vec![0x0, 0x3]); // length of unit + zero
+        expected.extend(b"foo\0");
+        expected.extend(b"cm\0");
+        expected.extend(vec![0x0, 0x1, 0x0, 0x2, 0x0, 0x3, 0xAB, 0xCD]);
+        assert_eq!(expected, argument.as_bytes::<BigEndian>());
+        assert_eq!(expected.len(), argument.len_new());
+    }
 }
@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+    use crate::fibex_check::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn test_file(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join(name)
+    }
+
+    #[test]
+    fn test_check_valid_fibexes() {
+        let findings = check_fibexes(&[
+            test_file("dlt-messages.xml"),
+            test_file("dlt-compu-methods.xml"),
+        ])
+        .expect("could not check fibexes");
+        assert_eq!(Vec::<FibexFinding>::new(), findings);
+    }
+
+    #[test]
+    fn test_check_arxml_is_unsupported() {
+        let arxml = test_file("dlt-messages.arxml");
+        let findings = check_fibexes(&[test_file("dlt-messages.xml"), arxml.clone()])
+            .expect("could not check fibexes");
+        assert_eq!(
+            vec![FibexFinding {
+                file: arxml.clone(),
+                line: 1,
+                column: 0,
+                issue: FibexIssue::UnsupportedFile,
+            }],
+            findings
+        );
+        assert_eq!(
+            format!("{}:1:0: ARXML files can not be checked", arxml.display()),
+            findings[0].to_string()
+        );
+    }
+
+    #[test]
+    fn test_line_and_column_count_characters() {
+        use crate::fibex::line_and_column_at;
+        let content = "<A>\n  <B NAME=\"Größe\"/>".as_bytes();
+        let position = content.len() - 2;
+        // "ö" and "ß" take two bytes each
+        assert_eq!((2, 17), line_and_column_at(content, position));
+        assert_eq!((1, 0), line_and_column_at(content, 0));
+    }
+
+    #[test]
+    fn test_check_broken_fibex() {
+        let file = test_file("dlt-broken.xml");
+        let findings = check_fibexes(std::slice::from_ref(&file)).expect("could not check fibex");
+        let found: Vec<(usize, FibexIssue)> = findings
+            .iter()
+            .map(|finding| {
+                assert_eq!(file, finding.file);
+                (finding.line, finding.issue.clone())
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    5,
+                    FibexIssue::ByteLengthMismatch {
+                        pdu_id: "ID_6000".to_string(),
+                        declared: 4,
+                        signals: 2,
+                    }
+                ),
+                (
+                    25,
+                    FibexIssue::UnresolvedSignalRef {
+                        pdu_id: "ID_6001".to_string(),
+                        signal_ref: "S_MISSING".to_string(),
+                    }
+                ),
+                (
+                    37,
+                    FibexIssue::DuplicatePduId {
+                        pdu_id: "ID_6000".to_string(),
+                    }
+                ),
+                (
+                    57,
+                    FibexIssue::UnresolvedPduRef {
+                        frame_id: "ID_100".to_string(),
+                        pdu_ref: "ID_6002".to_string(),
+                    }
+                ),
+                (
+                    61,
+                    FibexIssue::DuplicateFrameId {
+                        frame_id: "ID_100".to_string(),
+                    }
+                ),
+                (
+                    78,
+                    FibexIssue::UnresolvedCodingRef {
+                        signal_id: "S_NO_CODING".to_string(),
+                        coding_ref: "C_MISSING".to_string(),
+                    }
+                ),
+                (
+                    93,
+                    FibexIssue::UnsupportedBaseDataType {
+                        id: "C_BYTES".to_string(),
+                        base_data_type: "A_UINT24".to_string(),
+                    }
+                ),
+            ],
+            found
+        );
+        assert_eq!(
+            format!(
+                "{}:61:{}: duplicate frame id ID_100",
+                file.display(),
+                findings[4].column
+            ),
+            findings[4].to_string()
+        );
+    }
+}
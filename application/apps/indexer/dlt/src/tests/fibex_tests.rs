@@ -1,7 +1,17 @@
 #[cfg(test)]
 mod tests {
+    use crate::dlt::*;
+    use crate::fibex::{
+        read_fibexes, ApplicationId, ContextId, FibexMetadata, FrameId, FrameMetadata, PduMetadata,
+        PhysicalValue,
+    };
+    use crate::fibex_cache::read_fibexes_with_cache;
+    use crate::tests::TestMessage;
+    use std::collections::HashMap;
+    use std::fs;
     use std::path::PathBuf;
-    use crate::fibex::read_fibexes;
+    use std::sync::Arc;
+    use tempdir::TempDir;
     #[test]
     fn test_fibex_parsing() {
         let fibex = read_fibexes(vec![
@@ -10,4 +20,265 @@ mod tests {
         .expect("can't parse fibex");
         println!("{:?}", fibex);
     }
+
+    #[test]
+    fn test_fibex_compu_methods() {
+        let fibex = read_fibexes(vec![
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-compu-methods.xml")
+        ])
+        .expect("can't parse fibex");
+        let frame = fibex
+            .frame_map
+            .get(&FrameId("ID_80".to_string()))
+            .expect("frame missing");
+        let pdu = &frame.pdus[1];
+        assert_eq!(3, pdu.signal_types.len());
+        assert_eq!(3, pdu.signal_conversions.len());
+        assert!(pdu.signal_conversions[2].is_none());
+
+        let gear = pdu.signal_conversions[0]
+            .as_ref()
+            .expect("no conversion for Gear");
+        assert_eq!("Gear", gear.signal_name);
+        assert_eq!(None, gear.compu_method.unit);
+        assert_eq!(
+            Some(PhysicalValue::Text("PARK".to_string())),
+            gear.compu_method.convert(3.0)
+        );
+        assert_eq!(None, gear.compu_method.convert(4.0));
+
+        let temp = pdu.signal_conversions[1]
+            .as_ref()
+            .expect("no conversion for Temp");
+        assert_eq!("Temp", temp.signal_name);
+        assert_eq!(Some("°C".to_string()), temp.compu_method.unit);
+        assert_eq!(
+            Some(PhysicalValue::Number(23.5)),
+            temp.compu_method.convert(7350.0)
+        );
+    }
+
+    #[test]
+    fn test_fibex_skips_invalid_units_and_scales() {
+        let tmp_dir = TempDir::new("fibex").expect("could not create temp dir");
+        let fibex_path = tmp_dir.path().join("invalid.xml");
+        let compu_methods = fs::read_to_string(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-compu-methods.xml"),
+        )
+        .expect("could not read fibex")
+        .replace("<ho:V>100</ho:V>", "<ho:V>0</ho:V>")
+        .replace(
+            "<ho:UNITS>",
+            "<ho:UNITS><ho:UNIT><ho:SHORT-NAME>km</ho:SHORT-NAME></ho:UNIT>",
+        );
+        fs::write(&fibex_path, compu_methods).expect("could not write fibex");
+        let fibex = read_fibexes(vec![fibex_path]).expect("can't parse fibex");
+        let pdu = &fibex
+            .frame_map
+            .get(&FrameId("ID_80".to_string()))
+            .expect("frame missing")
+            .pdus[1];
+        let temp = pdu.signal_conversions[1]
+            .as_ref()
+            .expect("no conversion for Temp");
+        // the unit after the one without ID is still read
+        assert_eq!(Some("°C".to_string()), temp.compu_method.unit);
+        // the scale with denominator 0 is skipped, so the raw value is kept
+        assert!(temp.compu_method.scales.is_empty());
+        assert_eq!(None, temp.compu_method.convert(7350.0));
+    }
+
+    #[test]
+    fn test_format_converted_signals() {
+        let fibex = Arc::new(
+            read_fibexes(vec![
+                PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-compu-methods.xml")
+            ])
+            .expect("can't parse fibex"),
+        );
+        let message = |gear: u8| {
+            let mut data = vec![gear];
+            data.extend_from_slice(&7350u16.to_be_bytes());
+            data.push(42);
+            TestMessage::new()
+                .without_extended_header()
+                .payload(PayloadContent::NonVerbose(80, data))
+                .fibex(fibex.clone())
+                .build()
+        };
+        let text = message(3).payload_text();
+        assert!(text.contains("Gear=PARK"), "{}", text);
+        assert!(text.contains("Temp=23.5 °C"), "{}", text);
+        assert!(text.ends_with(" 42"), "{}", text);
+
+        // raw values without a matching scale are kept
+        let text = message(7).payload_text();
+        assert!(text.contains("Gear=7"), "{}", text);
+
+        let msg = message(3);
+        let arguments = msg.arguments().expect("no arguments");
+        assert_eq!(Some("Temp".to_string()), arguments[2].name);
+        assert_eq!(Value::F64(23.5), arguments[2].value);
+        assert_eq!(Value::StringVal("PARK".to_string()), arguments[1].value);
+    }
+
+    #[test]
+    fn test_format_array_and_struct_signals() {
+        let fibex = |kind: TypeInfoKind| {
+            let signal_type = |kind| TypeInfo {
+                kind,
+                coding: StringCoding::ASCII,
+                has_variable_info: false,
+                has_trace_info: false,
+            };
+            let frame = Arc::new(FrameMetadata {
+                short_name: "ID_80".to_string(),
+                pdus: vec![Arc::new(PduMetadata {
+                    description: None,
+                    signal_types: vec![
+                        signal_type(TypeInfoKind::Unsigned(TypeLength::BitLength8)),
+                        signal_type(kind),
+                        signal_type(TypeInfoKind::Unsigned(TypeLength::BitLength8)),
+                    ],
+                    signal_names: vec![None, None, None],
+                    signal_conversions: vec![None, None, None],
+                })],
+                application_id: None,
+                context_id: None,
+                message_type: None,
+                message_info: None,
+            });
+            let mut frame_map = HashMap::new();
+            frame_map.insert(FrameId("ID_80".to_string()), frame);
+            Arc::new(FibexMetadata {
+                frame_map_with_key: HashMap::new(),
+                frame_map,
+            })
+        };
+        let message = |kind| {
+            TestMessage::new()
+                .without_extended_header()
+                .payload(PayloadContent::NonVerbose(80, vec![42, 1, 2, 3]))
+                .fibex(fibex(kind))
+                .build()
+        };
+        let array = message(TypeInfoKind::Array(ArrayElementKind::Unsigned(
+            TypeLength::BitLength8,
+        )));
+        let text = array.to_string();
+        let expected = format!("{0}42 {0}<array> ", DLT_ARGUMENT_SENTINAL);
+        assert!(text.ends_with(&expected), "{:?}", text);
+        assert_eq!(2, array.arguments().expect("no arguments").len());
+
+        let text = message(TypeInfoKind::Struct).to_string();
+        let expected = format!("{0}42 {0}<struct> ", DLT_ARGUMENT_SENTINAL);
+        assert!(text.ends_with(&expected), "{:?}", text);
+    }
+
+    #[test]
+    fn test_fibex_cache() {
+        let tmp_dir = TempDir::new("fibex_cache").expect("could not create temp dir");
+        let cache_dir = tmp_dir.path().join("cache");
+        let fibex_path = tmp_dir.path().join("catalogue.xml");
+        let compu_methods =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-compu-methods.xml");
+        let messages = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-messages.xml");
+        fs::copy(&compu_methods, &fibex_path).expect("could not copy fibex");
+
+        let parsed = read_fibexes_with_cache(vec![fibex_path.clone()], &cache_dir)
+            .expect("can't parse fibex");
+        assert_eq!(
+            read_fibexes(vec![compu_methods]).expect("can't parse fibex"),
+            parsed
+        );
+        let cache_files: Vec<PathBuf> = fs::read_dir(&cache_dir)
+            .expect("no cache written")
+            .map(|entry| entry.expect("can't read cache dir").path())
+            .collect();
+        assert_eq!(1, cache_files.len());
+        let cache_file = &cache_files[0];
+        let written = fs::metadata(cache_file).and_then(|m| m.modified()).unwrap();
+
+        // a valid cache is loaded and not written again
+        let cached = read_fibexes_with_cache(vec![fibex_path.clone()], &cache_dir)
+            .expect("can't load cached fibex");
+        assert_eq!(parsed, cached);
+        assert_eq!(
+            written,
+            fs::metadata(cache_file).and_then(|m| m.modified()).unwrap()
+        );
+
+        // another path to the same fibex uses the same cache
+        let other_path = cache_dir.join("..").join("catalogue.xml");
+        let cached =
+            read_fibexes_with_cache(vec![other_path], &cache_dir).expect("can't load cached fibex");
+        assert_eq!(parsed, cached);
+        assert_eq!(1, fs::read_dir(&cache_dir).unwrap().count());
+        assert_eq!(
+            written,
+            fs::metadata(cache_file).and_then(|m| m.modified()).unwrap()
+        );
+
+        // a changed fibex invalidates the cache
+        fs::copy(&messages, &fibex_path).expect("could not copy fibex");
+        let reparsed = read_fibexes_with_cache(vec![fibex_path.clone()], &cache_dir)
+            .expect("can't parse fibex");
+        assert_eq!(
+            read_fibexes(vec![messages]).expect("can't parse fibex"),
+            reparsed
+        );
+
+        // a broken cache is rebuilt
+        fs::write(cache_file, b"no cache").expect("could not overwrite cache");
+        let rebuilt = read_fibexes_with_cache(vec![fibex_path.clone()], &cache_dir)
+            .expect("can't parse fibex");
+        assert_eq!(reparsed, rebuilt);
+        assert_eq!(
+            rebuilt,
+            read_fibexes_with_cache(vec![fibex_path], &cache_dir).expect("can't load cached fibex")
+        );
+        assert_eq!(1, fs::read_dir(&cache_dir).unwrap().count());
+    }
+
+    #[test]
+    fn test_fibex_cache_shares_frames() {
+        let tmp_dir = TempDir::new("fibex_cache").expect("could not create temp dir");
+        let cache_dir = tmp_dir.path().join("cache");
+        let fibex_path =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-compu-methods.xml");
+        read_fibexes_with_cache(vec![fibex_path.clone()], &cache_dir).expect("can't parse fibex");
+        let cached =
+            read_fibexes_with_cache(vec![fibex_path], &cache_dir).expect("can't load fibex");
+        let frame = &cached.frame_map[&FrameId("ID_80".to_string())];
+        let keyed_frame = &cached.frame_map_with_key[&(
+            ContextId("STAT".to_string()),
+            ApplicationId("VEH".to_string()),
+            FrameId("ID_80".to_string()),
+        )];
+        assert!(Arc::ptr_eq(frame, keyed_frame));
+    }
+
+    #[test]
+    fn test_fibex_cache_is_pruned() {
+        let tmp_dir = TempDir::new("fibex_cache").expect("could not create temp dir");
+        let cache_dir = tmp_dir.path().join("cache");
+        fs::create_dir_all(&cache_dir).expect("could not create cache dir");
+        for i in 0..12 {
+            fs::write(cache_dir.join(format!("{:016x}.fibex.bin", i)), b"old")
+                .expect("could not write cache file");
+        }
+        fs::write(cache_dir.join("unrelated.txt"), b"keep").expect("could not write file");
+        let fibex_path =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-compu-methods.xml");
+        read_fibexes_with_cache(vec![fibex_path.clone()], &cache_dir).expect("can't parse fibex");
+        let remaining: Vec<PathBuf> = fs::read_dir(&cache_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(9, remaining.len());
+        assert!(remaining.contains(&cache_dir.join("unrelated.txt")));
+        // the new cache is kept and used
+        read_fibexes_with_cache(vec![fibex_path], &cache_dir).expect("can't load fibex");
+        assert_eq!(9, fs::read_dir(&cache_dir).unwrap().count());
+    }
 }
@@ -0,0 +1,392 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::dlt_parse::*;
+    use crate::fibex::read_fibexes;
+    use crate::filter_expression::*;
+    use crate::filtering::*;
+    use crate::tests::{argument, is_selected, parse, string_argument, TestMessage};
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    fn text(s: &str) -> FilterValue {
+        FilterValue::Text(s.to_string())
+    }
+
+    fn message(
+        app_id: &str,
+        context_id: &str,
+        session_id: Option<u32>,
+        message_type: MessageType,
+        payload: &str,
+    ) -> Vec<u8> {
+        let msg = TestMessage::new()
+            .ids(app_id, context_id)
+            .session_id(session_id)
+            .message_type(message_type.clone());
+        match message_type {
+            MessageType::Control(_) => {
+                msg.payload(PayloadContent::ControlMsg(ControlType::Request, vec![]))
+            }
+            _ => msg.text(payload),
+        }
+        .bytes()
+    }
+
+    /// `argument` with variable info
+    fn variable(name: &str, unit: &str, argument: Argument) -> Argument {
+        Argument {
+            type_info: TypeInfo {
+                has_variable_info: true,
+                ..argument.type_info
+            },
+            name: Some(name.to_string()),
+            unit: Some(unit.to_string()),
+            ..argument
+        }
+    }
+
+    fn log(app_id: &str, context_id: &str, level: LogLevel, payload: &str) -> Vec<u8> {
+        message(app_id, context_id, None, MessageType::Log(level), payload)
+    }
+
+    fn filter_config(expr: &str) -> ProcessedDltFilterConfig {
+        process_filter_config(DltFilterConfig {
+            filter_expr: Some(FilterExprSource::Text(expr.to_string())),
+            ..Default::default()
+        })
+        .expect("invalid filter config")
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        let expr: FilterExpr =
+            r#"(app == "NAV" && level <= WARN) || ctx in ["DIAG","UDS"] || payload ~ /timeout/"#
+                .parse()
+                .expect("could not parse");
+        assert_eq!(
+            FilterExpr::Or(vec![
+                FilterExpr::And(vec![
+                    FilterExpr::Compare {
+                        field: FilterField::App,
+                        op: CompareOp::Eq,
+                        value: text("NAV"),
+                    },
+                    FilterExpr::Compare {
+                        field: FilterField::Level,
+                        op: CompareOp::Le,
+                        value: text("WARN"),
+                    },
+                ]),
+                FilterExpr::In {
+                    field: FilterField::Context,
+                    values: vec![text("DIAG"), text("UDS")],
+                },
+                FilterExpr::Matches {
+                    field: FilterField::Payload,
+                    pattern: "timeout".to_string(),
+                },
+            ]),
+            expr
+        );
+        // && binds stronger than ||
+        assert_eq!(
+            FilterExpr::parse("session == 1 || session == 2 && !(type == LOG)").unwrap(),
+            FilterExpr::Or(vec![
+                FilterExpr::Compare {
+                    field: FilterField::Session,
+                    op: CompareOp::Eq,
+                    value: FilterValue::Number(1),
+                },
+                FilterExpr::And(vec![
+                    FilterExpr::Compare {
+                        field: FilterField::Session,
+                        op: CompareOp::Eq,
+                        value: FilterValue::Number(2),
+                    },
+                    FilterExpr::Not(Box::new(FilterExpr::Compare {
+                        field: FilterField::Type,
+                        op: CompareOp::Eq,
+                        value: text("LOG"),
+                    })),
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_argument_fields() {
+        let compare = |field, op, value| FilterExpr::Compare { field, op, value };
+        assert_eq!(
+            FilterExpr::And(vec![
+                compare(
+                    FilterField::Argument(ArgumentSelector::Index(2)),
+                    CompareOp::Gt,
+                    FilterValue::Number(120)
+                ),
+                compare(
+                    FilterField::Argument(ArgumentSelector::Name("temp".to_string())),
+                    CompareOp::Le,
+                    FilterValue::Float(-2.5)
+                ),
+                compare(
+                    FilterField::Unit(ArgumentSelector::Name("vehicle speed".to_string())),
+                    CompareOp::Eq,
+                    text("km/h")
+                ),
+            ]),
+            FilterExpr::parse(
+                r#"arg[2] > 120 && arg.temp <= -2.5 && arg["vehicle speed"].unit == "km/h""#
+            )
+            .unwrap()
+        );
+        let json = serde_json::to_string(&FilterExpr::parse("arg.speed >= 1.5").unwrap()).unwrap();
+        assert_eq!(
+            r#"{"Compare":{"field":{"Argument":{"Name":"speed"}},"op":"Ge","value":1.5}}"#,
+            json
+        );
+    }
+
+    #[test]
+    fn test_format_expression() {
+        let inputs = [
+            r#"(app == "NAV" && level <= WARN) || ctx in ["DIAG","UDS"] || payload ~ /timeout/"#,
+            r#"!(ecu != "ECU\"1") && payload ~ "a/b" && session >= 0x10"#,
+            "(session < 3 || session > 7) && type in [LOG, CONTROL]",
+            r#"arg[0] > 120.0 && arg.speed in [-1, 2.5] && arg["a b"].unit ~ /km/"#,
+        ];
+        for input in inputs {
+            let expr = FilterExpr::parse(input).expect("could not parse");
+            let formatted = expr.to_string();
+            assert_eq!(
+                expr,
+                FilterExpr::parse(&formatted).expect("could not parse formatted expression"),
+                "{}",
+                formatted
+            );
+        }
+        assert_eq!(
+            r#"(app == "NAV" || app == "HMI") && level <= 3"#,
+            FilterExpr::parse("(apid == NAV || app == HMI) && level <= 3")
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_expression_as_json() {
+        let expr = FilterExpr::parse(r#"ctx in ["DIAG", "UDS"] && level > 2"#).unwrap();
+        let json = serde_json::to_string(&expr).expect("could not serialize");
+        assert_eq!(
+            r#"{"And":[{"In":{"field":"Context","values":["DIAG","UDS"]}},{"Compare":{"field":"Level","op":"Gt","value":2}}]}"#,
+            json
+        );
+        let config: DltFilterConfig =
+            serde_json::from_str(&format!(r#"{{"filter_expr": {}}}"#, json)).unwrap();
+        assert_eq!(
+            Some(FilterExprSource::Expr(expr.clone())),
+            config.filter_expr
+        );
+        let config: DltFilterConfig =
+            serde_json::from_str(r#"{"filter_expr": "ctx in [\"DIAG\", \"UDS\"] && level > 2"}"#)
+                .unwrap();
+        let compiled = config.filter_expr.expect("no expression").compile();
+        assert!(compiled.is_ok());
+    }
+
+    #[test]
+    fn test_expression_errors() {
+        let syntax_error = |input: &str| match FilterExpr::parse(input) {
+            Err(FilterExprError::Syntax { position, .. }) => position,
+            res => panic!("expected syntax error for {}, got {:?}", input, res),
+        };
+        assert_eq!(0, syntax_error(""));
+        assert_eq!(0, syntax_error("foo == 1"));
+        assert_eq!(4, syntax_error("app NAV"));
+        assert_eq!(11, syntax_error("(app == NAV"));
+        assert_eq!(11, syntax_error("app == NAV )"));
+        assert_eq!(6, syntax_error("ctx ~ /a"));
+        assert_eq!(7, syntax_error("app == $"));
+
+        let invalid = |input: &str| match FilterExpr::parse(input).unwrap().compile() {
+            Err(FilterExprError::Invalid { reason }) => reason,
+            res => panic!("expected invalid expression for {}, got {:?}", input, res),
+        };
+        assert!(invalid("level <= LOUD").contains("LOUD"));
+        assert!(invalid("app < NAV").contains("App"));
+        assert!(invalid("payload ~ /(/").contains("regular expression"));
+        assert!(invalid("type == SPAM").contains("SPAM"));
+        assert!(invalid("arg[0] > fast").contains("Argument"));
+        assert!(invalid("session == -1").contains("Session"));
+        assert_eq!(6, syntax_error("arg[0 > 1"));
+        assert_eq!(4, syntax_error("arg == 1"));
+        assert_eq!(7, syntax_error("arg[0].name == x"));
+        assert!(process_filter_config(DltFilterConfig {
+            filter_expr: Some(FilterExprSource::Text("level ==".to_string())),
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_filter_with_expression() {
+        let config = filter_config(
+            r#"(app == "NAV" && level <= WARN) || ctx in ["DIAG","UDS"] || payload ~ /timeout/"#,
+        );
+        let expected = [
+            (log("NAV", "ROUT", LogLevel::Error, "route found"), true),
+            (log("NAV", "ROUT", LogLevel::Info, "route found"), false),
+            (log("HMI", "UDS", LogLevel::Verbose, "request"), true),
+            (log("HMI", "MAIN", LogLevel::Info, "request"), false),
+            (log("HMI", "MAIN", LogLevel::Info, "request timeout"), true),
+        ];
+        for (i, (msg, selected)) in expected.iter().enumerate() {
+            assert_eq!(*selected, is_selected(msg, &config), "message {}", i);
+        }
+        // control messages have no level
+        let control = message(
+            "NAV",
+            "CTRL",
+            None,
+            MessageType::Control(ControlType::Request),
+            "",
+        );
+        assert!(!is_selected(&control, &filter_config("level <= VERBOSE")));
+        assert!(!is_selected(&control, &filter_config("!(level > FATAL)")));
+        assert!(!is_selected(&control, &filter_config("level != WARN")));
+        assert!(!is_selected(
+            &control,
+            &filter_config("!(type == LOG || level > FATAL)")
+        ));
+        assert!(is_selected(
+            &control,
+            &filter_config("!(level > FATAL) || type == CONTROL")
+        ));
+        assert!(is_selected(
+            &control,
+            &filter_config("!(type == LOG && level > FATAL)")
+        ));
+        assert!(is_selected(&control, &filter_config("type == CONTROL")));
+        // messages without session id do not match any session
+        let info = MessageType::Log(LogLevel::Info);
+        let session_config = filter_config("session in [7, 8]");
+        assert!(is_selected(
+            &message("A", "B", Some(8), info.clone(), ""),
+            &session_config
+        ));
+        assert!(!is_selected(
+            &message("A", "B", None, info, ""),
+            &session_config
+        ));
+    }
+
+    #[test]
+    fn test_expression_without_payload_skips_payload() {
+        let mut msg = log("DLTD", "INTM", LogLevel::Info, "x");
+        // break the type info of the only argument (type info, length, "x\0")
+        let payload_start = msg.len() - 8;
+        msg[payload_start..payload_start + 4].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+        assert!(dlt_message(&msg, None, 0, None, None, DltFraming::None).is_err());
+        assert!(matches!(
+            dlt_message(&msg, Some(&filter_config("app != DLTD")), 0, None, None, DltFraming::None),
+            Ok((rest, ParsedMessage::FilteredOut)) if rest.is_empty()
+        ));
+        assert!(dlt_message(
+            &msg,
+            Some(&filter_config("app != DLTD || payload ~ /x/")),
+            0,
+            None,
+            None,
+            DltFraming::None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_filter_verbose_arguments() {
+        let msg = TestMessage::new()
+            .arguments(vec![
+                string_argument("speed"),
+                Argument {
+                    fixed_point: Some(FixedPoint {
+                        quantization: 0.5,
+                        offset: FixedPointValue::I32(10),
+                    }),
+                    ..variable(
+                        "speed",
+                        "km/h",
+                        argument(
+                            TypeInfoKind::UnsignedFixedPoint(FixedPointWidth::Width32),
+                            Value::U32(250),
+                        ),
+                    )
+                },
+                variable(
+                    "temp",
+                    "C",
+                    argument(
+                        TypeInfoKind::Signed(TypeLength::BitLength16),
+                        Value::I16(-7),
+                    ),
+                ),
+            ])
+            .bytes();
+        let expected = [
+            // physical value of speed is 250 * 0.5 + 10
+            ("arg.speed > 120", true),
+            ("arg.speed > 135", false),
+            ("arg.speed == 135", true),
+            ("arg[1] in [1, 135]", true),
+            ("arg.temp < -5", true),
+            ("arg.temp != -7", false),
+            ("arg[0] == speed", true),
+            ("arg[0] ~ /^sp/", true),
+            ("arg[0] > 1", false),
+            ("arg[7] < 1000", false),
+            ("arg.unknown == 1", false),
+            (r#"arg.speed.unit == "km/h""#, true),
+            ("arg.temp.unit ~ /^km/", false),
+            ("arg[0].unit == km", false),
+            ("arg[1] == 135 && arg[2] == -7.0", true),
+        ];
+        for (expr, selected) in expected.iter() {
+            assert_eq!(
+                *selected,
+                is_selected(&msg, &filter_config(expr)),
+                "{}",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn test_filter_non_verbose_arguments() {
+        let fibex = read_fibexes(vec![
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/dlt-messages.xml")
+        ])
+        .expect("can't parse fibex");
+        // frame 64: "direction", "speed: ", SINT64, "heading: ", SINT64
+        let mut data = vec![];
+        data.extend_from_slice(&130i64.to_be_bytes());
+        data.extend_from_slice(&(-90i64).to_be_bytes());
+        let msg = TestMessage::new()
+            .ecu_id(None)
+            .timestamp(None)
+            .without_extended_header()
+            .payload(PayloadContent::NonVerbose(64, data))
+            .bytes();
+        let fibex = Some(Arc::new(fibex));
+        let speeding = filter_config("arg[2] > 120");
+        assert!(parse(&msg, &speeding, fibex.clone()).is_some());
+        assert!(parse(
+            &msg,
+            &filter_config(r#"arg[1] == "speed: " && arg[4] == -90"#),
+            fibex.clone()
+        )
+        .is_some());
+        assert!(parse(&msg, &filter_config("arg[2] > 130"), fibex).is_none());
+        // without fibex the signals are unknown
+        assert!(!is_selected(&msg, &speeding));
+    }
+}
@@ -0,0 +1,369 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::dlt_parse::*;
+    use crate::filtering::*;
+    use crate::tests::{argument, is_selected, TestMessage};
+    use pretty_assertions::assert_eq;
+
+    fn message(session_id: Option<u32>, message_type: MessageType) -> Vec<u8> {
+        message_with_ids("ECU1", "APP1", "CTX1", session_id, message_type)
+    }
+
+    fn log_message(ecu_id: &str, app_id: &str, context_id: &str, level: LogLevel) -> Vec<u8> {
+        message_with_ids(ecu_id, app_id, context_id, None, MessageType::Log(level))
+    }
+
+    fn message_with_ids(
+        ecu_id: &str,
+        app_id: &str,
+        context_id: &str,
+        session_id: Option<u32>,
+        message_type: MessageType,
+    ) -> Vec<u8> {
+        let msg = TestMessage::new()
+            .ecu_id(Some(ecu_id))
+            .ids(app_id, context_id)
+            .session_id(session_id)
+            .message_type(message_type.clone());
+        match message_type {
+            MessageType::Control(_) => {
+                msg.payload(PayloadContent::ControlMsg(ControlType::Request, vec![]))
+            }
+            _ => msg.arguments(vec![argument(TypeInfoKind::Bool, Value::Bool(1))]),
+        }
+        .bytes()
+    }
+
+    fn message_without_extended_header(ecu_id: Option<&str>, session_id: Option<u32>) -> Vec<u8> {
+        TestMessage::new()
+            .ecu_id(ecu_id)
+            .session_id(session_id)
+            .without_extended_header()
+            .payload(PayloadContent::NonVerbose(7, vec![1, 2, 3]))
+            .bytes()
+    }
+
+    #[test]
+    fn test_filter_session_ids() {
+        let filter_config = process_filter_config(DltFilterConfig {
+            session_ids: Some(vec![42, 43]),
+            ..Default::default()
+        })
+        .expect("invalid filter config");
+        let log = MessageType::Log(LogLevel::Info);
+        assert!(is_selected(&message(Some(42), log.clone()), &filter_config));
+        assert!(!is_selected(&message(Some(7), log.clone()), &filter_config));
+        // messages without session id are not in the allowed sessions
+        assert!(!is_selected(&message(None, log), &filter_config));
+    }
+
+    #[test]
+    fn test_filter_message_types() {
+        let filter_config: DltFilterConfig = serde_json::from_str(
+            r#"{"message_types": ["Log", {"Control": null}, {"NetworkTrace": "Can"}]}"#,
+        )
+        .expect("could not read filter config");
+        assert_eq!(
+            Some(vec![
+                MessageTypeFilter::Log,
+                MessageTypeFilter::Control(None),
+                MessageTypeFilter::NetworkTrace(Some(NetworkTraceType::Can)),
+            ]),
+            filter_config.message_types
+        );
+        let filter_config = process_filter_config(filter_config).expect("invalid filter config");
+        let selected = vec![
+            MessageType::Log(LogLevel::Debug),
+            MessageType::Control(ControlType::Request),
+            MessageType::Control(ControlType::Response),
+            MessageType::NetworkTrace(NetworkTraceType::Can),
+        ];
+        for message_type in selected {
+            assert!(
+                is_selected(&message(None, message_type.clone()), &filter_config),
+                "{:?} should be selected",
+                message_type
+            );
+        }
+        let filtered = vec![
+            MessageType::NetworkTrace(NetworkTraceType::Ethernet),
+            MessageType::ApplicationTrace(ApplicationTraceType::State),
+        ];
+        for message_type in filtered {
+            assert!(
+                !is_selected(&message(None, message_type.clone()), &filter_config),
+                "{:?} should be filtered",
+                message_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_filter_message_sub_kinds() {
+        let filter_config = process_filter_config(DltFilterConfig {
+            message_types: Some(vec![
+                MessageTypeFilter::ApplicationTrace(Some(ApplicationTraceType::FunctionIn)),
+                MessageTypeFilter::Control(Some(ControlType::Response)),
+            ]),
+            session_ids: Some(vec![1]),
+            ..Default::default()
+        })
+        .expect("invalid filter config");
+        let function_in = MessageType::ApplicationTrace(ApplicationTraceType::FunctionIn);
+        assert!(is_selected(
+            &message(Some(1), function_in.clone()),
+            &filter_config
+        ));
+        assert!(!is_selected(&message(Some(2), function_in), &filter_config));
+        assert!(!is_selected(
+            &message(
+                Some(1),
+                MessageType::ApplicationTrace(ApplicationTraceType::FunctionOut)
+            ),
+            &filter_config
+        ));
+        assert!(is_selected(
+            &message(Some(1), MessageType::Control(ControlType::Response)),
+            &filter_config
+        ));
+        assert!(!is_selected(
+            &message(Some(1), MessageType::Control(ControlType::Request)),
+            &filter_config
+        ));
+    }
+
+    #[test]
+    fn test_exclude_ids() {
+        let filter_config = process_filter_config(DltFilterConfig {
+            exclude_app_ids: Some(vec!["DLTD".to_string()]),
+            exclude_context_ids: Some(vec!["INTM".to_string()]),
+            exclude_ecu_ids: Some(vec!["HMI".to_string()]),
+            ..Default::default()
+        })
+        .expect("invalid filter config");
+        let info = LogLevel::Info;
+        assert!(is_selected(
+            &log_message("ECU1", "NAV", "ROUT", info),
+            &filter_config
+        ));
+        assert!(!is_selected(
+            &log_message("ECU1", "DLTD", "ROUT", info),
+            &filter_config
+        ));
+        assert!(!is_selected(
+            &log_message("ECU1", "NAV", "INTM", info),
+            &filter_config
+        ));
+        assert!(!is_selected(
+            &log_message("HMI", "NAV", "ROUT", info),
+            &filter_config
+        ));
+    }
+
+    #[test]
+    fn test_excluded_messages_skip_payload() {
+        let mut msg = log_message("ECU1", "DLTD", "INTM", LogLevel::Info);
+        // break the type info of the only argument
+        let payload_start = msg.len() - 5;
+        msg[payload_start..payload_start + 4].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+        assert!(dlt_message(&msg, None, 0, None, None, DltFraming::None).is_err());
+        let filter_config = process_filter_config(DltFilterConfig {
+            exclude_context_ids: Some(vec!["INTM".to_string()]),
+            ..Default::default()
+        })
+        .expect("invalid filter config");
+        assert!(matches!(
+            dlt_message(&msg, Some(&filter_config), 0, None, None, DltFraming::None),
+            Ok((rest, ParsedMessage::FilteredOut)) if rest.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_level_overrides() {
+        let filter_config: DltFilterConfig = serde_json::from_str(
+            r#"{
+                "min_log_level": 3,
+                "level_overrides": [
+                    {"app_id": "NAV", "context_id": "ROUT", "min_log_level": 2},
+                    {"app_id": "NAV", "min_log_level": 6},
+                    {"ecu_id": "ECU2", "min_log_level": 0}
+                ]
+            }"#,
+        )
+        .expect("could not read filter config");
+        let filter_config = process_filter_config(filter_config).expect("invalid filter config");
+        let expected = vec![
+            (("ECU1", "APP1", "CTX1", LogLevel::Warn), true),
+            (("ECU1", "APP1", "CTX1", LogLevel::Info), false),
+            (("ECU1", "NAV", "CTX1", LogLevel::Verbose), true),
+            (("ECU1", "NAV", "ROUT", LogLevel::Error), true),
+            (("ECU1", "NAV", "ROUT", LogLevel::Warn), false),
+            (("ECU2", "APP1", "CTX1", LogLevel::Verbose), true),
+        ];
+        for ((ecu_id, app_id, context_id, level), selected) in expected {
+            assert_eq!(
+                selected,
+                is_selected(
+                    &log_message(ecu_id, app_id, context_id, level),
+                    &filter_config
+                ),
+                "{} {} {} {:?}",
+                ecu_id,
+                app_id,
+                context_id,
+                level
+            );
+        }
+    }
+
+    #[test]
+    fn test_time_range() {
+        let time_range = |clock, start, end| {
+            process_filter_config(DltFilterConfig {
+                time_range: Some(TimeRange { clock, start, end }),
+                ..Default::default()
+            })
+            .expect("invalid filter config")
+        };
+        // the uptime of the test messages is 0.5 ms
+        let msg = message(None, MessageType::Log(LogLevel::Info));
+        assert!(is_selected(
+            &msg,
+            &time_range(TimeClock::Uptime, Some(0), Some(1))
+        ));
+        assert!(is_selected(
+            &msg,
+            &time_range(TimeClock::Uptime, None, Some(1))
+        ));
+        assert!(!is_selected(
+            &msg,
+            &time_range(TimeClock::Uptime, Some(1), None)
+        ));
+        assert!(!is_selected(
+            &msg,
+            &time_range(TimeClock::Uptime, None, Some(0))
+        ));
+        // messages without storage header are not filtered
+        assert!(is_selected(
+            &msg,
+            &time_range(TimeClock::StorageHeader, Some(1), Some(2))
+        ));
+        // the limits in ms saturate when they are converted to µs
+        let max_range = time_range(TimeClock::Uptime, Some(u64::MAX), Some(u64::MAX));
+        assert_eq!(
+            Some(ProcessedTimeRange {
+                clock: TimeClock::Uptime,
+                start_us: Some(u64::MAX),
+                end_us: Some(u64::MAX),
+            }),
+            max_range.time_range
+        );
+        assert!(!is_selected(&msg, &max_range));
+        assert!(is_selected(
+            &msg,
+            &time_range(TimeClock::Uptime, None, Some(u64::MAX / 999))
+        ));
+
+        let filter_config: DltFilterConfig = serde_json::from_str(
+            r#"{"time_range": {"clock": "StorageHeader", "start": 1000, "end": null}}"#,
+        )
+        .expect("could not read filter config");
+        assert_eq!(
+            Some(TimeRange {
+                clock: TimeClock::StorageHeader,
+                start: Some(1000),
+                end: None,
+            }),
+            filter_config.time_range
+        );
+    }
+
+    #[test]
+    fn test_ids_of_standard_header_without_extended_header() {
+        let filter_config = process_filter_config(DltFilterConfig {
+            ecu_ids: Some(vec!["ECU1".to_string(), "ECU2".to_string()]),
+            exclude_ecu_ids: Some(vec!["ECU2".to_string()]),
+            session_ids: Some(vec![42]),
+            ..Default::default()
+        })
+        .expect("invalid filter config");
+        assert!(is_selected(
+            &message_without_extended_header(Some("ECU1"), Some(42)),
+            &filter_config
+        ));
+        assert!(!is_selected(
+            &message_without_extended_header(Some("ECU2"), Some(42)),
+            &filter_config
+        ));
+        assert!(!is_selected(
+            &message_without_extended_header(Some("ECU3"), Some(42)),
+            &filter_config
+        ));
+        assert!(!is_selected(
+            &message_without_extended_header(Some("ECU1"), Some(7)),
+            &filter_config
+        ));
+        // missing ids never match an allow-list
+        assert!(!is_selected(
+            &message_without_extended_header(None, Some(42)),
+            &filter_config
+        ));
+        assert!(!is_selected(
+            &message_without_extended_header(Some("ECU1"), None),
+            &filter_config
+        ));
+
+        // without allow-lists, messages without ids are only checked against exclusions
+        let exclude_only = process_filter_config(DltFilterConfig {
+            exclude_ecu_ids: Some(vec!["ECU2".to_string()]),
+            ..Default::default()
+        })
+        .expect("invalid filter config");
+        assert!(is_selected(
+            &message_without_extended_header(None, None),
+            &exclude_only
+        ));
+    }
+
+    #[test]
+    fn test_missing_field_policy() {
+        let filter_config = |missing_fields: &str| {
+            let json = format!(
+                r#"{{"app_ids": ["NONV"], "min_log_level": 4, "missing_fields": {}}}"#,
+                missing_fields
+            );
+            let filter_config: DltFilterConfig =
+                serde_json::from_str(&json).expect("could not read filter config");
+            process_filter_config(filter_config).expect("invalid filter config")
+        };
+        let msg = message_without_extended_header(Some("ECU1"), None);
+        assert!(is_selected(&msg, &filter_config(r#""Include""#)));
+        assert!(!is_selected(&msg, &filter_config(r#""Exclude""#)));
+        let default_ids = |app_id: &str, log_level: u8| {
+            filter_config(&format!(
+                r#"{{"Default": {{"app_id": "{}", "context_id": "NONV", "log_level": {}}}}}"#,
+                app_id, log_level
+            ))
+        };
+        assert!(is_selected(&msg, &default_ids("NONV", 4)));
+        assert!(!is_selected(&msg, &default_ids("APP1", 4)));
+        assert!(!is_selected(&msg, &default_ids("NONV", 5)));
+        // an invalid level is not filtered by level
+        assert!(is_selected(&msg, &default_ids("NONV", 9)));
+        // the policy does not apply to messages with extended header
+        assert!(!is_selected(
+            &log_message("ECU1", "APP1", "CTX1", LogLevel::Info),
+            &filter_config(r#""Include""#)
+        ));
+
+        // without filters on the extended header nothing is excluded
+        let ecu_only = process_filter_config(DltFilterConfig {
+            ecu_ids: Some(vec!["ECU1".to_string()]),
+            missing_fields: Some(MissingFieldPolicy::Exclude),
+            ..Default::default()
+        })
+        .expect("invalid filter config");
+        assert!(is_selected(&msg, &ecu_only));
+    }
+}
@@ -1,6 +1,193 @@
 #[macro_use]
 mod dlt_tests;
+mod arxml_tests;
+mod dlt_check_tests;
+mod dlt_control_tests;
+mod dlt_convert_tests;
+mod dlt_counter_tests;
 mod dlt_file_tests;
 mod dlt_net_tests;
 mod dlt_parse_tests;
+mod fibex_check_tests;
 mod fibex_tests;
+mod filter_expression_tests;
+mod filtering_tests;
+mod payload_search_tests;
+
+use crate::dlt::*;
+use crate::dlt_parse::{dlt_message, DltFraming, ParsedMessage};
+use crate::fibex::FibexMetadata;
+use crate::filtering::ProcessedDltFilterConfig;
+use std::sync::Arc;
+
+/// builds the messages of the tests
+///
+/// without further settings it is a big endian verbose info message of
+/// ECU1, APP1 and CTX1 with message counter 0, timestamp 5 and no arguments
+pub(crate) struct TestMessage {
+    config: MessageConfig,
+    fibex: Option<Arc<FibexMetadata>>,
+    storage_timestamp: Option<DltTimeStamp>,
+}
+
+impl TestMessage {
+    pub(crate) fn new() -> Self {
+        TestMessage {
+            config: MessageConfig {
+                version: 1,
+                counter: 0,
+                endianness: Endianness::Big,
+                ecu_id: Some("ECU1".to_string()),
+                session_id: None,
+                timestamp: Some(5),
+                payload: Payload2 {
+                    payload_content: PayloadContent::Verbose(vec![]),
+                },
+                extended_header_info: Some(ExtendedHeaderConfig {
+                    message_type: MessageType::Log(LogLevel::Info),
+                    app_id: "APP1".to_string(),
+                    context_id: "CTX1".to_string(),
+                }),
+            },
+            fibex: None,
+            storage_timestamp: None,
+        }
+    }
+
+    pub(crate) fn counter(mut self, counter: u8) -> Self {
+        self.config.counter = counter;
+        self
+    }
+
+    pub(crate) fn endianness(mut self, endianness: Endianness) -> Self {
+        self.config.endianness = endianness;
+        self
+    }
+
+    pub(crate) fn ecu_id(mut self, ecu_id: Option<&str>) -> Self {
+        self.config.ecu_id = ecu_id.map(str::to_string);
+        self
+    }
+
+    pub(crate) fn session_id(mut self, session_id: Option<u32>) -> Self {
+        self.config.session_id = session_id;
+        self
+    }
+
+    pub(crate) fn timestamp(mut self, timestamp: Option<u32>) -> Self {
+        self.config.timestamp = timestamp;
+        self
+    }
+
+    /// the extended header with these IDs, an info log message if there was no extended header
+    pub(crate) fn ids(mut self, app_id: &str, context_id: &str) -> Self {
+        let message_type = self.message_type_or_info();
+        self.config.extended_header_info = Some(ExtendedHeaderConfig {
+            message_type,
+            app_id: app_id.to_string(),
+            context_id: context_id.to_string(),
+        });
+        self
+    }
+
+    /// the extended header with this type, with APP1 and CTX1 if there was no extended header
+    pub(crate) fn message_type(mut self, message_type: MessageType) -> Self {
+        let header = self
+            .config
+            .extended_header_info
+            .get_or_insert_with(|| ExtendedHeaderConfig {
+                message_type: MessageType::Log(LogLevel::Info),
+                app_id: "APP1".to_string(),
+                context_id: "CTX1".to_string(),
+            });
+        header.message_type = message_type;
+        self
+    }
+
+    pub(crate) fn without_extended_header(mut self) -> Self {
+        self.config.extended_header_info = None;
+        self
+    }
+
+    pub(crate) fn payload(mut self, payload_content: PayloadContent) -> Self {
+        self.config.payload = Payload2 { payload_content };
+        self
+    }
+
+    pub(crate) fn arguments(self, arguments: Vec<Argument>) -> Self {
+        self.payload(PayloadContent::Verbose(arguments))
+    }
+
+    /// one string argument
+    pub(crate) fn text(self, text: &str) -> Self {
+        self.arguments(vec![string_argument(text)])
+    }
+
+    pub(crate) fn fibex(mut self, fibex: Arc<FibexMetadata>) -> Self {
+        self.fibex = Some(fibex);
+        self
+    }
+
+    /// a storage header with the ECU id of the message (see `Message::add_storage_header`)
+    pub(crate) fn storage_header(mut self, timestamp: DltTimeStamp) -> Self {
+        self.storage_timestamp = Some(timestamp);
+        self
+    }
+
+    pub(crate) fn build(self) -> Message {
+        let msg = Message::new(self.config, self.fibex, None);
+        match self.storage_timestamp {
+            Some(timestamp) => msg.add_storage_header(Some(timestamp)),
+            None => msg,
+        }
+    }
+
+    pub(crate) fn bytes(self) -> Vec<u8> {
+        self.build().as_bytes()
+    }
+
+    fn message_type_or_info(&self) -> MessageType {
+        match &self.config.extended_header_info {
+            Some(header) => header.message_type.clone(),
+            None => MessageType::Log(LogLevel::Info),
+        }
+    }
+}
+
+/// a verbose argument without name, unit and trace info
+pub(crate) fn argument(kind: TypeInfoKind, value: Value) -> Argument {
+    Argument {
+        type_info: TypeInfo {
+            kind,
+            coding: StringCoding::UTF8,
+            has_variable_info: false,
+            has_trace_info: false,
+        },
+        name: None,
+        trace_info: None,
+        unit: None,
+        fixed_point: None,
+        value,
+    }
+}
+
+pub(crate) fn string_argument(text: &str) -> Argument {
+    argument(TypeInfoKind::StringType, Value::StringVal(text.to_string()))
+}
+
+/// parses a message without framing, None if it is filtered out
+pub(crate) fn parse(
+    msg: &[u8],
+    filter_config: &ProcessedDltFilterConfig,
+    fibex: Option<Arc<FibexMetadata>>,
+) -> Option<Message> {
+    match dlt_message(msg, Some(filter_config), 0, None, fibex, DltFraming::None) {
+        Ok((_, ParsedMessage::Item(msg))) => Some(msg),
+        Ok((_, ParsedMessage::FilteredOut)) => None,
+        res => panic!("could not parse message: {:?}", res),
+    }
+}
+
+pub(crate) fn is_selected(msg: &[u8], filter_config: &ProcessedDltFilterConfig) -> bool {
+    parse(msg, filter_config, None).is_some()
+}
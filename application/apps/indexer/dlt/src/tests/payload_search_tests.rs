@@ -0,0 +1,159 @@
+#[cfg(test)]
+mod tests {
+    use crate::dlt::*;
+    use crate::filtering::*;
+    use crate::payload_search::*;
+    use crate::tests::{parse, string_argument, TestMessage};
+    use pretty_assertions::assert_eq;
+
+    fn search(pattern: &str, regex: bool, ignore_case: bool) -> PayloadMatcher {
+        PayloadSearch {
+            pattern: pattern.to_string(),
+            regex,
+            ignore_case,
+        }
+        .compile()
+        .expect("invalid search")
+    }
+
+    #[test]
+    fn test_find_matches() {
+        assert_eq!(
+            vec![(4, 7), (12, 15)],
+            search("a.b", false, false).find("xxx a.b axb a.b")
+        );
+        assert_eq!(
+            vec![(4, 7), (8, 11), (12, 15)],
+            search("a.b", true, false).find("xxx a.b axb a.b")
+        );
+        assert_eq!(
+            vec![(0, 5), (6, 11)],
+            search("error", false, true).find("Error ERROR")
+        );
+        assert!(search("error", false, false).find("Error ERROR").is_empty());
+        // plain text is not a regular expression
+        assert!(search("(", false, false).is_match("f(x)"));
+        let invalid = PayloadSearch {
+            pattern: "(".to_string(),
+            regex: true,
+            ignore_case: false,
+        };
+        assert!(matches!(
+            invalid.compile(),
+            Err(PayloadSearchError::InvalidRegex { .. })
+        ));
+        assert!(matches!(
+            process_filter_config(DltFilterConfig {
+                payload_search: Some(invalid),
+                ..Default::default()
+            }),
+            Err(FilterConfigError::PayloadSearch(_))
+        ));
+        let from_json: PayloadSearch =
+            serde_json::from_str(r#"{"pattern": "timeout"}"#).expect("could not read search");
+        assert_eq!(
+            PayloadSearch {
+                pattern: "timeout".to_string(),
+                regex: false,
+                ignore_case: false,
+            },
+            from_json
+        );
+    }
+
+    #[test]
+    fn test_filter_by_payload_search() {
+        let filter_config = process_filter_config(DltFilterConfig {
+            payload_search: Some(PayloadSearch {
+                pattern: r"speed \d+".to_string(),
+                regex: true,
+                ignore_case: false,
+            }),
+            ..Default::default()
+        })
+        .expect("invalid filter config");
+        // the arguments are rendered with a space in between
+        let msg = parse(
+            &TestMessage::new()
+                .arguments(vec![string_argument("speed"), string_argument("120")])
+                .bytes(),
+            &filter_config,
+            None,
+        )
+        .expect("message should be selected");
+        let line = IndexLine::new(Some(&filter_config), &msg).expect("line should be selected");
+        assert_eq!(msg.to_string(), line.text);
+        // the positions are byte ranges in the line of the index
+        assert_eq!(1, line.positions.len());
+        let (start, end) = line.positions[0];
+        assert_eq!(
+            format!("speed{}120", DLT_ARGUMENT_SENTINAL),
+            &line.text[start..end]
+        );
+        assert!(line.text[..start].ends_with(DLT_ARGUMENT_SENTINAL));
+
+        let msg = parse(
+            &TestMessage::new().text("speed: unknown").bytes(),
+            &filter_config,
+            None,
+        )
+        .expect("message should be parsed");
+        assert!(IndexLine::new(Some(&filter_config), &msg).is_none());
+        assert_eq!(
+            IndexLine {
+                text: msg.to_string(),
+                positions: vec![],
+            },
+            IndexLine::new(None, &msg).expect("line should be selected")
+        );
+    }
+
+    #[test]
+    fn test_index_line_with_trace_info() {
+        let filter_config = |show_trace_info| {
+            process_filter_config(DltFilterConfig {
+                payload_search: Some(PayloadSearch {
+                    pattern: "speed".to_string(),
+                    regex: false,
+                    ignore_case: false,
+                }),
+                show_trace_info: Some(show_trace_info),
+                ..Default::default()
+            })
+            .expect("invalid filter config")
+        };
+        let with_trace_info = filter_config(true);
+        let msg = parse(
+            &TestMessage::new()
+                .arguments(vec![Argument {
+                    type_info: TypeInfo {
+                        has_trace_info: true,
+                        ..string_argument("").type_info
+                    },
+                    trace_info: Some("sensor.c:read_speed".to_string()),
+                    ..string_argument("speed 120")
+                }])
+                .bytes(),
+            &with_trace_info,
+            None,
+        )
+        .expect("message should be selected");
+
+        let line = IndexLine::new(Some(&with_trace_info), &msg).expect("line should be selected");
+        assert_eq!(format!("{:#}", msg), line.text);
+        assert!(line.text.ends_with(&format!(
+            "{}speed 120{}sensor.c:read_speed",
+            DLT_ARGUMENT_SENTINAL, DLT_COLUMN_SENTINAL
+        )));
+        // only the payload column is searched, not the trace info after it
+        assert_eq!(1, line.positions.len());
+        let (start, end) = line.positions[0];
+        assert_eq!("speed", &line.text[start..end]);
+        assert!(line.text[..start].ends_with(DLT_ARGUMENT_SENTINAL));
+
+        let line =
+            IndexLine::new(Some(&filter_config(false)), &msg).expect("line should be selected");
+        assert_eq!(msg.to_string(), line.text);
+        assert!(!line.text.contains("sensor.c"));
+    }
+}
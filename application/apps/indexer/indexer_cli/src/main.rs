@@ -22,10 +22,15 @@ extern crate lazy_static;
 use async_std::task;
 use crossbeam_channel as cc;
 use crossbeam_channel::unbounded;
+use dlt::dlt_check::CorruptionResults;
 use dlt::dlt_file::export_as_dlt_file;
+use dlt::dlt_parse::DltFraming;
 use dlt::dlt_parse::StatisticsResults;
 use dlt::dlt_pcap::convert_to_dlt_file;
+use dlt::dlt_reader::DltReaderMode;
 use dlt::fibex::FibexMetadata;
+use dlt::filter_expression::{FilterExpr, FilterExprSource};
+use dlt::payload_search::{self, PayloadMatch, PayloadSearch};
 use failure::{err_msg, Error};
 use indexer_base::chunks::{serialize_chunks, Chunk, ChunkResults};
 use indexer_base::config::*;
@@ -33,7 +38,7 @@ use indexer_base::error_reporter::*;
 use indexer_base::export::export_file_line_based;
 use indexer_base::progress::IndexingResults;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::rc::Rc;
+use std::sync::Arc;
 
 lazy_static! {
     static ref EXAMPLE_FIBEX: std::path::PathBuf =
@@ -248,6 +253,17 @@ fn main() {
                         .long("out")
                         .value_name("OUT")
                         .help("Output file, \"<file_to_export>.out\" if not present"),
+                )
+                .arg(
+                    Arg::with_name("mmap")
+                        .short("m")
+                        .long("mmap")
+                        .help("map the input file into memory instead of buffered reading"),
+                )
+                .arg(
+                    Arg::with_name("serial")
+                        .long("serial")
+                        .help("messages are framed by serial headers (DLS\\x01)"),
                 ),
         )
         .subcommand(
@@ -328,11 +344,60 @@ fn main() {
                         .value_name("FILTER_CONFIG")
                         .help("json file that defines dlt filter settings"),
                 )
+                .arg(
+                    Arg::with_name("filter_expr")
+                        .long("filter-expr")
+                        .value_name("FILTER_EXPR")
+                        .help("only select messages that match this expression, e.g. 'app == \"NAV\" && level <= WARN'"),
+                )
+                .arg(
+                    Arg::with_name("search")
+                        .long("search")
+                        .value_name("PATTERN")
+                        .help("only select messages with this text in the payload"),
+                )
+                .arg(
+                    Arg::with_name("search_regex")
+                        .long("regex")
+                        .requires("search")
+                        .help("the search pattern is a regular expression"),
+                )
+                .arg(
+                    Arg::with_name("ignore_case")
+                        .long("ignore-case")
+                        .requires("search")
+                        .help("search case-insensitive"),
+                )
+                .arg(
+                    Arg::with_name("trace_info")
+                        .long("trace-info")
+                        .help("put out the trace info of verbose arguments in a column after the payload"),
+                )
                 .arg(
                     Arg::with_name("stdout")
                         .short("s")
                         .long("stdout")
                         .help("put out chunk information on stdout"),
+                )
+                .arg(
+                    Arg::with_name("jobs")
+                        .short("j")
+                        .long("jobs")
+                        .value_name("JOBS")
+                        .help("number of threads used for parsing")
+                        .required(false)
+                        .default_value("1"),
+                )
+                .arg(
+                    Arg::with_name("mmap")
+                        .short("m")
+                        .long("mmap")
+                        .help("map the input file into memory instead of buffered reading"),
+                )
+                .arg(
+                    Arg::with_name("serial")
+                        .long("serial")
+                        .help("messages are framed by serial headers (DLS\\x01)"),
                 ),
         )
         .subcommand(
@@ -377,6 +442,35 @@ fn main() {
                         .value_name("FILTER_CONFIG")
                         .help("json file that defines dlt filter settings"),
                 )
+                .arg(
+                    Arg::with_name("filter_expr")
+                        .long("filter-expr")
+                        .value_name("FILTER_EXPR")
+                        .help("only select messages that match this expression, e.g. 'app == \"NAV\" && level <= WARN'"),
+                )
+                .arg(
+                    Arg::with_name("search")
+                        .long("search")
+                        .value_name("PATTERN")
+                        .help("only select messages with this text in the payload"),
+                )
+                .arg(
+                    Arg::with_name("search_regex")
+                        .long("regex")
+                        .requires("search")
+                        .help("the search pattern is a regular expression"),
+                )
+                .arg(
+                    Arg::with_name("ignore_case")
+                        .long("ignore-case")
+                        .requires("search")
+                        .help("search case-insensitive"),
+                )
+                .arg(
+                    Arg::with_name("trace_info")
+                        .long("trace-info")
+                        .help("put out the trace info of verbose arguments in a column after the payload"),
+                )
                 .arg(
                     Arg::with_name("direct")
                         .short("d")
@@ -426,6 +520,35 @@ fn main() {
                         .value_name("FILTER_CONFIG")
                         .help("json file that defines dlt filter settings"),
                 )
+                .arg(
+                    Arg::with_name("filter_expr")
+                        .long("filter-expr")
+                        .value_name("FILTER_EXPR")
+                        .help("only select messages that match this expression, e.g. 'app == \"NAV\" && level <= WARN'"),
+                )
+                .arg(
+                    Arg::with_name("search")
+                        .long("search")
+                        .value_name("PATTERN")
+                        .help("only select messages with this text in the payload"),
+                )
+                .arg(
+                    Arg::with_name("search_regex")
+                        .long("regex")
+                        .requires("search")
+                        .help("the search pattern is a regular expression"),
+                )
+                .arg(
+                    Arg::with_name("ignore_case")
+                        .long("ignore-case")
+                        .requires("search")
+                        .help("search case-insensitive"),
+                )
+                .arg(
+                    Arg::with_name("trace_info")
+                        .long("trace-info")
+                        .help("put out the trace info of verbose arguments in a column after the payload"),
+                )
                 .arg(
                     Arg::with_name("stdout")
                         .short("s")
@@ -449,6 +572,96 @@ fn main() {
                         .short("s")
                         .long("stdout")
                         .help("put out chunk information on stdout"),
+                )
+                .arg(
+                    Arg::with_name("mmap")
+                        .short("m")
+                        .long("mmap")
+                        .help("map the input file into memory instead of buffered reading"),
+                )
+                .arg(
+                    Arg::with_name("serial")
+                        .long("serial")
+                        .help("messages are framed by serial headers (DLS\\x01)"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("dlt-check")
+                .about("report corrupted parts of a dlt file")
+                .arg(
+                    Arg::with_name("input")
+                        .short("i")
+                        .long("input")
+                        .help("the DLT file to check")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("mmap")
+                        .short("m")
+                        .long("mmap")
+                        .help("map the input file into memory instead of buffered reading"),
+                )
+                .arg(
+                    Arg::with_name("serial")
+                        .long("serial")
+                        .help("messages are framed by serial headers (DLS\\x01)"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("dlt-to-verbose")
+                .about("convert the non-verbose messages of a dlt file to verbose messages")
+                .arg(
+                    Arg::with_name("input")
+                        .short("i")
+                        .long("input")
+                        .help("the DLT file to convert")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("out")
+                        .value_name("OUT")
+                        .required(true)
+                        .help("Output file"),
+                )
+                .arg(
+                    Arg::with_name("fibex")
+                        .short("f")
+                        .long("fibex")
+                        .value_name("FIBEX")
+                        .required(true)
+                        .multiple(true)
+                        .help("the fibex files that describe the non-verbose messages"),
+                )
+                .arg(
+                    Arg::with_name("mmap")
+                        .short("m")
+                        .long("mmap")
+                        .help("map the input file into memory instead of buffered reading"),
+                )
+                .arg(
+                    Arg::with_name("serial")
+                        .long("serial")
+                        .help("messages are framed by serial headers (DLS\\x01)"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("fibex-check")
+                .about("report unresolved references and inconsistencies in fibex files")
+                .arg(
+                    Arg::with_name("input")
+                        .help("the fibex files to check")
+                        .required(true)
+                        .multiple(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("print the findings as json"),
                 ),
         )
         .get_matches();
@@ -473,6 +686,12 @@ fn main() {
         handle_dlt_udp_subcommand(matches)
     } else if let Some(matches) = matches.subcommand_matches("dlt-stats") {
         handle_dlt_stats_subcommand(matches, start, use_stderr_for_status_updates)
+    } else if let Some(matches) = matches.subcommand_matches("dlt-check") {
+        handle_dlt_check_subcommand(matches, start, use_stderr_for_status_updates)
+    } else if let Some(matches) = matches.subcommand_matches("dlt-to-verbose") {
+        handle_dlt_to_verbose_subcommand(matches, start, use_stderr_for_status_updates)
+    } else if let Some(matches) = matches.subcommand_matches("fibex-check") {
+        handle_fibex_check_subcommand(matches, start, use_stderr_for_status_updates)
     } else if let Some(matches) = matches.subcommand_matches("discover") {
         handle_discover_subcommand(matches)
     }
@@ -696,8 +915,15 @@ fn main() {
             let ending = &file_path.extension().expect("could not get extension");
             if ending.to_str() == Some("dlt") {
                 trace!("was dlt file");
-                export_as_dlt_file(file_path, out_path, SectionConfig { sections }, tx)
-                    .expect("export did not work");
+                export_as_dlt_file(
+                    file_path,
+                    out_path,
+                    SectionConfig { sections },
+                    tx,
+                    dlt_reader_mode(matches),
+                    dlt_framing(matches),
+                )
+                .expect("export did not work");
             } else {
                 trace!("was regular file");
                 export_file_line_based(
@@ -717,22 +943,8 @@ fn main() {
     fn handle_dlt_subcommand(matches: &clap::ArgMatches, start: std::time::Instant) {
         debug!("handle_dlt_subcommand");
         if let (Some(file_name), Some(tag)) = (matches.value_of("input"), matches.value_of("tag")) {
-            let filter_conf: Option<dlt::filtering::DltFilterConfig> = match matches
-                .value_of("filter_config")
-            {
-                Some(filter_config_file_name) => {
-                    let config_path = path::PathBuf::from(filter_config_file_name);
-                    let mut cnf_file = match fs::File::open(&config_path) {
-                        Ok(file) => file,
-                        Err(_) => {
-                            report_error(format!("could not open filter config {:?}", config_path));
-                            std::process::exit(2)
-                        }
-                    };
-                    dlt::filtering::read_filter_options(&mut cnf_file).ok()
-                }
-                None => None,
-            };
+            let filter_conf = dlt_filter_config(matches);
+            let has_search = matches.is_present("search");
             let append: bool = matches.is_present("append");
             let source_file_size = match fs::metadata(file_name) {
                 Ok(file_meta) => file_meta.len() as usize,
@@ -753,6 +965,9 @@ fn main() {
 
             let (tx, rx): (cc::Sender<ChunkResults>, cc::Receiver<ChunkResults>) = unbounded();
             let chunk_size = value_t_or_exit!(matches.value_of("chunk_size"), usize);
+            let jobs = value_t_or_exit!(matches.value_of("jobs"), usize);
+            let reader_mode = dlt_reader_mode(matches);
+            let framing = dlt_framing(matches);
             let tag_string = tag.to_string();
 
             // let filter_config: Option<dlt::filtering::ProcessedDltFilterConfig> =
@@ -761,8 +976,8 @@ fn main() {
             // let res = task::block_on(dlt_file_future);
 
             let progress_bar = initialize_progress_bar(source_file_size as u64);
-            thread::spawn(move || {
-                if let Err(why) = dlt::dlt_file::create_index_and_mapping_dlt(
+            let indexing = thread::spawn(move || {
+                match dlt::dlt_file::create_index_and_mapping_dlt_parallel(
                     IndexingConfig {
                         tag: tag_string.as_str(),
                         chunk_size,
@@ -779,9 +994,15 @@ fn main() {
                     //     components: None,
                     // },
                     load_test_fibex(),
+                    reader_mode,
+                    framing,
+                    jobs,
                 ) {
-                    report_error(format!("couldn't process: {}", why));
-                    std::process::exit(2)
+                    Ok(payload_matches) => payload_matches,
+                    Err(why) => {
+                        report_error(format!("couldn't process: {}", why));
+                        std::process::exit(2)
+                    }
                 }
             });
             let mut chunks: Vec<Chunk> = vec![];
@@ -827,6 +1048,12 @@ fn main() {
                 }
             }
 
+            if has_search {
+                match indexing.join() {
+                    Ok(payload_matches) => write_payload_matches(file_name, &payload_matches),
+                    Err(_) => report_error("dlt indexer panicked"),
+                }
+            }
             println!("done with handle_dlt_subcommand");
             std::process::exit(0)
         }
@@ -835,22 +1062,8 @@ fn main() {
     fn handle_dlt_pcap_subcommand(matches: &clap::ArgMatches) {
         debug!("handle_dlt_pcap_subcommand");
         if let (Some(file_name), Some(tag)) = (matches.value_of("input"), matches.value_of("tag")) {
-            let filter_conf: Option<dlt::filtering::DltFilterConfig> = match matches
-                .value_of("filter_config")
-            {
-                Some(filter_config_file_name) => {
-                    let config_path = path::PathBuf::from(filter_config_file_name);
-                    let mut cnf_file = match fs::File::open(&config_path) {
-                        Ok(file) => file,
-                        Err(_) => {
-                            report_error(format!("could not open filter config {:?}", config_path));
-                            std::process::exit(2)
-                        }
-                    };
-                    dlt::filtering::read_filter_options(&mut cnf_file).ok()
-                }
-                None => None,
-            };
+            let filter_conf = dlt_filter_config(matches);
+            let has_search = matches.is_present("search");
             let append: bool = matches.is_present("append");
             let fallback_out = file_name.to_string() + ".out";
             let out_path = path::PathBuf::from(
@@ -870,12 +1083,12 @@ fn main() {
             let in_one_go: bool = matches.is_present("direct");
             if in_one_go {
                 println!("in one go: pcap");
-                let _ = convert_to_dlt_file(file_path, filter_conf, tx, load_test_fibex_rc());
+                let _ = convert_to_dlt_file(file_path, filter_conf, tx, load_test_fibex_shared());
             } else {
                 let shutdown_channel = async_std::sync::channel(1);
 
-                thread::spawn(move || {
-                    let why = dlt::dlt_pcap::create_index_and_mapping_dlt_from_pcap(
+                let indexing = thread::spawn(move || {
+                    let res = dlt::dlt_pcap::create_index_and_mapping_dlt_from_pcap(
                         IndexingConfig {
                             tag: tag_string.as_str(),
                             chunk_size,
@@ -886,12 +1099,14 @@ fn main() {
                         filter_conf,
                         &tx,
                         shutdown_channel.1,
-                        load_test_fibex_rc(),
+                        load_test_fibex_shared(),
                     );
-
-                    if let Err(reason) = why {
-                        report_error(format!("couldn't process: {}", reason));
-                        std::process::exit(2)
+                    match res {
+                        Ok(payload_matches) => payload_matches,
+                        Err(reason) => {
+                            report_error(format!("couldn't process: {}", reason));
+                            std::process::exit(2)
+                        }
                     }
                 });
                 let mut chunks: Vec<Chunk> = vec![];
@@ -930,6 +1145,12 @@ fn main() {
                     }
                 }
 
+                if has_search {
+                    match indexing.join() {
+                        Ok(payload_matches) => write_payload_matches(file_name, &payload_matches),
+                        Err(_) => report_error("dlt pcap indexer panicked"),
+                    }
+                }
                 println!("done with handle_dlt_pcap_subcommand");
                 std::process::exit(0)
             }
@@ -942,22 +1163,7 @@ fn main() {
             matches.value_of("tag"),
             matches.value_of("output"),
         ) {
-            let filter_conf: Option<dlt::filtering::DltFilterConfig> = match matches
-                .value_of("filter_config")
-            {
-                Some(filter_config_file_name) => {
-                    let config_path = path::PathBuf::from(filter_config_file_name);
-                    let mut cnf_file = match fs::File::open(&config_path) {
-                        Ok(file) => file,
-                        Err(_) => {
-                            report_error(format!("could not open filter config {:?}", config_path));
-                            std::process::exit(2)
-                        }
-                    };
-                    dlt::filtering::read_filter_options(&mut cnf_file).ok()
-                }
-                None => None,
-            };
+            let filter_conf = dlt_filter_config(matches);
             let out_path = path::PathBuf::from(output);
             let mapping_out_path: path::PathBuf =
                 path::PathBuf::from(output.to_string() + ".map.json");
@@ -1194,6 +1400,8 @@ fn main() {
     ) {
         let file_name = matches.value_of("input").expect("input must be present");
         let file_path = path::PathBuf::from(file_name);
+        let reader_mode = dlt_reader_mode(matches);
+        let framing = dlt_framing(matches);
         let f = match fs::File::open(&file_path) {
             Ok(file) => file,
             Err(_) => {
@@ -1215,7 +1423,9 @@ fn main() {
         ) = unbounded();
 
         thread::spawn(move || {
-            if let Err(why) = dlt::dlt_parse::get_dlt_file_info(&file_path, &tx, None) {
+            if let Err(why) =
+                dlt::dlt_parse::get_dlt_file_info(&file_path, &tx, None, reader_mode, framing)
+            {
                 report_error(format!("couldn't collect statistics: {}", why));
                 std::process::exit(2)
             }
@@ -1278,6 +1488,176 @@ fn main() {
             }
         }
     }
+
+    fn handle_dlt_check_subcommand(
+        matches: &clap::ArgMatches,
+        start: std::time::Instant,
+        status_updates: bool,
+    ) {
+        let file_name = matches.value_of("input").expect("input must be present");
+        let file_path = path::PathBuf::from(file_name);
+        let reader_mode = dlt_reader_mode(matches);
+        let framing = dlt_framing(matches);
+        let source_file_size = match fs::metadata(&file_path) {
+            Ok(file_meta) => file_meta.len() as usize,
+            Err(_) => {
+                report_error(format!("could not open {:?}", file_path));
+                std::process::exit(2)
+            }
+        };
+        let progress_bar = initialize_progress_bar(source_file_size as u64);
+        let (tx, rx): (
+            cc::Sender<CorruptionResults>,
+            cc::Receiver<CorruptionResults>,
+        ) = unbounded();
+
+        thread::spawn(move || {
+            if let Err(why) =
+                dlt::dlt_check::check_dlt_file(&file_path, framing, reader_mode, &tx, None)
+            {
+                report_error(format!("couldn't check dlt file: {}", why));
+                std::process::exit(2)
+            }
+        });
+        loop {
+            match rx.recv() {
+                Ok(Ok(IndexingProgress::GotItem { item: report })) => {
+                    progress_bar.finish_and_clear();
+                    match serde_json::to_string_pretty(&report) {
+                        Ok(report) => println!("{}", report),
+                        Err(e) => {
+                            report_error(format!("serializing report failed: {}", e));
+                            std::process::exit(2)
+                        }
+                    }
+                    if status_updates {
+                        duration_report(start, format!("checking {} bytes", source_file_size));
+                    }
+                }
+                Ok(Ok(IndexingProgress::Progress { ticks: t })) => {
+                    progress_bar.set_position(t.0 as u64);
+                }
+                Ok(Ok(IndexingProgress::Finished)) => {
+                    trace!("finished...");
+                    break;
+                }
+                Ok(Err(Notification {
+                    severity,
+                    content,
+                    line,
+                })) => {
+                    if severity == Severity::WARNING {
+                        report_warning_ln(content, line);
+                    } else {
+                        report_error_ln(content, line);
+                    }
+                }
+                Ok(Ok(IndexingProgress::Stopped)) => {
+                    report_warning("IndexingProgress::Stopped");
+                    break;
+                }
+                Err(_) => {
+                    report_error("couldn't process");
+                    std::process::exit(2)
+                }
+            }
+        }
+    }
+
+    fn handle_dlt_to_verbose_subcommand(
+        matches: &clap::ArgMatches,
+        start: std::time::Instant,
+        status_updates: bool,
+    ) {
+        let file_name = matches.value_of("input").expect("input must be present");
+        let file_path = path::PathBuf::from(file_name);
+        let out_path =
+            path::PathBuf::from(matches.value_of("output").expect("output must be present"));
+        let fibex_paths: Vec<path::PathBuf> = matches
+            .values_of("fibex")
+            .expect("fibex must be present")
+            .map(path::PathBuf::from)
+            .collect();
+        let fibex_metadata = match dlt::fibex_cache::read_fibexes_cached(fibex_paths) {
+            Ok(fibex_metadata) => Arc::new(fibex_metadata),
+            Err(e) => {
+                report_error(format!("couldn't read fibex files: {}", e));
+                std::process::exit(2)
+            }
+        };
+        let (tx, rx): (
+            cc::Sender<dlt::dlt_convert::ConversionResults>,
+            cc::Receiver<dlt::dlt_convert::ConversionResults>,
+        ) = unbounded();
+        let report = match dlt::dlt_convert::convert_to_verbose(
+            &file_path,
+            &out_path,
+            fibex_metadata,
+            dlt_framing(matches),
+            dlt_reader_mode(matches),
+            &tx,
+            None,
+        ) {
+            Ok(report) => report,
+            Err(e) => {
+                report_error(format!("couldn't convert dlt file: {}", e));
+                std::process::exit(2)
+            }
+        };
+        drop(tx);
+        for notification in rx.iter().filter_map(Result::err) {
+            report_error_ln(notification.content, notification.line);
+        }
+        match serde_json::to_string_pretty(&report) {
+            Ok(report) => println!("{}", report),
+            Err(e) => {
+                report_error(format!("serializing report failed: {}", e));
+                std::process::exit(2)
+            }
+        }
+        if status_updates {
+            duration_report(start, format!("converting {} messages", report.messages));
+        }
+    }
+
+    fn handle_fibex_check_subcommand(
+        matches: &clap::ArgMatches,
+        start: std::time::Instant,
+        status_updates: bool,
+    ) {
+        let files: Vec<path::PathBuf> = matches
+            .values_of("input")
+            .expect("input must be present")
+            .map(path::PathBuf::from)
+            .collect();
+        let findings = match dlt::fibex_check::check_fibexes(&files) {
+            Ok(findings) => findings,
+            Err(e) => {
+                report_error(format!("couldn't check fibex files: {}", e));
+                std::process::exit(2)
+            }
+        };
+        if matches.is_present("json") {
+            match serde_json::to_string_pretty(&findings) {
+                Ok(findings) => println!("{}", findings),
+                Err(e) => {
+                    report_error(format!("serializing findings failed: {}", e));
+                    std::process::exit(2)
+                }
+            }
+        } else {
+            for finding in &findings {
+                println!("{}", finding);
+            }
+            println!("{} problems found in {} files", findings.len(), files.len());
+        }
+        if status_updates {
+            duration_report(start, format!("checking {} fibex files", files.len()));
+        }
+        if !findings.is_empty() {
+            std::process::exit(1)
+        }
+    }
 }
 
 fn duration_report(start: std::time::Instant, report: String) {
@@ -1301,12 +1681,91 @@ fn duration_report_throughput(
         report, duration_in_s, amount_per_second, unit
     );
 }
-fn load_test_fibex_rc() -> Option<Rc<FibexMetadata>> {
-    load_test_fibex().map(std::rc::Rc::new)
+/// the filter config from the json file, with the filter expression, search and
+/// output options of the command line
+fn dlt_filter_config(matches: &clap::ArgMatches) -> Option<dlt::filtering::DltFilterConfig> {
+    let mut filter_conf = match matches.value_of("filter_config") {
+        Some(filter_config_file_name) => {
+            let config_path = path::PathBuf::from(filter_config_file_name);
+            let mut cnf_file = match fs::File::open(&config_path) {
+                Ok(file) => file,
+                Err(_) => {
+                    report_error(format!("could not open filter config {:?}", config_path));
+                    std::process::exit(2)
+                }
+            };
+            dlt::filtering::read_filter_options(&mut cnf_file).ok()
+        }
+        None => None,
+    };
+    if let Some(filter_expr) = matches.value_of("filter_expr") {
+        let expr = match FilterExpr::parse(filter_expr).and_then(|expr| {
+            expr.compile()?;
+            Ok(expr)
+        }) {
+            Ok(expr) => expr,
+            Err(e) => {
+                report_error(format!("invalid filter expression: {}", e));
+                std::process::exit(2)
+            }
+        };
+        filter_conf.get_or_insert_with(Default::default).filter_expr =
+            Some(FilterExprSource::Expr(expr));
+    }
+    if let Some(pattern) = matches.value_of("search") {
+        let search = PayloadSearch {
+            pattern: pattern.to_string(),
+            regex: matches.is_present("search_regex"),
+            ignore_case: matches.is_present("ignore_case"),
+        };
+        if let Err(e) = search.compile() {
+            report_error(format!("invalid search: {}", e));
+            std::process::exit(2)
+        }
+        filter_conf
+            .get_or_insert_with(Default::default)
+            .payload_search = Some(search);
+    }
+    if matches.is_present("trace_info") {
+        filter_conf
+            .get_or_insert_with(Default::default)
+            .show_trace_info = Some(true);
+    }
+    filter_conf
+}
+
+/// writes the matches of the payload search next to the input file
+fn write_payload_matches(file_name: &str, payload_matches: &[PayloadMatch]) {
+    match payload_search::write_payload_matches(path::Path::new(file_name), payload_matches) {
+        Ok(matches_out_path) => println!(
+            "{} matching lines written to {:?}",
+            payload_matches.len(),
+            matches_out_path
+        ),
+        Err(e) => report_error(format!("could not write search results: {}", e)),
+    }
+}
+fn dlt_framing(matches: &clap::ArgMatches) -> DltFraming {
+    if matches.is_present("serial") {
+        DltFraming::SerialHeader
+    } else {
+        DltFraming::StorageHeader
+    }
+}
+
+fn dlt_reader_mode(matches: &clap::ArgMatches) -> DltReaderMode {
+    if matches.is_present("mmap") {
+        DltReaderMode::MemoryMapped
+    } else {
+        DltReaderMode::Buffered
+    }
+}
+fn load_test_fibex_shared() -> Option<Arc<FibexMetadata>> {
+    load_test_fibex().map(std::sync::Arc::new)
 }
 fn load_test_fibex() -> Option<FibexMetadata> {
     Some(
-        dlt::fibex::read_fibexes(vec![EXAMPLE_FIBEX.clone()]).unwrap_or_else(|_e| {
+        dlt::fibex_cache::read_fibexes_cached(vec![EXAMPLE_FIBEX.clone()]).unwrap_or_else(|_e| {
             report_error(format!("could not open {:?}", EXAMPLE_FIBEX.clone()));
             std::process::exit(3);
         }),
@@ -117,6 +117,7 @@ export class DialogsFileOptionsDltComponent implements OnDestroy, AfterContentIn
     public _ng_filters: IStats | undefined = undefined;
     public _ng_fibex: IPCMessages.IFilePickerFileInfo[] = [];
     public _ng_error: string | undefined;
+    public _ng_lostMessages: number = 0;
     public _ng_dispayed = ['id', ...CLevelOrder];
     public _ng_filterSubject: Subject<string> = new Subject<string>();
     public _ng_filterValue: string = '';
@@ -185,7 +186,7 @@ export class DialogsFileOptionsDltComponent implements OnDestroy, AfterContentIn
 
     public _ng_onFibex() {
         ElectronIpcService.request(new IPCMessages.FilePickerRequest({
-            filter: [{ name: 'XML files', extensions: ['xml'] }],
+            filter: [{ name: 'FIBEX / ARXML files', extensions: ['xml', 'arxml'] }],
             multiple: true,
         }), IPCMessages.FilePickerResponse).then((responce: IPCMessages.FilePickerResponse) => {
             if (typeof responce.error === 'string') {
@@ -331,6 +332,7 @@ export class DialogsFileOptionsDltComponent implements OnDestroy, AfterContentIn
 
     private _setFilters() {
         (this._ng_filters as any) = { };
+        this._ng_lostMessages = this._getLostMessages();
         Object.keys(this._stats).forEach((section: string, index: number) => {
             if (CStatCaptions[section] === undefined || !(this._stats[section] instanceof Array)) {
                 return;
@@ -349,6 +351,7 @@ export class DialogsFileOptionsDltComponent implements OnDestroy, AfterContentIn
                     stats[key] = item[1][key] === undefined ? 0 : item[1][key];
                 });
                 stats.id = item[0];
+                stats.description = this._getDescription(section, stats.id);
                 if (this.options !== undefined ) {
                     if (this.options.filters[section] instanceof Array && this.options.filters[section].indexOf(stats.id) !== -1) {
                         stats.state = true;
@@ -368,6 +371,40 @@ export class DialogsFileOptionsDltComponent implements OnDestroy, AfterContentIn
         this._forceUpdate();
     }
 
+    private _getDescription(section: string, id: string): string | undefined {
+        if (this._stats.ecu_registry === undefined || this._stats.ecu_registry === null) {
+            return undefined;
+        }
+        const ecus = this._stats.ecu_registry.ecus;
+        for (const ecu of Object.keys(ecus)) {
+            for (const app of Object.keys(ecus[ecu])) {
+                const registered = ecus[ecu][app];
+                if (section === 'app_ids' && app === id && typeof registered.description === 'string' && registered.description !== '') {
+                    return registered.description;
+                }
+                const context = registered.contexts[id];
+                if (section === 'context_ids' && context !== undefined && typeof context.description === 'string' && context.description !== '') {
+                    return context.description;
+                }
+            }
+        }
+        return undefined;
+    }
+
+    private _getLostMessages(): number {
+        if (this._stats.message_counters === undefined || this._stats.message_counters === null) {
+            return 0;
+        }
+        const ecus = this._stats.message_counters.ecus;
+        let lost: number = 0;
+        Object.keys(ecus).forEach((ecu: string) => {
+            Object.keys(ecus[ecu]).forEach((session: string) => {
+                lost += ecus[ecu][session].lost_messages;
+            });
+        });
+        return lost;
+    }
+
     private _forceUpdate() {
         if (this._destroyed) {
             return;
@@ -30,6 +30,7 @@ const CLevelsColors = {
 
 export interface IStatRow {
     id: string;
+    description?: string;
     state: boolean;
     non_log: number;
     log_fatal: number;
@@ -393,7 +393,7 @@ export class SidebarAppDLTConnectorComponent implements OnDestroy, AfterContentI
 
     public _ng_onAddFibexFile() {
         ElectronIpcService.request(new IPCMessages.FilePickerRequest({
-            filter: [{ name: 'XML files', extensions: ['xml'] }],
+            filter: [{ name: 'FIBEX / ARXML files', extensions: ['xml', 'arxml'] }],
             multiple: true,
         }), IPCMessages.FilePickerResponse).then((responce: IPCMessages.FilePickerResponse) => {
             if (typeof responce.error === 'string') {
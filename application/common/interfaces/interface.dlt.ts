@@ -17,6 +17,11 @@ export interface IIndexDltParams {
 	tag: string;
 	out: string;
 	chunk_size?: number;
+	/**
+	 * Number of threads used for indexing, 1 if not set. Only files with storage
+	 * headers are split up, other files are always indexed by one thread.
+	 */
+	jobs?: number;
 	append: boolean;
 	stdout: boolean;
 	statusUpdates: boolean;
@@ -27,8 +32,101 @@ export interface DltFilterConf {
 	app_ids?: Array<string>;
 	ecu_ids?: Array<string>;
 	context_ids?: Array<string>;
+	trace_infos?: Array<string>;
+	session_ids?: Array<number>;
+	message_types?: Array<DltMessageTypeFilter>;
+	exclude_app_ids?: Array<string>;
+	exclude_context_ids?: Array<string>;
+	exclude_ecu_ids?: Array<string>;
+	level_overrides?: Array<DltLevelOverride>;
+	filter_expr?: string | DltFilterExpr;
+	time_range?: DltTimeRange;
+	missing_fields?: DltMissingFieldPolicy;
+	payload_search?: DltPayloadSearch;
+	show_trace_info?: boolean;
 }
 
+/**
+ * Only selects messages with this text in the payload (rendered verbose arguments
+ * or fibex decoded non-verbose text). Plain text unless regex is set.
+ */
+export interface DltPayloadSearch {
+	pattern: string;
+	regex?: boolean;
+	ignore_case?: boolean;
+}
+
+/**
+ * Positions of the matches in one line of the index, byte ranges in the formatted
+ * message (the line without tag and line number). The indexers write them as json
+ * array next to the index file (<index file>.matches.json).
+ */
+export interface DltPayloadMatch {
+	line: number;
+	positions: Array<[number, number]>;
+}
+
+/**
+ * How app, context, level and type filters treat messages without extended header.
+ * Include if not set.
+ */
+export type DltMissingFieldPolicy =
+	| 'Include'
+	| 'Exclude'
+	| { Default: { app_id: string; context_id: string; log_level: number } };
+
+/**
+ * Selects messages from start (inclusive) to end (exclusive), both in ms.
+ * StorageHeader: wall clock (ms since the unix epoch), Uptime: ECU uptime of the standard header
+ */
+export interface DltTimeRange {
+	clock: 'StorageHeader' | 'Uptime';
+	start?: number;
+	end?: number;
+}
+
+export type DltArgumentSelector = { Index: number } | { Name: string };
+export type DltFilterField =
+	| 'App'
+	| 'Context'
+	| 'Ecu'
+	| 'Level'
+	| 'Session'
+	| 'Type'
+	| 'Payload'
+	| { Argument: DltArgumentSelector }
+	| { Unit: DltArgumentSelector };
+/**
+ * Filter expression, e.g. '(app == "NAV" && level <= WARN) || ctx in ["DIAG","UDS"] || payload ~ /timeout/'
+ * or the same expression as tree
+ */
+export type DltFilterExpr =
+	| { And: Array<DltFilterExpr> }
+	| { Or: Array<DltFilterExpr> }
+	| { Not: DltFilterExpr }
+	| { Compare: { field: DltFilterField; op: 'Eq' | 'Ne' | 'Lt' | 'Le' | 'Gt' | 'Ge'; value: string | number } }
+	| { In: { field: DltFilterField; values: Array<string | number> } }
+	| { Matches: { field: DltFilterField; pattern: string } };
+
+/**
+ * Replaces min_log_level for messages of these IDs, the first matching override is used
+ */
+export interface DltLevelOverride {
+	app_id?: string;
+	context_id?: string;
+	ecu_id?: string;
+	min_log_level: number;
+}
+
+/**
+ * "Log" or e.g. { Control: null } for all control messages, { NetworkTrace: "Can" } for one kind
+ */
+export type DltMessageTypeFilter =
+	| 'Log'
+	| { ApplicationTrace: string | { Invalid: number } | null }
+	| { NetworkTrace: string | { UserDefined: number } | null }
+	| { Control: string | { Unknown: number } | null };
+
 export enum DltLogLevel {
 	Fatal = 0x1 << 4,
 	Error = 0x2 << 4,
@@ -49,11 +147,46 @@ export interface LevelDistribution {
 	log_invalid: number;
 }
 
+export interface RegisteredContext {
+	description: string | null;
+	log_level: number | null;
+	trace_status: number | null;
+}
+
+export interface RegisteredApplication {
+	description: string | null;
+	contexts: { [context_id: string]: RegisteredContext };
+}
+
+export interface EcuRegistry {
+	ecus: { [ecu_id: string]: { [app_id: string]: RegisteredApplication } };
+}
+
+export interface CounterGap {
+	index: number;
+	expected: number;
+	received: number;
+	lost: number;
+}
+
+export interface SessionCounters {
+	messages: number;
+	lost_messages: number;
+	gaps: CounterGap[];
+}
+
+export interface MessageCounters {
+	// messages without session id are listed under "NONE"
+	ecus: { [ecu_id: string]: { [session_id: string]: SessionCounters } };
+}
+
 export interface StatisticInfo {
 	app_ids: Array<[string, LevelDistribution]>;
 	context_ids: Array<[string, LevelDistribution]>;
 	ecu_ids: Array<[string, LevelDistribution]>;
 	contained_non_verbose: boolean;
+	ecu_registry: EcuRegistry;
+	message_counters: MessageCounters;
 }
 
 export interface IFibexConfig {